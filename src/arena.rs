@@ -0,0 +1,148 @@
+//! Compact string set: every string stored once, end to end in a single
+//! buffer, in the order it was first inserted.
+//!
+//! A `HashSet<String>` spends a heap allocation and a 24-byte header on
+//! each string, and callers that also want the strings in order keep a
+//! second copy in a `Vec<String>`. Here a string costs its bytes, its end
+//! offset and a slot in an open-addressing index, and its id (insertion
+//! position) stands in for the copy.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Empty index slot.
+const EMPTY: u32 = u32::MAX;
+
+/// Insertion-ordered set of unique strings.
+#[derive(Default)]
+pub struct Arena {
+    bytes: String,
+    /// End offset in `bytes` of each string, by id
+    ends: Vec<usize>,
+    /// Open-addressing table of ids, at most three-quarters full
+    index: Vec<u32>,
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `s`, returning false if it was already there.
+    pub fn insert(&mut self, s: &str) -> bool {
+        if (self.ends.len() + 1) * 4 > self.index.len() * 3 {
+            self.grow();
+        }
+        let slot = match self.probe(s) {
+            Ok(_) => return false,
+            Err(slot) => slot,
+        };
+        let id = u32::try_from(self.ends.len()).expect("fewer than 4 billion strings");
+        self.bytes.push_str(s);
+        self.ends.push(self.bytes.len());
+        self.index[slot] = id;
+        true
+    }
+
+    /// The id of `s`, if it is in the set.
+    pub fn id(&self, s: &str) -> Option<usize> {
+        if self.index.is_empty() {
+            return None;
+        }
+        self.probe(s).ok()
+    }
+
+    pub fn contains(&self, s: &str) -> bool {
+        self.id(s).is_some()
+    }
+
+    /// The string with id `id` (the `id`-th inserted, from 0).
+    pub fn get(&self, id: usize) -> &str {
+        let start = id.checked_sub(1).map_or(0, |prev| self.ends[prev]);
+        &self.bytes[start..self.ends[id]]
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// The strings in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        (0..self.len()).map(|id| self.get(id))
+    }
+
+    /// The id of `s` as `Ok`, or the empty slot it would go in as `Err`.
+    /// The index must have room.
+    fn probe(&self, s: &str) -> Result<usize, usize> {
+        let mask = self.index.len() - 1;
+        let mut slot = hash(s) as usize & mask;
+        loop {
+            match self.index[slot] {
+                EMPTY => return Err(slot),
+                id if self.get(id as usize) == s => return Ok(id as usize),
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    /// Double the index (starting at 16 slots) and re-place every id.
+    fn grow(&mut self) {
+        let size = (self.index.len() * 2).max(16);
+        self.index = vec![EMPTY; size];
+        let mask = size - 1;
+        for id in 0..self.ends.len() {
+            let mut slot = hash(self.get(id)) as usize & mask;
+            while self.index[slot] != EMPTY {
+                slot = (slot + 1) & mask;
+            }
+            self.index[slot] = id as u32;
+        }
+    }
+}
+
+fn hash(s: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    s.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_lookup() {
+        let mut arena = Arena::new();
+        assert!(!arena.contains("buddy"));
+        assert!(arena.insert("buddy"));
+        assert!(arena.insert("Buddy1990"));
+        assert!(arena.insert(""));
+        assert!(arena.insert("josé"));
+        assert!(!arena.insert("buddy"));
+        assert!(!arena.insert(""));
+        assert_eq!(arena.len(), 4);
+        assert_eq!(arena.id("Buddy1990"), Some(1));
+        assert_eq!(arena.get(3), "josé");
+        assert_eq!(arena.id("buddy1990"), None);
+        let all: Vec<&str> = arena.iter().collect();
+        assert_eq!(all, ["buddy", "Buddy1990", "", "josé"]);
+    }
+
+    #[test]
+    fn test_growth() {
+        let mut arena = Arena::new();
+        for i in 0..10_000 {
+            assert!(arena.insert(&format!("word{}", i)));
+        }
+        for i in 0..10_000 {
+            assert_eq!(arena.id(&format!("word{}", i)), Some(i));
+            assert!(!arena.insert(&format!("word{}", i)));
+        }
+        assert!(!arena.contains("word10000"));
+        assert!(arena.index.len() >= arena.len() * 4 / 3);
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{date, status};
+
+/// Engagement metadata recorded before any cracking command runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Engagement {
+    /// What is in scope (systems, accounts, hash dumps).
+    #[serde(default)]
+    pub scope: String,
+    /// Ticket, contract or letter authorizing the test.
+    #[serde(default)]
+    pub authorization: String,
+    #[serde(default)]
+    pub operator: Option<String>,
+    /// Audit log location; defaults to `<engagement file>.log`.
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+}
+
+impl Engagement {
+    fn is_complete(&self) -> bool {
+        !self.scope.trim().is_empty() && !self.authorization.trim().is_empty()
+    }
+}
+
+/// Authorization gate: load the engagement file, prompt for missing or
+/// unconfirmed details (unless `authorized` and the file is complete), then
+/// append an entry for `command` to the audit log.
+pub fn gate(engagement_path: &Path, authorized: bool, command: &str) -> Result<()> {
+    let mut engagement = if engagement_path.exists() {
+        let content = std::fs::read_to_string(engagement_path).with_context(|| {
+            format!("Failed to read engagement: {}", engagement_path.display())
+        })?;
+        toml::from_str(&content).with_context(|| "Failed to parse engagement TOML")?
+    } else {
+        Engagement::default()
+    };
+
+    if !(authorized && engagement.is_complete()) {
+        prompt(&mut engagement)?;
+        let content =
+            toml::to_string_pretty(&engagement).with_context(|| "Failed to serialize engagement")?;
+        std::fs::write(engagement_path, content).with_context(|| {
+            format!("Failed to write engagement: {}", engagement_path.display())
+        })?;
+    }
+
+    let log_path = engagement
+        .audit_log
+        .clone()
+        .unwrap_or_else(|| engagement_path.with_extension("log"));
+    let mut log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to open audit log: {}", log_path.display()))?;
+    writeln!(
+        log,
+        "{}\tcommand={}\tscope={}\tauthorization={}\toperator={}\targs={}",
+        timestamp(),
+        command,
+        engagement.scope.trim(),
+        engagement.authorization.trim(),
+        engagement.operator.as_deref().unwrap_or("-"),
+        std::env::args().skip(1).collect::<Vec<_>>().join(" ")
+    )
+    .with_context(|| "Failed to write audit log")?;
+
+    status!(
+        "{} Engagement: {} (auth: {}) — logged to {}",
+        ">>".cyan().bold(),
+        engagement.scope.trim(),
+        engagement.authorization.trim(),
+        log_path.display()
+    );
+    Ok(())
+}
+
+fn prompt(engagement: &mut Engagement) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+
+    println!(
+        "{} This tool may only be used against systems you own or are authorized to test.",
+        "AUTHORIZATION".yellow().bold()
+    );
+    engagement.scope = ask(&mut input, "Scope statement", &engagement.scope)?;
+    engagement.authorization = ask(&mut input, "Authorization reference", &engagement.authorization)?;
+    let operator = ask(&mut input, "Operator", engagement.operator.as_deref().unwrap_or(""))?;
+    engagement.operator = (!operator.is_empty()).then_some(operator);
+
+    if !engagement.is_complete() {
+        bail!("Scope and authorization reference are required");
+    }
+    let confirm = ask(&mut input, "I confirm I am authorized to test this scope [y/N]", "")?;
+    if !confirm.eq_ignore_ascii_case("y") && !confirm.eq_ignore_ascii_case("yes") {
+        bail!("Authorization not confirmed");
+    }
+    Ok(())
+}
+
+fn ask(input: &mut impl BufRead, question: &str, current: &str) -> Result<String> {
+    if current.is_empty() {
+        print!("  {}: ", question);
+    } else {
+        print!("  {} [{}]: ", question, current);
+    }
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let answer = line.trim();
+    Ok(if answer.is_empty() { current } else { answer }.to_string())
+}
+
+/// Current UTC time as `YYYY-MM-DDTHH:MM:SSZ`.
+fn timestamp() -> String {
+    let secs = date::unix_now();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = date::civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
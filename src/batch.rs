@@ -0,0 +1,507 @@
+//! `crack-batch`: many profile + hash file jobs from one manifest, such as
+//! a classroom lab with a target per student.
+//!
+//! Manifests are TOML, or JSON when the file ends in `.json`. JSON is read
+//! into the same value tree TOML produces, so both share one schema:
+//!
+//! ```toml
+//! [[jobs]]
+//! name = "alice"              # optional, defaults to the hash file's stem
+//! profile = "alice.toml"      # paths are relative to the manifest
+//! hash_file = "alice.txt"
+//! algo = "md5"
+//! depth = 2                   # optional, 1-3
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::cracker::{self, CrackConfig, CrackResult, Found, HashAlgorithm};
+use crate::estimate;
+use crate::generator::{self, GeneratorConfig};
+use crate::metrics::Metrics;
+use crate::profile::Profile;
+use crate::report::Tiers;
+use crate::wordlist;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub jobs: Vec<Job>,
+}
+
+/// One target: a profile to generate from and the hashes to crack.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub profile: PathBuf,
+    pub hash_file: PathBuf,
+    pub algo: String,
+    #[serde(default = "default_depth")]
+    pub depth: u8,
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+    #[serde(default = "default_max_length")]
+    pub max_length: usize,
+}
+
+fn default_depth() -> u8 {
+    GeneratorConfig::default().depth
+}
+
+fn default_min_length() -> usize {
+    GeneratorConfig::default().min_length
+}
+
+fn default_max_length() -> usize {
+    GeneratorConfig::default().max_length
+}
+
+impl Manifest {
+    /// Load and check a manifest, resolving job paths against its directory.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest: {}", path.display()))?;
+        let mut manifest: Manifest = if path.extension().is_some_and(|e| e == "json") {
+            json::parse(&content)
+                .and_then(|value| value.try_into().map_err(Into::into))
+                .with_context(|| "Failed to parse manifest JSON")?
+        } else {
+            toml::from_str(&content).with_context(|| "Failed to parse manifest TOML")?
+        };
+
+        if manifest.jobs.is_empty() {
+            bail!("The manifest has no jobs");
+        }
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for (i, job) in manifest.jobs.iter_mut().enumerate() {
+            job.algorithm()
+                .and_then(|_| match job.depth {
+                    1..=3 => Ok(()),
+                    d => bail!("Depth must be 1-3, not {}", d),
+                })
+                .with_context(|| format!("Job {} ({})", i + 1, job.name()))?;
+            job.profile = dir.join(&job.profile);
+            job.hash_file = dir.join(&job.hash_file);
+        }
+        Ok(manifest)
+    }
+}
+
+impl Job {
+    /// The job's name, or its hash file's stem.
+    pub fn name(&self) -> String {
+        match self.name {
+            Some(ref name) => name.clone(),
+            None => self
+                .hash_file
+                .file_stem()
+                .map_or_else(|| "job".to_string(), |s| s.to_string_lossy().into_owned()),
+        }
+    }
+
+    pub fn algorithm(&self) -> Result<HashAlgorithm> {
+        match HashAlgorithm::from_str(&self.algo) {
+            Some(algo) => Ok(algo),
+            None => bail!("Unknown algorithm: {}", self.algo),
+        }
+    }
+
+    /// Generator settings, scheduled for cracking.
+    pub fn config(&self) -> GeneratorConfig {
+        GeneratorConfig {
+            depth: self.depth,
+            min_length: self.min_length,
+            max_length: self.max_length,
+            likely_first: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// What one job did.
+#[derive(Debug)]
+pub struct Outcome {
+    pub algorithm: HashAlgorithm,
+    pub targets: Vec<String>,
+    pub results: Vec<CrackResult>,
+    /// The tiers of the job's candidate stream, for reports.
+    pub tiers: Tiers,
+    pub tested: u64,
+    pub elapsed: Duration,
+}
+
+/// Run one job to the end of its candidates or its last hash. Quiet jobs
+/// skip the live status table and per-hash lines, so several can run side
+/// by side. Progress is added to `metrics` as it is made.
+pub fn run(job: &Job, quiet: bool, metrics: &Metrics) -> Result<Outcome> {
+    let algorithm = job.algorithm()?;
+    let targets = wordlist::read_wordlist(&job.hash_file)?;
+    if targets.is_empty() {
+        bail!("No hashes in {}", job.hash_file.display());
+    }
+    let profile = Profile::load(&job.profile)?;
+    let config = job.config();
+
+    let mut tiers = Tiers::default();
+    for tier in estimate::tier_counts(&profile, &config) {
+        tiers.push(tier.name, Some(tier.candidates));
+    }
+
+    metrics.add_targets(targets.len() as u64);
+    let candidates = generator::candidate_stream(&profile, &config);
+    let started = Instant::now();
+    let mut tested = 0;
+    let results = if quiet {
+        crack_quietly(&targets, algorithm, candidates, &mut tested, metrics)
+    } else {
+        let crack_config = CrackConfig {
+            expected: tiers.total(),
+            ..Default::default()
+        };
+        let mut cracked = 0;
+        cracker::hash::crack_hashes(
+            &targets,
+            algorithm,
+            candidates,
+            &crack_config,
+            |n, results| {
+                metrics.add_tested(n - tested);
+                metrics.add_cracked((results.len() - cracked) as u64);
+                (tested, cracked) = (n, results.len());
+            },
+        )?
+    };
+
+    Ok(Outcome {
+        algorithm,
+        targets,
+        results,
+        tiers,
+        tested,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// The cracking loop without any output, on the distributed workers'
+/// chunk checker.
+fn crack_quietly(
+    targets: &[String],
+    algo: HashAlgorithm,
+    candidates: impl Iterator<Item = String> + Send,
+    tested: &mut u64,
+    metrics: &Metrics,
+) -> Vec<CrackResult> {
+    let started = Instant::now();
+    let mut live = targets.to_vec();
+    let mut results: Vec<CrackResult> = Vec::new();
+    cracker::for_each_chunk(candidates, cracker::hash::chunk_size(algo), |chunk| {
+        for mut hit in cracker::hash::check_chunk(&live, algo, chunk) {
+            if results
+                .iter()
+                .any(|r| r.hash.eq_ignore_ascii_case(&hit.hash))
+            {
+                continue;
+            }
+            let offset = chunk.iter().position(|c| *c == hit.plaintext).unwrap_or(0);
+            hit.found = Some(Found {
+                index: *tested + offset as u64,
+                elapsed: started.elapsed(),
+            });
+            live.retain(|t| !t.eq_ignore_ascii_case(&hit.hash));
+            results.push(hit);
+            metrics.add_cracked(1);
+        }
+        *tested += chunk.len() as u64;
+        metrics.add_tested(chunk.len() as u64);
+        !live.is_empty()
+    });
+    results
+}
+
+/// Just enough JSON to read a manifest into a TOML value tree.
+mod json {
+    use anyhow::{bail, Result};
+    use toml::{map::Map, Value};
+
+    pub fn parse(text: &str) -> Result<Value> {
+        let mut parser = Parser {
+            chars: text.char_indices().peekable(),
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if let Some((at, _)) = parser.chars.next() {
+            bail!("Trailing characters at byte {}", at);
+        }
+        value.ok_or_else(|| anyhow::anyhow!("The document is null"))
+    }
+
+    struct Parser<'a> {
+        chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    }
+
+    impl Parser<'_> {
+        fn skip_whitespace(&mut self) {
+            while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        }
+
+        fn expect(&mut self, expected: char) -> Result<()> {
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, c)) if c == expected => Ok(()),
+                Some((at, c)) => bail!("Expected '{}' at byte {}, found '{}'", expected, at, c),
+                None => bail!("Expected '{}', found the end", expected),
+            }
+        }
+
+        /// A value; `None` for null, which TOML can't hold.
+        fn value(&mut self) -> Result<Option<Value>> {
+            self.skip_whitespace();
+            let Some(&(at, c)) = self.chars.peek() else {
+                bail!("Unexpected end of JSON");
+            };
+            Ok(Some(match c {
+                '{' => self.object()?,
+                '[' => self.array()?,
+                '"' => Value::String(self.string()?),
+                't' | 'f' | 'n' => {
+                    let word = self.word();
+                    match word.as_str() {
+                        "true" => Value::Boolean(true),
+                        "false" => Value::Boolean(false),
+                        "null" => return Ok(None),
+                        _ => bail!("Unexpected '{}' at byte {}", word, at),
+                    }
+                }
+                '-' | '0'..='9' => {
+                    let word = self.word();
+                    if let Ok(n) = word.parse::<i64>() {
+                        Value::Integer(n)
+                    } else if let Ok(x) = word.parse::<f64>() {
+                        Value::Float(x)
+                    } else {
+                        bail!("Bad number '{}' at byte {}", word, at);
+                    }
+                }
+                c => bail!("Unexpected '{}' at byte {}", c, at),
+            }))
+        }
+
+        fn word(&mut self) -> String {
+            let mut word = String::new();
+            while let Some((_, c)) = self
+                .chars
+                .next_if(|(_, c)| c.is_ascii_alphanumeric() || "+-.".contains(*c))
+            {
+                word.push(c);
+            }
+            word
+        }
+
+        fn object(&mut self) -> Result<Value> {
+            self.expect('{')?;
+            let mut map = Map::new();
+            self.skip_whitespace();
+            if self.chars.next_if(|(_, c)| *c == '}').is_some() {
+                return Ok(Value::Table(map));
+            }
+            loop {
+                self.skip_whitespace();
+                let key = self.string()?;
+                self.expect(':')?;
+                // Null members are left out, as if absent
+                if let Some(value) = self.value()? {
+                    map.insert(key, value);
+                }
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some((_, ',')) => continue,
+                    Some((_, '}')) => return Ok(Value::Table(map)),
+                    _ => bail!("Expected ',' or '}}' in object"),
+                }
+            }
+        }
+
+        fn array(&mut self) -> Result<Value> {
+            self.expect('[')?;
+            let mut items = Vec::new();
+            self.skip_whitespace();
+            if self.chars.next_if(|(_, c)| *c == ']').is_some() {
+                return Ok(Value::Array(items));
+            }
+            loop {
+                match self.value()? {
+                    Some(value) => items.push(value),
+                    None => bail!("null is not allowed in arrays"),
+                }
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some((_, ',')) => continue,
+                    Some((_, ']')) => return Ok(Value::Array(items)),
+                    _ => bail!("Expected ',' or ']' in array"),
+                }
+            }
+        }
+
+        fn string(&mut self) -> Result<String> {
+            self.expect('"')?;
+            let mut out = String::new();
+            loop {
+                match self.chars.next() {
+                    Some((_, '"')) => return Ok(out),
+                    Some((at, '\\')) => match self.chars.next() {
+                        Some((_, '"')) => out.push('"'),
+                        Some((_, '\\')) => out.push('\\'),
+                        Some((_, '/')) => out.push('/'),
+                        Some((_, 'n')) => out.push('\n'),
+                        Some((_, 'r')) => out.push('\r'),
+                        Some((_, 't')) => out.push('\t'),
+                        Some((_, 'b')) => out.push('\u{8}'),
+                        Some((_, 'f')) => out.push('\u{c}'),
+                        Some((_, 'u')) => {
+                            let hex: String = (0..4)
+                                .filter_map(|_| self.chars.next())
+                                .map(|(_, c)| c)
+                                .collect();
+                            match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                                Some(c) => out.push(c),
+                                None => bail!("Bad \\u escape at byte {}", at),
+                            }
+                        }
+                        _ => bail!("Bad escape at byte {}", at),
+                    },
+                    Some((_, c)) => out.push(c),
+                    None => bail!("Unterminated string"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::Digest;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pg-batch-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_toml_and_json_manifests_agree() {
+        let dir = scratch("manifests");
+        let toml_path = dir.join("jobs.toml");
+        let json_path = dir.join("jobs.json");
+        std::fs::write(
+            &toml_path,
+            r#"
+            [[jobs]]
+            name = "alice"
+            profile = "alice.toml"
+            hash_file = "hashes/alice.txt"
+            algo = "md5"
+            depth = 3
+
+            [[jobs]]
+            profile = "bob.toml"
+            hash_file = "bob.txt"
+            algo = "NTLM"
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            &json_path,
+            r#"{"jobs": [
+                {"name": "alice", "profile": "alice.toml", "hash_file": "hashes/alice.txt",
+                 "algo": "md5", "depth": 3},
+                {"name": null, "profile": "bob.toml", "hash_file": "bob.txt", "algo": "NTLM"}
+            ]}"#,
+        )
+        .unwrap();
+
+        for path in [&toml_path, &json_path] {
+            let manifest = Manifest::load(path).unwrap();
+            let [alice, bob] = &manifest.jobs[..] else {
+                panic!("{:?}", manifest);
+            };
+            assert_eq!(alice.name(), "alice");
+            assert_eq!(alice.hash_file, dir.join("hashes/alice.txt"));
+            assert_eq!(alice.depth, 3);
+            assert_eq!(bob.name(), "bob");
+            assert_eq!(bob.algorithm().unwrap(), HashAlgorithm::Ntlm);
+            assert_eq!(bob.depth, 2);
+        }
+    }
+
+    #[test]
+    fn test_bad_manifests() {
+        let dir = scratch("bad");
+        let path = dir.join("jobs.toml");
+        let job = |extra: &str| {
+            format!(
+                "[[jobs]]\nprofile = \"p.toml\"\nhash_file = \"h.txt\"\n{}\n",
+                extra
+            )
+        };
+        for (content, error) in [
+            ("jobs = []".to_string(), "no jobs"),
+            (job("algo = \"md6\""), "Unknown algorithm"),
+            (job("algo = \"md5\"\ndepth = 4"), "Depth"),
+            (job("algo = \"md5\"\nhashes = \"h.txt\""), "parse"),
+        ] {
+            std::fs::write(&path, content).unwrap();
+            let message = format!("{:#}", Manifest::load(&path).unwrap_err());
+            assert!(message.contains(error), "{}", message);
+        }
+        assert!(json::parse(r#"{"a": [1, 2,]}"#).is_err());
+        assert!(json::parse(r#"{"a": "é\n"} x"#).is_err());
+        assert_eq!(
+            json::parse(r#"{"a": "é\n", "b": -1.5}"#).unwrap()["a"].as_str(),
+            Some("é\n")
+        );
+    }
+
+    #[test]
+    fn test_quiet_run() {
+        let dir = scratch("run");
+        std::fs::write(
+            dir.join("john.toml"),
+            "[personal]\nfirst_name = \"John\"\nbirthdate = \"1990-05-15\"\n",
+        )
+        .unwrap();
+        let job = Job {
+            name: None,
+            profile: dir.join("john.toml"),
+            hash_file: dir.join("john.txt"),
+            algo: "md5".to_string(),
+            depth: 2,
+            min_length: 6,
+            max_length: 32,
+        };
+        // md5("john1990"), and one nothing generates
+        let target = hex::encode(md5::Md5::digest("john1990"));
+        std::fs::write(&job.hash_file, format!("{}\n{}\n", target, "0".repeat(32))).unwrap();
+
+        let metrics = Metrics::default();
+        let outcome = run(&job, true, &metrics).unwrap();
+        assert_eq!(outcome.results.len(), 1);
+        let hit = &outcome.results[0];
+        assert_eq!(hit.plaintext, "john1990");
+        assert_eq!(
+            outcome.tiers.at(hit.found.unwrap().index),
+            Some("Seed numbers")
+        );
+        // The second hash survives, so every candidate was tried
+        assert_eq!(Some(outcome.tested), outcome.tiers.total());
+        let text = metrics.render();
+        assert!(text.contains(&format!("_candidates_tested_total {}\n", outcome.tested)));
+        assert!(text.contains("_hashes_cracked_total 1\n"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Scalable Bloom filter, for deduplicating generations too large to keep
+//! every candidate in a set.
+//!
+//! A Bloom filter remembers what it has seen in a few bits per item, at the
+//! price of false positives: now and then a new item is reported as seen.
+//! This one needs no item count up front. It starts small and, each time
+//! the current slice fills, adds one twice as large with half the
+//! false-positive rate, so the rate over all slices stays under the one
+//! asked for.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// False-positive rate used when none is given.
+pub const DEFAULT_RATE: f64 = 0.001;
+
+/// Items the first slice holds before the next one is added.
+const FIRST_CAPACITY: usize = 1 << 16;
+
+/// Each slice's false-positive rate as a fraction of the previous one's;
+/// the rates of all slices add up to at most the first's / (1 - this).
+const TIGHTENING: f64 = 0.5;
+
+/// A set of strings that may wrongly claim to contain one it doesn't.
+pub struct Bloom {
+    slices: Vec<Slice>,
+}
+
+/// One fixed-size filter, full once `capacity` items are in.
+struct Slice {
+    bits: Vec<u64>,
+    bit_count: u64,
+    hashes: u64,
+    capacity: usize,
+    len: usize,
+    rate: f64,
+}
+
+impl Bloom {
+    /// An empty filter whose false-positive rate stays under `rate`.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            slices: vec![Slice::new(FIRST_CAPACITY, rate * (1.0 - TIGHTENING))],
+        }
+    }
+
+    /// Record `item`, returning false if it was (probably) seen before.
+    pub fn insert(&mut self, item: &str) -> bool {
+        let hash = hash_pair(item);
+        if self.slices.iter().any(|slice| slice.contains(hash)) {
+            return false;
+        }
+        let last = self.slices.last().expect("a filter has a slice");
+        if last.len >= last.capacity {
+            let next = Slice::new(last.capacity * 2, last.rate * TIGHTENING);
+            self.slices.push(next);
+        }
+        self.slices.last_mut().expect("just checked").insert(hash);
+        true
+    }
+
+    /// Bytes held by the bit arrays.
+    pub fn memory(&self) -> usize {
+        self.slices.iter().map(|slice| slice.bits.len() * 8).sum()
+    }
+}
+
+impl Slice {
+    /// A slice sized for `capacity` items at `rate`: m = -n ln p / (ln 2)^2
+    /// bits and k = m / n ln 2 hashes.
+    fn new(capacity: usize, rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = (-(capacity as f64) * rate.ln() / (ln2 * ln2)).ceil() as u64;
+        let bit_count = bit_count.max(64);
+        let hashes = (bit_count as f64 / capacity as f64 * ln2).round().max(1.0) as u64;
+        Self {
+            bits: vec![0; bit_count.div_ceil(64) as usize],
+            bit_count,
+            hashes,
+            capacity,
+            len: 0,
+            rate,
+        }
+    }
+
+    /// The bits for `hash`, by double hashing.
+    fn positions(&self, (h1, h2): (u64, u64)) -> impl Iterator<Item = u64> {
+        let bit_count = self.bit_count;
+        (0..self.hashes).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_count)
+    }
+
+    fn contains(&self, hash: (u64, u64)) -> bool {
+        self.positions(hash)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    fn insert(&mut self, hash: (u64, u64)) {
+        for bit in self.positions(hash) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+        self.len += 1;
+    }
+}
+
+/// Two independent hashes of `item`. `DefaultHasher::new` always starts
+/// from the same keys, so runs of the same build agree.
+fn hash_pair(item: &str) -> (u64, u64) {
+    let mut first = DefaultHasher::new();
+    item.hash(&mut first);
+    let h1 = first.finish();
+    let mut second = DefaultHasher::new();
+    h1.hash(&mut second);
+    item.hash(&mut second);
+    // Never zero, so the probes step apart
+    (h1, second.finish() | 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut bloom = Bloom::new(DEFAULT_RATE);
+        let items: Vec<String> = (0..200_000).map(|i| format!("candidate{}", i)).collect();
+        let fresh = items.iter().filter(|item| bloom.insert(item)).count();
+        for item in &items {
+            assert!(!bloom.insert(item), "{} forgotten", item);
+        }
+        // Grown past the first slice, losing at most a handful to false
+        // positives along the way
+        assert!(bloom.slices.len() > 1);
+        assert!(
+            fresh > items.len() - 200,
+            "{} of {} new",
+            fresh,
+            items.len()
+        );
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let rate = 0.01;
+        let mut bloom = Bloom::new(rate);
+        for i in 0..100_000 {
+            bloom.insert(&format!("seen{}", i));
+        }
+        let trials = 100_000;
+        let false_positives = (0..trials)
+            .filter(|i| !bloom.insert(&format!("unseen{}", i)))
+            .count();
+        assert!(
+            (false_positives as f64) < rate * trials as f64,
+            "{} false positives",
+            false_positives
+        );
+        // A few bytes per item, against dozens for a set of strings (the
+        // newest slice is allocated in full before it fills)
+        assert!(bloom.memory() < 200_000 * 8, "{} bytes", bloom.memory());
+    }
+}
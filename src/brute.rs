@@ -0,0 +1,165 @@
+//! Incremental brute force: every string over a charset, shortest first.
+//!
+//! Each length is expanded as a mask of `?1` placeholders, so the keyspace is
+//! walked lazily and a run can restart at any position without replaying
+//! the candidates before it.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::mask::{Mask, MaskIter};
+
+/// Brute-force settings as given on the command line, kept in sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BruteSpec {
+    /// Charset names and literals joined with `+`, e.g. `lower+digits+!@`
+    pub charset: String,
+    /// Length or inclusive range, e.g. `6-8`
+    pub length: String,
+}
+
+/// The exhaustive keyspace for a charset and length range.
+#[derive(Debug, Clone)]
+pub struct BruteForce {
+    /// One mask per length, shortest first.
+    masks: Vec<Mask>,
+}
+
+impl BruteForce {
+    pub fn new(spec: &BruteSpec) -> Result<Self> {
+        let charset = parse_charset(&spec.charset)?;
+        let (min, max) = parse_length(&spec.length)?;
+        let custom = [Some(charset), None, None, None];
+        let masks = (min..=max)
+            .map(|len| Mask::parse(&"?1".repeat(len), &custom))
+            .collect::<Result<_>>()?;
+        Ok(Self { masks })
+    }
+
+    /// Number of candidates across all lengths (saturating).
+    pub fn keyspace(&self) -> u128 {
+        self.masks
+            .iter()
+            .fold(0u128, |acc, m| acc.saturating_add(m.keyspace()))
+    }
+
+    /// Candidates starting at index `start` of the full keyspace.
+    pub fn candidates_from(&self, start: u128) -> BruteIter {
+        let mut masks = self.masks.clone().into_iter();
+        let mut rest = start;
+        let current = loop {
+            match masks.next() {
+                Some(mask) if rest >= mask.keyspace() => rest -= mask.keyspace(),
+                Some(mask) => break Some(mask.candidates_from(rest)),
+                None => break None,
+            }
+        };
+        BruteIter { masks, current }
+    }
+}
+
+/// Iterator returned by [`BruteForce::candidates_from`].
+pub struct BruteIter {
+    masks: std::vec::IntoIter<Mask>,
+    current: Option<MaskIter>,
+}
+
+impl Iterator for BruteIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if let Some(candidate) = self.current.as_mut()?.next() {
+                return Some(candidate);
+            }
+            self.current = self.masks.next().map(|m| m.candidates());
+        }
+    }
+}
+
+/// `lower+digits+!@` as a mask charset (`?l?d!@`).
+fn parse_charset(spec: &str) -> Result<String> {
+    let mut charset = String::new();
+    for part in spec.split('+') {
+        charset.push_str(match part {
+            "lower" => "?l",
+            "upper" => "?u",
+            "digits" => "?d",
+            "symbols" => "?s",
+            "hex" => "?h",
+            "all" => "?a",
+            "" => bail!("Empty charset component in '{}'", spec),
+            // Anything else is a set of literal characters
+            literal => {
+                for c in literal.chars() {
+                    if c == '?' {
+                        charset.push('?');
+                    }
+                    charset.push(c);
+                }
+                continue;
+            }
+        });
+    }
+    Ok(charset)
+}
+
+/// `6-8` or `6` as an inclusive length range.
+fn parse_length(spec: &str) -> Result<(usize, usize)> {
+    let parse = |s: &str| -> Result<usize> {
+        s.trim()
+            .parse()
+            .with_context(|| format!("Invalid length '{}'", spec))
+    };
+    let (min, max) = match spec.split_once('-') {
+        Some((min, max)) => (parse(min)?, parse(max)?),
+        None => (parse(spec)?, parse(spec)?),
+    };
+    if min == 0 || min > max {
+        bail!("Invalid length range '{}'", spec);
+    }
+    Ok((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute(charset: &str, length: &str) -> Result<BruteForce> {
+        BruteForce::new(&BruteSpec {
+            charset: charset.to_string(),
+            length: length.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_keyspace_and_order() {
+        let b = brute("digits+ab", "1-2").unwrap();
+        assert_eq!(b.keyspace(), 12 + 144);
+        let all: Vec<String> = b.candidates_from(0).collect();
+        assert_eq!(all.len(), 156);
+        assert_eq!(all[0], "0");
+        assert_eq!(all[11], "b");
+        assert_eq!(all[12], "00");
+        assert_eq!(all[155], "bb");
+    }
+
+    #[test]
+    fn test_resume_position() {
+        let b = brute("lower", "2-3").unwrap();
+        let all: Vec<String> = b.candidates_from(0).collect();
+        for start in [0u128, 675, 676, 1000] {
+            let rest: Vec<String> = b.candidates_from(start).collect();
+            assert_eq!(rest, all[start as usize..]);
+        }
+    }
+
+    #[test]
+    fn test_bad_specs() {
+        assert!(brute("lower", "0-4").is_err());
+        assert!(brute("lower", "8-6").is_err());
+        assert!(brute("lower++digits", "6").is_err());
+        let literal = brute("x?", "1").unwrap();
+        assert_eq!(literal.candidates_from(0).collect::<Vec<_>>(), ["x", "?"]);
+    }
+}
@@ -0,0 +1,351 @@
+//! On-disk digest caches: every candidate a profile generates, hashed once
+//! per algorithm, so re-running the same profile against a new hash set is
+//! a lookup instead of a cracking pass.
+//!
+//! A cache file is named after the algorithm and a fingerprint of the
+//! profile's content, the generator settings, the base wordlist's size and
+//! modification time and [`CACHE_VERSION`], so an edited profile, a
+//! different depth or a newer generator gets a cache of its own. It holds
+//! one `<hex digest>\t<candidate>` line per candidate, in stream order.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::cracker::{self, CrackResult, Found, HashAlgorithm};
+use crate::generator::GeneratorConfig;
+use crate::status;
+
+/// Candidates digested per parallel batch while building.
+const BUILD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Part of every fingerprint. Bump it whenever the generator starts
+/// producing different candidates for the same profile and settings, so
+/// caches built by an older version are rebuilt rather than trusted.
+pub const CACHE_VERSION: u32 = 1;
+
+/// A profile's candidates by digest, for one algorithm.
+pub struct DigestCache {
+    algo: HashAlgorithm,
+    /// (digest, candidate) in stream order.
+    entries: Vec<(Vec<u8>, String)>,
+    /// Digest -> position in `entries`; the first candidate wins.
+    index: HashMap<Vec<u8>, usize>,
+}
+
+impl DigestCache {
+    /// Load the cache for `profile` at `config` from `dir`, or build it from
+    /// `candidates` and save it there when there is none yet.
+    pub fn open<I>(
+        dir: &Path,
+        profile: &Path,
+        config: &GeneratorConfig,
+        algo: HashAlgorithm,
+        candidates: impl FnOnce() -> I,
+    ) -> Result<Self>
+    where
+        I: Iterator<Item = String> + Send,
+    {
+        if algo == HashAlgorithm::Bcrypt {
+            bail!("bcrypt is salted per hash, so its digests can't be cached");
+        }
+        let path = cache_path(dir, profile, config, algo)?;
+        if path.exists() {
+            let cache = Self::load(&path, algo)?;
+            status!(
+                "{} Digest cache: {} ({} candidates)",
+                ">>".cyan().bold(),
+                path.display(),
+                cache.len()
+            );
+            return Ok(cache);
+        }
+
+        let started = Instant::now();
+        let cache = Self::build(candidates(), algo);
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory: {}", dir.display()))?;
+        cache.save(&path)?;
+        status!(
+            "{} Built digest cache of {} candidates in {:.1}s: {}",
+            ">>".cyan().bold(),
+            cache.len(),
+            started.elapsed().as_secs_f64(),
+            path.display()
+        );
+        Ok(cache)
+    }
+
+    /// Digest every candidate, in parallel batches.
+    pub fn build(candidates: impl Iterator<Item = String> + Send, algo: HashAlgorithm) -> Self {
+        let mut entries = Vec::new();
+        cracker::for_each_chunk(candidates, BUILD_CHUNK_SIZE, |chunk| {
+            let digests: Vec<Vec<u8>> = chunk
+                .par_iter()
+                .map(|c| cracker::hash::digest(algo, c).unwrap_or_default())
+                .collect();
+            entries.extend(digests.into_iter().zip(chunk.iter().cloned()));
+            true
+        });
+        Self::from_entries(algo, entries)
+    }
+
+    fn from_entries(algo: HashAlgorithm, entries: Vec<(Vec<u8>, String)>) -> Self {
+        let mut index = HashMap::with_capacity(entries.len());
+        for (i, (digest, _)) in entries.iter().enumerate() {
+            index.entry(digest.clone()).or_insert(i);
+        }
+        Self {
+            algo,
+            entries,
+            index,
+        }
+    }
+
+    fn load(path: &Path, algo: HashAlgorithm) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open digest cache: {}", path.display()))?;
+        let mut entries = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.with_context(|| "Failed to read digest cache")?;
+            let parsed = line
+                .split_once('\t')
+                .and_then(|(digest, candidate)| Some((hex::decode(digest).ok()?, candidate)));
+            let Some((digest, candidate)) = parsed else {
+                bail!(
+                    "Corrupt digest cache {} at line {}; delete it to rebuild",
+                    path.display(),
+                    i + 1
+                );
+            };
+            entries.push((digest, candidate.to_string()));
+        }
+        Ok(Self::from_entries(algo, entries))
+    }
+
+    /// Write the cache, through a temporary file so an interrupted save
+    /// never leaves a truncated cache behind.
+    fn save(&self, path: &Path) -> Result<()> {
+        let partial = path.with_extension("partial");
+        let file = File::create(&partial)
+            .with_context(|| format!("Failed to create digest cache: {}", partial.display()))?;
+        let mut writer = BufWriter::new(file);
+        for (digest, candidate) in &self.entries {
+            writeln!(writer, "{}\t{}", hex::encode(digest), candidate)?;
+        }
+        writer
+            .flush()
+            .with_context(|| "Failed to write digest cache")?;
+        std::fs::rename(&partial, path)
+            .with_context(|| format!("Failed to save digest cache: {}", path.display()))
+    }
+
+    /// Candidates in the cache.
+    pub fn len(&self) -> u64 {
+        self.entries.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The cracked ones among `hashes`, with their candidates' positions in
+    /// the stream.
+    pub fn lookup(&self, hashes: &[String]) -> Vec<CrackResult> {
+        let started = Instant::now();
+        hashes
+            .iter()
+            .filter_map(|hash| {
+                let digest = hex::decode(hash.trim()).ok()?;
+                let &i = self.index.get(&digest)?;
+                Some(CrackResult {
+                    hash: hash.to_lowercase(),
+                    plaintext: self.entries[i].1.clone(),
+                    algorithm: self.algo,
+                    found: Some(Found {
+                        index: i as u64,
+                        elapsed: started.elapsed(),
+                    }),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Where the cache for `profile` at `config` lives in `dir`.
+pub fn cache_path(
+    dir: &Path,
+    profile: &Path,
+    config: &GeneratorConfig,
+    algo: HashAlgorithm,
+) -> Result<PathBuf> {
+    let content = std::fs::read(profile)
+        .with_context(|| format!("Failed to read profile: {}", profile.display()))?;
+    let mut hasher = Sha256::new();
+    hasher.update(format!("version={}\n", CACHE_VERSION));
+    hasher.update(&content);
+    for line in settings(config)? {
+        hasher.update(line);
+        hasher.update("\n");
+    }
+    let fingerprint = hex::encode(hasher.finalize());
+    Ok(dir.join(format!(
+        "{}-{}.digests",
+        algo.to_string().to_lowercase(),
+        &fingerprint[..16]
+    )))
+}
+
+/// One `name=value` line per setting that changes what is generated.
+///
+/// The config is taken apart field by field, so a new setting doesn't
+/// compile until it is given a line here (or left out on purpose).
+fn settings(config: &GeneratorConfig) -> Result<Vec<String>> {
+    let GeneratorConfig {
+        depth,
+        min_length,
+        max_length,
+        all_pairs,
+        markov,
+        likely_first,
+        separators,
+        years,
+        recent_year,
+        date_formats,
+        keyboard,
+        policy,
+        charset,
+        exclude_chars,
+        base_wordlist,
+        rules,
+        hashcat_rules,
+        insertions,
+        case_toggles,
+        rotations,
+        bloom,
+        topics,
+        template,
+        sources,
+    } = config;
+
+    let rules: Vec<String> = rules.iter().cloned().map(String::from).collect();
+    let hashcat_rules: Vec<String> = hashcat_rules.iter().cloned().map(String::from).collect();
+    // The dictionary's size and modification time stand in for its content
+    let base_wordlist = match base_wordlist {
+        Some(path) => {
+            let meta = std::fs::metadata(path)
+                .with_context(|| format!("Failed to read wordlist: {}", path.display()))?;
+            let modified = meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok());
+            format!(
+                "{} {} {}",
+                path.display(),
+                meta.len(),
+                modified.map_or(0, |t| t.as_nanos())
+            )
+        }
+        None => String::new(),
+    };
+    let years = years
+        .as_ref()
+        .map(|years| format!("{}-{}", years.start(), years.end()));
+    // Extra sources are closures, so only where they run can be told apart
+    let sources: Vec<&str> = sources.iter().map(|s| s.after.unwrap_or("")).collect();
+
+    Ok(vec![
+        format!("depth={}", depth),
+        format!("min_length={}", min_length),
+        format!("max_length={}", max_length),
+        format!("all_pairs={}", all_pairs),
+        format!("markov={}", markov),
+        format!("likely_first={}", likely_first),
+        format!("separators={:?}", separators),
+        format!("years={}", years.unwrap_or_default()),
+        format!("recent_year={}", recent_year.map_or(0, u32::from)),
+        format!("date_formats={:?}", date_formats),
+        format!("keyboard={:?}", keyboard),
+        format!("policy={:?}", policy),
+        format!("charset={:?}", charset),
+        format!("exclude_chars={:?}", exclude_chars),
+        format!("base_wordlist={}", base_wordlist),
+        format!("rules={:?}", rules),
+        format!("hashcat_rules={:?}", hashcat_rules),
+        format!("insertions={}", insertions),
+        format!("case_toggles={}", case_toggles),
+        format!("rotations={}", rotations),
+        format!("bloom={}", bloom.unwrap_or(0.0)),
+        format!("topics={:?}", topics),
+        format!("template={:?}", template),
+        format!("sources={:?}", sources),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_save_and_lookup() {
+        let dir = std::env::temp_dir().join(format!("pg-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let profile = dir.with_extension("toml");
+        std::fs::write(&profile, "[personal]\nfirst_name = \"John\"\n").unwrap();
+        let config = GeneratorConfig::default();
+        let words = || ["alpha", "bravo", "charlie"].map(String::from).into_iter();
+        let target = |word| hex::encode(cracker::hash::digest(HashAlgorithm::Sha1, word).unwrap());
+        let hashes = vec![target("charlie").to_uppercase(), target("zulu")];
+
+        let built = DigestCache::open(&dir, &profile, &config, HashAlgorithm::Sha1, words).unwrap();
+        // The second open reads the file instead of generating
+        let loaded = DigestCache::open(&dir, &profile, &config, HashAlgorithm::Sha1, || {
+            std::iter::empty()
+        })
+        .unwrap();
+        for cache in [built, loaded] {
+            assert_eq!(cache.len(), 3);
+            let found = cache.lookup(&hashes);
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].plaintext, "charlie");
+            assert_eq!(found[0].found.unwrap().index, 2);
+        }
+
+        // Other settings or algorithms get caches of their own
+        let deeper = GeneratorConfig {
+            depth: 3,
+            ..config.clone()
+        };
+        let path = cache_path(&dir, &profile, &config, HashAlgorithm::Sha1).unwrap();
+        assert_ne!(
+            path,
+            cache_path(&dir, &profile, &deeper, HashAlgorithm::Sha1).unwrap()
+        );
+        assert_ne!(
+            path,
+            cache_path(&dir, &profile, &config, HashAlgorithm::Md5).unwrap()
+        );
+        assert!(DigestCache::open(&dir, &profile, &config, HashAlgorithm::Bcrypt, words).is_err());
+
+        // A base wordlist that grows gets a new cache too
+        let wordlist = dir.join("base.txt");
+        std::fs::write(&wordlist, "alpha\n").unwrap();
+        let blended = GeneratorConfig {
+            base_wordlist: Some(wordlist.clone()),
+            ..config.clone()
+        };
+        let before = cache_path(&dir, &profile, &blended, HashAlgorithm::Sha1).unwrap();
+        std::fs::write(&wordlist, "alpha\nbravo\n").unwrap();
+        assert_ne!(
+            before,
+            cache_path(&dir, &profile, &blended, HashAlgorithm::Sha1).unwrap()
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_file(&profile);
+    }
+}
@@ -0,0 +1,149 @@
+//! Combinator attack: every left word joined to every right word.
+//!
+//! Both word lists are held once in memory and the cross-product is walked
+//! lazily, so a 10K × 100K attack never materializes a billion strings.
+
+use std::sync::Arc;
+
+/// Two word lists and the separators tried between them.
+#[derive(Debug, Clone)]
+pub struct Combinator {
+    left: Arc<[String]>,
+    right: Arc<[String]>,
+    separators: Arc<[String]>,
+}
+
+impl Combinator {
+    /// An empty `separators` list means plain concatenation.
+    pub fn new(left: Vec<String>, right: Vec<String>, separators: Vec<String>) -> Self {
+        let separators = if separators.is_empty() {
+            vec![String::new()]
+        } else {
+            separators
+        };
+        Self {
+            left: dedup(left).into(),
+            right: dedup(right).into(),
+            separators: dedup(separators).into(),
+        }
+    }
+
+    /// Number of candidates the attack expands to.
+    pub fn keyspace(&self) -> u128 {
+        self.left.len() as u128 * self.separators.len() as u128 * self.right.len() as u128
+    }
+
+    /// The candidate at `index` of [`Combinator::candidates`], without
+    /// walking the ones before it.
+    pub fn candidate(&self, index: u128) -> Option<String> {
+        if index >= self.keyspace() {
+            return None;
+        }
+        let right = self.right.len() as u128;
+        let separators = self.separators.len() as u128;
+        let (rest, r) = (index / right, index % right);
+        let (l, s) = (rest / separators, rest % separators);
+        Some(format!(
+            "{}{}{}",
+            self.left[l as usize], self.separators[s as usize], self.right[r as usize]
+        ))
+    }
+
+    /// Every `left + separator + right`, with the right word changing fastest.
+    pub fn candidates(&self) -> CombinatorIter {
+        CombinatorIter {
+            lists: self.clone(),
+            left: 0,
+            separator: 0,
+            right: 0,
+        }
+    }
+}
+
+/// Iterator returned by [`Combinator::candidates`].
+pub struct CombinatorIter {
+    lists: Combinator,
+    left: usize,
+    separator: usize,
+    right: usize,
+}
+
+impl Iterator for CombinatorIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let lists = &self.lists;
+        if lists.right.is_empty() {
+            return None;
+        }
+        let left = lists.left.get(self.left)?;
+        let candidate = format!(
+            "{}{}{}",
+            left, lists.separators[self.separator], lists.right[self.right]
+        );
+
+        self.right += 1;
+        if self.right == lists.right.len() {
+            self.right = 0;
+            self.separator += 1;
+            if self.separator == lists.separators.len() {
+                self.separator = 0;
+                self.left += 1;
+            }
+        }
+        Some(candidate)
+    }
+}
+
+/// Drop repeated words, keeping the first occurrence.
+fn dedup(words: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    words
+        .into_iter()
+        .filter(|w| seen.insert(w.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cross_product() {
+        let c = Combinator::new(
+            words(&["john", "buddy", "john"]),
+            words(&["2024", "!"]),
+            words(&["", "_"]),
+        );
+        assert_eq!(c.keyspace(), 8);
+        let all: Vec<String> = c.candidates().collect();
+        assert_eq!(
+            all,
+            words(&[
+                "john2024",
+                "john!",
+                "john_2024",
+                "john_!",
+                "buddy2024",
+                "buddy!",
+                "buddy_2024",
+                "buddy_!"
+            ])
+        );
+        for (i, candidate) in all.iter().enumerate() {
+            assert_eq!(c.candidate(i as u128).as_ref(), Some(candidate));
+        }
+        assert_eq!(c.candidate(8), None);
+    }
+
+    #[test]
+    fn test_empty_side() {
+        let c = Combinator::new(words(&["john"]), Vec::new(), Vec::new());
+        assert_eq!(c.keyspace(), 0);
+        assert_eq!(c.candidates().count(), 0);
+    }
+}
@@ -1,8 +1,14 @@
-/// Embedded common passwords, keyboard patterns, and common affixes.
+//! Embedded common passwords, keyboard patterns, and common affixes.
+
+use crate::keyboard::Layout;
 
 /// Top common passwords embedded at compile time.
 pub const COMMON_PASSWORDS: &str = include_str!("../data/common_passwords.txt");
 
+/// Everyday words popular in passwords, by language, one
+/// `language: word word ...` line at a time.
+const COMMON_WORDS: &str = include_str!("../data/common_words.txt");
+
 /// Returns deduplicated list of common passwords.
 pub fn common_passwords() -> Vec<String> {
     COMMON_PASSWORDS
@@ -12,34 +18,144 @@ pub fn common_passwords() -> Vec<String> {
         .collect()
 }
 
-/// Common keyboard walk patterns.
-pub fn keyboard_patterns() -> Vec<String> {
-    vec![
-        // Row walks
-        "qwerty", "qwertyuiop", "qwert", "asdfgh", "asdfghjkl", "zxcvbn", "zxcvbnm",
-        // Diagonal walks
-        "qazwsx", "1qaz2wsx", "1qaz2wsx3edc", "zaq1xsw2",
-        // Number runs
-        "123456", "1234567", "12345678", "123456789", "1234567890",
-        "0987654321", "987654321", "654321", "54321",
-        // Numpad patterns
-        "147258369", "159357", "789456123", "321654987",
-        // Repeats
-        "aaaaaa", "000000", "111111", "222222", "555555", "666666", "777777", "88888888",
-        "999999", "112233", "123123", "121212", "131313", "123321",
-        // Short keyboard
-        "qwer", "asdf", "zxcv", "1234", "4321",
-        // Other common patterns
-        "abcdef", "abcdefg", "abcdefgh", "abcd1234", "1234abcd",
-        "abc123", "123abc", "aaa111", "zzz999",
-    ]
-    .into_iter()
-    .map(String::from)
-    .collect()
+/// Everyday words for `language` (`en`, `es`, `id`, `de`, `fr`), in file
+/// order; empty for a language without a dictionary.
+pub fn language_words(language: &str) -> Vec<String> {
+    COMMON_WORDS
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once(':'))
+        .filter(|(lang, _)| lang.trim() == language)
+        .flat_map(|(_, words)| words.split_whitespace().map(String::from))
+        .collect()
 }
 
-/// Common numeric suffixes appended to words.
-pub fn numeric_suffixes() -> Vec<String> {
+/// Whether there is a dictionary for `language`.
+pub fn is_language(language: &str) -> bool {
+    !language_words(language).is_empty()
+}
+
+/// Words popular in passwords about a topic (sports, music, ...), one
+/// `topic: triggers | words` line at a time.
+const TOPIC_WORDS: &str = include_str!("../data/topic_words.txt");
+
+/// Each line of the topic dictionaries: its topic, whether it is the
+/// topic's first (general) line, its triggers and its words.
+fn topic_lines() -> impl Iterator<Item = (&'static str, bool, Vec<&'static str>, &'static str)> {
+    let mut last = "";
+    TOPIC_WORDS
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| {
+            let (topic, rest) = l.split_once(':')?;
+            let (triggers, words) = rest.split_once('|')?;
+            Some((topic.trim(), triggers.split_whitespace().collect(), words))
+        })
+        .map(move |(topic, triggers, words)| {
+            let first = topic != last;
+            last = topic;
+            (topic, first, triggers, words)
+        })
+}
+
+/// The topics there are dictionaries for, in file order.
+pub fn topics() -> Vec<&'static str> {
+    let mut topics = Vec::new();
+    for (topic, first, _, _) in topic_lines() {
+        if first && !topics.contains(&topic) {
+            topics.push(topic);
+        }
+    }
+    topics
+}
+
+/// Whether there is a dictionary for `topic`.
+pub fn is_topic(topic: &str) -> bool {
+    topics().contains(&topic)
+}
+
+/// Topics with a trigger in one of `interests` (lowercase letters and
+/// digits only, like the triggers), in file order.
+pub fn matching_topics(interests: &[String]) -> Vec<&'static str> {
+    let mut topics = Vec::new();
+    for (topic, _, triggers, _) in topic_lines() {
+        if !topics.contains(&topic) && matches(&triggers, interests) {
+            topics.push(topic);
+        }
+    }
+    topics
+}
+
+/// The words of `topic`: those of the lines whose triggers appear in one
+/// of `interests` (a team's mascots, players and chants), then its general
+/// ones.
+pub fn topic_words(topic: &str, interests: &[String]) -> Vec<String> {
+    let lines: Vec<_> = topic_lines().filter(|(t, _, _, _)| *t == topic).collect();
+    let specific = lines
+        .iter()
+        .filter(|(_, first, triggers, _)| !first && matches(triggers, interests));
+    let general = lines.iter().filter(|(_, first, _, _)| *first);
+    specific
+        .chain(general)
+        .flat_map(|(_, _, _, words)| words.split_whitespace().map(String::from))
+        .collect()
+}
+
+/// Whether one of `interests` contains one of `triggers`.
+fn matches(triggers: &[&str], interests: &[String]) -> bool {
+    triggers
+        .iter()
+        .any(|trigger| interests.iter().any(|i| i.contains(trigger)))
+}
+
+/// Common keyboard walk patterns: the walks on `layout`, then the number
+/// runs, numpad patterns and repeats every layout shares.
+pub fn keyboard_patterns(layout: Layout) -> Vec<String> {
+    let mut patterns = layout.walks();
+    patterns.extend(
+        [
+            // Number runs
+            "123456", "1234567", "12345678", "123456789", "1234567890",
+            "0987654321", "987654321", "654321", "54321",
+            // Numpad patterns
+            "147258369", "159357", "789456123", "321654987",
+            // Repeats
+            "aaaaaa", "000000", "111111", "222222", "555555", "666666", "777777", "88888888",
+            "999999", "112233", "123123", "121212", "131313", "123321",
+            // Short runs
+            "1234", "4321",
+            // Other common patterns
+            "abcdef", "abcdefg", "abcdefgh", "abcd1234", "1234abcd",
+            "abc123", "123abc", "aaa111", "zzz999",
+        ]
+        .into_iter()
+        .map(String::from),
+    );
+    patterns
+}
+
+/// Short phrases people build passwords from, one word at a time; `{}`
+/// marks where a seed word goes.
+pub const PASSPHRASES: [&[&str]; 10] = [
+    &["i", "love", "{}"],
+    &["i", "love", "you", "{}"],
+    &["love", "{}"],
+    &["{}", "forever"],
+    &["{}", "rocks"],
+    &["{}", "is", "my", "life"],
+    &["{}", "is", "the", "best"],
+    &["{}", "my", "love"],
+    &["{}", "and", "{}"],
+    &["{}", "loves", "{}"],
+];
+
+/// Separators between passphrase words unless the profile or
+/// `--word-separator` sets its own.
+pub const PASSPHRASE_SEPARATORS: [&str; 4] = ["", " ", "-", "_"];
+
+/// Common numeric suffixes appended to words, with every year in `years`
+/// in full and the 2000s among them as two digits.
+pub fn numeric_suffixes(years: std::ops::RangeInclusive<u16>) -> Vec<String> {
     let mut suffixes = Vec::new();
 
     // Single digits
@@ -51,16 +167,16 @@ pub fn numeric_suffixes() -> Vec<String> {
         suffixes.push(format!("{}", n));
     }
     // Common triple digits
-    for &n in &[100, 111, 123, 321, 234, 420, 666, 777, 007, 911] {
-        suffixes.push(format!("{}", n));
+    for n in ["100", "111", "123", "321", "234", "420", "666", "777", "007", "911"] {
+        suffixes.push(n.to_string());
     }
-    // Years 1950-2026
-    for y in 1950..=2026 {
+    // Years
+    for y in years.clone() {
         suffixes.push(format!("{}", y));
     }
     // Short years
-    for y in 0..=26 {
-        suffixes.push(format!("{:02}", y));
+    for y in (*years.start()).max(2000)..=(*years.end()).min(2099) {
+        suffixes.push(format!("{:02}", y % 100));
     }
 
     suffixes
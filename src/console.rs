@@ -0,0 +1,30 @@
+//! Where status messages go.
+//!
+//! Normally stdout, but when stdout carries data (candidates piped out of
+//! `generate -o -`, a JSON report) they move to stderr so the data stays
+//! clean.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STDOUT_IS_DATA: AtomicBool = AtomicBool::new(false);
+
+/// Declare whether stdout carries data for the rest of the run.
+pub fn set_stdout_is_data(is_data: bool) {
+    STDOUT_IS_DATA.store(is_data, Ordering::Relaxed);
+}
+
+pub fn stdout_is_data() -> bool {
+    STDOUT_IS_DATA.load(Ordering::Relaxed)
+}
+
+/// `println!` for status messages, which must stay out of piped data.
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::console::stdout_is_data() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
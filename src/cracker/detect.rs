@@ -0,0 +1,152 @@
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use super::HashAlgorithm;
+use crate::wordlist;
+
+/// What a `crack` argument turned out to be.
+#[derive(Debug)]
+pub enum Target {
+    /// One or more hashes of a single algorithm.
+    Hashes {
+        hashes: Vec<String>,
+        algorithm: HashAlgorithm,
+    },
+    /// A WPA/WPA2 handshake capture (.cap/.pcap/.pcapng/.hccapx).
+    Handshake(PathBuf),
+    /// Recognized, but no verifier exists for it yet.
+    Unsupported(TargetKind),
+}
+
+/// Formats that can be recognized but not yet cracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    Shadow,
+    Zip,
+    Pdf,
+    Kdbx,
+}
+
+impl fmt::Display for TargetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shadow => write!(f, "shadow/crypt file"),
+            Self::Zip => write!(f, "zip archive"),
+            Self::Pdf => write!(f, "PDF document"),
+            Self::Kdbx => write!(f, "KeePass database"),
+        }
+    }
+}
+
+/// Sniff a `crack` argument: an existing path is inspected by magic bytes and
+/// content, anything else is treated as a literal hash string.
+///
+/// `algo` overrides detection for hashes (needed to pick NTLM over MD5).
+pub fn detect(input: &str, algo: Option<HashAlgorithm>) -> Result<Target> {
+    let path = Path::new(input);
+    if !path.is_file() {
+        let algorithm = match algo.or_else(|| guess_algorithm(input)) {
+            Some(a) => a,
+            None => bail!("'{}' is neither a file nor a recognized hash", input),
+        };
+        return Ok(Target::Hashes {
+            hashes: vec![input.to_string()],
+            algorithm,
+        });
+    }
+
+    let mut magic = [0u8; 8];
+    let n = std::fs::File::open(path)
+        .and_then(|mut f| f.read(&mut magic))
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if let Some(target) = sniff_magic(&magic[..n], path) {
+        return Ok(target);
+    }
+
+    let lines = wordlist::read_wordlist(path)?;
+    if lines.is_empty() {
+        bail!("{} is empty", path.display());
+    }
+    if lines.iter().any(|l| is_shadow_line(l)) {
+        return Ok(Target::Unsupported(TargetKind::Shadow));
+    }
+
+    let algorithm = match algo.or_else(|| guess_algorithm(&lines[0])) {
+        Some(a) => a,
+        None => bail!("Could not recognize hashes in {}; pass --algo", path.display()),
+    };
+    if algo.is_none() && lines.iter().any(|l| guess_algorithm(l) != Some(algorithm)) {
+        bail!("{} mixes hash types; pass --algo or split the file", path.display());
+    }
+    Ok(Target::Hashes {
+        hashes: lines,
+        algorithm,
+    })
+}
+
+/// Guess the algorithm of a single hash from its shape.
+///
+/// 32-hex hashes are reported as MD5; NTLM looks identical and needs `--algo`.
+pub fn guess_algorithm(hash: &str) -> Option<HashAlgorithm> {
+    let hash = hash.trim();
+    if ["$2a$", "$2b$", "$2y$"].iter().any(|p| hash.starts_with(p)) {
+        return Some(HashAlgorithm::Bcrypt);
+    }
+    if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hash.len() {
+        32 => Some(HashAlgorithm::Md5),
+        40 => Some(HashAlgorithm::Sha1),
+        64 => Some(HashAlgorithm::Sha256),
+        128 => Some(HashAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+fn sniff_magic(magic: &[u8], path: &Path) -> Option<Target> {
+    let handshake = || Some(Target::Handshake(path.to_path_buf()));
+    match magic {
+        [0xd4, 0xc3, 0xb2, 0xa1, ..] | [0xa1, 0xb2, 0xc3, 0xd4, ..] => handshake(), // pcap
+        [0x0a, 0x0d, 0x0d, 0x0a, ..] => handshake(),                               // pcapng
+        [b'H', b'C', b'P', b'X', ..] => handshake(),                               // hccapx
+        [b'P', b'K', 0x03, 0x04, ..] => Some(Target::Unsupported(TargetKind::Zip)),
+        [b'%', b'P', b'D', b'F', ..] => Some(Target::Unsupported(TargetKind::Pdf)),
+        [0x03, 0xd9, 0xa2, 0x9a, ..] => Some(Target::Unsupported(TargetKind::Kdbx)),
+        _ => None,
+    }
+}
+
+/// `user:$id$salt$hash:...` as found in /etc/shadow.
+fn is_shadow_line(line: &str) -> bool {
+    let mut fields = line.split(':');
+    matches!(
+        (fields.next(), fields.next()),
+        (Some(user), Some(hash)) if !user.is_empty() && hash.starts_with('$')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guess_algorithm() {
+        let md5 = "5f4dcc3b5aa765d61d8327deb882cf99";
+        assert!(matches!(guess_algorithm(md5), Some(HashAlgorithm::Md5)));
+        let sha1 = "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8";
+        assert!(matches!(guess_algorithm(sha1), Some(HashAlgorithm::Sha1)));
+        let bcrypt = "$2b$04$abcdefghijklmnopqrstuu";
+        assert!(matches!(guess_algorithm(bcrypt), Some(HashAlgorithm::Bcrypt)));
+        assert!(guess_algorithm("not-a-hash").is_none());
+    }
+
+    #[test]
+    fn test_shadow_line() {
+        assert!(is_shadow_line("root:$6$salt$abcdef:19000:0:99999:7:::"));
+        assert!(!is_shadow_line("5f4dcc3b5aa765d61d8327deb882cf99"));
+    }
+}
@@ -0,0 +1,477 @@
+//! Distributed cracking over plain TCP.
+//!
+//! The server owns the candidate stream and hands out fixed-size chunks;
+//! workers only need the algorithm, the remaining hashes and the chunk itself.
+//! Workers prove they belong to the run with a shared token before they see
+//! any hash, and every plaintext they report is hashed again on the server
+//! before it counts. The protocol is line-based:
+//!
+//! ```text
+//! worker: HELLO <token> <name>
+//! server: JOB <algo> | DENIED
+//! worker: NEXT
+//! server: CHUNK <id> <n_candidates> <n_hashes>, then hashes, then candidates
+//!         | WAIT (all chunks in flight, ask again) | DONE
+//! worker: RESULT <id> <n>, then n lines of <hash>\t<plaintext>
+//! ```
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+
+use super::{hash, progress_bar, CrackResult, HashAlgorithm};
+use crate::metrics::Metrics;
+
+type Candidates = Box<dyn Iterator<Item = String> + Send>;
+
+/// Most bytes of a greeting read before the token is checked; a real one
+/// is a token and a host name.
+const MAX_HELLO: u64 = 8192;
+
+/// How long a connection has to send its greeting.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shared server state: what's been handed out and what's come back.
+struct Coordinator {
+    algo: HashAlgorithm,
+    candidates: Candidates,
+    exhausted: bool,
+    next_id: u64,
+    /// Chunks returned by disconnected workers, handed out again first.
+    retry: Vec<(u64, Vec<String>)>,
+    in_flight: HashMap<u64, Vec<String>>,
+    remaining: Vec<String>,
+    results: Vec<CrackResult>,
+    tested: u64,
+    workers: usize,
+}
+
+enum Assignment {
+    Chunk(u64, Vec<String>),
+    Wait,
+    Done,
+}
+
+impl Coordinator {
+    fn is_done(&self) -> bool {
+        self.remaining.is_empty()
+            || (self.exhausted && self.retry.is_empty() && self.in_flight.is_empty())
+    }
+
+    fn assign(&mut self, chunk_size: usize) -> Assignment {
+        if self.remaining.is_empty() {
+            return Assignment::Done;
+        }
+        if let Some((id, chunk)) = self.retry.pop() {
+            self.in_flight.insert(id, chunk.clone());
+            return Assignment::Chunk(id, chunk);
+        }
+        if !self.exhausted {
+            let chunk: Vec<String> = self.candidates.by_ref().take(chunk_size).collect();
+            if !chunk.is_empty() {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.in_flight.insert(id, chunk.clone());
+                return Assignment::Chunk(id, chunk);
+            }
+            self.exhausted = true;
+        }
+        if self.in_flight.is_empty() {
+            Assignment::Done
+        } else {
+            Assignment::Wait
+        }
+    }
+
+    /// Record a returned chunk and the cracks claimed for it. Claims for
+    /// hashes that aren't outstanding, or whose plaintext doesn't hash to
+    /// them, are dropped; returns how many were.
+    fn complete(&mut self, id: u64, found: Vec<(String, String)>) -> usize {
+        if let Some(chunk) = self.in_flight.remove(&id) {
+            self.tested += chunk.len() as u64;
+        }
+        let mut rejected = 0;
+        for (hash, plaintext) in found {
+            if !hash::verify_digest(self.algo, &plaintext, &hash) {
+                rejected += 1;
+                continue;
+            }
+            if let Some(pos) = self.remaining.iter().position(|h| *h == hash) {
+                self.remaining.remove(pos);
+                self.results.push(CrackResult {
+                    hash,
+                    plaintext,
+                    algorithm: self.algo,
+                    found: None,
+                });
+            }
+        }
+        rejected
+    }
+
+    fn requeue(&mut self, ids: &[u64]) {
+        for id in ids {
+            if let Some(chunk) = self.in_flight.remove(id) {
+                self.retry.push((*id, chunk));
+            }
+        }
+    }
+}
+
+/// Run the coordinating server until every hash is cracked or the candidate
+/// stream is exhausted, then return the aggregated results. Only workers
+/// that present `token` are given work.
+pub fn serve(
+    bind: &str,
+    token: &str,
+    hashes: &[String],
+    algo: HashAlgorithm,
+    candidates: Candidates,
+    chunk_size: usize,
+    metrics: &Metrics,
+) -> Result<Vec<CrackResult>> {
+    let listener =
+        TcpListener::bind(bind).with_context(|| format!("Failed to bind {}", bind))?;
+    println!(
+        "{} Serving {} {} hash(es) on {} (chunk size {})",
+        ">>".cyan().bold(),
+        hashes.len(),
+        algo,
+        bind,
+        chunk_size
+    );
+
+    let remaining = match algo {
+        HashAlgorithm::Bcrypt => hashes.to_vec(),
+        _ => hashes.iter().map(|h| h.to_lowercase()).collect(),
+    };
+    let state = Arc::new((
+        Mutex::new(Coordinator {
+            algo,
+            candidates,
+            exhausted: false,
+            next_id: 0,
+            retry: Vec::new(),
+            in_flight: HashMap::new(),
+            remaining,
+            results: Vec::new(),
+            tested: 0,
+            workers: 0,
+        }),
+        Condvar::new(),
+    ));
+
+    let accept_state = Arc::clone(&state);
+    let token: Arc<str> = Arc::from(token);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let state = Arc::clone(&accept_state);
+            let token = Arc::clone(&token);
+            thread::spawn(move || {
+                let peer = stream
+                    .peer_addr()
+                    .map(|a| a.to_string())
+                    .unwrap_or_default();
+                if let Err(e) = handle_worker(stream, &state, &token, algo, chunk_size) {
+                    eprintln!("{} worker {}: {:#}", "WARNING".yellow().bold(), peer, e);
+                }
+            });
+        }
+    });
+
+    metrics.add_targets(hashes.len() as u64);
+    let pb = progress_bar();
+    let (lock, cvar) = &*state;
+    let mut coord = lock.lock().unwrap();
+    let (mut reported, mut counted) = (0, 0);
+    while !coord.is_done() {
+        coord = cvar.wait_timeout(coord, Duration::from_millis(500)).unwrap().0;
+        metrics.add_tested(coord.tested - counted);
+        metrics.add_cracked((coord.results.len() - reported) as u64);
+        metrics.set_workers(coord.workers as u64);
+        counted = coord.tested;
+        pb.set_position(coord.tested);
+        pb.set_message(format!(
+            "{} worker(s), {}/{} cracked",
+            coord.workers,
+            coord.results.len(),
+            hashes.len()
+        ));
+        for r in &coord.results[reported..] {
+            pb.println(format!(
+                "  {} Found: {} -> {}",
+                "✓".green().bold(),
+                r.hash.dimmed(),
+                r.plaintext.green().bold()
+            ));
+        }
+        reported = coord.results.len();
+    }
+    pb.finish_and_clear();
+
+    Ok(std::mem::take(&mut coord.results))
+}
+
+fn handle_worker(
+    stream: TcpStream,
+    state: &(Mutex<Coordinator>, Condvar),
+    token: &str,
+    algo: HashAlgorithm,
+    chunk_size: usize,
+) -> Result<()> {
+    let (lock, cvar) = state;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut owned: Vec<u64> = Vec::new();
+
+    // Nothing unbounded is read from a peer that hasn't shown the token
+    writer.set_read_timeout(Some(HELLO_TIMEOUT))?;
+    let hello = read_hello(&mut reader)?;
+    let Some(credentials) = hello.strip_prefix("HELLO ") else {
+        bail!("unexpected greeting");
+    };
+    let presented = credentials.split_whitespace().next().unwrap_or_default();
+    if !same_token(presented, token) {
+        writeln!(writer, "DENIED")?;
+        bail!("wrong token");
+    }
+    // Workers go quiet for as long as a chunk takes to crack
+    writer.set_read_timeout(None)?;
+    writeln!(writer, "JOB {}", algo.to_string().to_lowercase())?;
+    lock.lock().unwrap().workers += 1;
+
+    let outcome = serve_requests(&mut reader, &mut writer, state, chunk_size, &mut owned);
+
+    let mut coord = lock.lock().unwrap();
+    coord.workers -= 1;
+    coord.requeue(&owned);
+    cvar.notify_all();
+    outcome
+}
+
+fn serve_requests(
+    reader: &mut BufReader<TcpStream>,
+    writer: &mut TcpStream,
+    (lock, cvar): &(Mutex<Coordinator>, Condvar),
+    chunk_size: usize,
+    owned: &mut Vec<u64>,
+) -> Result<()> {
+    while let Some(line) = read_line(reader)? {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("NEXT") => {
+                let (assignment, remaining) = {
+                    let mut coord = lock.lock().unwrap();
+                    (coord.assign(chunk_size), coord.remaining.clone())
+                };
+                match assignment {
+                    Assignment::Chunk(id, chunk) => {
+                        owned.push(id);
+                        let mut msg =
+                            format!("CHUNK {} {} {}\n", id, chunk.len(), remaining.len());
+                        for line in remaining.iter().chain(&chunk) {
+                            msg.push_str(line);
+                            msg.push('\n');
+                        }
+                        writer.write_all(msg.as_bytes())?;
+                    }
+                    Assignment::Wait => writeln!(writer, "WAIT")?,
+                    Assignment::Done => {
+                        writeln!(writer, "DONE")?;
+                        return Ok(());
+                    }
+                }
+            }
+            Some("RESULT") => {
+                let id: u64 = parse_field(parts.next())?;
+                let n: usize = parse_field(parts.next())?;
+                if !owned.contains(&id) {
+                    bail!("RESULT for chunk {}, which this worker doesn't hold", id);
+                }
+                let mut found = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let line = read_line(reader)?.context("truncated RESULT")?;
+                    if let Some((hash, plaintext)) = line.split_once('\t') {
+                        found.push((hash.to_string(), plaintext.to_string()));
+                    }
+                }
+                let rejected = lock.lock().unwrap().complete(id, found);
+                owned.retain(|o| *o != id);
+                cvar.notify_all();
+                if rejected > 0 {
+                    eprintln!(
+                        "{} dropped {} claimed crack(s) that don't verify",
+                        "WARNING".yellow().bold(),
+                        rejected
+                    );
+                }
+            }
+            _ => bail!("unexpected message '{}'", line),
+        }
+    }
+    Ok(())
+}
+
+/// Connect to a server with its shared `token` and crack chunks until it
+/// reports DONE.
+pub fn work(server: &str, name: &str, token: &str) -> Result<()> {
+    let stream =
+        TcpStream::connect(server).with_context(|| format!("Failed to connect to {}", server))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    writeln!(writer, "HELLO {} {}", token, name)?;
+    let job = read_line(&mut reader)?.context("server closed the connection")?;
+    if job == "DENIED" {
+        bail!("{} refused the token", server);
+    }
+    let algo = match job.strip_prefix("JOB ").and_then(HashAlgorithm::from_str) {
+        Some(a) => a,
+        None => bail!("unexpected job line '{}'", job),
+    };
+    println!("{} Connected to {} ({} job)", ">>".cyan().bold(), server, algo);
+
+    let mut chunks = 0u64;
+    let mut tested = 0u64;
+    loop {
+        writeln!(writer, "NEXT")?;
+        let Some(line) = read_line(&mut reader)? else {
+            break;
+        };
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("CHUNK") => {
+                let id: u64 = parse_field(parts.next())?;
+                let n_candidates: usize = parse_field(parts.next())?;
+                let n_hashes: usize = parse_field(parts.next())?;
+                let hashes = read_lines(&mut reader, n_hashes)?;
+                let chunk = read_lines(&mut reader, n_candidates)?;
+
+                let found = hash::check_chunk(&hashes, algo, &chunk);
+                let mut msg = format!("RESULT {} {}\n", id, found.len());
+                for r in &found {
+                    println!(
+                        "  {} Found: {} -> {}",
+                        "✓".green().bold(),
+                        r.hash.dimmed(),
+                        r.plaintext.green().bold()
+                    );
+                    msg.push_str(&format!("{}\t{}\n", r.hash, r.plaintext));
+                }
+                writer.write_all(msg.as_bytes())?;
+
+                chunks += 1;
+                tested += chunk.len() as u64;
+            }
+            Some("WAIT") => thread::sleep(Duration::from_secs(1)),
+            Some("DONE") => break,
+            _ => bail!("unexpected message '{}'", line),
+        }
+    }
+
+    println!(
+        "{} Worker finished: {} chunk(s), {} candidates",
+        "DONE".green().bold(),
+        chunks,
+        tested
+    );
+    Ok(())
+}
+
+/// Compare tokens without stopping at the first difference, so response
+/// times don't give the token away a byte at a time.
+fn same_token(presented: &str, token: &str) -> bool {
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// The greeting line, reading no more than [`MAX_HELLO`] bytes of it.
+fn read_hello(reader: &mut impl BufRead) -> Result<String> {
+    Ok(read_line(&mut reader.take(MAX_HELLO))?.unwrap_or_default())
+}
+
+fn read_lines(reader: &mut impl BufRead, n: usize) -> Result<Vec<String>> {
+    (0..n)
+        .map(|_| read_line(reader)?.context("truncated message"))
+        .collect()
+}
+
+fn parse_field<T: std::str::FromStr>(field: Option<&str>) -> Result<T> {
+    field
+        .and_then(|f| f.parse().ok())
+        .context("malformed protocol line")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinator(hashes: &[&str]) -> Coordinator {
+        Coordinator {
+            algo: HashAlgorithm::Md5,
+            candidates: Box::new(std::iter::empty()),
+            exhausted: true,
+            next_id: 1,
+            retry: Vec::new(),
+            in_flight: HashMap::from([(0, vec!["john1990".to_string()])]),
+            remaining: hashes.iter().map(|h| h.to_string()).collect(),
+            results: Vec::new(),
+            tested: 0,
+            workers: 1,
+        }
+    }
+
+    #[test]
+    fn test_complete_verifies_claims() {
+        let target = hex::encode(hash::digest(HashAlgorithm::Md5, "john1990").unwrap());
+        let other = hex::encode(hash::digest(HashAlgorithm::Md5, "buddy").unwrap());
+        let mut coord = coordinator(&[&target, &other]);
+        let claims = vec![
+            (other.clone(), "john1990".to_string()),
+            ("0".repeat(32), "john1990".to_string()),
+            (target.clone(), "john1990".to_string()),
+        ];
+        assert_eq!(coord.complete(0, claims), 2);
+        assert_eq!(coord.remaining, [other]);
+        assert_eq!(coord.results.len(), 1);
+        assert_eq!(coord.results[0].plaintext, "john1990");
+        assert_eq!(coord.tested, 1);
+    }
+
+    #[test]
+    fn test_read_hello() {
+        let mut hello = std::io::Cursor::new(b"HELLO s3cret lab-pc-1\nNEXT\n".to_vec());
+        assert_eq!(read_hello(&mut hello).unwrap(), "HELLO s3cret lab-pc-1");
+        assert_eq!(read_line(&mut hello).unwrap().unwrap(), "NEXT");
+
+        // A greeting that never ends is cut off, not buffered
+        let mut endless = std::io::Cursor::new(vec![b'a'; 64 * 1024]);
+        assert_eq!(read_hello(&mut endless).unwrap().len() as u64, MAX_HELLO);
+    }
+
+    #[test]
+    fn test_same_token() {
+        assert!(same_token("s3cret", "s3cret"));
+        assert!(!same_token("s3cres", "s3cret"));
+        assert!(!same_token("s3cre", "s3cret"));
+        assert!(!same_token("", "s3cret"));
+    }
+}
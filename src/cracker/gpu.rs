@@ -0,0 +1,300 @@
+//! Optional GPU prefilter for fast hashes (built with `--features gpu`).
+//!
+//! Candidates are padded into a single 64-byte block on the host and the GPU
+//! runs one compression per candidate, flagging those whose digest equals a
+//! target. Flagged candidates, and any too long to fit one block, are then
+//! confirmed by the regular CPU path, so the GPU only ever narrows the work
+//! and can never report a match on its own.
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::mpsc;
+use std::task::{Context, Poll, Waker};
+
+use anyhow::{anyhow, Result};
+use wgpu::util::DeviceExt;
+
+use super::simd::{big_endian, pack_block};
+use super::HashAlgorithm;
+
+const COMMON_SHADER: &str = include_str!("shaders/common.wgsl");
+
+/// Invocations per workgroup; must match `@workgroup_size` in common.wgsl.
+const WORKGROUP_SIZE: usize = 64;
+
+/// A compiled compression pipeline plus the uploaded target digests.
+pub struct GpuFilter {
+    algo: HashAlgorithm,
+    adapter_name: String,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    targets: wgpu::Buffer,
+    n_targets: u32,
+    max_batch: usize,
+}
+
+impl GpuFilter {
+    /// Set up the GPU path for `algo` and `hashes`. Returns `None` when the
+    /// algorithm has no kernel, a target isn't a valid digest, or no adapter
+    /// is available — callers then stay on the CPU.
+    pub fn new(algo: HashAlgorithm, hashes: &[String]) -> Option<Self> {
+        let shader = algo_shader(algo)?;
+        let mut target_words = Vec::new();
+        for hash in hashes {
+            target_words.extend(digest_words(algo, hash)?);
+        }
+
+        let instance =
+            wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle_from_env());
+        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok()?;
+        let (device, queue) =
+            block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("password-guesser compress"),
+            source: wgpu::ShaderSource::Wgsl(format!("{}\n{}", shader, COMMON_SHADER).into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("password-guesser compress"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let targets = buffer_init(&device, &target_words, wgpu::BufferUsages::STORAGE);
+
+        let limits = device.limits();
+        let max_batch = (limits.max_compute_workgroups_per_dimension as usize * WORKGROUP_SIZE)
+            .min(limits.max_storage_buffer_binding_size as usize / 64);
+
+        Some(Self {
+            algo,
+            adapter_name: adapter.get_info().name,
+            device,
+            queue,
+            pipeline,
+            targets,
+            n_targets: hashes.len() as u32,
+            max_batch,
+        })
+    }
+
+    /// Name of the adapter the kernels run on.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// Indices into `chunk` that need a CPU check: GPU digest hits plus any
+    /// candidate too long for the single-block kernel.
+    pub fn filter(&self, chunk: &[String]) -> Result<Vec<usize>> {
+        let mut flagged = Vec::new();
+        for (offset, batch) in chunk.chunks(self.max_batch.max(1)).enumerate() {
+            let base = offset * self.max_batch.max(1);
+            flagged.extend(self.filter_batch(batch)?.into_iter().map(|i| base + i));
+        }
+        Ok(flagged)
+    }
+
+    fn filter_batch(&self, batch: &[String]) -> Result<Vec<usize>> {
+        let mut blocks = Vec::with_capacity(batch.len() * 16);
+        let mut overlong = Vec::new();
+        for (i, candidate) in batch.iter().enumerate() {
+            match pack_block(self.algo, candidate) {
+                Some(block) => blocks.extend(block),
+                None => {
+                    overlong.push(i);
+                    blocks.extend([0u32; 16]);
+                }
+            }
+        }
+
+        let device = &self.device;
+        let blocks = buffer_init(device, &blocks, wgpu::BufferUsages::STORAGE);
+        let params = buffer_init(
+            device,
+            &[batch.len() as u32, self.n_targets, 0, 0],
+            wgpu::BufferUsages::UNIFORM,
+        );
+        let hits_size = (batch.len() * 4) as u64;
+        let hits = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: hits_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: hits_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                bind(0, &blocks),
+                bind(1, &self.targets),
+                bind(2, &hits),
+                bind(3, &params),
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(batch.len().div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&hits, 0, &readback, 0, hits_size);
+        self.queue.submit([encoder.finish()]);
+
+        let (tx, rx) = mpsc::channel();
+        readback.map_async(wgpu::MapMode::Read, .., move |r| {
+            let _ = tx.send(r);
+        });
+        device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| anyhow!("GPU poll failed: {}", e))?;
+        rx.recv()?.map_err(|e| anyhow!("GPU readback failed: {}", e))?;
+
+        let mut flagged = overlong;
+        {
+            let view = readback
+                .get_mapped_range(..)
+                .map_err(|e| anyhow!("GPU readback failed: {}", e))?;
+            for (i, word) in view.chunks_exact(4).enumerate() {
+                if word != [0, 0, 0, 0] {
+                    flagged.push(i);
+                }
+            }
+        }
+        readback.unmap();
+        flagged.sort_unstable();
+        flagged.dedup();
+        Ok(flagged)
+    }
+}
+
+fn algo_shader(algo: HashAlgorithm) -> Option<&'static str> {
+    match algo {
+        HashAlgorithm::Md5 => Some(include_str!("shaders/md5.wgsl")),
+        HashAlgorithm::Sha1 => Some(include_str!("shaders/sha1.wgsl")),
+        HashAlgorithm::Sha256 => Some(include_str!("shaders/sha256.wgsl")),
+        HashAlgorithm::Ntlm => Some(include_str!("shaders/md4.wgsl")),
+        HashAlgorithm::Sha512 | HashAlgorithm::Bcrypt => None,
+    }
+}
+
+/// A hex digest as the state words the kernel produces.
+fn digest_words(algo: HashAlgorithm, hash: &str) -> Option<Vec<u32>> {
+    let bytes = hex::decode(hash.trim()).ok()?;
+    let expected = match algo {
+        HashAlgorithm::Md5 | HashAlgorithm::Ntlm => 16,
+        HashAlgorithm::Sha1 => 20,
+        HashAlgorithm::Sha256 => 32,
+        _ => return None,
+    };
+    if bytes.len() != expected {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|b| {
+                let b = b.try_into().unwrap();
+                if big_endian(algo) {
+                    u32::from_be_bytes(b)
+                } else {
+                    u32::from_le_bytes(b)
+                }
+            })
+            .collect(),
+    )
+}
+
+fn buffer_init(device: &wgpu::Device, words: &[u32], usage: wgpu::BufferUsages) -> wgpu::Buffer {
+    let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: None,
+        contents: &bytes,
+        usage,
+    })
+}
+
+fn bind(binding: u32, buffer: &wgpu::Buffer) -> wgpu::BindGroupEntry<'_> {
+    wgpu::BindGroupEntry {
+        binding,
+        resource: buffer.as_entire_binding(),
+    }
+}
+
+/// Drive a future to completion on the current thread. wgpu's native
+/// adapter/device requests resolve immediately, so this never really spins.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shaders_validate() {
+        use wgpu::naga;
+
+        for algo in [
+            HashAlgorithm::Md5,
+            HashAlgorithm::Sha1,
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Ntlm,
+        ] {
+            let source = format!("{}\n{}", algo_shader(algo).unwrap(), COMMON_SHADER);
+            let module = naga::front::wgsl::parse_str(&source)
+                .unwrap_or_else(|e| panic!("{}: {}", algo, e.emit_to_string(&source)));
+            naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::default(),
+            )
+            .validate(&module)
+            .unwrap_or_else(|e| panic!("{}: {:?}", algo, e));
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_cpu() {
+        // Only meaningful where a device exists; elsewhere the CPU path runs.
+        let chunk: Vec<String> = ["hello", "password", "Buddy1990", &"x".repeat(60)]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        for (algo, target) in [
+            (HashAlgorithm::Md5, "5f4dcc3b5aa765d61d8327deb882cf99"),
+            (HashAlgorithm::Sha1, "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8"),
+            (
+                HashAlgorithm::Sha256,
+                "5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8",
+            ),
+            (HashAlgorithm::Ntlm, "8846f7eaee8fb117ad06bdd830b7586c"),
+        ] {
+            let Some(gpu) = GpuFilter::new(algo, &[target.to_string()]) else {
+                return;
+            };
+            assert_eq!(gpu.filter(&chunk).unwrap(), vec![1, 3], "{}", algo);
+        }
+    }
+}
@@ -1,189 +1,414 @@
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Mutex;
+use std::time::Instant;
 
 use anyhow::{bail, Result};
 use colored::Colorize;
 use digest::Digest;
-use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 
-use super::{CrackResult, HashAlgorithm};
+use super::status::StatusTable;
+use super::{for_each_chunk, simd, CrackConfig, CrackResult, Found, HashAlgorithm, Pacer};
+use crate::status;
 
-/// Crack one or more hashes against a list of candidates.
-pub fn crack_hashes(
+/// Candidates handed to rayon per batch for fast hashes.
+const FAST_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Candidates per rayon task within a fast-hash chunk; a multiple of
+/// `simd::LANES`.
+const TASK_SIZE: usize = 1024;
+
+/// bcrypt is ~100/sec, so keep batches small enough to stop promptly.
+const BCRYPT_CHUNK_SIZE: usize = 256;
+
+/// Candidates per chunk the built-in loop uses for `algo`.
+pub fn chunk_size(algo: HashAlgorithm) -> usize {
+    match algo {
+        HashAlgorithm::Bcrypt => BCRYPT_CHUNK_SIZE,
+        _ => FAST_CHUNK_SIZE,
+    }
+}
+
+/// Crack one or more hashes against a stream of candidates.
+///
+/// Candidates are pulled from the iterator on a producer thread and hashed
+/// in parallel chunks, so only a chunk or two of them is held at a time;
+/// a generator's dedup set behind the iterator still grows with its output.
+/// `on_chunk` is called after every chunk with the number of candidates
+/// tested so far and the results found so far (used for checkpoints).
+/// A deadline in `config` ends the run between chunks.
+pub fn crack_hashes<I, F>(
     hashes: &[String],
     algo: HashAlgorithm,
-    candidates: &[String],
-) -> Result<Vec<CrackResult>> {
+    candidates: I,
+    config: &CrackConfig,
+    on_chunk: F,
+) -> Result<Vec<CrackResult>>
+where
+    I: Iterator<Item = String> + Send,
+    F: FnMut(u64, &[CrackResult]),
+{
     if hashes.is_empty() {
         bail!("No hashes provided");
     }
 
-    println!(
-        "{} Cracking {} hash(es) with {} algorithm using {} candidates...",
+    status!(
+        "{} Cracking {} hash(es) with {} algorithm...",
         ">>".cyan().bold(),
         hashes.len(),
         algo,
-        candidates.len()
     );
 
     match algo {
-        HashAlgorithm::Bcrypt => crack_bcrypt(hashes, candidates),
-        _ => crack_fast_hash(hashes, algo, candidates),
+        HashAlgorithm::Bcrypt => crack_bcrypt(hashes, candidates, config, on_chunk),
+        _ => crack_fast_hash(hashes, algo, candidates, config, on_chunk),
     }
 }
 
-/// Crack fast hashes (MD5, SHA1, SHA256, SHA512) using rayon.
-fn crack_fast_hash(
+/// Crack fast hashes (MD5, SHA1, SHA256, SHA512, NTLM) using rayon.
+///
+/// Each rayon task hashes `TASK_SIZE` candidates, `simd::LANES` at a time
+/// so short MD5/NTLM candidates take the multi-lane path, and collects its
+/// hits in its own buffer. Buffers are merged once per chunk, so workers
+/// share no state in the hot loop beyond one counter update per task.
+fn crack_fast_hash<I, F>(
     hashes: &[String],
     algo: HashAlgorithm,
-    candidates: &[String],
-) -> Result<Vec<CrackResult>> {
+    candidates: I,
+    config: &CrackConfig,
+    mut on_chunk: F,
+) -> Result<Vec<CrackResult>>
+where
+    I: Iterator<Item = String> + Send,
+    F: FnMut(u64, &[CrackResult]),
+{
     let target_hashes: Vec<String> = hashes.iter().map(|h| h.to_lowercase()).collect();
-    let total_hashes = target_hashes.len();
-    let found_count = AtomicUsize::new(0);
-    let all_found = AtomicBool::new(false);
-    let results: Mutex<Vec<CrackResult>> = Mutex::new(Vec::new());
-    let checked = AtomicUsize::new(0);
+    // Raw digest -> hex target: lookups are O(1) however many targets there
+    // are, and cracked targets are dropped between chunks.
+    let mut targets: HashMap<Vec<u8>, String> = target_hashes
+        .iter()
+        .filter_map(|t| Some((hex::decode(t.trim()).ok()?, t.clone())))
+        .collect();
+    let total_hashes = targets.len();
+    let mut results: Vec<CrackResult> = Vec::new();
 
-    let pb = ProgressBar::new(candidates.len() as u64);
-    pb.set_style(
-        ProgressStyle::with_template(
-            "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({per_sec}) {msg}",
-        )
-        .unwrap()
-        .progress_chars("█▉▊▋▌▍▎▏ "),
-    );
+    let mut tested = 0u64;
+    let started = Instant::now();
+
+    let table = StatusTable::new(&target_hashes, config.expected);
+    let mut pacer = Pacer::new(config);
+
+    let mut prefilter = Prefilter::new(algo, &target_hashes);
 
-    candidates.par_iter().for_each(|candidate| {
-        if all_found.load(Ordering::Relaxed) {
-            return;
+    let check = |live: &HashMap<Vec<u8>, String>, index: u64, candidate: &String, digest: &[u8]| {
+        let target = live.get(digest)?;
+        if config.double_check && !verify_digest(algo, candidate, target) {
+            table.println(format!(
+                "  {} Rejected unverifiable match: {} -> {}",
+                "!".yellow().bold(),
+                target.dimmed(),
+                candidate
+            ));
+            return None;
         }
+        Some(CrackResult {
+            hash: target.clone(),
+            plaintext: candidate.clone(),
+            algorithm: algo,
+            found: Some(Found {
+                index,
+                elapsed: started.elapsed(),
+            }),
+        })
+    };
 
-        let hash_hex = compute_hash(algo, candidate);
-
-        // Check against all target hashes
-        for target in &target_hashes {
-            if hash_hex == *target {
-                let mut res = results.lock().unwrap();
-                res.push(CrackResult {
-                    hash: target.clone(),
-                    plaintext: candidate.clone(),
-                    algorithm: algo,
-                });
-                let count = found_count.fetch_add(1, Ordering::Relaxed) + 1;
-                pb.println(format!(
-                    "  {} Found: {} -> {}",
-                    "✓".green().bold(),
-                    target.dimmed(),
-                    candidate.green().bold()
-                ));
-                if count >= total_hashes {
-                    all_found.store(true, Ordering::Relaxed);
-                }
+    for_each_chunk(candidates, FAST_CHUNK_SIZE, |chunk| {
+        let live = &targets;
+        let base = tested;
+        let mut hits: Vec<CrackResult> = match prefilter.flagged(chunk) {
+            Some(flagged) => {
+                let hits = flagged
+                    .par_iter()
+                    .filter_map(|&i| {
+                        let digest = digest(algo, &chunk[i])?;
+                        check(live, base + i as u64, &chunk[i], &digest)
+                    })
+                    .collect();
+                // The GPU screened the whole chunk
+                table.add(chunk.len() as u64);
+                hits
             }
-        }
+            None => chunk
+                .par_chunks(TASK_SIZE)
+                .enumerate()
+                .flat_map_iter(|(t, task)| {
+                    let mut hits = Vec::new();
+                    let first = base + (t * TASK_SIZE) as u64;
+                    for (g, group) in task.chunks(simd::LANES).enumerate() {
+                        let lane = first + (g * simd::LANES) as u64;
+                        simd::for_each_digest(algo, group, |i, digest| {
+                            hits.extend(check(live, lane + i as u64, &group[i], digest));
+                        });
+                    }
+                    table.add(task.len() as u64);
+                    hits
+                })
+                .collect(),
+        };
+        tested += chunk.len() as u64;
 
-        let prev = checked.fetch_add(1, Ordering::Relaxed);
-        if prev % 1000 == 0 {
-            pb.set_position(prev as u64);
+        // In stream order, so the first candidate to hit a target wins
+        hits.sort_by_key(|r| r.found.map(|f| f.index));
+        for r in hits {
+            let Ok(raw) = hex::decode(&r.hash) else {
+                continue;
+            };
+            // Another candidate in this chunk may have hit it first
+            if targets.remove(&raw).is_none() {
+                continue;
+            }
+            table.cracked(&r.hash, &r.plaintext);
+            table.println(format!(
+                "  {} Found: {} -> {}",
+                "✓".green().bold(),
+                r.hash.dimmed(),
+                r.plaintext.green().bold()
+            ));
+            results.push(r);
         }
+        on_chunk(tested, &results);
+        pacer.tick(&table, tested, results.len(), total_hashes) && results.len() < total_hashes
     });
 
-    pb.finish_and_clear();
+    table.finish();
 
-    let results = results.into_inner().unwrap();
     Ok(results)
 }
 
+/// Optional GPU stage in front of the CPU hash loop. Without the `gpu`
+/// feature, or without a usable device, every candidate goes to the CPU.
+struct Prefilter {
+    #[cfg(feature = "gpu")]
+    gpu: Option<super::gpu::GpuFilter>,
+}
+
+impl Prefilter {
+    #[cfg(feature = "gpu")]
+    fn new(algo: HashAlgorithm, targets: &[String]) -> Self {
+        let gpu = super::gpu::GpuFilter::new(algo, targets);
+        if let Some(ref gpu) = gpu {
+            status!("{} GPU prefilter on {}", ">>".cyan().bold(), gpu.adapter_name());
+        }
+        Self { gpu }
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    fn new(_algo: HashAlgorithm, _targets: &[String]) -> Self {
+        Self {}
+    }
+
+    /// Indices in `chunk` the CPU must check, or `None` to check all of them.
+    fn flagged(&mut self, chunk: &[String]) -> Option<Vec<usize>> {
+        #[cfg(feature = "gpu")]
+        if let Some(ref gpu) = self.gpu {
+            match gpu.filter(chunk) {
+                Ok(flagged) => return Some(flagged),
+                Err(e) => {
+                    eprintln!("{} {:#}; falling back to CPU", "WARNING".yellow().bold(), e);
+                    self.gpu = None;
+                }
+            }
+        }
+        #[cfg(not(feature = "gpu"))]
+        let _ = chunk;
+        None
+    }
+}
+
 /// Crack bcrypt hashes (much slower, uses bcrypt::verify).
-fn crack_bcrypt(hashes: &[String], candidates: &[String]) -> Result<Vec<CrackResult>> {
+fn crack_bcrypt<I, F>(
+    hashes: &[String],
+    candidates: I,
+    config: &CrackConfig,
+    mut on_chunk: F,
+) -> Result<Vec<CrackResult>>
+where
+    I: Iterator<Item = String> + Send,
+    F: FnMut(u64, &[CrackResult]),
+{
     let results: Mutex<Vec<CrackResult>> = Mutex::new(Vec::new());
     let total_hashes = hashes.len();
     let found_count = AtomicUsize::new(0);
     let all_found = AtomicBool::new(false);
+    // bcrypt is salted, so no lookup table; just stop verifying cracked targets
+    let solved: Vec<AtomicBool> = hashes.iter().map(|_| AtomicBool::new(false)).collect();
 
-    let pb = ProgressBar::new(candidates.len() as u64);
-    pb.set_style(
-        ProgressStyle::with_template(
-            "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({per_sec}) {msg}",
-        )
-        .unwrap()
-        .progress_chars("█▉▊▋▌▍▎▏ "),
-    );
-    pb.set_message("(bcrypt is slow ~100/sec)");
+    let mut tested = 0u64;
+    let started = Instant::now();
 
-    candidates.par_iter().enumerate().for_each(|(i, candidate)| {
-        if all_found.load(Ordering::Relaxed) {
-            return;
-        }
+    let table = StatusTable::new(hashes, config.expected).with_note("(bcrypt is slow ~100/sec)");
+    let mut pacer = Pacer::new(config);
+
+    for_each_chunk(candidates, BCRYPT_CHUNK_SIZE, |chunk| {
+        let base = tested;
+        chunk.par_iter().enumerate().for_each(|(i, candidate)| {
+            if all_found.load(Ordering::Relaxed) {
+                return;
+            }
 
-        for target in hashes {
-            if let Ok(true) = bcrypt::verify(candidate, target) {
-                let mut res = results.lock().unwrap();
-                res.push(CrackResult {
-                    hash: target.clone(),
-                    plaintext: candidate.clone(),
-                    algorithm: HashAlgorithm::Bcrypt,
-                });
-                let count = found_count.fetch_add(1, Ordering::Relaxed) + 1;
-                pb.println(format!(
-                    "  {} Found: {} -> {}",
-                    "✓".green().bold(),
-                    target.dimmed(),
-                    candidate.green().bold()
-                ));
-                if count >= total_hashes {
-                    all_found.store(true, Ordering::Relaxed);
+            for (target, solved) in hashes.iter().zip(&solved) {
+                if solved.load(Ordering::Relaxed) {
+                    continue;
+                }
+                if let Ok(true) = bcrypt::verify(candidate, target) {
+                    if solved.swap(true, Ordering::Relaxed) {
+                        continue;
+                    }
+                    let mut res = results.lock().unwrap();
+                    res.push(CrackResult {
+                        hash: target.clone(),
+                        plaintext: candidate.clone(),
+                        algorithm: HashAlgorithm::Bcrypt,
+                        found: Some(Found {
+                            index: base + i as u64,
+                            elapsed: started.elapsed(),
+                        }),
+                    });
+                    let count = found_count.fetch_add(1, Ordering::Relaxed) + 1;
+                    table.cracked(target, candidate);
+                    table.println(format!(
+                        "  {} Found: {} -> {}",
+                        "✓".green().bold(),
+                        target.dimmed(),
+                        candidate.green().bold()
+                    ));
+                    if count >= total_hashes {
+                        all_found.store(true, Ordering::Relaxed);
+                    }
                 }
             }
-        }
 
-        if i % 10 == 0 {
-            pb.set_position(i as u64);
-        }
+            table.add(1);
+        });
+        tested += chunk.len() as u64;
+        let res = results.lock().unwrap();
+        on_chunk(tested, &res);
+        pacer.tick(&table, tested, res.len(), total_hashes) && !all_found.load(Ordering::Relaxed)
     });
 
-    pb.finish_and_clear();
+    table.finish();
 
     let results = results.into_inner().unwrap();
     Ok(results)
 }
 
-/// Compute the hex-encoded hash of a candidate.
-fn compute_hash(algo: HashAlgorithm, input: &str) -> String {
-    match algo {
-        HashAlgorithm::Md5 => {
-            let mut hasher = md5::Md5::new();
-            hasher.update(input.as_bytes());
-            hex::encode(hasher.finalize())
-        }
-        HashAlgorithm::Sha1 => {
-            let mut hasher = sha1::Sha1::new();
-            hasher.update(input.as_bytes());
-            hex::encode(hasher.finalize())
-        }
-        HashAlgorithm::Sha256 => {
-            let mut hasher = sha2::Sha256::new();
-            hasher.update(input.as_bytes());
-            hex::encode(hasher.finalize())
-        }
-        HashAlgorithm::Sha512 => {
-            let mut hasher = sha2::Sha512::new();
-            hasher.update(input.as_bytes());
-            hex::encode(hasher.finalize())
-        }
+/// Test one batch of candidates against `hashes` in parallel, quietly.
+/// Used by distributed workers, which report results to the server.
+pub fn check_chunk(hashes: &[String], algo: HashAlgorithm, chunk: &[String]) -> Vec<CrackResult> {
+    if algo == HashAlgorithm::Bcrypt {
+        return chunk
+            .par_iter()
+            .flat_map_iter(|candidate| {
+                hashes
+                    .iter()
+                    .filter(|target| bcrypt::verify(candidate, target).unwrap_or(false))
+                    .map(|target| CrackResult {
+                        hash: target.clone(),
+                        plaintext: candidate.clone(),
+                        algorithm: algo,
+                        found: None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+
+    let targets: HashSet<String> = hashes.iter().map(|h| h.to_lowercase()).collect();
+    chunk
+        .par_iter()
+        .filter_map(|candidate| {
+            let hash_hex = compute_hash(algo, candidate);
+            targets.contains(&hash_hex).then(|| CrackResult {
+                hash: hash_hex,
+                plaintext: candidate.clone(),
+                algorithm: algo,
+                found: None,
+            })
+        })
+        .collect()
+}
+
+/// Confirm a match: decode the target to raw bytes and compare against a
+/// fresh digest of the candidate's exact input bytes, bypassing the
+/// hex/lowercase string path of the hot loop.
+///
+/// How independent the digest is depends on the algorithm. SHA-1 is
+/// recomputed by the multi-lane compression in [`simd`], not the library
+/// the hot loop uses. MD5 and NTLM matches mostly come from the multi-lane
+/// code, so the library checks them. SHA-256 and SHA-512, and MD5/NTLM
+/// candidates too long for one block, are hashed by the same library that
+/// found them, so only the comparison is new.
+pub fn verify_digest(algo: HashAlgorithm, candidate: &str, target: &str) -> bool {
+    if algo == HashAlgorithm::Bcrypt {
+        return bcrypt::verify(candidate, target).unwrap_or(false);
+    }
+    let Ok(expected) = hex::decode(target.trim()) else {
+        return false;
+    };
+    if algo == HashAlgorithm::Sha1 {
+        return simd::sha1_message(candidate.as_bytes())[..] == expected[..];
+    }
+    digest(algo, candidate).is_some_and(|actual| actual == expected)
+}
+
+/// Raw digest of a candidate's exact input bytes, computed the scalar way;
+/// `None` for bcrypt, whose output depends on a salt.
+pub fn digest(algo: HashAlgorithm, candidate: &str) -> Option<Vec<u8>> {
+    Some(match algo {
+        HashAlgorithm::Md5 => md5::Md5::digest(candidate.as_bytes()).to_vec(),
+        HashAlgorithm::Sha1 => sha1::Sha1::digest(candidate.as_bytes()).to_vec(),
+        HashAlgorithm::Sha256 => sha2::Sha256::digest(candidate.as_bytes()).to_vec(),
+        HashAlgorithm::Sha512 => sha2::Sha512::digest(candidate.as_bytes()).to_vec(),
         HashAlgorithm::Ntlm => {
-            let utf16le: Vec<u8> = input.encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
-            let mut hasher = md4::Md4::new();
-            hasher.update(&utf16le);
-            hex::encode(hasher.finalize())
+            let utf16le: Vec<u8> = candidate.encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+            md4::Md4::digest(&utf16le).to_vec()
         }
-        HashAlgorithm::Bcrypt => {
-            // bcrypt doesn't produce a hex hash for comparison
-            unreachable!("bcrypt uses verify, not hash comparison")
+        HashAlgorithm::Bcrypt => return None,
+    })
+}
+
+/// Where a salt joins the password, as in hashcat's `md5($pass.$salt)`
+/// (`-m 10`) and `md5($salt.$pass)` (`-m 20`) families.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaltPosition {
+    Append,
+    Prepend,
+}
+
+/// Confirm a claimed result: `plaintext`, joined with `salt` if there is
+/// one, hashes to `target`. bcrypt carries its own salt; NTLM has none.
+pub fn verify(
+    algo: HashAlgorithm,
+    target: &str,
+    plaintext: &str,
+    salt: Option<(&str, SaltPosition)>,
+) -> Result<bool> {
+    let input = match salt {
+        None => plaintext.to_string(),
+        Some(_) if matches!(algo, HashAlgorithm::Bcrypt | HashAlgorithm::Ntlm) => {
+            bail!("{} hashes don't take a separate salt", algo)
         }
-    }
+        Some((salt, SaltPosition::Append)) => format!("{}{}", plaintext, salt),
+        Some((salt, SaltPosition::Prepend)) => format!("{}{}", salt, plaintext),
+    };
+    Ok(verify_digest(algo, &input, target))
+}
+
+/// Compute the hex-encoded hash of a candidate.
+fn compute_hash(algo: HashAlgorithm, input: &str) -> String {
+    // bcrypt doesn't produce a hex hash for comparison
+    hex::encode(digest(algo, input).expect("bcrypt uses verify, not hash comparison"))
 }
 
 #[cfg(test)]
@@ -196,4 +421,72 @@ mod tests {
         let result = compute_hash(HashAlgorithm::Ntlm, "password");
         assert_eq!(result, "8846f7eaee8fb117ad06bdd830b7586c");
     }
+
+    #[test]
+    fn test_verify_digest() {
+        let md5 = "5f4dcc3b5aa765d61d8327deb882cf99";
+        assert!(verify_digest(HashAlgorithm::Md5, "password", md5));
+        assert!(verify_digest(HashAlgorithm::Md5, "password", &md5.to_uppercase()));
+        assert!(!verify_digest(HashAlgorithm::Md5, "Password", md5));
+        assert!(verify_digest(HashAlgorithm::Ntlm, "password", "8846f7eaee8fb117ad06bdd830b7586c"));
+        let sha1 = "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8";
+        assert!(verify_digest(HashAlgorithm::Sha1, "password", sha1));
+        assert!(!verify_digest(HashAlgorithm::Sha1, "Password", sha1));
+    }
+
+    #[test]
+    fn test_verify_salted() {
+        // md5("password" . "salt") and md5("salt" . "password")
+        let appended = "b305cadbb3bce54f3aa59c64fec00dea";
+        let prepended = "67a1e09bb1f83f5007dc119c14d663aa";
+        let md5 = |target, salt| verify(HashAlgorithm::Md5, target, "password", salt).unwrap();
+        assert!(md5(appended, Some(("salt", SaltPosition::Append))));
+        assert!(md5(prepended, Some(("salt", SaltPosition::Prepend))));
+        assert!(!md5(appended, Some(("salt", SaltPosition::Prepend))));
+        assert!(!md5(appended, None));
+        let salt = Some(("salt", SaltPosition::Append));
+        assert!(verify(HashAlgorithm::Ntlm, "x", "pw", salt).is_err());
+    }
+
+    #[test]
+    fn test_many_targets_each_reported_once() {
+        let mut hashes: Vec<String> = (0..1000).map(|i| format!("{:032x}", i)).collect();
+        for word in ["alpha", "bravo"] {
+            hashes.push(compute_hash(HashAlgorithm::Md5, word).to_uppercase());
+        }
+        let candidates = ["alpha", "zulu", "alpha", "bravo", "bravo"].map(String::from);
+
+        let results = crack_hashes(
+            &hashes,
+            HashAlgorithm::Md5,
+            candidates.into_iter(),
+            &CrackConfig::default(),
+            |_, _| {},
+        )
+        .unwrap();
+        let mut found: Vec<&str> = results.iter().map(|r| r.plaintext.as_str()).collect();
+        found.sort();
+        assert_eq!(found, vec!["alpha", "bravo"]);
+    }
+
+    #[test]
+    fn test_first_hit_across_tasks_wins() {
+        // The same plaintext twice, in two different tasks of one chunk
+        let target = compute_hash(HashAlgorithm::Md5, "hit");
+        let mut candidates: Vec<String> = (0..3 * TASK_SIZE).map(|i| format!("c{}", i)).collect();
+        candidates[TASK_SIZE + 5] = "hit".to_string();
+        candidates[2 * TASK_SIZE + 1] = "hit".to_string();
+
+        let results = crack_hashes(
+            &[target],
+            HashAlgorithm::Md5,
+            candidates.into_iter(),
+            &CrackConfig::default(),
+            |_, _| {},
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        let found = results[0].found.unwrap();
+        assert_eq!(found.index, (TASK_SIZE + 5) as u64);
+    }
 }
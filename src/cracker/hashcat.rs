@@ -0,0 +1,184 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+
+use super::wifi::command_exists;
+use super::{CrackResult, HashAlgorithm};
+use crate::status;
+
+/// Crack hashes by piping candidates into a hashcat child process.
+///
+/// hashcat reads the wordlist from stdin in straight mode (`-a 0`) and
+/// writes `hash:plain` lines to an outfile, which is parsed back into
+/// [`CrackResult`]s once it exits.
+pub fn crack_hashes<I>(
+    hashes: &[String],
+    algo: HashAlgorithm,
+    candidates: I,
+) -> Result<Vec<CrackResult>>
+where
+    I: Iterator<Item = String>,
+{
+    if !command_exists("hashcat") {
+        bail!(
+            "hashcat not found. Install it:\n\
+             - macOS: brew install hashcat\n\
+             - Ubuntu/Debian: sudo apt install hashcat\n\
+             - Arch: sudo pacman -S hashcat\n\
+             - Or download from https://hashcat.net/hashcat/"
+        );
+    }
+
+    let (hashfile, file) = TempFile::create("hashes")?;
+    let mut writer = BufWriter::new(file);
+    for hash in hashes {
+        writeln!(writer, "{}", hash)?;
+    }
+    writer.flush()?;
+    drop(writer);
+    let (outfile, _) = TempFile::create("out")?;
+
+    status!(
+        "{} Cracking {} hash(es) with hashcat -m {} ({})...",
+        ">>".cyan().bold(),
+        hashes.len(),
+        algo.hashcat_mode(),
+        algo
+    );
+
+    let mut child = Command::new("hashcat")
+        .arg("-m")
+        .arg(algo.hashcat_mode().to_string())
+        .arg("-a")
+        .arg("0")
+        .arg("--potfile-disable")
+        .arg("--quiet")
+        .arg("--outfile")
+        .arg(outfile.0.as_os_str())
+        .arg("--outfile-format")
+        .arg("1,2")
+        .arg(hashfile.0.as_os_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context("Failed to execute hashcat")?;
+
+    let stdin = child.stdin.take().context("Failed to open hashcat stdin")?;
+    let mut writer = BufWriter::new(stdin);
+    let mut fed = 0u64;
+    for candidate in candidates {
+        // hashcat closes stdin early once every hash is cracked
+        if writeln!(writer, "{}", candidate).is_err() {
+            break;
+        }
+        fed += 1;
+    }
+    // Dropping the writer closes stdin so hashcat sees end of input
+    let _ = writer.flush();
+    drop(writer);
+
+    let status = child.wait().context("Failed to wait for hashcat")?;
+
+    // Exit codes: 0 = cracked, 1 = exhausted, anything else is an error
+    if !matches!(status.code(), Some(0) | Some(1)) {
+        bail!("hashcat exited with code {}", status.code().unwrap_or(-1));
+    }
+    status!("{} Fed {} candidates to hashcat", ">>".cyan().bold(), fed);
+
+    let written = std::fs::read(&outfile.0).context("Failed to read the hashcat outfile")?;
+    Ok(parse_outfile(&String::from_utf8_lossy(&written), algo))
+}
+
+/// A file in the temp directory, created new for one hashcat run and
+/// removed when dropped, however the run ends.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Create an empty file with the extension `ext`, named after the
+    /// process and a counter so concurrent runs never share one.
+    fn create(ext: &str) -> Result<(Self, File)> {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+
+        loop {
+            let n = NEXT.fetch_add(1, Ordering::Relaxed);
+            let name = format!("password_guesser_{}_{}.{}", std::process::id(), n, ext);
+            let path = std::env::temp_dir().join(name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok((Self(path), file)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Parse `hash:plain` outfile lines. Hashes never contain ':', plaintexts may,
+/// though hashcat writes those, and any with non-ASCII bytes, as `$HEX[...]`.
+/// Only the line ending is stripped: spaces around a plaintext are part of it.
+fn parse_outfile(text: &str, algo: HashAlgorithm) -> Vec<CrackResult> {
+    let mut results: Vec<CrackResult> = Vec::new();
+    for line in text.lines() {
+        if let Some((hash, plaintext)) = line.split_once(':') {
+            if !results.iter().any(|r| r.hash == hash) {
+                results.push(CrackResult {
+                    hash: hash.to_string(),
+                    plaintext: unhex(plaintext),
+                    algorithm: algo,
+                    found: None,
+                });
+            }
+        }
+    }
+    results
+}
+
+/// Decode hashcat's `$HEX[...]` form. Anything else, or bytes that are not
+/// UTF-8, comes back as written.
+fn unhex(plaintext: &str) -> String {
+    plaintext
+        .strip_prefix("$HEX[")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .and_then(|digits| hex::decode(digits).ok())
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_else(|| plaintext.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_outfile() {
+        let text = [
+            "5f4dcc3b5aa765d61d8327deb882cf99:password\n",
+            "5f4dcc3b5aa765d61d8327deb882cf99:password\n",
+            // As hashcat writes "a:b" and "pässword"
+            "2d5a5ff5d4f7c14e1d9e2a4e8dde3c5b:$HEX[613a62]\n",
+            "0a33b0f5e3c4a2b9d1f7e6c8b5a4d3e2:$HEX[70c3a4737377c3b67264]\n",
+            "1b2c3d4e5f60718293a4b5c6d7e8f901:$HEX[70ff]\n",
+            "0cbc6611f5540bd0809a388dc95a615b: pass word \r\n",
+        ]
+        .concat();
+        let results = parse_outfile(&text, HashAlgorithm::Md5);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].plaintext, "password");
+        assert_eq!(results[1].plaintext, "a:b");
+        assert_eq!(results[2].plaintext, "p\u{e4}ssw\u{f6}rd");
+        assert_eq!(results[3].plaintext, "$HEX[70ff]");
+        assert_eq!(results[4].plaintext, " pass word ");
+    }
+}
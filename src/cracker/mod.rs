@@ -1,10 +1,28 @@
+pub mod detect;
+pub mod distributed;
+#[cfg(feature = "gpu")]
+pub mod gpu;
 pub mod hash;
+pub mod hashcat;
+pub mod simd;
+pub mod status;
 pub mod wifi;
+pub mod wpa;
 
 use std::fmt;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::estimate;
+use crate::status;
+use status::StatusTable;
 
 /// Supported hash algorithms.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HashAlgorithm {
     Md5,
     Sha1,
@@ -15,6 +33,7 @@ pub enum HashAlgorithm {
 }
 
 impl HashAlgorithm {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "md5" => Some(Self::Md5),
@@ -26,6 +45,18 @@ impl HashAlgorithm {
             _ => None,
         }
     }
+
+    /// hashcat `-m` hash-mode for this algorithm.
+    pub fn hashcat_mode(&self) -> u32 {
+        match self {
+            Self::Md5 => 0,
+            Self::Sha1 => 100,
+            Self::Sha256 => 1400,
+            Self::Sha512 => 1700,
+            Self::Bcrypt => 3200,
+            Self::Ntlm => 1000,
+        }
+    }
 }
 
 impl fmt::Display for HashAlgorithm {
@@ -41,12 +72,98 @@ impl fmt::Display for HashAlgorithm {
     }
 }
 
+/// Options for the hash cracking loop.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrackConfig {
+    /// Re-verify every fast-hash match with a byte-level recomputation
+    /// before reporting it.
+    pub double_check: bool,
+    /// Stop after the chunk in flight at this time, as if the candidates
+    /// had run out, so the reported position stays resumable.
+    pub deadline: Option<Instant>,
+    /// Print a one-line status snapshot this often.
+    pub status_interval: Option<Duration>,
+    /// Candidates the run is expected to test, for the progress ETA.
+    pub expected: Option<u64>,
+}
+
+impl CrackConfig {
+    /// Whether the deadline, if any, has passed.
+    pub fn out_of_time(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+}
+
+/// Wall-clock side of a cracking loop: status snapshots and the deadline.
+struct Pacer {
+    started: Instant,
+    next_status: Option<Instant>,
+    config: CrackConfig,
+}
+
+impl Pacer {
+    fn new(config: &CrackConfig) -> Self {
+        let started = Instant::now();
+        Self {
+            started,
+            next_status: config.status_interval.map(|i| started + i),
+            config: *config,
+        }
+    }
+
+    /// Print a snapshot if one is due; false once the
+    /// deadline has passed.
+    fn tick(&mut self, table: &StatusTable, tested: u64, cracked: usize, targets: usize) -> bool {
+        let now = Instant::now();
+        if let (Some(due), Some(interval)) = (self.next_status, self.config.status_interval) {
+            if now >= due {
+                let elapsed = now.duration_since(self.started).as_secs_f64();
+                let left = match self.config.deadline {
+                    Some(d) => format!(
+                        ", {} left",
+                        estimate::format_duration(d.saturating_duration_since(now).as_secs_f64())
+                    ),
+                    None => String::new(),
+                };
+                // Unlike println above the table, this also reaches logs when
+                // stderr isn't a terminal
+                table.suspend(|| {
+                    status!(
+                        "  {} {}: {} tested ({}), {}/{} cracked{}",
+                        "◷".cyan(),
+                        estimate::format_duration(elapsed),
+                        tested,
+                        estimate::format_rate(tested as f64 / elapsed),
+                        cracked,
+                        targets,
+                        left
+                    )
+                });
+                self.next_status = Some(due + interval);
+            }
+        }
+        !self.config.out_of_time()
+    }
+}
+
 /// Result of cracking a single hash.
 #[derive(Debug)]
 pub struct CrackResult {
     pub hash: String,
     pub plaintext: String,
     pub algorithm: HashAlgorithm,
+    /// Where and when the built-in loop found it; unknown for matches
+    /// reported by hashcat, distributed workers or an earlier session.
+    pub found: Option<Found>,
+}
+
+/// Where a plaintext sat in the candidate stream and when it turned up.
+#[derive(Debug, Clone, Copy)]
+pub struct Found {
+    /// Position in the stream the loop was given, counted from 0.
+    pub index: u64,
+    /// Time since the loop started.
+    pub elapsed: Duration,
 }
 
 impl fmt::Display for CrackResult {
@@ -54,3 +171,46 @@ impl fmt::Display for CrackResult {
         write!(f, "{} -> {} ({})", self.hash, self.plaintext, self.algorithm)
     }
 }
+
+/// Feed `candidates` to `process` in chunks, generating the next chunk on a
+/// background thread while the current one is being hashed. Stops early once
+/// `process` returns false.
+pub fn for_each_chunk<I, F>(candidates: I, chunk_size: usize, mut process: F)
+where
+    I: Iterator<Item = String> + Send,
+    F: FnMut(&[String]) -> bool,
+{
+    let (tx, rx) = mpsc::sync_channel::<Vec<String>>(2);
+
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            let mut candidates = candidates;
+            loop {
+                let chunk: Vec<String> = candidates.by_ref().take(chunk_size).collect();
+                if chunk.is_empty() || tx.send(chunk).is_err() {
+                    break;
+                }
+            }
+        });
+
+        for chunk in rx.iter() {
+            if !process(&chunk) {
+                break;
+            }
+        }
+        // Dropping the receiver unblocks the producer if we stopped early.
+        drop(rx);
+    });
+}
+
+/// Spinner for open-ended candidate streams.
+pub fn progress_bar() -> ProgressBar {
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::with_template(
+            "{spinner:.green} [{elapsed_precise}] {pos} candidates ({per_sec}) {msg}",
+        )
+        .unwrap(),
+    );
+    pb
+}
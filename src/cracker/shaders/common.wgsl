@@ -0,0 +1,48 @@
+// Shared entry point. Each invocation compresses one pre-padded 64-byte
+// block (16 words, endianness already fixed up on the host) and flags it if
+// the digest equals any target. `compress` and DIGEST_WORDS come from the
+// algorithm-specific source prepended to this file.
+
+@group(0) @binding(0) var<storage, read> blocks: array<u32>;
+@group(0) @binding(1) var<storage, read> targets: array<u32>;
+@group(0) @binding(2) var<storage, read_write> hits: array<u32>;
+// x = candidate count, y = target count
+@group(0) @binding(3) var<uniform> params: vec4<u32>;
+
+fn rotl(x: u32, n: u32) -> u32 {
+    return (x << n) | (x >> (32u - n));
+}
+
+fn rotr(x: u32, n: u32) -> u32 {
+    return (x >> n) | (x << (32u - n));
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.x) {
+        return;
+    }
+
+    var m: array<u32, 16>;
+    for (var k = 0u; k < 16u; k++) {
+        m[k] = blocks[i * 16u + k];
+    }
+    let digest = compress(m);
+
+    var hit = 0u;
+    for (var t = 0u; t < params.y; t++) {
+        var equal = true;
+        for (var k = 0u; k < DIGEST_WORDS; k++) {
+            if (targets[t * DIGEST_WORDS + k] != digest[k]) {
+                equal = false;
+                break;
+            }
+        }
+        if (equal) {
+            hit = 1u;
+            break;
+        }
+    }
+    hits[i] = hit;
+}
@@ -0,0 +1,46 @@
+// MD4 single-block compression (NTLM = MD4 over UTF-16LE).
+
+const DIGEST_WORDS: u32 = 4u;
+
+var<private> MD4_ORDER: array<u32, 48> = array<u32, 48>(
+    0u, 1u, 2u, 3u, 4u, 5u, 6u, 7u, 8u, 9u, 10u, 11u, 12u, 13u, 14u, 15u,
+    0u, 4u, 8u, 12u, 1u, 5u, 9u, 13u, 2u, 6u, 10u, 14u, 3u, 7u, 11u, 15u,
+    0u, 8u, 4u, 12u, 2u, 10u, 6u, 14u, 1u, 9u, 5u, 13u, 3u, 11u, 7u, 15u,
+);
+
+var<private> MD4_SHIFT: array<u32, 12> = array<u32, 12>(
+    3u, 7u, 11u, 19u,
+    3u, 5u, 9u, 13u,
+    3u, 9u, 11u, 15u,
+);
+
+fn compress(m: array<u32, 16>) -> array<u32, 4> {
+    var w = m;
+    var a = 0x67452301u;
+    var b = 0xefcdab89u;
+    var c = 0x98badcfeu;
+    var d = 0x10325476u;
+
+    for (var i = 0u; i < 48u; i++) {
+        let round = i / 16u;
+        var f: u32;
+        var k: u32;
+        if (round == 0u) {
+            f = (b & c) | (~b & d);
+            k = 0u;
+        } else if (round == 1u) {
+            f = (b & c) | (b & d) | (c & d);
+            k = 0x5a827999u;
+        } else {
+            f = b ^ c ^ d;
+            k = 0x6ed9eba1u;
+        }
+        let t = rotl(a + f + w[MD4_ORDER[i]] + k, MD4_SHIFT[round * 4u + i % 4u]);
+        a = d;
+        d = c;
+        c = b;
+        b = t;
+    }
+
+    return array<u32, 4>(a + 0x67452301u, b + 0xefcdab89u, c + 0x98badcfeu, d + 0x10325476u);
+}
@@ -0,0 +1,51 @@
+// SHA-1 single-block compression.
+
+const DIGEST_WORDS: u32 = 5u;
+
+fn compress(m: array<u32, 16>) -> array<u32, 5> {
+    var w = m;
+    var a = 0x67452301u;
+    var b = 0xefcdab89u;
+    var c = 0x98badcfeu;
+    var d = 0x10325476u;
+    var e = 0xc3d2e1f0u;
+
+    for (var i = 0u; i < 80u; i++) {
+        // Message schedule in a 16-word ring: W[i-3], W[i-8], W[i-14], W[i-16]
+        if (i >= 16u) {
+            w[i & 15u] = rotl(
+                w[(i + 13u) & 15u] ^ w[(i + 8u) & 15u] ^ w[(i + 2u) & 15u] ^ w[i & 15u],
+                1u,
+            );
+        }
+        var f: u32;
+        var k: u32;
+        if (i < 20u) {
+            f = (b & c) | (~b & d);
+            k = 0x5a827999u;
+        } else if (i < 40u) {
+            f = b ^ c ^ d;
+            k = 0x6ed9eba1u;
+        } else if (i < 60u) {
+            f = (b & c) | (b & d) | (c & d);
+            k = 0x8f1bbcdcu;
+        } else {
+            f = b ^ c ^ d;
+            k = 0xca62c1d6u;
+        }
+        let t = rotl(a, 5u) + f + e + k + w[i & 15u];
+        e = d;
+        d = c;
+        c = rotl(b, 30u);
+        b = a;
+        a = t;
+    }
+
+    return array<u32, 5>(
+        a + 0x67452301u,
+        b + 0xefcdab89u,
+        c + 0x98badcfeu,
+        d + 0x10325476u,
+        e + 0xc3d2e1f0u,
+    );
+}
@@ -0,0 +1,419 @@
+//! Multi-lane MD4/MD5 for short candidates, and multi-lane SHA-1 for WPA.
+//!
+//! Almost every generated candidate fits in a single 64-byte block, so the
+//! padding can be done up front and `LANES` candidates compressed side by
+//! side. Each step is written as a loop over lanes with no data-dependent
+//! branches, which LLVM turns into SSE2/AVX2 vector code. PBKDF2 for WPA
+//! chains 8K SHA-1 compressions per candidate, so [`sha1_lanes`] takes the
+//! chain value in rather than starting from the IV.
+
+use super::hash::digest;
+use super::HashAlgorithm;
+
+/// Candidates compressed together. 8 x u32 fills one AVX2 register.
+pub const LANES: usize = 8;
+
+/// Longest message that still pads into one 64-byte block.
+pub const MAX_BLOCK_MESSAGE: usize = 55;
+
+/// One 32-bit word across all lanes.
+pub type Lanes = [u32; LANES];
+
+/// Compresses `LANES` packed blocks into `LANES` 4-word states.
+type LaneFn = fn(&[[u32; 16]; LANES]) -> [[u32; 4]; LANES];
+
+const MD5_K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+const MD5_SHIFT: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+const MD4_ORDER: [[usize; 16]; 3] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15],
+    [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15],
+];
+
+const MD4_SHIFT: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+
+const IV: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+pub const SHA1_IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// MD4/MD5 are little-endian throughout; SHA-1/SHA-2 are big-endian.
+pub fn big_endian(algo: HashAlgorithm) -> bool {
+    matches!(algo, HashAlgorithm::Sha1 | HashAlgorithm::Sha256 | HashAlgorithm::Sha512)
+}
+
+/// Pad a candidate into one compression block as 16 words, or `None` if it
+/// needs more than one block. NTLM candidates are encoded as UTF-16LE.
+pub fn pack_block(algo: HashAlgorithm, candidate: &str) -> Option<[u32; 16]> {
+    let mut block = [0u8; 64];
+    let len = match algo {
+        HashAlgorithm::Ntlm => {
+            let mut len = 0;
+            for unit in candidate.encode_utf16() {
+                if len + 2 > MAX_BLOCK_MESSAGE {
+                    return None;
+                }
+                block[len..len + 2].copy_from_slice(&unit.to_le_bytes());
+                len += 2;
+            }
+            len
+        }
+        _ => {
+            let bytes = candidate.as_bytes();
+            if bytes.len() > MAX_BLOCK_MESSAGE {
+                return None;
+            }
+            block[..bytes.len()].copy_from_slice(bytes);
+            bytes.len()
+        }
+    };
+    block[len] = 0x80;
+    let bits = (len as u64) * 8;
+    if big_endian(algo) {
+        block[56..].copy_from_slice(&bits.to_be_bytes());
+    } else {
+        block[56..].copy_from_slice(&bits.to_le_bytes());
+    }
+
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        let bytes = bytes.try_into().unwrap();
+        *word = if big_endian(algo) {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        };
+    }
+    Some(words)
+}
+
+/// Call `f(index, digest)` for every candidate in `group`.
+///
+/// Full groups of MD5/NTLM candidates go through the multi-lane path; lanes
+/// holding a candidate too long for one block, and all other algorithms,
+/// use the scalar digest.
+pub fn for_each_digest(algo: HashAlgorithm, group: &[String], mut f: impl FnMut(usize, &[u8])) {
+    let lanes: Option<LaneFn> = match algo {
+        HashAlgorithm::Md5 => Some(md5_lanes),
+        HashAlgorithm::Ntlm => Some(md4_lanes),
+        _ => None,
+    };
+    let Some(lanes) = lanes.filter(|_| group.len() == LANES) else {
+        for (i, candidate) in group.iter().enumerate() {
+            f(i, &digest(algo, candidate).unwrap_or_default());
+        }
+        return;
+    };
+
+    let mut blocks = [[0u32; 16]; LANES];
+    let mut packed = [false; LANES];
+    for (i, candidate) in group.iter().enumerate() {
+        if let Some(block) = pack_block(algo, candidate) {
+            blocks[i] = block;
+            packed[i] = true;
+        }
+    }
+
+    let states = lanes(&blocks);
+    for (i, candidate) in group.iter().enumerate() {
+        if packed[i] {
+            let mut bytes = [0u8; 16];
+            for (out, word) in bytes.chunks_exact_mut(4).zip(states[i]) {
+                out.copy_from_slice(&word.to_le_bytes());
+            }
+            f(i, &bytes);
+        } else {
+            f(i, &digest(algo, candidate).unwrap_or_default());
+        }
+    }
+}
+
+/// Word-major view of the blocks: `m[k][lane]` is word k of that lane.
+fn transpose(blocks: &[[u32; 16]; LANES]) -> [Lanes; 16] {
+    let mut m = [[0u32; LANES]; 16];
+    for (lane, block) in blocks.iter().enumerate() {
+        for (k, word) in block.iter().enumerate() {
+            m[k][lane] = *word;
+        }
+    }
+    m
+}
+
+fn finish(state: [Lanes; 4]) -> [[u32; 4]; LANES] {
+    let mut out = [[0u32; 4]; LANES];
+    for (lane, digest) in out.iter_mut().enumerate() {
+        for (i, word) in digest.iter_mut().enumerate() {
+            *word = state[i][lane].wrapping_add(IV[i]);
+        }
+    }
+    out
+}
+
+/// One MD5 compression of `LANES` single-block messages from the IV.
+pub fn md5_lanes(blocks: &[[u32; 16]; LANES]) -> [[u32; 4]; LANES] {
+    let m = transpose(blocks);
+    let mut s = [[IV[0]; LANES], [IV[1]; LANES], [IV[2]; LANES], [IV[3]; LANES]];
+
+    #[inline(always)]
+    fn step(s: &mut [Lanes; 4], m: &Lanes, k: u32, shift: u32, f: impl Fn(u32, u32, u32) -> u32) {
+        let [a, b, c, d] = *s;
+        let mut nb = [0u32; LANES];
+        for l in 0..LANES {
+            let sum = a[l].wrapping_add(f(b[l], c[l], d[l])).wrapping_add(k).wrapping_add(m[l]);
+            nb[l] = b[l].wrapping_add(sum.rotate_left(shift));
+        }
+        *s = [d, nb, b, c];
+    }
+
+    for i in 0..16 {
+        step(&mut s, &m[i], MD5_K[i], MD5_SHIFT[0][i % 4], |b, c, d| (b & c) | (!b & d));
+    }
+    for i in 16..32 {
+        let g = (5 * i + 1) % 16;
+        step(&mut s, &m[g], MD5_K[i], MD5_SHIFT[1][i % 4], |b, c, d| (d & b) | (!d & c));
+    }
+    for i in 32..48 {
+        let g = (3 * i + 5) % 16;
+        step(&mut s, &m[g], MD5_K[i], MD5_SHIFT[2][i % 4], |b, c, d| b ^ c ^ d);
+    }
+    for i in 48..64 {
+        let g = (7 * i) % 16;
+        step(&mut s, &m[g], MD5_K[i], MD5_SHIFT[3][i % 4], |b, c, d| c ^ (b | !d));
+    }
+    finish(s)
+}
+
+/// One MD4 compression of `LANES` single-block messages from the IV.
+pub fn md4_lanes(blocks: &[[u32; 16]; LANES]) -> [[u32; 4]; LANES] {
+    let m = transpose(blocks);
+    let mut s = [[IV[0]; LANES], [IV[1]; LANES], [IV[2]; LANES], [IV[3]; LANES]];
+
+    #[inline(always)]
+    fn step(s: &mut [Lanes; 4], m: &Lanes, k: u32, shift: u32, f: impl Fn(u32, u32, u32) -> u32) {
+        let [a, b, c, d] = *s;
+        let mut na = [0u32; LANES];
+        for l in 0..LANES {
+            let sum = a[l].wrapping_add(f(b[l], c[l], d[l])).wrapping_add(k).wrapping_add(m[l]);
+            na[l] = sum.rotate_left(shift);
+        }
+        *s = [d, na, b, c];
+    }
+
+    for (i, &k) in MD4_ORDER[0].iter().enumerate() {
+        step(&mut s, &m[k], 0, MD4_SHIFT[0][i % 4], |b, c, d| (b & c) | (!b & d));
+    }
+    for (i, &k) in MD4_ORDER[1].iter().enumerate() {
+        step(&mut s, &m[k], 0x5a827999, MD4_SHIFT[1][i % 4], |b, c, d| {
+            (b & c) | (b & d) | (c & d)
+        });
+    }
+    for (i, &k) in MD4_ORDER[2].iter().enumerate() {
+        step(&mut s, &m[k], 0x6ed9eba1, MD4_SHIFT[2][i % 4], |b, c, d| b ^ c ^ d);
+    }
+    finish(s)
+}
+
+/// One SHA-1 compression of `LANES` word-major blocks into `state`, each
+/// lane continuing from its own chain value.
+///
+/// Eight lanes only beat a scalar SHA-1 once they fit one AVX2 register, so
+/// the same code is also built for AVX2 and picked when the CPU has it.
+pub fn sha1_lanes(state: &mut [Lanes; 5], m: &[Lanes; 16]) {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2, checked just above
+        return unsafe { sha1_lanes_avx2(state, m) };
+    }
+    sha1_lanes_portable(state, m)
+}
+
+/// SHA-1 of a message of any length through [`sha1_lanes`], padded into as
+/// many blocks as it takes: a second implementation to check the scalar
+/// one's matches against.
+pub fn sha1_message(message: &[u8]) -> [u8; 20] {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    padded.resize(padded.len().div_ceil(64) * 64, 0);
+    if padded.len() - message.len() < 9 {
+        padded.resize(padded.len() + 64, 0);
+    }
+    let bits = (message.len() as u64) * 8;
+    let end = padded.len();
+    padded[end - 8..].copy_from_slice(&bits.to_be_bytes());
+
+    let mut state = SHA1_IV.map(|iv| [iv; LANES]);
+    for block in padded.chunks_exact(64) {
+        let mut m = [[0u32; LANES]; 16];
+        for (word, bytes) in m.iter_mut().zip(block.chunks_exact(4)) {
+            *word = [u32::from_be_bytes(bytes.try_into().unwrap()); LANES];
+        }
+        sha1_lanes(&mut state, &m);
+    }
+    let mut out = [0u8; 20];
+    for (bytes, word) in out.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word[0].to_be_bytes());
+    }
+    out
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn sha1_lanes_avx2(state: &mut [Lanes; 5], m: &[Lanes; 16]) {
+    sha1_lanes_portable(state, m)
+}
+
+#[inline(always)]
+fn sha1_lanes_portable(state: &mut [Lanes; 5], m: &[Lanes; 16]) {
+    let mut w = [[0u32; LANES]; 80];
+    w[..16].copy_from_slice(m);
+    for t in 16..80 {
+        let mut next = [0u32; LANES];
+        for (l, word) in next.iter_mut().enumerate() {
+            *word = (w[t - 3][l] ^ w[t - 8][l] ^ w[t - 14][l] ^ w[t - 16][l]).rotate_left(1);
+        }
+        w[t] = next;
+    }
+    let mut s = *state;
+
+    #[inline(always)]
+    fn step(s: &mut [Lanes; 5], w: &Lanes, k: u32, f: impl Fn(u32, u32, u32) -> u32) {
+        let [a, b, c, d, e] = *s;
+        let mut na = [0u32; LANES];
+        let mut nc = [0u32; LANES];
+        for l in 0..LANES {
+            na[l] = a[l]
+                .rotate_left(5)
+                .wrapping_add(f(b[l], c[l], d[l]))
+                .wrapping_add(e[l])
+                .wrapping_add(k)
+                .wrapping_add(w[l]);
+            nc[l] = b[l].rotate_left(30);
+        }
+        *s = [na, a, nc, c, d];
+    }
+
+    for w in &w[..20] {
+        step(&mut s, w, 0x5a827999, |b, c, d| (b & c) | (!b & d));
+    }
+    for w in &w[20..40] {
+        step(&mut s, w, 0x6ed9eba1, |b, c, d| b ^ c ^ d);
+    }
+    for w in &w[40..60] {
+        step(&mut s, w, 0x8f1bbcdc, |b, c, d| (b & c) | (b & d) | (c & d));
+    }
+    for w in &w[60..80] {
+        step(&mut s, w, 0xca62c1d6, |b, c, d| b ^ c ^ d);
+    }
+    for (word, s) in state.iter_mut().zip(s) {
+        for l in 0..LANES {
+            word[l] = word[l].wrapping_add(s[l]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lanes_match_scalar() {
+        let group: Vec<String> = ["", "a", "password", "Buddy1990", "j0hn_$m17h", "ünïcode"]
+            .iter()
+            .map(|s| s.to_string())
+            .chain(["x".repeat(27), "y".repeat(55), "z".repeat(56), "w".repeat(200)])
+            .collect();
+
+        for algo in [HashAlgorithm::Md5, HashAlgorithm::Ntlm] {
+            for window in group.windows(LANES) {
+                let mut seen = 0;
+                for_each_digest(algo, window, |i, digest| {
+                    let scalar = super::digest(algo, &window[i]).unwrap();
+                    assert_eq!(digest, scalar, "{} {:?}", algo, window[i]);
+                    seen += 1;
+                });
+                assert_eq!(seen, LANES);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sha1_lanes_match_scalar() {
+        let words = ["", "password", "Buddy1990", &"y".repeat(55)];
+        let mut blocks = [[0u32; 16]; LANES];
+        for (lane, block) in blocks.iter_mut().enumerate() {
+            *block = pack_block(HashAlgorithm::Sha1, words[lane % words.len()]).unwrap();
+        }
+        let mut state = [[0u32; LANES]; 5];
+        for (word, iv) in state.iter_mut().zip(SHA1_IV) {
+            *word = [iv; LANES];
+        }
+        sha1_lanes(&mut state, &transpose(&blocks));
+        for lane in 0..LANES {
+            let out: Vec<u8> = state.iter().flat_map(|w| w[lane].to_be_bytes()).collect();
+            assert_eq!(Some(out), digest(HashAlgorithm::Sha1, words[lane % words.len()]));
+        }
+    }
+
+    #[test]
+    fn test_sha1_message() {
+        for len in [0, 3, 55, 56, 63, 64, 119, 120, 200] {
+            let message = "k".repeat(len);
+            assert_eq!(
+                Some(sha1_message(message.as_bytes()).to_vec()),
+                digest(HashAlgorithm::Sha1, &message),
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_pack_block_matches_reference_compression() {
+        use digest::generic_array::GenericArray;
+
+        // One compression of the packed block from the standard IV must
+        // reproduce the digest.
+        let block = pack_block(HashAlgorithm::Sha256, "password").unwrap();
+        let bytes: Vec<u8> = block.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let mut state = [
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ];
+        sha2::compress256(&mut state, &[*GenericArray::from_slice(&bytes)]);
+        let out: Vec<u8> = state.iter().flat_map(|w| w.to_be_bytes()).collect();
+        assert_eq!(Some(out), digest(HashAlgorithm::Sha256, "password"));
+
+        let block = pack_block(HashAlgorithm::Sha1, "password").unwrap();
+        let bytes: Vec<u8> = block.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let mut state = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+        sha1::compress(&mut state, &[*GenericArray::from_slice(&bytes)]);
+        let out: Vec<u8> = state.iter().flat_map(|w| w.to_be_bytes()).collect();
+        assert_eq!(Some(out), digest(HashAlgorithm::Sha1, "password"));
+
+        // NTLM packs UTF-16LE: 8 chars -> 16 bytes -> 128 bits
+        let block = pack_block(HashAlgorithm::Ntlm, "password").unwrap();
+        assert_eq!(block[0], u32::from_le_bytes([b'p', 0, b'a', 0]));
+        assert_eq!(block[4], 0x80);
+        assert_eq!(block[14], 128);
+
+        assert!(pack_block(HashAlgorithm::Md5, &"a".repeat(55)).is_some());
+        assert!(pack_block(HashAlgorithm::Md5, &"a".repeat(56)).is_none());
+        assert!(pack_block(HashAlgorithm::Ntlm, &"a".repeat(27)).is_some());
+        assert!(pack_block(HashAlgorithm::Ntlm, &"a".repeat(28)).is_none());
+    }
+}
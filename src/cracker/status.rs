@@ -0,0 +1,297 @@
+//! Live status table for cracking runs: a header with the candidate count,
+//! rate, elapsed time and ETA, and one row per target showing whether it has
+//! fallen yet. Like the spinner it replaces, it draws to stderr and only
+//! when that is a terminal.
+//!
+//! Workers add to one atomic counter as they finish candidates, so the count
+//! is exact at any moment, including when a run stops partway through a
+//! chunk. The rate is sampled over a sliding window rather than averaged
+//! from the start, so it tracks slowdowns such as longer candidates.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+use crate::estimate;
+use crate::status;
+
+/// Rows shown before the rest are folded into one summary row, so a large
+/// dump doesn't scroll the header off the screen.
+const MAX_ROWS: usize = 20;
+
+/// Characters of each target shown in its row.
+const LABEL_WIDTH: usize = 32;
+
+/// How long each rate sample spans.
+const RATE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Minimum time between header redraws, however often workers report.
+const DRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Rate bookkeeping, touched by one worker at a time.
+struct Sampler {
+    window_start: Instant,
+    window_tested: u64,
+    /// Candidates per second over the last complete window.
+    rate: Option<f64>,
+    last_draw: Option<Instant>,
+}
+
+pub struct StatusTable {
+    multi: MultiProgress,
+    header: ProgressBar,
+    rows: Vec<ProgressBar>,
+    /// Target -> row, for the targets that have their own row.
+    index: HashMap<String, usize>,
+    /// Row summarizing the targets beyond `MAX_ROWS`, if there are any.
+    overflow: Option<(ProgressBar, usize)>,
+    overflow_cracked: AtomicUsize,
+    cracked: AtomicUsize,
+    total: usize,
+    started: Instant,
+    tested: AtomicU64,
+    /// Candidates the run should take, when known, for the ETA.
+    expected: Option<u64>,
+    note: &'static str,
+    sampler: Mutex<Sampler>,
+}
+
+impl StatusTable {
+    /// A table with one pending row per target (or per label, for WPA).
+    /// `expected` is the number of candidates the run should take, if known.
+    pub fn new(targets: &[String], expected: Option<u64>) -> Self {
+        let multi = MultiProgress::new();
+        let header = multi.add(ProgressBar::new_spinner());
+        header.set_style(
+            ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] {msg}").unwrap(),
+        );
+
+        let row_style = ProgressStyle::with_template("  {prefix} {msg}").unwrap();
+        let shown = if targets.len() > MAX_ROWS {
+            MAX_ROWS - 1
+        } else {
+            targets.len()
+        };
+        let mut rows = Vec::with_capacity(shown);
+        let mut index = HashMap::new();
+        for (i, target) in targets.iter().take(shown).enumerate() {
+            let row = multi.add(ProgressBar::new_spinner().with_style(row_style.clone()));
+            row.set_prefix(label(target));
+            row.set_message("pending".dimmed().to_string());
+            rows.push(row);
+            index.entry(target.clone()).or_insert(i);
+        }
+        let overflow = (targets.len() > shown).then(|| {
+            let row = multi.add(ProgressBar::new_spinner().with_style(row_style));
+            (row, targets.len() - shown)
+        });
+
+        let started = Instant::now();
+        let table = Self {
+            multi,
+            header,
+            rows,
+            index,
+            overflow,
+            overflow_cracked: AtomicUsize::new(0),
+            cracked: AtomicUsize::new(0),
+            total: targets.len(),
+            started,
+            tested: AtomicU64::new(0),
+            expected,
+            note: "",
+            sampler: Mutex::new(Sampler {
+                window_start: started,
+                window_tested: 0,
+                rate: None,
+                last_draw: None,
+            }),
+        };
+        table.draw_header(0, None);
+        table.update_overflow();
+        table
+    }
+
+    /// Extra text after the header, e.g. a warning that bcrypt is slow.
+    pub fn with_note(mut self, note: &'static str) -> Self {
+        self.note = note;
+        self.draw_header(0, None);
+        self
+    }
+
+    /// Count `n` more candidates as tested; safe to call from every worker.
+    pub fn add(&self, n: u64) {
+        self.tested.fetch_add(n, Ordering::Relaxed);
+        // Whoever holds the sampler redraws; the others just count
+        let Ok(mut sampler) = self.sampler.try_lock() else {
+            return;
+        };
+        let tested = self.tested();
+        let now = Instant::now();
+        if sampler.last_draw.is_some_and(|t| now - t < DRAW_INTERVAL) {
+            return;
+        }
+        let window = now - sampler.window_start;
+        if window >= RATE_WINDOW {
+            sampler.rate = Some((tested - sampler.window_tested) as f64 / window.as_secs_f64());
+            sampler.window_start = now;
+            sampler.window_tested = tested;
+        }
+        sampler.last_draw = Some(now);
+        let rate = sampler.rate.or_else(|| {
+            let elapsed = (now - self.started).as_secs_f64();
+            (elapsed > 0.0).then(|| tested as f64 / elapsed)
+        });
+        drop(sampler);
+        self.draw_header(tested, rate);
+    }
+
+    /// Candidates actually tested so far.
+    pub fn tested(&self) -> u64 {
+        self.tested.load(Ordering::Relaxed)
+    }
+
+    /// Mark `target` cracked, with the time it fell.
+    pub fn cracked(&self, target: &str, plaintext: &str) {
+        self.cracked.fetch_add(1, Ordering::Relaxed);
+        match self.index.get(target) {
+            Some(&i) => {
+                let elapsed = indicatif::HumanDuration(self.started.elapsed());
+                self.rows[i].set_message(format!(
+                    "{} {} {}",
+                    "cracked".green().bold(),
+                    plaintext.green(),
+                    format!("after {}", elapsed).dimmed()
+                ));
+            }
+            None => {
+                self.overflow_cracked.fetch_add(1, Ordering::Relaxed);
+                self.update_overflow();
+            }
+        }
+        let rate = self.sampler.lock().unwrap().rate;
+        self.draw_header(self.tested(), rate);
+    }
+
+    /// Print a line above the table (or plainly when the table is hidden).
+    pub fn println(&self, line: String) {
+        self.header.println(line);
+    }
+
+    /// Run `f` with the table cleared, for output that must be seen even
+    /// when stderr isn't a terminal.
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.multi.suspend(f)
+    }
+
+    /// Remove the table once the run is over and print the final totals.
+    pub fn finish(&self) {
+        for row in &self.rows {
+            row.finish_and_clear();
+        }
+        if let Some((ref row, _)) = self.overflow {
+            row.finish_and_clear();
+        }
+        self.header.finish_and_clear();
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let tested = self.tested();
+        status!(
+            "{} Tested {} candidates in {} ({})",
+            ">>".cyan().bold(),
+            tested,
+            estimate::format_duration(elapsed),
+            estimate::format_rate(tested as f64 / elapsed.max(f64::EPSILON))
+        );
+    }
+
+    fn draw_header(&self, tested: u64, rate: Option<f64>) {
+        let mut line = format!("{} candidates", tested);
+        if let Some(rate) = rate {
+            line += &format!(" ({})", estimate::format_rate(rate));
+        }
+        if let Some(expected) = self.expected.filter(|&e| e > 0) {
+            line += &format!(
+                " {:.1}%",
+                100.0 * tested.min(expected) as f64 / expected as f64
+            );
+            if let Some(rate) = rate.filter(|&r| r > 0.0) {
+                let left = expected.saturating_sub(tested) as f64 / rate;
+                line += &format!(" ETA {}", estimate::format_duration(left));
+            }
+        }
+        line += &format!(
+            " | {}/{} cracked",
+            self.cracked.load(Ordering::Relaxed),
+            self.total
+        );
+        if !self.note.is_empty() {
+            line += &format!(" {}", self.note);
+        }
+        self.header.set_message(line);
+    }
+
+    fn update_overflow(&self) {
+        if let Some((ref row, hidden)) = self.overflow {
+            row.set_message(
+                format!(
+                    "… {} more, {} cracked",
+                    hidden,
+                    self.overflow_cracked.load(Ordering::Relaxed)
+                )
+                .dimmed()
+                .to_string(),
+            );
+        }
+    }
+}
+
+/// A target shortened to a fixed-width label.
+fn label(target: &str) -> String {
+    if target.chars().count() <= LABEL_WIDTH {
+        format!("{:<width$}", target, width = LABEL_WIDTH)
+    } else {
+        let head: String = target.chars().take(LABEL_WIDTH - 1).collect();
+        format!("{}…", head)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_width() {
+        assert_eq!(label("abc").len(), LABEL_WIDTH);
+        let long = label(&"f".repeat(64));
+        assert_eq!(long.chars().count(), LABEL_WIDTH);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn test_overflow_rows() {
+        let targets: Vec<String> = (0..30).map(|i| format!("{:032x}", i)).collect();
+        let table = StatusTable::new(&targets, None);
+        assert_eq!(table.rows.len(), MAX_ROWS - 1);
+        table.cracked(&targets[0], "a");
+        table.cracked(&targets[29], "b");
+        assert_eq!(table.cracked.load(Ordering::Relaxed), 2);
+        assert_eq!(table.overflow_cracked.load(Ordering::Relaxed), 1);
+        table.finish();
+    }
+
+    #[test]
+    fn test_count_is_exact_across_threads() {
+        let table = StatusTable::new(&["x".to_string()], Some(8000));
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| (0..1000).for_each(|_| table.add(1)));
+            }
+        });
+        assert_eq!(table.tested(), 8000);
+    }
+}
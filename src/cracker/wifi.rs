@@ -71,7 +71,7 @@ pub fn crack_with_hashcat(handshake: &Path, wordlist: &Path) -> Result<()> {
     }
 
     // Convert .cap to .hccapx if needed
-    let hccapx_path = if handshake.extension().map_or(false, |e| e == "cap" || e == "pcap") {
+    let hccapx_path = if handshake.extension().is_some_and(|e| e == "cap" || e == "pcap") {
         let hccapx = handshake.with_extension("hccapx");
         convert_cap_to_hccapx(handshake, &hccapx)?;
         hccapx
@@ -149,7 +149,7 @@ fn convert_cap_to_hccapx(cap: &Path, hccapx: &Path) -> Result<()> {
 }
 
 /// Check if a command exists on PATH.
-fn command_exists(cmd: &str) -> bool {
+pub fn command_exists(cmd: &str) -> bool {
     Command::new("which")
         .arg(cmd)
         .output()
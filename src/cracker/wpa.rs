@@ -0,0 +1,381 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use digest::Digest;
+use hmac::{Hmac, Mac};
+use rayon::prelude::*;
+
+use super::simd::{self, Lanes, LANES, SHA1_IV};
+use super::status::StatusTable;
+use super::{for_each_chunk, CrackConfig, Pacer};
+
+type HmacSha1 = Hmac<sha1::Sha1>;
+type HmacMd5 = Hmac<md5::Md5>;
+
+/// Size of one hccapx record.
+const HCCAPX_LEN: usize = 393;
+
+/// PBKDF2 is ~8K SHA1 compressions per candidate, so batches stay small.
+const WPA_CHUNK_SIZE: usize = 2048;
+
+/// One WPA/WPA2 EAPOL handshake, as stored in an hccapx record.
+#[derive(Debug, Clone)]
+pub struct Handshake {
+    pub essid: Vec<u8>,
+    pub keyver: u8,
+    pub keymic: [u8; 16],
+    pub mac_ap: [u8; 6],
+    pub nonce_ap: [u8; 32],
+    pub mac_sta: [u8; 6],
+    pub nonce_sta: [u8; 32],
+    /// EAPOL frame with the MIC field zeroed.
+    pub eapol: Vec<u8>,
+}
+
+impl Handshake {
+    /// Parse every record in an hccapx file.
+    pub fn load_hccapx(path: &Path) -> Result<Vec<Self>> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if data.is_empty() || data.len() % HCCAPX_LEN != 0 {
+            bail!("{} is not a valid hccapx file", path.display());
+        }
+        data.chunks(HCCAPX_LEN).map(Self::parse_record).collect()
+    }
+
+    fn parse_record(r: &[u8]) -> Result<Self> {
+        if &r[0..4] != b"HCPX" {
+            bail!("Bad hccapx signature");
+        }
+        let essid_len = (r[9] as usize).min(32);
+        let eapol_len = (u16::from_le_bytes([r[135], r[136]]) as usize).min(256);
+        let mut eapol = r[137..137 + eapol_len].to_vec();
+        if eapol.len() >= 97 {
+            eapol[81..97].fill(0);
+        }
+
+        Ok(Self {
+            essid: r[10..10 + essid_len].to_vec(),
+            keyver: r[42],
+            keymic: r[43..59].try_into()?,
+            mac_ap: r[59..65].try_into()?,
+            nonce_ap: r[65..97].try_into()?,
+            mac_sta: r[97..103].try_into()?,
+            nonce_sta: r[103..135].try_into()?,
+            eapol,
+        })
+    }
+
+    /// Check a passphrase by recomputing PMK -> PTK -> MIC.
+    pub fn verify(&self, passphrase: &str) -> bool {
+        self.matches(&pmk(passphrase.as_bytes(), &self.essid))
+    }
+
+    /// Check a PMK derived for this handshake's ESSID.
+    pub fn matches(&self, pmk: &[u8; 32]) -> bool {
+        let kck = self.kck(pmk);
+        self.mic(&kck).is_some_and(|mic| mic == self.keymic)
+    }
+
+    /// First 16 bytes of the PTK (PRF-512 block 0) are all the MIC needs.
+    fn kck(&self, pmk: &[u8; 32]) -> [u8; 16] {
+        let (mac_lo, mac_hi) = sorted(&self.mac_ap, &self.mac_sta);
+        let (nonce_lo, nonce_hi) = sorted(&self.nonce_ap, &self.nonce_sta);
+
+        let mut prf = HmacSha1::new_from_slice(pmk).expect("HMAC accepts any key length");
+        prf.update(b"Pairwise key expansion\0");
+        prf.update(mac_lo);
+        prf.update(mac_hi);
+        prf.update(nonce_lo);
+        prf.update(nonce_hi);
+        prf.update(&[0]);
+        let block = prf.finalize().into_bytes();
+
+        let mut kck = [0u8; 16];
+        kck.copy_from_slice(&block[..16]);
+        kck
+    }
+
+    fn mic(&self, kck: &[u8; 16]) -> Option<[u8; 16]> {
+        let mut mic = [0u8; 16];
+        match self.keyver {
+            1 => {
+                let mut mac = HmacMd5::new_from_slice(kck).expect("HMAC accepts any key length");
+                mac.update(&self.eapol);
+                mic.copy_from_slice(&mac.finalize().into_bytes());
+            }
+            2 => {
+                let mut mac = HmacSha1::new_from_slice(kck).expect("HMAC accepts any key length");
+                mac.update(&self.eapol);
+                mic.copy_from_slice(&mac.finalize().into_bytes()[..16]);
+            }
+            // keyver 3 (802.11w, AES-CMAC) is not supported natively
+            _ => return None,
+        }
+        Some(mic)
+    }
+}
+
+/// Crack an hccapx handshake in-process, without aircrack-ng or hashcat.
+///
+/// Candidates are verified in parallel chunks; each rayon task derives the
+/// PMKs of `LANES` candidates at once with [`pmk_lanes`], once per ESSID,
+/// and checks the MIC of every handshake for that ESSID. `on_chunk`
+/// receives the number of candidates tested so far after every chunk. Of
+/// `config`, only the deadline, status interval and expected count apply.
+pub fn crack_hccapx<I, F>(
+    handshake: &Path,
+    candidates: I,
+    config: &CrackConfig,
+    mut on_chunk: F,
+) -> Result<Option<String>>
+where
+    I: Iterator<Item = String> + Send,
+    F: FnMut(u64),
+{
+    let handshakes = Handshake::load_hccapx(handshake)?;
+    if handshakes.iter().all(|h| h.keyver != 1 && h.keyver != 2) {
+        bail!("Only WPA/WPA2 (keyver 1/2) handshakes can be cracked natively; use --use-hashcat");
+    }
+
+    let ssid = String::from_utf8_lossy(&handshakes[0].essid).into_owned();
+    println!(
+        "{} Cracking {} handshake(s) for '{}' natively...",
+        ">>".cyan().bold(),
+        handshakes.len(),
+        ssid
+    );
+
+    // One PMK per ESSID serves every handshake captured for it
+    let mut essids: Vec<&[u8]> = Vec::new();
+    for h in &handshakes {
+        if !essids.contains(&h.essid.as_slice()) {
+            essids.push(&h.essid);
+        }
+    }
+
+    let stop = AtomicBool::new(false);
+    let found: Mutex<Option<String>> = Mutex::new(None);
+    let mut tested = 0u64;
+    let table = StatusTable::new(std::slice::from_ref(&ssid), config.expected);
+    let mut pacer = Pacer::new(config);
+
+    for_each_chunk(candidates, WPA_CHUNK_SIZE, |chunk| {
+        chunk.par_chunks(LANES).for_each(|group| {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let passphrases: Vec<&[u8]> = group.iter().map(|c| c.as_bytes()).collect();
+            for essid in &essids {
+                let pmks = pmk_lanes(&passphrases, essid);
+                let same = handshakes.iter().filter(|h| h.essid == *essid);
+                for h in same {
+                    for (candidate, pmk) in group.iter().zip(&pmks) {
+                        if h.matches(pmk) {
+                            stop.store(true, Ordering::Relaxed);
+                            table.cracked(&ssid, candidate);
+                            table.println(format!(
+                                "  {} Key found: {}",
+                                "✓".green().bold(),
+                                candidate.green().bold()
+                            ));
+                            found.lock().unwrap().get_or_insert_with(|| candidate.clone());
+                        }
+                    }
+                }
+            }
+            table.add(group.len() as u64);
+        });
+        tested += chunk.len() as u64;
+        on_chunk(tested);
+        let cracked = stop.load(Ordering::Relaxed);
+        pacer.tick(&table, tested, cracked as usize, 1) && !cracked
+    });
+
+    table.finish();
+    Ok(found.into_inner().unwrap())
+}
+
+/// PBKDF2-HMAC-SHA1(passphrase, ssid, 4096, 32), one candidate at a time
+/// through the `hmac` crate. The reference for [`pmk_lanes`].
+pub fn pmk(passphrase: &[u8], ssid: &[u8]) -> [u8; 32] {
+    let prf = HmacSha1::new_from_slice(passphrase).expect("HMAC accepts any key length");
+    let mut out = [0u8; 40];
+
+    for (i, block) in out.chunks_mut(20).enumerate() {
+        let mut mac = prf.clone();
+        mac.update(ssid);
+        mac.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = mac.finalize().into_bytes();
+        let mut t = u;
+
+        for _ in 1..4096 {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = mac.finalize().into_bytes();
+            t.iter_mut().zip(u.iter()).for_each(|(t, u)| *t ^= u);
+        }
+        block.copy_from_slice(&t);
+    }
+
+    let mut pmk = [0u8; 32];
+    pmk.copy_from_slice(&out[..32]);
+    pmk
+}
+
+/// PBKDF2-HMAC-SHA1(passphrase, ssid, 4096, 32) for up to `LANES`
+/// passphrases at once; lanes past the end of `passphrases` are left empty.
+///
+/// Every HMAC message here is at most one block, so after keying the ipad
+/// and opad states once per lane, each iteration is two multi-lane SHA-1
+/// compressions. ESSIDs are at most 32 bytes, which keeps the salt and the
+/// block counter inside one block too.
+pub fn pmk_lanes(passphrases: &[&[u8]], ssid: &[u8]) -> Vec<[u8; 32]> {
+    assert!(passphrases.len() <= LANES && ssid.len() <= 32);
+
+    let mut ipad = [[0u32; LANES]; 16];
+    let mut opad = [[0u32; LANES]; 16];
+    for (lane, passphrase) in passphrases.iter().enumerate() {
+        // Keys longer than a block are hashed first, as HMAC specifies
+        let hashed;
+        let key = if passphrase.len() > 64 {
+            hashed = sha1::Sha1::digest(passphrase);
+            hashed.as_slice()
+        } else {
+            passphrase
+        };
+        let mut block = [0u8; 64];
+        block[..key.len()].copy_from_slice(key);
+        for (k, word) in block.chunks_exact(4).enumerate() {
+            let word = u32::from_be_bytes(word.try_into().unwrap());
+            ipad[k][lane] = word ^ 0x36363636;
+            opad[k][lane] = word ^ 0x5c5c5c5c;
+        }
+    }
+    let inner = keyed(&ipad);
+    let outer = keyed(&opad);
+
+    let mut out = vec![[0u8; 32]; passphrases.len()];
+    for i in 0..2u32 {
+        // U1 = HMAC(ssid || i), the same message in every lane
+        let mut salt = [0u8; 64];
+        salt[..ssid.len()].copy_from_slice(ssid);
+        salt[ssid.len()..ssid.len() + 4].copy_from_slice(&(i + 1).to_be_bytes());
+        salt[ssid.len() + 4] = 0x80;
+        salt[56..].copy_from_slice(&((64 + ssid.len() as u64 + 4) * 8).to_be_bytes());
+        let mut m = [[0u32; LANES]; 16];
+        for (k, word) in salt.chunks_exact(4).enumerate() {
+            m[k] = [u32::from_be_bytes(word.try_into().unwrap()); LANES];
+        }
+        let mut s = inner;
+        simd::sha1_lanes(&mut s, &m);
+        let mut u = outer;
+        simd::sha1_lanes(&mut u, &digest_block(&s));
+
+        let mut t = u;
+        for _ in 1..4096 {
+            u = hmac_digest(&inner, &outer, &u);
+            for (t, u) in t.iter_mut().zip(&u) {
+                for l in 0..LANES {
+                    t[l] ^= u[l];
+                }
+            }
+        }
+
+        // 20 bytes from the first block, 12 from the second
+        let words = if i == 0 { 0..5 } else { 0..3 };
+        for (lane, pmk) in out.iter_mut().enumerate() {
+            for k in words.clone() {
+                let at = i as usize * 20 + k * 4;
+                pmk[at..at + 4].copy_from_slice(&t[k][lane].to_be_bytes());
+            }
+        }
+    }
+    out
+}
+
+/// Chain value after compressing one key block from the IV.
+fn keyed(block: &[Lanes; 16]) -> [Lanes; 5] {
+    let mut state = SHA1_IV.map(|word| [word; LANES]);
+    simd::sha1_lanes(&mut state, block);
+    state
+}
+
+/// HMAC-SHA1 of a 20-byte digest, from the keyed inner and outer states.
+fn hmac_digest(inner: &[Lanes; 5], outer: &[Lanes; 5], digest: &[Lanes; 5]) -> [Lanes; 5] {
+    let mut s = *inner;
+    simd::sha1_lanes(&mut s, &digest_block(digest));
+    let mut o = *outer;
+    simd::sha1_lanes(&mut o, &digest_block(&s));
+    o
+}
+
+/// A 20-byte digest padded as the second block of an HMAC message.
+fn digest_block(digest: &[Lanes; 5]) -> [Lanes; 16] {
+    let mut m = [[0u32; LANES]; 16];
+    m[..5].copy_from_slice(digest);
+    m[5] = [0x80000000; LANES];
+    m[15] = [(64 + 20) * 8; LANES];
+    m
+}
+
+fn sorted<'a>(a: &'a [u8], b: &'a [u8]) -> (&'a [u8], &'a [u8]) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pmk_ieee_vector() {
+        // IEEE 802.11i-2004 Annex H.4 test vector
+        let pmk = pmk(b"password", b"IEEE");
+        assert_eq!(
+            hex::encode(pmk),
+            "f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e"
+        );
+    }
+
+    #[test]
+    fn test_pmk_lanes_match_scalar() {
+        let long = "x".repeat(80);
+        let passphrases: Vec<&[u8]> = ["password", "Buddy1990", "", &long, "hunter22"]
+            .iter()
+            .map(|p| p.as_bytes())
+            .collect();
+        for ssid in [&b"IEEE"[..], b"", &[b'n'; 32]] {
+            let pmks = pmk_lanes(&passphrases, ssid);
+            assert_eq!(pmks.len(), passphrases.len());
+            for (passphrase, lane) in passphrases.iter().zip(&pmks) {
+                assert_eq!(*lane, pmk(passphrase, ssid));
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_roundtrip() {
+        let mut hs = Handshake {
+            essid: b"SmithFamily".to_vec(),
+            keyver: 2,
+            keymic: [0; 16],
+            mac_ap: [0x00, 0x11, 0x22, 0x33, 0x44, 0x55],
+            nonce_ap: [0xaa; 32],
+            mac_sta: [0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb],
+            nonce_sta: [0x55; 32],
+            eapol: vec![0x01; 121],
+        };
+        let kck = hs.kck(&pmk(b"Buddy1990", &hs.essid));
+        hs.keymic = hs.mic(&kck).unwrap();
+
+        assert!(hs.verify("Buddy1990"));
+        assert!(!hs.verify("Buddy1991"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Calendar helpers on top of the system clock (UTC, no timezone database).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch, or 0 if the clock is before it.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Current UTC calendar year.
+pub fn current_year() -> i64 {
+    civil_from_days((unix_now() / 86_400) as i64).0
+}
+
+/// Days since 1970-01-01 to (year, month, day), proleptic Gregorian.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Date layouts by locale, one `locale: order separators | months` line each.
+const DATE_FORMATS: &str = include_str!("../data/date_formats.txt");
+
+fn date_locales() -> impl Iterator<Item = (&'static str, &'static str, &'static str)> {
+    DATE_FORMATS
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| {
+            let (locale, rest) = l.split_once(':')?;
+            let (layout, months) = rest.split_once('|')?;
+            Some((locale.trim(), layout, months))
+        })
+}
+
+/// Season names by locale, one `locale: season season ...` line each.
+const SEASON_NAMES: &str = include_str!("../data/season_names.txt");
+
+fn season_locales() -> impl Iterator<Item = (&'static str, &'static str)> {
+    SEASON_NAMES
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once(':'))
+        .map(|(locale, names)| (locale.trim(), names))
+}
+
+/// Whether `locale` is among `locales` or they include `all`.
+fn selected(locale: &str, locales: &[String]) -> bool {
+    locales.iter().any(|want| want == locale || want == "all")
+}
+
+/// Month names in the given locales, January first within each and without
+/// repeats (`us` and `en` share theirs). `all` selects every locale.
+pub fn month_names(locales: &[String]) -> Vec<&'static str> {
+    let mut names = Vec::new();
+    for (_, _, months) in date_locales().filter(|(l, _, _)| selected(l, locales)) {
+        for name in months.split_whitespace() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Season names in the given locales, most common first within each and
+/// without repeats. `all` selects every locale.
+pub fn season_names(locales: &[String]) -> Vec<&'static str> {
+    let mut names = Vec::new();
+    for (_, seasons) in season_locales().filter(|(l, _)| selected(l, locales)) {
+        for name in seasons.split_whitespace() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Whether `locale` is one [`regional_formats`] knows, or `all`.
+pub fn is_date_locale(locale: &str) -> bool {
+    locale == "all" || date_locales().any(|(l, _, _)| l == locale)
+}
+
+/// A date written the ways the given locales write dates: day, month and
+/// year in the locale's order, joined by each of its separators or run
+/// together, padded or not, with four- or two-digit years, and with the
+/// month's name (15mei1990). `all` selects every locale.
+pub fn regional_formats(year: &str, month: u32, day: u32, locales: &[String]) -> Vec<String> {
+    let (dd, mm) = (format!("{:02}", day), format!("{:02}", month));
+    let (d, m) = (day.to_string(), month.to_string());
+    let yy = if year.len() == 4 { &year[2..] } else { year };
+
+    let mut formats: Vec<String> = Vec::new();
+    let mut push = |f: String| {
+        if !formats.contains(&f) {
+            formats.push(f);
+        }
+    };
+    for (_, layout, months) in date_locales().filter(|(l, _, _)| selected(l, locales)) {
+        let mut layout = layout.split_whitespace();
+        let order = layout.next().unwrap_or("dmy");
+        let separators: Vec<&str> = layout.collect();
+        let in_order = |day: &str, month: &str, year: &str, sep: &str| match order {
+            "mdy" => [month, day, year].join(sep),
+            "ymd" => [year, month, day].join(sep),
+            _ => [day, month, year].join(sep),
+        };
+        let day_month = |day: &str, month: &str, sep: &str| match order {
+            "dmy" => [day, month].join(sep),
+            _ => [month, day].join(sep),
+        };
+
+        // Run together, padded only: 15051990, 150590, 1505
+        for y in [year, yy] {
+            push(in_order(&dd, &mm, y, ""));
+        }
+        push(day_month(&dd, &mm, ""));
+        // 15.05.1990, 15.5.90, 15.05
+        for sep in &separators {
+            for (day, month) in [(&dd, &mm), (&d, &m)] {
+                for y in [year, yy] {
+                    push(in_order(day, month, y, sep));
+                }
+                push(day_month(day, month, sep));
+            }
+        }
+        // may, 15may, may1990, 15may1990, and may15 where the month leads
+        if let Some(name) = months.split_whitespace().nth(month as usize - 1) {
+            push(name.to_string());
+            push(format!("{}{}", d, name));
+            for y in [year, yy] {
+                push(format!("{}{}", name, y));
+                push(format!("{}{}{}", d, name, y));
+            }
+            if order == "mdy" {
+                push(format!("{}{}", name, d));
+            }
+        }
+    }
+    formats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn test_regional_formats() {
+        let locales = |codes: &[&str]| codes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let de = regional_formats("1990", 5, 3, &locales(&["de"]));
+        for expected in [
+            "03051990",
+            "03.05.1990",
+            "3.5.90",
+            "03.05",
+            "mai",
+            "3mai1990",
+        ] {
+            assert!(de.contains(&expected.to_string()), "missing {}", expected);
+        }
+        assert!(!de.contains(&"05/03/1990".to_string()));
+
+        let us = regional_formats("1990", 5, 3, &locales(&["us"]));
+        assert!(us.contains(&"05/03/1990".to_string()) && us.contains(&"may3".to_string()));
+        let nl = regional_formats("1990", 5, 3, &locales(&["nl"]));
+        assert!(nl.contains(&"3mei1990".to_string()) && nl.contains(&"03-05-1990".to_string()));
+        let sv = regional_formats("1990", 5, 3, &locales(&["sv"]));
+        assert!(sv.contains(&"19900503".to_string()) && sv.contains(&"1990-05-03".to_string()));
+
+        let all = regional_formats("1990", 5, 3, &locales(&["all"]));
+        assert!(all.len() > de.len() && all.contains(&"mayo".to_string()));
+        assert!(regional_formats("1990", 5, 3, &locales(&["xx"])).is_empty());
+        assert!(is_date_locale("pl") && is_date_locale("all") && !is_date_locale("xx"));
+    }
+
+    #[test]
+    fn test_month_and_season_names() {
+        let locales = |codes: &[&str]| codes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let en = month_names(&locales(&["us", "en"]));
+        assert_eq!(en.len(), 12);
+        assert_eq!((en[0], en[11]), ("january", "december"));
+        let months = month_names(&locales(&["de", "id"]));
+        assert!(months.contains(&"oktober") && months.contains(&"desember"));
+        assert_eq!(months.iter().filter(|&&m| m == "oktober").count(), 1);
+        assert!(month_names(&locales(&["ru"])).is_empty());
+
+        assert_eq!(season_names(&locales(&["en"]))[0], "summer");
+        assert!(season_names(&locales(&["es"])).contains(&"verano"));
+        assert!(season_names(&locales(&["all"])).contains(&"herbst"));
+        assert!(season_names(&locales(&["id"])).is_empty());
+    }
+}
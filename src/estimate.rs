@@ -0,0 +1,233 @@
+//! Keyspace counting and throughput calibration, to predict how long a run
+//! will take before committing to it.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::cracker::{hash, simd, wpa, HashAlgorithm};
+use crate::generator::{self, Dedup, GeneratorConfig};
+use crate::profile::Profile;
+
+/// What is being timed: a hash algorithm, or WPA handshakes (PBKDF2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Hash(HashAlgorithm),
+    Wpa,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hash(algo) => write!(f, "{}", algo),
+            Self::Wpa => write!(f, "WPA"),
+        }
+    }
+}
+
+/// Unique candidates a tier contributes after deduplication.
+#[derive(Debug, Clone)]
+pub struct TierCount {
+    pub name: &'static str,
+    pub candidates: u64,
+    /// Bytes they take in a wordlist, newlines included.
+    pub bytes: u64,
+}
+
+/// Count what each tier adds at `config`, without keeping the candidates.
+pub fn tier_counts(profile: &Profile, config: &GeneratorConfig) -> Vec<TierCount> {
+    let mut dedup = Dedup::new(config);
+    generator::tiers(profile, config)
+        .into_iter()
+        .map(|tier| {
+            let name = tier.name();
+            let (candidates, bytes) = tier
+                .candidates()
+                .filter(|c| dedup.admit(c))
+                .fold((0, 0), |(n, bytes), c| (n + 1, bytes + c.len() as u64 + 1));
+            TierCount {
+                name,
+                candidates,
+                bytes,
+            }
+        })
+        .collect()
+}
+
+/// Candidates per second against `hashes`, measured for about `duration`.
+///
+/// Batches start at one candidate per thread and double while they are
+/// quick, so slow algorithms (bcrypt, WPA) overshoot by at most one batch.
+/// bcrypt cost and the number of targets both matter; fast hashes are
+/// looked up in a set, so only the algorithm does.
+pub fn calibrate(target: Target, hashes: &[String], duration: Duration) -> f64 {
+    let start = Instant::now();
+    let mut batch = rayon::current_num_threads();
+    let mut tested = 0usize;
+    while start.elapsed() < duration {
+        let chunk: Vec<String> = (tested..tested + batch)
+            .map(|i| format!("calibrate{}", i))
+            .collect();
+        let began = Instant::now();
+        match target {
+            Target::Hash(algo) => {
+                hash::check_chunk(hashes, algo, &chunk);
+            }
+            Target::Wpa => {
+                chunk.par_chunks(simd::LANES).for_each(|group| {
+                    let passphrases: Vec<&[u8]> = group.iter().map(|c| c.as_bytes()).collect();
+                    wpa::pmk_lanes(&passphrases, b"calibration");
+                });
+            }
+        }
+        tested += batch;
+        if began.elapsed() < duration / 8 {
+            batch = (batch * 2).min(64 * 1024);
+        }
+    }
+    tested as f64 / start.elapsed().as_secs_f64()
+}
+
+/// The work factor of a bcrypt hash, e.g. 12 for `$2b$12$...`.
+pub fn bcrypt_cost(hash: &str) -> Option<u32> {
+    let mut parts = hash.trim().split('$');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(""), Some(_), Some(cost)) => cost.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Seconds each candidate costs against all of `hashes`. Verifies are
+/// timed for about `duration` against the cheapest hash only; every other
+/// hash weighs 2^(its cost - that cost) as much, since each cost step
+/// doubles the work.
+pub fn bcrypt_seconds_per_candidate(hashes: &[String], duration: Duration) -> f64 {
+    let costs: Vec<Option<u32>> = hashes.iter().map(|h| bcrypt_cost(h)).collect();
+    let cheapest = hashes
+        .iter()
+        .zip(&costs)
+        .filter_map(|(hash, cost)| Some((hash, (*cost)?)))
+        .min_by_key(|&(_, cost)| cost);
+    let Some((hash, base)) = cheapest else {
+        // No readable cost: time them all
+        let target = Target::Hash(HashAlgorithm::Bcrypt);
+        return 1.0 / calibrate(target, hashes, duration);
+    };
+    let rate = calibrate(
+        Target::Hash(HashAlgorithm::Bcrypt),
+        std::slice::from_ref(hash),
+        duration,
+    );
+    let weight: f64 = costs
+        .iter()
+        .map(|cost| cost.map_or(1.0, |c| 2f64.powi((c - base) as i32)))
+        .sum();
+    weight / rate
+}
+
+/// Stand-in targets for timing when no real hashes are given. bcrypt uses
+/// the library's default cost, which real hashes often exceed; WPA needs none.
+pub fn placeholder_hashes(target: Target) -> Vec<String> {
+    match target {
+        Target::Hash(HashAlgorithm::Bcrypt) => {
+            vec![bcrypt::hash("password-guesser", bcrypt::DEFAULT_COST).expect("valid bcrypt cost")]
+        }
+        Target::Hash(HashAlgorithm::Sha512) => vec!["0".repeat(128)],
+        Target::Hash(HashAlgorithm::Sha256) => vec!["0".repeat(64)],
+        Target::Hash(HashAlgorithm::Sha1) => vec!["0".repeat(40)],
+        Target::Hash(_) => vec!["0".repeat(32)],
+        Target::Wpa => Vec::new(),
+    }
+}
+
+/// Seconds as a short human-readable duration: `<1s`, `42s`, `3m 20s`,
+/// `5h 12m`, `12d 4h`, `> 100 years`.
+pub fn format_duration(seconds: f64) -> String {
+    if !seconds.is_finite() || seconds > 100.0 * 365.0 * 86400.0 {
+        return "> 100 years".to_string();
+    }
+    let s = seconds.round() as u64;
+    match s {
+        0 => "<1s".to_string(),
+        1..=59 => format!("{}s", s),
+        60..=3599 => format!("{}m {}s", s / 60, s % 60),
+        3600..=86399 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        _ => format!("{}d {}h", s / 86400, s % 86400 / 3600),
+    }
+}
+
+/// A rate like `1.2M/s`.
+pub fn format_rate(per_second: f64) -> String {
+    match per_second {
+        r if r >= 1e9 => format!("{:.1}G/s", r / 1e9),
+        r if r >= 1e6 => format!("{:.1}M/s", r / 1e6),
+        r if r >= 1e3 => format!("{:.1}K/s", r / 1e3),
+        r => format!("{:.1}/s", r),
+    }
+}
+
+/// A file size like `3.4 MB`.
+pub fn format_size(bytes: u64) -> String {
+    match bytes as f64 {
+        b if b >= 1e9 => format!("{:.1} GB", b / 1e9),
+        b if b >= 1e6 => format!("{:.1} MB", b / 1e6),
+        b if b >= 1e3 => format!("{:.1} KB", b / 1e3),
+        _ => format!("{} B", bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bcrypt_cost() {
+        let hash = "$2b$12$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW";
+        assert_eq!(bcrypt_cost(hash), Some(12));
+        assert_eq!(bcrypt_cost("$2y$04$abc"), Some(4));
+        assert_eq!(bcrypt_cost("5f4dcc3b5aa765d61d8327deb882cf99"), None);
+    }
+
+    #[test]
+    fn test_tier_counts_match_stream() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            pet_name = "Buddy"
+            birthdate = "1990-05-15"
+            "#,
+        )
+        .unwrap();
+        let config = GeneratorConfig::default();
+        let counts = tier_counts(&profile, &config);
+        assert_eq!(counts[0].name, "Common passwords");
+        let total: u64 = counts.iter().map(|t| t.candidates).sum();
+        assert_eq!(
+            total,
+            generator::candidate_stream(&profile, &config).count() as u64
+        );
+        let bytes: u64 = counts.iter().map(|t| t.bytes).sum();
+        let written: usize = generator::candidate_stream(&profile, &config)
+            .map(|c| c.len() + 1)
+            .sum();
+        assert_eq!(bytes, written as u64);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.2), "<1s");
+        assert_eq!(format_duration(42.0), "42s");
+        assert_eq!(format_duration(200.0), "3m 20s");
+        assert_eq!(format_duration(5.0 * 3600.0 + 720.0), "5h 12m");
+        assert_eq!(format_duration(f64::INFINITY), "> 100 years");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(25_300), "25.3 KB");
+        assert_eq!(format_size(3_400_000), "3.4 MB");
+    }
+}
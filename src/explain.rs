@@ -0,0 +1,162 @@
+//! Coverage checks: for each of a list of known passwords (say, ones
+//! cracked on an engagement), the tier and rules that would have produced
+//! it from a profile, or why none would. Researchers use it to see where
+//! the generator falls short.
+
+use std::collections::HashMap;
+
+use crate::generator::{self, Dedup, GeneratorConfig};
+use crate::profile::Profile;
+
+/// What the generator makes of one known password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub password: String,
+    pub verdict: Verdict,
+}
+
+/// Whether, and how, the generator produces a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Produced as the `position`th candidate (1-based), by `rules` applied
+    /// to seeds from `fields`.
+    Generated {
+        position: u64,
+        tier: &'static str,
+        fields: Vec<&'static str>,
+        rules: Vec<&'static str>,
+    },
+    /// Never produced: outside the length bounds, so filtered out whatever
+    /// the tiers make.
+    WrongLength,
+    /// Never produced: breaks the password policy, charset or excluded
+    /// characters.
+    Rejected,
+    /// Never produced by any tier.
+    Missed,
+}
+
+/// Explain each of `passwords` against the candidates of `profile` under
+/// `config`, in the order given. The candidate stream is walked once, and
+/// only until every password has been found.
+pub fn explain(
+    profile: &Profile,
+    config: &GeneratorConfig,
+    passwords: &[String],
+) -> Vec<Explanation> {
+    let mut filters = Dedup::new(config);
+    let mut verdicts: Vec<Verdict> = passwords
+        .iter()
+        .map(|p| match filters.check(p) {
+            generator::Verdict::Length => Verdict::WrongLength,
+            generator::Verdict::Policy => Verdict::Rejected,
+            generator::Verdict::Admitted | generator::Verdict::Duplicate => Verdict::Missed,
+        })
+        .collect();
+    let mut wanted: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, password) in passwords.iter().enumerate() {
+        if verdicts[i] == Verdict::Missed {
+            wanted.entry(password.as_str()).or_default().push(i);
+        }
+    }
+
+    let mut stream = generator::candidate_stream(profile, config);
+    let mut position = 0;
+    while !wanted.is_empty() {
+        let Some(candidate) = stream.next() else {
+            break;
+        };
+        position += 1;
+        let Some(indices) = wanted.remove(candidate.as_str()) else {
+            continue;
+        };
+        let origin = stream.origin();
+        for i in indices {
+            verdicts[i] = Verdict::Generated {
+                position,
+                tier: stream.tier(),
+                fields: origin.fields.clone(),
+                rules: origin.rules.clone(),
+            };
+        }
+    }
+
+    passwords
+        .iter()
+        .cloned()
+        .zip(verdicts)
+        .map(|(password, verdict)| Explanation { password, verdict })
+        .collect()
+}
+
+/// How many explanations each tier accounts for, in the order the tiers
+/// first appear among them.
+pub fn by_tier(explanations: &[Explanation]) -> Vec<(&'static str, usize)> {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for explanation in explanations {
+        if let Verdict::Generated { tier, .. } = explanation.verdict {
+            match counts.iter_mut().find(|(name, _)| *name == tier) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((tier, 1)),
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain() {
+        let profile: Profile = toml::from_str(
+            "[personal]\nfirst_name = \"John\"\npet_name = \"Buddy\"\nbirthdate = \"1990-05-15\"",
+        )
+        .unwrap();
+        let config = GeneratorConfig::default();
+        let passwords: Vec<String> = ["Buddy1990", "password", "xk29!!qz", "abc", "Buddy1990"]
+            .map(String::from)
+            .to_vec();
+        let explained = explain(&profile, &config, &passwords);
+        assert_eq!(explained.len(), 5);
+
+        let Verdict::Generated {
+            position,
+            tier,
+            ref fields,
+            ref rules,
+        } = explained[0].verdict
+        else {
+            panic!("Buddy1990 not generated");
+        };
+        assert_eq!(tier, "Applying affixes");
+        assert_eq!(fields, &["personal.pet_name"]);
+        assert_eq!(rules, &["numeric_suffix"]);
+        let all: Vec<String> = generator::candidate_stream(&profile, &config).collect();
+        assert_eq!(all[position as usize - 1], "Buddy1990");
+        assert_eq!(explained[4].verdict, explained[0].verdict);
+
+        assert!(matches!(
+            explained[1].verdict,
+            Verdict::Generated {
+                tier: "Common passwords",
+                ..
+            }
+        ));
+        assert_eq!(explained[2].verdict, Verdict::Missed);
+        assert_eq!(explained[3].verdict, Verdict::WrongLength);
+        let strict = GeneratorConfig {
+            policy: crate::policy::Policy::parse("min-digit=1").ok(),
+            ..config
+        };
+        assert_eq!(
+            explain(&profile, &strict, &passwords)[1].verdict,
+            Verdict::Rejected
+        );
+        assert_eq!(
+            by_tier(&explained),
+            [("Applying affixes", 2), ("Common passwords", 1)]
+        );
+    }
+}
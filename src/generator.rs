@@ -1,17 +1,85 @@
-use std::collections::HashSet;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
 
 use indicatif::{ProgressBar, ProgressStyle};
 
+use crate::arena::Arena;
+use crate::bloom::Bloom;
 use crate::common;
-use crate::mutations;
-use crate::profile::Profile;
+use crate::date;
+use crate::keyboard::Layout;
+use crate::markov::Markov;
+use crate::mutations::{self, HashcatRule, Mutation};
+use crate::policy::{Charset, Policy};
+use crate::profile::{Profile, SeedClass, SeedSource, Transform, DEFAULT_WEIGHT};
+use crate::rules::Rule;
+use crate::template::{self, Template};
+use crate::unicode;
+use crate::wordlist;
 
 /// Depth controls how many tiers of candidates are generated.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct GeneratorConfig {
     pub depth: u8,       // 1-3
     pub min_length: usize,
     pub max_length: usize,
+    /// Combine every pair of seeds instead of only high-value class pairs.
+    pub all_pairs: bool,
+    /// Within each tier, try candidates in order of Markov likelihood.
+    pub markov: bool,
+    /// Schedule tiers for cracking rather than for a wordlist: seed + number
+    /// combinations and word combinations before the generic affixes.
+    pub likely_first: bool,
+    /// Separators for joining seed words, in place of the profile's
+    /// `[custom] separators` (or "", "_" and "."); empty keeps those.
+    pub separators: Vec<String>,
+    /// Years appended as numeric suffixes; see [`default_years`] when unset.
+    pub years: Option<RangeInclusive<u16>>,
+    /// Year whose neighbours are appended to every seed (`Summer2026!`,
+    /// `summer2025`), fixed when a run starts so it and its resumes agree;
+    /// `None` leaves them out.
+    pub recent_year: Option<u16>,
+    /// Locales whose date formats to write the birthdate in, in place of
+    /// the profile's `locales`; empty keeps those.
+    pub date_formats: Vec<String>,
+    /// Keyboard layout the keyboard walks, typos and shifted digits are
+    /// typed on.
+    pub keyboard: Layout,
+    /// Composition rules of the target's password policy; candidates that
+    /// break them are dropped.
+    pub policy: Option<Policy>,
+    /// Characters the target accepts; candidates with any other are dropped.
+    pub charset: Option<Charset>,
+    /// Characters the target rejects; candidates with any are dropped.
+    pub exclude_chars: String,
+    /// Dictionary whose entries are blended in as extra seed words, with
+    /// only light mutations.
+    pub base_wordlist: Option<PathBuf>,
+    /// User-defined mutation rules applied to every seed word.
+    pub rules: Vec<Rule>,
+    /// Rules in hashcat's syntax, from a community rule set, applied to
+    /// every seed word.
+    pub hashcat_rules: Vec<HashcatRule>,
+    /// Most digits or symbols inserted inside a seed word at once; 0 turns
+    /// the mid-word insertions off.
+    pub insertions: usize,
+    /// Most letters of a seed word whose case is flipped at once (`joHn`,
+    /// `jOhN`); 0 turns the case toggles off.
+    pub case_toggles: usize,
+    /// Rotate seed words left and right (`ohnj`, `njoh`), for deep runs.
+    pub rotations: bool,
+    /// Deduplicate with a Bloom filter of this false-positive rate instead
+    /// of an exact set: a fraction of the memory, but a false positive
+    /// drops a candidate as a duplicate when it was never produced.
+    pub bloom: Option<f64>,
+    /// Topic dictionaries (sports, music, ...) to blend in as light seeds:
+    /// those the profile's interests bring in, plus any listed; `None`
+    /// leaves them out.
+    pub topics: Option<Vec<String>>,
+    /// Kind of account under attack, for its own tier of conventions.
+    pub template: Template,
+    /// Extra generation stages, spliced in among the built-in tiers.
+    pub sources: Vec<ExtraSource>,
 }
 
 impl Default for GeneratorConfig {
@@ -20,13 +88,291 @@ impl Default for GeneratorConfig {
             depth: 2,
             min_length: 6,
             max_length: 32,
+            all_pairs: false,
+            markov: false,
+            likely_first: false,
+            separators: Vec::new(),
+            years: None,
+            recent_year: None,
+            date_formats: Vec::new(),
+            keyboard: Layout::default(),
+            policy: None,
+            charset: None,
+            exclude_chars: String::new(),
+            base_wordlist: None,
+            rules: Vec::new(),
+            hashcat_rules: Vec::new(),
+            insertions: 1,
+            case_toggles: 0,
+            rotations: false,
+            bloom: None,
+            topics: None,
+            template: Template::default(),
+            sources: Vec::new(),
         }
     }
 }
 
+impl GeneratorConfig {
+    /// The run's current year, which ages and default year ranges count
+    /// from: [`recent_year`](Self::recent_year) when the run fixed one.
+    pub fn year(&self) -> u16 {
+        self.recent_year.unwrap_or_else(this_year)
+    }
+}
+
+/// First year suffixed when the profile gives no birth year.
+pub const FIRST_YEAR: u16 = 1950;
+
+/// The current UTC year, as the generator numbers years.
+pub fn this_year() -> u16 {
+    date::current_year().clamp(FIRST_YEAR.into(), u16::MAX.into()) as u16
+}
+
+/// The years worth suffixing for `profile`: from its earliest birth year,
+/// or [`FIRST_YEAR`] without one, to `last`, the run's current year.
+pub fn default_years(profile: &Profile, last: u16) -> RangeInclusive<u16> {
+    let first = profile
+        .birth_years(last)
+        .into_iter()
+        .min()
+        .map_or(FIRST_YEAR, |year| year.clamp(1900, last.into()) as u16);
+    first..=last
+}
+
+/// Where a group of candidates came from: the profile fields of the seeds
+/// involved and the mutation rules applied, in order.
+#[derive(Debug, Clone, Default)]
+pub struct Origin {
+    pub fields: Vec<&'static str>,
+    pub rules: Vec<&'static str>,
+}
+
+impl Origin {
+    fn new(fields: &[&'static str], rules: &[&'static str]) -> Self {
+        Self {
+            fields: fields.to_vec(),
+            rules: rules.to_vec(),
+        }
+    }
+}
+
+/// Candidates produced together from the same seed(s) by the same rules.
+pub struct Group {
+    pub origin: Origin,
+    pub items: Vec<String>,
+}
+
+/// Lazily-evaluated source of candidate groups for one generation tier.
+pub type GroupIter = Box<dyn Iterator<Item = Group> + Send>;
+
+/// A stage of generation: a named source of candidate groups, expanded on
+/// demand. The built-in tiers are [`Tier`]s; other stages are added to a
+/// run through [`GeneratorConfig::sources`].
+pub trait CandidateSource: Send {
+    /// Name shown in progress output and tier statistics.
+    fn name(&self) -> &'static str;
+
+    /// Candidates the source produces before deduplication, if known
+    /// without expanding it.
+    fn estimated_size(&self) -> Option<u64> {
+        None
+    }
+
+    /// Whether the candidates already come most likely first, so Markov
+    /// ordering leaves them alone.
+    fn ranked(&self) -> bool {
+        false
+    }
+
+    /// Whether the run's length, policy and charset filters apply. Ready-made
+    /// candidates (the lines of a base wordlist) are only deduplicated.
+    fn filtered(&self) -> bool {
+        true
+    }
+
+    /// The candidate groups, produced lazily.
+    fn groups(self: Box<Self>) -> GroupIter;
+}
+
+impl dyn CandidateSource {
+    /// The source's candidates without provenance.
+    pub fn candidates(self: Box<Self>) -> impl Iterator<Item = String> {
+        self.groups().flat_map(|g| g.items)
+    }
+
+    /// The source's candidates that pass `dedup`, in Markov order if
+    /// `markov` is set and the source isn't ranked already.
+    pub fn admitted(self: Box<Self>, dedup: &mut Dedup, markov: bool) -> Vec<String> {
+        let reorder = markov && !self.ranked();
+        let filtered = self.filtered();
+        let admitted: Vec<String> = self
+            .candidates()
+            .filter(|c| dedup.check_from(filtered, c) == Verdict::Admitted)
+            .collect();
+        if reorder {
+            Markov::common().rank(admitted, |c| c)
+        } else {
+            admitted
+        }
+    }
+
+    /// Like [`admitted`](Self::admitted), for a `dedup` that keeps what it
+    /// admits: the ids of the source's candidates in [`Dedup::admitted`]
+    /// rather than copies of them.
+    pub fn admitted_ids(self: Box<Self>, dedup: &mut Dedup, markov: bool) -> Vec<usize> {
+        let reorder = markov && !self.ranked();
+        let first = dedup.admitted().map_or(0, Arena::len);
+        let filtered = self.filtered();
+        for candidate in self.candidates() {
+            dedup.check_from(filtered, &candidate);
+        }
+        let Some(arena) = dedup.admitted() else {
+            return Vec::new();
+        };
+        let ids = first..arena.len();
+        if reorder {
+            let tier: Vec<(usize, &str)> = ids.map(|id| (id, arena.get(id))).collect();
+            let ranked = Markov::common().rank(tier, |&(_, text)| text);
+            ranked.into_iter().map(|(id, _)| id).collect()
+        } else {
+            ids.collect()
+        }
+    }
+}
+
+/// A built-in generation tier. Candidates are produced on demand.
+pub struct Tier {
+    pub name: &'static str,
+    pub groups: GroupIter,
+    /// Already most likely first (a frequency list or the user's own
+    /// guesses), so Markov ordering leaves it alone.
+    pub ranked: bool,
+    /// Candidates before deduplication, when known up front.
+    pub size: Option<u64>,
+}
+
+impl CandidateSource for Tier {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn estimated_size(&self) -> Option<u64> {
+        self.size
+    }
+
+    fn ranked(&self) -> bool {
+        self.ranked
+    }
+
+    fn groups(self: Box<Self>) -> GroupIter {
+        self.groups
+    }
+}
+
+/// Ready-made candidates (say, the lines of a wordlist) as a generation
+/// stage: kept in the order given, read a batch at a time, and only
+/// deduplicated.
+pub struct Lines {
+    name: &'static str,
+    lines: Box<dyn Iterator<Item = String> + Send>,
+}
+
+impl Lines {
+    pub fn new(name: &'static str, lines: impl Iterator<Item = String> + Send + 'static) -> Self {
+        Self {
+            name,
+            lines: Box::new(lines),
+        }
+    }
+}
+
+impl CandidateSource for Lines {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn ranked(&self) -> bool {
+        true
+    }
+
+    fn filtered(&self) -> bool {
+        false
+    }
+
+    fn groups(self: Box<Self>) -> GroupIter {
+        const BATCH: usize = 4096;
+
+        let mut lines = self.lines;
+        Box::new(std::iter::from_fn(move || {
+            let items: Vec<String> = lines.by_ref().take(BATCH).collect();
+            (!items.is_empty()).then(|| Group {
+                origin: Origin::default(),
+                items,
+            })
+        }))
+    }
+}
+
+/// Builds an extra generation stage for a run from its profile and config,
+/// or `None` when the stage has nothing to offer.
+pub type BuildSource =
+    dyn Fn(&Profile, &GeneratorConfig) -> Option<Box<dyn CandidateSource>> + Send + Sync;
+
+/// A generation stage registered on top of the built-in tiers.
+#[derive(Clone)]
+pub struct ExtraSource {
+    /// Built-in tier to run it after; `None` runs it after all of them.
+    pub after: Option<&'static str>,
+    pub build: std::sync::Arc<BuildSource>,
+}
+
+impl ExtraSource {
+    /// A stage run after all the built-in tiers.
+    pub fn new(
+        build: impl Fn(&Profile, &GeneratorConfig) -> Option<Box<dyn CandidateSource>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            after: None,
+            build: std::sync::Arc::new(build),
+        }
+    }
+
+    /// Run the stage right after the built-in tier named `tier` instead
+    /// (after all of them if that tier isn't generated).
+    pub fn after(mut self, tier: &'static str) -> Self {
+        self.after = Some(tier);
+        self
+    }
+}
+
+impl std::fmt::Debug for ExtraSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtraSource")
+            .field("after", &self.after)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A candidate with full provenance, for research consumers of the library.
+#[cfg(feature = "metadata")]
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Candidate {
+    pub text: String,
+    pub tier: &'static str,
+    pub source_fields: Vec<&'static str>,
+    pub rules_applied: Vec<&'static str>,
+}
+
 /// Generate all candidate passwords based on profile and config.
 pub fn generate_candidates(profile: &Profile, config: &GeneratorConfig) -> Vec<String> {
-    let mut seen = HashSet::new();
+    let mut dedup = Dedup::new(config);
+    // The exact dedup keeps every candidate it admits, so only their ids are
+    // collected until the end; a Bloom filter keeps none
+    let mut ids = Vec::new();
     let mut candidates = Vec::new();
 
     let pb = ProgressBar::new_spinner();
@@ -36,140 +382,2568 @@ pub fn generate_candidates(profile: &Profile, config: &GeneratorConfig) -> Vec<S
             .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
     );
 
-    let seed_words = profile.seed_words();
-    let seed_numbers = profile.seed_numbers();
+    for (i, tier) in tiers(profile, config).into_iter().enumerate() {
+        pb.set_message(format!("Tier {}: {}...", i + 1, tier.name()));
+        if dedup.admitted().is_some() {
+            ids.extend(tier.admitted_ids(&mut dedup, config.markov));
+        } else {
+            candidates.extend(tier.admitted(&mut dedup, config.markov));
+        }
+        let count = ids.len() + candidates.len();
+        pb.set_message(format!("Tier {} done: {} candidates", i + 1, count));
+    }
+
+    if let Some(arena) = dedup.into_admitted() {
+        candidates = ids.into_iter().map(|id| arena.get(id).to_string()).collect();
+    }
+    pb.finish_with_message(format!("Generated {} unique candidates", candidates.len()));
+    candidates
+}
+
+/// Stream unique candidates tier by tier without collecting them: the
+/// candidates of [`generate_candidates`], in the same order, produced on
+/// demand.
+///
+/// Only the dedup set is retained, one entry per unique candidate so far;
+/// each tier is expanded one seed at a time. With [`GeneratorConfig::bloom`]
+/// set, even that is a couple of bytes per candidate.
+///
+/// ```no_run
+/// use password_guesser::{generator, profile::Profile};
+///
+/// let profile = Profile::load("target_profile.toml".as_ref())?;
+/// let config = generator::GeneratorConfig::default();
+/// for candidate in generator::candidate_stream(&profile, &config).take(100) {
+///     println!("{}", candidate);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn candidate_stream(profile: &Profile, config: &GeneratorConfig) -> CandidateStream {
+    CandidateStream {
+        inner: MetadataStream::new(profile, config),
+        origin: Default::default(),
+    }
+}
+
+/// Like [`candidate_stream`], but every candidate carries its tier, source
+/// profile fields and the rules that produced it.
+#[cfg(feature = "metadata")]
+pub fn candidate_stream_with_metadata(
+    profile: &Profile,
+    config: &GeneratorConfig,
+) -> impl Iterator<Item = Candidate> {
+    MetadataStream::new(profile, config).map(|(text, tier, origin)| Candidate {
+        text,
+        tier,
+        source_fields: origin.fields.clone(),
+        rules_applied: origin.rules.clone(),
+    })
+}
+
+/// Every candidate, most likely first, for wordlists fed to tools that try
+/// them in file order.
+///
+/// Candidates are scored by the tier they came from, how many rules it took
+/// to produce them, and how common their trailing digits and symbols are
+/// among the embedded common passwords; equal scores keep generation order.
+/// Unlike [`candidate_stream`], this holds the whole list to sort it.
+pub fn likely_ordered(profile: &Profile, config: &GeneratorConfig) -> Vec<String> {
+    let (mut scored, arena) = scored(profile, config);
+    scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+    scored
+        .into_iter()
+        .map(|(_, id)| arena.get(id).to_string())
+        .collect()
+}
+
+/// At most `max` candidates, for targets that only allow so many guesses.
+///
+/// Tiers are taken in priority order and, once they overflow the budget,
+/// the least likely candidates (scored as in [`likely_ordered`]) are pruned.
+/// The survivors keep their generation order. Also returns how many
+/// candidates there were before pruning.
+pub fn within_budget(
+    profile: &Profile,
+    config: &GeneratorConfig,
+    max: usize,
+) -> (Vec<String>, usize) {
+    let (scored, arena) = scored(profile, config);
+    let mut scored: Vec<(f64, usize, usize)> = scored
+        .into_iter()
+        .enumerate()
+        .map(|(i, (cost, id))| (cost, i, id))
+        .collect();
+    let total = scored.len();
+    if total > max {
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        scored.truncate(max);
+        scored.sort_by_key(|&(_, i, _)| i);
+    }
+    let kept = scored
+        .into_iter()
+        .map(|(_, _, id)| arena.get(id).to_string())
+        .collect();
+    (kept, total)
+}
+
+/// Every candidate in stream order with its likelihood cost (lower is more
+/// likely), as an id in the returned arena.
+fn scored(profile: &Profile, config: &GeneratorConfig) -> (Vec<(f64, usize)>, Arena) {
+    let suffixes = SuffixFrequency::common();
+    let mut tier_index = 0;
+    let mut last_tier = "";
+    let mut stream = MetadataStream::new(profile, config);
+    // Filled only when a Bloom filter dedups, as the exact dedup keeps
+    // every candidate already
+    let mut kept = Arena::new();
+    let mut scored = Vec::new();
+    while let Some((text, tier, origin)) = stream.next() {
+        if tier != last_tier {
+            tier_index += 1;
+            last_tier = tier;
+        }
+        let rules = origin.rules.len().saturating_sub(1) as f64;
+        let cost = tier_index as f64
+            + RULE_COST * rules
+            + WEIGHT_COST * weight_deficit(profile, &origin)
+            + suffixes.cost(&text);
+        let id = match stream.dedup.admitted() {
+            Some(admitted) => admitted.id(&text).expect("admitted candidates are kept"),
+            None => {
+                kept.insert(&text);
+                kept.len() - 1
+            }
+        };
+        scored.push((cost, id));
+    }
+    (scored, stream.dedup.into_admitted().unwrap_or(kept))
+}
+
+/// Likelihood cost of each rule beyond the first, in tiers.
+const RULE_COST: f64 = 0.5;
+
+/// Likelihood cost of each point of weight a candidate's seeds fall short
+/// of the default by (or credit for each point over it), in tiers.
+const WEIGHT_COST: f64 = 0.1;
+
+/// How far the average weight of the seeds behind `origin` falls short of
+/// the default; negative for heavier seeds, 0 for candidates without seeds.
+fn weight_deficit(profile: &Profile, origin: &Origin) -> f64 {
+    if origin.fields.is_empty() {
+        return 0.0;
+    }
+    let total: u32 = origin.fields.iter().map(|f| profile.weight(f)).sum();
+    DEFAULT_WEIGHT as f64 - total as f64 / origin.fields.len() as f64
+}
+
+/// How often each trailing run of digits and symbols ends the common
+/// passwords that have letters in them (the empty run included).
+struct SuffixFrequency {
+    counts: std::collections::HashMap<String, usize>,
+    /// Count of the most common run.
+    best: usize,
+}
+
+impl SuffixFrequency {
+    fn common() -> Self {
+        let mut counts = std::collections::HashMap::new();
+        for password in common::COMMON_PASSWORDS.lines() {
+            if password.chars().any(|c| c.is_alphabetic()) {
+                let run = trailing_run(password).to_string();
+                *counts.entry(run).or_insert(0) += 1;
+            }
+        }
+        let best = counts.values().copied().max().unwrap_or(1);
+        Self { counts, best }
+    }
+
+    /// From 0 for the most common suffix up to about a tier and a half for
+    /// one never seen.
+    fn cost(&self, candidate: &str) -> f64 {
+        let count = self.counts.get(trailing_run(candidate)).copied();
+        (self.best as f64 / count.unwrap_or(1) as f64).log10() / 2.0
+    }
+}
+
+/// The digits and symbols after a candidate's last letter.
+fn trailing_run(word: &str) -> &str {
+    let start = word
+        .char_indices()
+        .rfind(|(_, c)| c.is_alphabetic())
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    &word[start..]
+}
+
+/// Iterator returned by [`candidate_stream`].
+pub struct CandidateStream {
+    inner: MetadataStream,
+    origin: std::sync::Arc<Origin>,
+}
+
+impl CandidateStream {
+    /// Per-tier statistics for the tiers reached so far; complete once the
+    /// stream is exhausted.
+    pub fn stats(&self) -> &[TierStats] {
+        &self.inner.stats
+    }
+
+    /// Name of the tier the last candidate came from.
+    pub fn tier(&self) -> &'static str {
+        self.inner.tier
+    }
+
+    /// The profile fields and rules behind the last candidate.
+    pub fn origin(&self) -> &Origin {
+        &self.origin
+    }
+
+    /// Skip the candidates in `known` (say, the lines of an earlier
+    /// wordlist) as duplicates, wherever the tiers produce them.
+    pub fn excluding(mut self, known: impl IntoIterator<Item = String>) -> Self {
+        for item in known {
+            self.inner.dedup.mark_seen(&item);
+        }
+        self
+    }
+
+    /// Carry on with `stages` after the stream's own, skipping whatever it
+    /// has produced so far. The length, policy and charset filters stay the
+    /// stream's.
+    pub fn then(mut self, stages: impl IntoIterator<Item = Box<dyn CandidateSource>>) -> Self {
+        let mut tiers: Vec<_> = self.inner.tiers.by_ref().collect();
+        tiers.extend(stages);
+        self.inner.tiers = tiers.into_iter();
+        self
+    }
+
+    /// Run the stream to its end and keep it to replay, so its size is known
+    /// before the first candidate is used. The candidates stay in the dedup
+    /// set, with only their ids on top. With [`GeneratorConfig::bloom`] set
+    /// there is nothing to replay from, so the stream comes back untouched.
+    pub fn into_replay(mut self) -> Result<Replay, Box<Self>> {
+        if self.inner.dedup.admitted().is_none() {
+            return Err(Box::new(self));
+        }
+        let mut ids = Vec::new();
+        while let Some((text, _, _)) = self.inner.next() {
+            let admitted = self.inner.dedup.admitted().expect("exact dedup");
+            ids.push(admitted.id(&text).expect("admitted candidates are kept"));
+        }
+        Ok(Replay {
+            arena: self.inner.dedup.into_admitted().expect("exact dedup"),
+            ids: ids.into_iter(),
+            stats: self.inner.stats,
+        })
+    }
+}
+
+impl Iterator for CandidateStream {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let (text, _, origin) = self.inner.next()?;
+        self.origin = origin;
+        Some(text)
+    }
+}
+
+/// A finished [`CandidateStream`], replayed in the same order by
+/// [`CandidateStream::into_replay`].
+pub struct Replay {
+    arena: Arena,
+    ids: std::vec::IntoIter<usize>,
+    stats: Vec<TierStats>,
+}
+
+impl Replay {
+    /// Per-tier statistics of the finished stream.
+    pub fn stats(&self) -> &[TierStats] {
+        &self.stats
+    }
+}
+
+impl Iterator for Replay {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.ids.next().map(|id| self.arena.get(id).to_string())
+    }
+
+    fn nth(&mut self, n: usize) -> Option<String> {
+        self.ids.nth(n).map(|id| self.arena.get(id).to_string())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Replay {}
+
+/// Deduplicated walk over all tiers that keeps each candidate's origin.
+/// Origins are shared per group, so tracking them costs one `Arc` clone.
+///
+/// With Markov ordering, each tier is expanded in full when reached and
+/// replayed from `ranked`, most likely first.
+struct MetadataStream {
+    tiers: std::vec::IntoIter<Box<dyn CandidateSource>>,
+    tier: &'static str,
+    groups: Option<GroupIter>,
+    current: std::vec::IntoIter<String>,
+    origin: std::sync::Arc<Origin>,
+    ranked: std::vec::IntoIter<(String, std::sync::Arc<Origin>)>,
+    markov: bool,
+    /// Whether the current tier goes through the length and policy filters
+    filtered: bool,
+    dedup: Dedup,
+    stats: Vec<TierStats>,
+}
+
+impl MetadataStream {
+    fn new(profile: &Profile, config: &GeneratorConfig) -> Self {
+        Self {
+            tiers: tiers(profile, config).into_iter(),
+            tier: "",
+            groups: None,
+            current: Vec::new().into_iter(),
+            origin: Default::default(),
+            ranked: Vec::new().into_iter(),
+            markov: config.markov,
+            filtered: true,
+            dedup: Dedup::new(config),
+            stats: Vec::new(),
+        }
+    }
+
+    /// Check `candidate` against the dedup filters, counting the verdict
+    /// for the current tier.
+    fn admit(&mut self, candidate: &str) -> bool {
+        let verdict = self.dedup.check_from(self.filtered, candidate);
+        if let Some(stats) = self.stats.last_mut() {
+            stats.record(verdict);
+        }
+        verdict == Verdict::Admitted
+    }
+
+    /// Expand a whole tier, deduplicated, most likely candidates first.
+    fn rank(&mut self, groups: GroupIter) -> Vec<(String, std::sync::Arc<Origin>)> {
+        let mut tier = Vec::new();
+        for group in groups {
+            let origin = std::sync::Arc::new(group.origin);
+            for item in group.items {
+                if self.admit(&item) {
+                    tier.push((item, origin.clone()));
+                }
+            }
+        }
+        Markov::common().rank(tier, |(text, _)| text)
+    }
+}
+
+impl Iterator for MetadataStream {
+    type Item = (String, &'static str, std::sync::Arc<Origin>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((candidate, origin)) = self.ranked.next() {
+                return Some((candidate, self.tier, origin));
+            }
+            while let Some(candidate) = self.current.next() {
+                if self.admit(&candidate) {
+                    return Some((candidate, self.tier, self.origin.clone()));
+                }
+            }
+            match self.groups.as_mut().and_then(|g| g.next()) {
+                Some(group) => {
+                    self.origin = std::sync::Arc::new(group.origin);
+                    self.current = group.items.into_iter();
+                }
+                None => {
+                    let tier = self.tiers.next()?;
+                    self.tier = tier.name();
+                    self.filtered = tier.filtered();
+                    self.stats.push(TierStats {
+                        name: tier.name(),
+                        ..Default::default()
+                    });
+                    if self.markov && !tier.ranked() {
+                        self.groups = None;
+                        self.ranked = self.rank(tier.groups()).into_iter();
+                    } else {
+                        self.groups = Some(tier.groups());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Build the generation stages for a run, in order: the built-in tiers
+/// enabled at the configured depth, with any [`GeneratorConfig::sources`]
+/// spliced in.
+pub fn tiers(profile: &Profile, config: &GeneratorConfig) -> Vec<Box<dyn CandidateSource>> {
+    let mut tiers: Vec<Box<dyn CandidateSource>> = Vec::new();
+    let mut extra: Vec<(Option<&'static str>, Box<dyn CandidateSource>)> = config
+        .sources
+        .iter()
+        .filter_map(|source| Some((source.after, (source.build)(profile, config)?)))
+        .collect();
+    for tier in builtin_tiers(profile, config) {
+        let name = tier.name;
+        tiers.push(Box::new(tier));
+        let (here, later): (Vec<_>, _) = extra
+            .into_iter()
+            .partition(|(after, _)| *after == Some(name));
+        tiers.extend(here.into_iter().map(|(_, source)| source));
+        extra = later;
+    }
+    tiers.extend(extra.into_iter().map(|(_, source)| source));
+    tiers
+}
+
+/// The built-in tiers enabled at the configured depth, in order.
+///
+/// With `likely_first`, the profile's own numbers are split out of the affix
+/// tier and tried right after the mutated seeds, and the generic affixes
+/// (mostly year suffixes) move behind the word combinations.
+fn builtin_tiers(profile: &Profile, config: &GeneratorConfig) -> Vec<Tier> {
+    let mut seeds = profile.seed_sources();
+    // Heavier fields first, within every tier
+    seeds.sort_by_key(|seed| std::cmp::Reverse(profile.weight(seed.field)));
+    let seed_numbers = profile.seed_numbers_in(&config.date_formats, config.year());
+    let separators = separators(profile, config);
+    let years = match config.years {
+        Some(ref years) => years.clone(),
+        None => default_years(profile, config.year()),
+    };
+    let mut tiers = Vec::new();
+    // Long-tail tiers held back until the word combinations are in
+    let mut deferred = Vec::new();
+
+    // Known guesses, verbatim and ahead of everything else
+    if !profile.guesses.passwords.is_empty() {
+        tiers.push(listed_tier(
+            "Known guesses",
+            Origin::new(&["guesses.passwords"], &["guess"]),
+            profile.guesses.passwords.clone(),
+            true,
+        ));
+    }
+
+    // Tier 0: The target type's own conventions (helpdesk, vendor defaults)
+    for (name, fields, items) in config.template.tiers(profile, years.clone()) {
+        let origin = Origin::new(fields, &["template"]);
+        tiers.push(listed_tier(name, origin, items, true));
+    }
 
     // Tier 1: Common passwords
-    pb.set_message("Tier 1: Common passwords...");
-    let common = common::common_passwords();
-    add_unique(&mut candidates, &mut seen, common.into_iter(), config);
-    pb.set_message(format!("Tier 1 done: {} candidates", candidates.len()));
+    tiers.push(listed_tier(
+        "Common passwords",
+        Origin::new(&[], &["common"]),
+        common::common_passwords(),
+        true,
+    ));
 
     // Tier 2: Mutated seed words
-    pb.set_message("Tier 2: Mutating seed words...");
-    let mut tier2 = Vec::new();
-    for word in &seed_words {
-        tier2.extend(mutations::mutate_word(word));
-        tier2.extend(mutations::double_word(word));
+    let leet = profile.leet.clone();
+    let depth = config.depth;
+    let recent_year = config.recent_year;
+    let keyboard = config.keyboard;
+    tiers.push(Tier {
+        name: "Mutating seed words",
+        groups: Box::new(seeds.clone().into_iter().flat_map(move |seed| {
+            let fields = [seed.field];
+            let mut groups = Vec::new();
+            if seed.has(Transform::AsIs) || seed.has(Transform::NoMutations) {
+                groups.push(Group {
+                    origin: Origin::new(&fields, &["verbatim"]),
+                    items: vec![seed.word.clone()],
+                });
+            }
+            if !seed.has(Transform::NoMutations) {
+                groups.push(Group {
+                    origin: Origin::new(&fields, &["mutate"]),
+                    items: mutations::mutate_word_with(&seed.word, &leet),
+                });
+                groups.push(Group {
+                    origin: Origin::new(&fields, &["double"]),
+                    items: mutations::double_word(&seed.word),
+                });
+                if let Some(year) = recent_year {
+                    groups.push(Group {
+                        origin: Origin::new(&fields, &["recent_years"]),
+                        items: [Mutation::RecentYears(year), Mutation::RecentYearsCapitalized(year)]
+                            .iter()
+                            .flat_map(|mutation| mutation.apply(&seed.word, &leet))
+                            .collect(),
+                    });
+                }
+                if depth >= 2 {
+                    groups.push(Group {
+                        origin: Origin::new(&fields, &["duplicate_at"]),
+                        items: mutations::duplications(&seed.word),
+                    });
+                    groups.push(Group {
+                        origin: Origin::new(&fields, &["shift_digits"]),
+                        items: Mutation::ShiftDigits(keyboard)
+                            .apply(&seed.word, &leet)
+                            .into_iter()
+                            .flat_map(|word| [mutations::capitalize_first(&word), word])
+                            .collect(),
+                    });
+                    groups.push(Group {
+                        origin: Origin::new(&fields, &["phonetic"]),
+                        items: mutations::Mutation::Phonetic
+                            .apply(&seed.word, &leet)
+                            .into_iter()
+                            .flat_map(|word| [mutations::capitalize_first(&word), word])
+                            .collect(),
+                    });
+                    groups.push(Group {
+                        origin: Origin::new(&fields, &["inflect"]),
+                        items: mutations::inflections(&seed.word)
+                            .into_iter()
+                            .flat_map(|word| [mutations::capitalize_first(&word), word])
+                            .collect(),
+                    });
+                }
+                if depth >= 3 {
+                    groups.push(Group {
+                        origin: Origin::new(&fields, &["toggle_at"]),
+                        items: mutations::position_toggles(&seed.word),
+                    });
+                }
+            }
+            groups
+        })),
+        ranked: false,
+        size: None,
+    });
+
+    // Tier 2b: The user's own mutation rules
+    if !config.rules.is_empty() {
+        let rules = config.rules.clone();
+        tiers.push(Tier {
+            name: "Applying user rules",
+            groups: Box::new(
+                seeds
+                    .clone()
+                    .into_iter()
+                    .filter(|seed| !seed.has(Transform::NoMutations))
+                    .map(move |seed| Group {
+                        origin: Origin::new(&[seed.field], &["user_rule"]),
+                        items: rules.iter().map(|rule| rule.apply(&seed.word)).collect(),
+                    }),
+            ),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 2b: hashcat rule sets (best64 and the like)
+    if !config.hashcat_rules.is_empty() {
+        let rules = config.hashcat_rules.clone();
+        tiers.push(Tier {
+            name: "Applying hashcat rules",
+            groups: Box::new(
+                seeds
+                    .clone()
+                    .into_iter()
+                    .filter(|seed| !seed.has(Transform::NoMutations))
+                    .map(move |seed| Group {
+                        origin: Origin::new(&[seed.field], &["hashcat_rule"]),
+                        items: rules
+                            .iter()
+                            .filter_map(|rule| rule.apply(&seed.word))
+                            .collect(),
+                    }),
+            ),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 2c: Case flipped at up to `case_toggles` letters
+    if config.case_toggles > 0 {
+        let max = config.case_toggles;
+        tiers.push(Tier {
+            name: "Toggling case",
+            groups: Box::new(
+                seeds
+                    .clone()
+                    .into_iter()
+                    .filter(|seed| !seed.has(Transform::NoMutations))
+                    .map(move |seed| Group {
+                        origin: Origin::new(&[seed.field], &["case_toggle"]),
+                        items: mutations::case_toggles(&seed.word, max),
+                    }),
+            ),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 2d: Seed words rotated by one or more characters
+    if config.rotations {
+        tiers.push(Tier {
+            name: "Rotating seed words",
+            groups: Box::new(
+                seeds
+                    .clone()
+                    .into_iter()
+                    .filter(|seed| !seed.has(Transform::NoMutations))
+                    .map(|seed| Group {
+                        origin: Origin::new(&[seed.field], &["rotate"]),
+                        items: mutations::rotations(&seed.word)
+                            .into_iter()
+                            .flat_map(|word| [mutations::capitalize_first(&word), word])
+                            .collect(),
+                    }),
+            ),
+            ranked: false,
+            size: None,
+        });
     }
-    add_unique(&mut candidates, &mut seen, tier2.into_iter(), config);
-    pb.set_message(format!("Tier 2 done: {} candidates", candidates.len()));
 
     // Tier 3: Seeds + affixes
     if config.depth >= 2 {
-        pb.set_message("Tier 3: Applying affixes...");
-        let mut tier3 = Vec::new();
-
-        let num_suffixes = common::numeric_suffixes();
+        let num_suffixes = common::numeric_suffixes(years.clone());
         let sym_suffixes = common::symbol_suffixes();
         let prefixes = common::common_prefixes();
+        let numbers = seed_numbers.clone();
+        let shifted: Vec<String> = seed_numbers
+            .iter()
+            .map(|number| mutations::shift_digits(number, config.keyboard))
+            .filter(|shifted| !seed_numbers.contains(shifted))
+            .collect();
+        let likely_first = config.likely_first;
 
-        for word in &seed_words {
-            // Numeric suffixes
-            for suffix in &num_suffixes {
-                tier3.extend(mutations::apply_suffix(word, suffix));
+        let affixed = seeds.clone().into_iter().flat_map(move |seed| {
+            if seed.has(Transform::NoAffixes) {
+                return Vec::new();
             }
-            // Symbol suffixes
-            for suffix in &sym_suffixes {
-                tier3.extend(mutations::apply_suffix(word, suffix));
+            let seed = &seed;
+            let fields = [seed.field];
+            let mut groups = vec![
+                // Numeric suffixes
+                Group {
+                    origin: Origin::new(&fields, &["numeric_suffix"]),
+                    items: num_suffixes
+                        .iter()
+                        .flat_map(|suffix| suffixed(seed, suffix))
+                        .collect(),
+                },
+                // Symbol suffixes
+                Group {
+                    origin: Origin::new(&fields, &["symbol_suffix"]),
+                    items: sym_suffixes
+                        .iter()
+                        .flat_map(|suffix| suffixed(seed, suffix))
+                        .collect(),
+                },
+                // Prefixes
+                Group {
+                    origin: Origin::new(&fields, &["prefix"]),
+                    items: prefixes
+                        .iter()
+                        .flat_map(|prefix| prefixed(prefix, seed))
+                        .collect(),
+                },
+            ];
+            // Seed numbers as suffixes, unless they get a tier of their own
+            if !likely_first {
+                groups.push(seed_number_group(seed, &numbers));
             }
-            // Prefixes
-            for prefix in &prefixes {
-                tier3.extend(mutations::apply_prefix(prefix, word));
+            // Seed numbers typed with Shift: john!(() for john1990
+            groups.push(Group {
+                origin: Origin::new(&fields, &["seed_number", "shift_digits"]),
+                items: shifted
+                    .iter()
+                    .flat_map(|number| with_number(seed, number))
+                    .collect(),
+            });
+            // Numeric suffixes after a capitalized last letter (johN123)
+            if depth >= 3 && !seed.has(Transform::AsIs) {
+                let last =
+                    mutations::Mutation::CapitalizeLast.apply(&seed.word, &Default::default());
+                groups.push(Group {
+                    origin: Origin::new(&fields, &["capitalize_last", "numeric_suffix"]),
+                    items: last
+                        .iter()
+                        .flat_map(|word| num_suffixes.iter().map(move |s| format!("{}{}", word, s)))
+                        .collect(),
+                });
             }
-            // Seed numbers as suffixes
-            for num in &seed_numbers {
-                tier3.extend(mutations::combine_word_number(word, num));
-            }
-        }
+            groups
+        });
 
-        // Also add seed numbers with common words
-        for num in &seed_numbers {
-            tier3.push(num.clone());
+        // Also add seed numbers on their own
+        let standalone = Group {
+            origin: Origin::new(&["numbers"], &["seed_number"]),
+            items: seed_numbers.clone(),
+        };
+        if likely_first {
+            let numbers = seed_numbers.clone();
+            let numbered = seeds
+                .clone()
+                .into_iter()
+                .filter(|seed| !seed.has(Transform::NoAffixes))
+                .map(move |seed| seed_number_group(&seed, &numbers));
+            tiers.push(Tier {
+                name: "Seed numbers",
+                groups: Box::new(numbered.chain(std::iter::once(standalone))),
+                ranked: false,
+                size: None,
+            });
+            deferred.push(Tier {
+                name: "Applying affixes",
+                groups: Box::new(affixed),
+                ranked: false,
+                size: None,
+            });
+        } else {
+            tiers.push(Tier {
+                name: "Applying affixes",
+                groups: Box::new(affixed.chain(std::iter::once(standalone))),
+                ranked: false,
+                size: None,
+            });
         }
+    }
 
-        add_unique(&mut candidates, &mut seen, tier3.into_iter(), config);
-        pb.set_message(format!("Tier 3 done: {} candidates", candidates.len()));
+    // Tier 3b: One family member's name with another's birthdate numbers
+    let family = profile.family(config.year());
+    if config.depth >= 2 && family.len() >= 2 && family.iter().any(|m| !m.numbers.is_empty()) {
+        tiers.push(Tier {
+            name: "Family numbers",
+            groups: Box::new((0..family.len()).flat_map(move |a| {
+                let mut groups = Vec::new();
+                for (b, other) in family.iter().enumerate() {
+                    if b == a || other.numbers.is_empty() {
+                        continue;
+                    }
+                    for name in &family[a].names {
+                        if name.has(Transform::NoAffixes) {
+                            continue;
+                        }
+                        groups.push(Group {
+                            origin: Origin::new(
+                                &[name.field, other.names[0].field],
+                                &["family_number"],
+                            ),
+                            items: other
+                                .numbers
+                                .iter()
+                                .flat_map(|num| with_number(name, num))
+                                .collect(),
+                        });
+                    }
+                }
+                groups
+            })),
+            ranked: false,
+            size: None,
+        });
     }
 
-    // Tier 4: Word combinations
+    // Tier 3c: The profile's numbers spelled out (seven, johntwentyone), and
+    // number words in the seeds written in digits (onelove → 1love)
     if config.depth >= 2 {
-        pb.set_message("Tier 4: Combining words...");
-        let mut tier4 = Vec::new();
-
-        for (i, a) in seed_words.iter().enumerate() {
-            for b in seed_words.iter().skip(i + 1) {
-                tier4.extend(mutations::combine_words(a, b));
+        let mut spelled: Vec<String> = Vec::new();
+        for word in seed_numbers.iter().filter_map(|n| mutations::spell_number(n)) {
+            if !spelled.contains(&word) {
+                spelled.push(word);
             }
-            // Word + seed number combos
-            for num in &seed_numbers {
-                tier4.extend(mutations::combine_word_number(a, num));
+        }
+        let standalone = Group {
+            origin: Origin::new(&["numbers"], &["spell_number"]),
+            items: spelled
+                .iter()
+                .flat_map(|word| [word.clone(), mutations::capitalize_first(word)])
+                .collect(),
+        };
+        let worded = seeds.clone().into_iter().flat_map(move |seed| {
+            let fields = [seed.field];
+            let mut groups = Vec::new();
+            if !seed.has(Transform::NoMutations) {
+                groups.push(Group {
+                    origin: Origin::new(&fields, &["number_words"]),
+                    items: mutations::number_words_to_digits(&seed.word)
+                        .into_iter()
+                        .flat_map(|word| [mutations::capitalize_first(&word), word])
+                        .collect(),
+                });
+            }
+            if !seed.has(Transform::NoAffixes) {
+                groups.push(Group {
+                    origin: Origin::new(&fields, &["spell_number"]),
+                    items: spelled
+                        .iter()
+                        .flat_map(|word| [word.clone(), mutations::capitalize_first(word)])
+                        .flat_map(|word| suffixed(&seed, &word))
+                        .collect(),
+                });
             }
+            groups
+        });
+        tiers.push(Tier {
+            name: "Number words",
+            groups: Box::new(std::iter::once(standalone).chain(worded)),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 3d: Month and season names with recent years (Oktober2023!),
+    // and at depth 3 after the seeds (buddyMay2024)
+    if config.depth >= 2 {
+        let locales = calendar_locales(profile, config);
+        let standalone = Group {
+            origin: Origin::new(&[], &["calendar"]),
+            items: calendar_words(&locales, years.clone()),
+        };
+        let stamps = calendar_stamps(&locales, years.clone());
+        let stamped = match config.depth {
+            3 => seeds.clone(),
+            _ => Vec::new(),
+        };
+        let stamped = stamped
+            .into_iter()
+            .filter(|seed| !seed.has(Transform::NoAffixes))
+            .map(move |seed| Group {
+                origin: Origin::new(&[seed.field], &["calendar_suffix"]),
+                items: stamps
+                    .iter()
+                    .flat_map(|stamp| suffixed(&seed, stamp))
+                    .collect(),
+            });
+        let tier = Tier {
+            name: "Month and season names",
+            groups: Box::new(std::iter::once(standalone).chain(stamped)),
+            ranked: false,
+            size: None,
+        };
+        // Generic like the other affixes, so held back with them
+        if config.likely_first {
+            deferred.push(tier);
+        } else {
+            tiers.push(tier);
         }
+    }
+
+    // Tier 4: Word combinations
+    if config.depth >= 2 {
+        let seeds = seeds.clone();
+        let partners = combination_partners(profile, &seeds, config.all_pairs);
+        let numbers = seed_numbers.clone();
+        let separators = separators.clone();
+        tiers.push(Tier {
+            name: "Combining words",
+            groups: Box::new((0..seeds.len()).flat_map(move |i| {
+                let a = &seeds[i];
+                let mut groups = Vec::new();
+                for b in partners[i].iter().map(|&j| &seeds[j]) {
+                    groups.push(Group {
+                        origin: Origin::new(&[a.field, b.field], &["combine"]),
+                        items: combined(a, b, separators.as_deref()),
+                    });
+                }
+                // Word + seed number combos
+                if !a.has(Transform::NoAffixes) {
+                    groups.push(seed_number_group(a, &numbers));
+                }
+                groups
+            })),
+            ranked: false,
+            size: None,
+        });
+    }
 
-        add_unique(&mut candidates, &mut seen, tier4.into_iter(), config);
-        pb.set_message(format!("Tier 4 done: {} candidates", candidates.len()));
+    tiers.append(&mut deferred);
+
+    // Tier 4b: Passphrases of seed words and filler words
+    if config.depth >= 2 {
+        let seeds: Vec<SeedSource> = seeds
+            .iter()
+            .filter(|seed| !seed.has(Transform::NoCombinations))
+            .cloned()
+            .collect();
+        let partners = combination_partners(profile, &seeds, config.all_pairs);
+        // Numbered phrases multiply fast, so they wait for depth 3
+        let numbers = if config.depth >= 3 {
+            seed_numbers.clone()
+        } else {
+            Vec::new()
+        };
+        let separators = separators.clone().unwrap_or_else(|| {
+            common::PASSPHRASE_SEPARATORS
+                .iter()
+                .map(|sep| sep.to_string())
+                .collect()
+        });
+        tiers.push(Tier {
+            name: "Building passphrases",
+            groups: Box::new((0..seeds.len()).flat_map(move |i| {
+                let a = &seeds[i];
+                let mut groups = Vec::new();
+                for template in common::PASSPHRASES {
+                    let slots = template.iter().filter(|&&w| w == "{}").count();
+                    let pairs: Vec<Vec<&SeedSource>> = match slots {
+                        1 => vec![vec![a]],
+                        _ => partners[i]
+                            .iter()
+                            .flat_map(|&j| [vec![a, &seeds[j]], vec![&seeds[j], a]])
+                            .collect(),
+                    };
+                    for filled in pairs {
+                        let fields: Vec<&'static str> = filled.iter().map(|s| s.field).collect();
+                        let phrases = passphrases(template, &filled, &separators);
+                        // The numbers follow the first separator's two forms
+                        // ("ilovebuddy2020")
+                        let numbered: Vec<String> = phrases[..2]
+                            .iter()
+                            .flat_map(|p| numbers.iter().map(move |n| format!("{}{}", p, n)))
+                            .collect();
+                        groups.push(Group {
+                            origin: Origin::new(&fields, &["passphrase"]),
+                            items: phrases,
+                        });
+                        if !numbered.is_empty()
+                            && filled.iter().all(|s| !s.has(Transform::NoAffixes))
+                        {
+                            groups.push(Group {
+                                origin: Origin::new(&fields, &["passphrase", "seed_number"]),
+                                items: numbered,
+                            });
+                        }
+                    }
+                }
+                groups
+            })),
+            ranked: false,
+            size: None,
+        });
     }
 
     // Tier 5: Keyboard patterns
     if config.depth >= 2 {
-        pb.set_message("Tier 5: Keyboard patterns...");
-        let patterns = common::keyboard_patterns();
-        add_unique(&mut candidates, &mut seen, patterns.into_iter(), config);
-        pb.set_message(format!("Tier 5 done: {} candidates", candidates.len()));
+        tiers.push(listed_tier(
+            "Keyboard patterns",
+            Origin::new(&[], &["keyboard"]),
+            common::keyboard_patterns(config.keyboard),
+            false,
+        ));
+    }
+
+    // Tier 5a: Typing slips on the seeds, and at depth 3 on the most common
+    // passwords
+    if config.depth >= 2 {
+        let layout = config.keyboard;
+        let slipped = seeds
+            .clone()
+            .into_iter()
+            .filter(|seed| !seed.has(Transform::NoMutations))
+            .map(move |seed| Group {
+                origin: Origin::new(&[seed.field], &["typo"]),
+                items: mutations::typos(&seed.word, layout)
+                    .into_iter()
+                    .flat_map(|typo| [mutations::capitalize_first(&typo), typo])
+                    .collect(),
+            });
+        let common = match config.depth {
+            3 => common::common_passwords(),
+            _ => Vec::new(),
+        };
+        let common = common
+            .into_iter()
+            .take(TYPO_COMMON_PASSWORDS)
+            .map(move |password| Group {
+                origin: Origin::new(&[], &["common", "typo"]),
+                items: mutations::typos(&password, layout),
+            });
+        tiers.push(Tier {
+            name: "Typos",
+            groups: Box::new(slipped.chain(common)),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 5b: Digits and symbols inside the seeds (pa55!word), and at depth
+    // 3 the seed numbers too (john1990smith)
+    if config.depth >= 2 && config.insertions > 0 {
+        let mut inserts: Vec<String> = MID_WORD_INSERTS.map(String::from).to_vec();
+        if config.depth >= 3 {
+            inserts.extend(seed_numbers.iter().cloned());
+        }
+        let max = config.insertions;
+        let inserted = seeds
+            .clone()
+            .into_iter()
+            .filter(|seed| !seed.has(Transform::NoMutations))
+            .map(move |seed| Group {
+                origin: Origin::new(&[seed.field], &["insertion"]),
+                items: mutations::insertions(&seed.word, &inserts, max)
+                    .into_iter()
+                    .flat_map(|word| [word.clone(), mutations::capitalize_first(&word)])
+                    .collect(),
+            });
+        tiers.push(Tier {
+            name: "Mid-word insertions",
+            groups: Box::new(inserted),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 5c: Seeds cut short (smithson -> smith, john -> joh), on their own
+    // and with the seed numbers, and at depth 3 the numeric suffixes
+    if config.depth >= 2 {
+        let numbers = seed_numbers.clone();
+        let suffixes = match config.depth {
+            3 => common::numeric_suffixes(years.clone()),
+            _ => Vec::new(),
+        };
+        let truncated = seeds
+            .clone()
+            .into_iter()
+            .filter(|seed| !seed.has(Transform::NoMutations) && !seed.has(Transform::AsIs))
+            .flat_map(move |seed| {
+                let fields = [seed.field];
+                let parts: Vec<String> = mutations::truncations(&seed.word)
+                    .into_iter()
+                    .flat_map(|part| [mutations::capitalize_first(&part), part])
+                    .collect();
+                let appended = |endings: &[String]| -> Vec<String> {
+                    parts
+                        .iter()
+                        .flat_map(|part| endings.iter().map(move |end| format!("{}{}", part, end)))
+                        .collect()
+                };
+                vec![
+                    Group {
+                        origin: Origin::new(&fields, &["truncate"]),
+                        items: parts.clone(),
+                    },
+                    Group {
+                        origin: Origin::new(&fields, &["truncate", "seed_number"]),
+                        items: appended(&numbers),
+                    },
+                    Group {
+                        origin: Origin::new(&fields, &["truncate", "numeric_suffix"]),
+                        items: appended(&suffixes),
+                    },
+                ]
+            });
+        tiers.push(Tier {
+            name: "Truncated seeds",
+            groups: Box::new(truncated),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 5d: The target's language's everyday words, as light seeds
+    if config.depth >= 2 {
+        if let Some(ref language) = profile.personal.language {
+            let words = common::language_words(language);
+            let numbers = seed_numbers.clone();
+            tiers.push(Tier {
+                name: "Language words",
+                groups: light_seed_groups(words, "personal.language", numbers, config.depth),
+                ranked: false,
+                size: None,
+            });
+        }
+    }
+
+    // Tier 5e: Topic words the interests bring in (a favorite team's
+    // mascots, players and chants), as light seeds
+    if config.depth >= 2 {
+        if let Some(ref listed) = config.topics {
+            let interests = profile.interests();
+            let mut topics: Vec<&str> = profile.topics();
+            for topic in listed {
+                if !topics.contains(&topic.as_str()) {
+                    topics.push(topic);
+                }
+            }
+            let words: Vec<String> = topics
+                .iter()
+                .flat_map(|topic| common::topic_words(topic, &interests))
+                .collect();
+            if !words.is_empty() {
+                let numbers = seed_numbers.clone();
+                tiers.push(Tier {
+                    name: "Topic words",
+                    groups: light_seed_groups(words, "interests.topics", numbers, config.depth),
+                    ranked: false,
+                    size: None,
+                });
+            }
+        }
+    }
+
+    // Base wordlist entries as light seeds, after the profile's own
+    if let Some(ref path) = config.base_wordlist {
+        // Checked to be readable when the run starts
+        let words = wordlist::stream_wordlist(path).map(|stream| stream.quiet());
+        let words = words.into_iter().flatten();
+        tiers.push(Tier {
+            name: "Base wordlist",
+            groups: light_seed_groups(words, "base_wordlist", seed_numbers.clone(), config.depth),
+            // Dictionaries like rockyou come most common first
+            ranked: true,
+            size: None,
+        });
     }
 
     // Tier 6: Deep mutations on combinations (depth=3 only)
     if config.depth >= 3 {
-        pb.set_message("Tier 6: Deep mutations on combinations...");
-        let mut tier6 = Vec::new();
+        let pairs = seeds.clone();
+        let partners = combination_partners(profile, &pairs, config.all_pairs);
+        let separators = separators.clone();
+        let leet = profile.leet.clone();
 
         // Mutate Tier 4 style combinations
-        for (i, a) in seed_words.iter().enumerate() {
-            for b in seed_words.iter().skip(i + 1) {
-                let combos = mutations::combine_words(a, b);
-                for combo in &combos {
-                    tier6.extend(mutations::mutate_combined(combo));
+        let combos = (0..pairs.len()).flat_map(move |i| {
+            let a = &pairs[i];
+            let mut groups = Vec::new();
+            for b in partners[i].iter().map(|&j| &pairs[j]) {
+                let fields = [a.field, b.field];
+                let mutate = !a.has(Transform::NoMutations) && !b.has(Transform::NoMutations);
+                let affix = !a.has(Transform::NoAffixes) && !b.has(Transform::NoAffixes);
+                for combo in combined(a, b, separators.as_deref()) {
+                    if mutate {
+                        groups.push(Group {
+                            origin: Origin::new(&fields, &["combine", "mutate_combined"]),
+                            items: mutations::mutate_combined_with(&combo, &leet),
+                        });
+                    }
                     // Add suffixes to combos
-                    for suffix in &["123", "!", "1", "12", "1!"] {
-                        tier6.push(format!("{}{}", combo, suffix));
+                    if affix {
+                        groups.push(Group {
+                            origin: Origin::new(&fields, &["combine", "combo_suffix"]),
+                            items: COMBO_SUFFIXES
+                                .iter()
+                                .map(|suffix| format!("{}{}", combo, suffix))
+                                .collect(),
+                        });
                     }
                 }
             }
-        }
+            groups
+        });
 
         // Mutated seeds + affixes
-        for word in &seed_words {
-            let mutated = mutations::mutate_word(word);
-            let num_suffixes = common::numeric_suffixes();
-            for m in &mutated {
-                for suffix in &num_suffixes {
-                    tier6.extend(mutations::apply_suffix(m, suffix));
+        let num_suffixes = common::numeric_suffixes(years.clone());
+        let leet = profile.leet.clone();
+        let affixed = seeds
+            .clone()
+            .into_iter()
+            .filter(|seed| !seed.has(Transform::NoMutations) && !seed.has(Transform::NoAffixes))
+            .map(move |seed| {
+                let mut items = Vec::new();
+                for m in &mutations::mutate_word_with(&seed.word, &leet) {
+                    for suffix in &num_suffixes {
+                        items.extend(mutations::apply_suffix(m, suffix));
+                    }
+                }
+                Group {
+                    origin: Origin::new(&[seed.field], &["mutate", "numeric_suffix"]),
+                    items,
                 }
+            });
+
+        tiers.push(Tier {
+            name: "Deep mutations on combinations",
+            groups: Box::new(combos.chain(affixed)),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    // Tier 7: Three-word combinations (depth=3 only)
+    if config.depth >= 3 {
+        let partners = combination_partners(profile, &seeds, config.all_pairs);
+        let numbers = seed_numbers.clone();
+        tiers.push(Tier {
+            name: "Combining three words",
+            groups: Box::new((0..seeds.len()).flat_map(move |i| {
+                let a = &seeds[i];
+                let mut groups = Vec::new();
+                for &j in &partners[i] {
+                    let b = &seeds[j];
+                    // Every pair among the three has to be worth combining
+                    for &k in partners[j].iter().filter(|k| partners[i].contains(k)) {
+                        let c = &seeds[k];
+                        groups.push(Group {
+                            origin: Origin::new(&[a.field, b.field, c.field], &["combine_three"]),
+                            items: tripled(a, b, c),
+                        });
+                    }
+                    // Two words and a seed number: name + pet + year
+                    if !a.has(Transform::NoAffixes) && !b.has(Transform::NoAffixes) {
+                        groups.push(Group {
+                            origin: Origin::new(&[a.field, b.field, "numbers"], &["combine_three"]),
+                            items: paired_with_numbers(a, b, &numbers),
+                        });
+                    }
+                }
+                groups
+            })),
+            ranked: false,
+            size: None,
+        });
+    }
+
+    tiers
+}
+
+/// For each seed, the indices of later seeds worth combining it with.
+///
+/// Seeds are compared in canonical form (lowercase letters and digits only);
+/// a pair is skipped when one contains the other, so overlapping seeds such
+/// as "john" + "johnny" or "new york" + "york" don't multiply into
+/// near-duplicate combinations. Unless `all_pairs` is set, pairs whose seed
+/// classes rarely appear together (hobby + hobby, SSID + ISP) are skipped too.
+///
+/// The profile's weights adjust this: a seed weighted 0 is never combined,
+/// two seeds both weighted under the default aren't either, and two both
+/// weighted over it are combined whatever their classes.
+fn combination_partners(
+    profile: &Profile,
+    seeds: &[SeedSource],
+    all_pairs: bool,
+) -> Vec<Vec<usize>> {
+    let canon: Vec<String> = seeds.iter().map(|s| canonical(&s.word)).collect();
+    let classes: Vec<SeedClass> = seeds.iter().map(|s| s.class()).collect();
+    let weights: Vec<u32> = seeds.iter().map(|s| profile.weight(s.field)).collect();
+    let weighed = |i: usize, j: usize| match (weights[i], weights[j]) {
+        (0, _) | (_, 0) => false,
+        _ if all_pairs => true,
+        (a, b) if a < DEFAULT_WEIGHT && b < DEFAULT_WEIGHT => false,
+        (a, b) if a > DEFAULT_WEIGHT && b > DEFAULT_WEIGHT => true,
+        _ => classes[i].pairs_well_with(classes[j]),
+    };
+    (0..seeds.len())
+        .map(|i| {
+            (i + 1..seeds.len())
+                .filter(|&j| weighed(i, j))
+                .filter(|&j| {
+                    !seeds[i].has(Transform::NoCombinations)
+                        && !seeds[j].has(Transform::NoCombinations)
+                })
+                .filter(|&j| !canon[i].contains(&canon[j]) && !canon[j].contains(&canon[i]))
+                .collect()
+        })
+        .collect()
+}
+
+/// A seed reduced to unaccented lowercase letters and digits for overlap
+/// checks.
+fn canonical(word: &str) -> String {
+    unicode::to_ascii(word)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// `seed` + `suffix`, in lower and capitalized form, or verbatim for as-is seeds.
+fn suffixed(seed: &SeedSource, suffix: &str) -> Vec<String> {
+    if seed.has(Transform::AsIs) {
+        vec![format!("{}{}", seed.word, suffix)]
+    } else {
+        mutations::apply_suffix(&seed.word, suffix)
+    }
+}
+
+/// `prefix` + `seed`, in lower and capitalized form, or verbatim for as-is seeds.
+fn prefixed(prefix: &str, seed: &SeedSource) -> Vec<String> {
+    if seed.has(Transform::AsIs) {
+        vec![format!("{}{}", prefix, seed.word)]
+    } else {
+        mutations::apply_prefix(prefix, &seed.word)
+    }
+}
+
+/// A seed joined with a number on either side.
+fn with_number(seed: &SeedSource, number: &str) -> Vec<String> {
+    if seed.has(Transform::AsIs) {
+        vec![
+            format!("{}{}", seed.word, number),
+            format!("{}{}", number, seed.word),
+        ]
+    } else {
+        mutations::combine_word_number(&seed.word, number)
+    }
+}
+
+/// A seed with each of the profile's numbers appended.
+fn seed_number_group(seed: &SeedSource, numbers: &[String]) -> Group {
+    Group {
+        origin: Origin::new(&[seed.field], &["seed_number"]),
+        items: numbers
+            .iter()
+            .flat_map(|num| with_number(seed, num))
+            .collect(),
+    }
+}
+
+/// Two seeds joined; an as-is seed keeps its spelling, so only the plain
+/// joins and the reversal are produced.
+fn combined(a: &SeedSource, b: &SeedSource, separators: Option<&[String]>) -> Vec<String> {
+    let separators = match separators {
+        Some(separators) => separators,
+        None if a.has(Transform::AsIs) || b.has(Transform::AsIs) => {
+            return vec![
+                format!("{}{}", a.word, b.word),
+                format!("{}_{}", a.word, b.word),
+                format!("{}.{}", a.word, b.word),
+                format!("{}{}", b.word, a.word),
+            ]
+        }
+        None => return mutations::combine_words(&a.word, &b.word),
+    };
+    if a.has(Transform::AsIs) || b.has(Transform::AsIs) {
+        let mut combos: Vec<String> = separators
+            .iter()
+            .map(|sep| format!("{}{}{}", a.word, sep, b.word))
+            .collect();
+        combos.push(format!("{}{}", b.word, a.word));
+        combos
+    } else {
+        mutations::combine_words_with(&a.word, &b.word, separators)
+    }
+}
+
+/// The separators set by `config` or the profile, if either sets any.
+fn separators(profile: &Profile, config: &GeneratorConfig) -> Option<Vec<String>> {
+    [&config.separators, &profile.custom.separators]
+        .into_iter()
+        .find(|separators| !separators.is_empty())
+        .cloned()
+}
+
+/// Three seeds in every order, verbatim if any of them is kept as-is.
+fn tripled(a: &SeedSource, b: &SeedSource, c: &SeedSource) -> Vec<String> {
+    if [a, b, c].iter().any(|s| s.has(Transform::AsIs)) {
+        let words = [&a.word, &b.word, &c.word];
+        mutations::ORDERINGS_OF_THREE
+            .iter()
+            .map(|&[x, y, z]| format!("{}{}{}", words[x], words[y], words[z]))
+            .collect()
+    } else {
+        mutations::combine_three(&a.word, &b.word, &c.word)
+    }
+}
+
+/// `template` with its `{}` slots filled by `seeds` in order, joined by each
+/// separator, all lowercase and with every word capitalized. As-is seeds
+/// keep their spelling in both.
+fn passphrases(template: &[&str], seeds: &[&SeedSource], separators: &[String]) -> Vec<String> {
+    let mut seeds = seeds.iter();
+    let (mut lower, mut title) = (Vec::new(), Vec::new());
+    for &word in template {
+        let seed = if word == "{}" { seeds.next() } else { None };
+        match seed {
+            Some(seed) if seed.has(Transform::AsIs) => {
+                lower.push(seed.word.clone());
+                title.push(seed.word.clone());
+            }
+            Some(seed) => {
+                let word = seed.word.to_lowercase();
+                title.push(mutations::capitalize_first(&word));
+                lower.push(word);
+            }
+            None => {
+                lower.push(word.to_string());
+                title.push(mutations::capitalize_first(word));
             }
         }
+    }
+    separators
+        .iter()
+        .flat_map(|sep| [lower.join(sep), title.join(sep)])
+        .collect()
+}
+
+/// Two seeds run together, both ways round, followed by each seed number.
+fn paired_with_numbers(a: &SeedSource, b: &SeedSource, numbers: &[String]) -> Vec<String> {
+    let pairs: Vec<String> = if a.has(Transform::AsIs) || b.has(Transform::AsIs) {
+        vec![
+            format!("{}{}", a.word, b.word),
+            format!("{}{}", b.word, a.word),
+        ]
+    } else {
+        let (a, b) = (a.word.to_lowercase(), b.word.to_lowercase());
+        let a_cap = mutations::capitalize_first(&a);
+        let b_cap = mutations::capitalize_first(&b);
+        vec![
+            format!("{}{}", a, b),
+            format!("{}{}", a_cap, b_cap),
+            format!("{}{}", b, a),
+            format!("{}{}", b_cap, a_cap),
+        ]
+    };
+    pairs
+        .iter()
+        .flat_map(|pair| numbers.iter().map(move |n| format!("{}{}", pair, n)))
+        .collect()
+}
 
-        add_unique(&mut candidates, &mut seen, tier6.into_iter(), config);
-        pb.set_message(format!("Tier 6 done: {} candidates", candidates.len()));
+/// The locales to name months and seasons in: those the birthdate is
+/// written in, or English when they name none.
+fn calendar_locales(profile: &Profile, config: &GeneratorConfig) -> Vec<String> {
+    let locales = if config.date_formats.is_empty() {
+        profile.personal.locales.clone()
+    } else {
+        config.date_formats.clone()
+    };
+    if date::month_names(&locales).is_empty() && date::season_names(&locales).is_empty() {
+        vec!["en".to_string()]
+    } else {
+        locales
     }
+}
 
-    pb.finish_with_message(format!("Generated {} unique candidates", candidates.len()));
-    candidates
+/// Month and season names in `locales`, on their own and then with each
+/// recent year of `years`, newest first: in full (`Oktober2023!`,
+/// `summer24`) and, for months, cut to three letters (`des24`, `Dec2024`).
+fn calendar_words(locales: &[String], years: RangeInclusive<u16>) -> Vec<String> {
+    let months = date::month_names(locales);
+    let names: Vec<&str> = months.iter().chain(&date::season_names(locales)).copied().collect();
+    let mut words: Vec<String> = names
+        .iter()
+        .flat_map(|name| [name.to_string(), mutations::capitalize_first(name)])
+        .collect();
+    for year in template::recent(years) {
+        let short = &year[year.len().saturating_sub(2)..];
+        for name in &names {
+            let cap = mutations::capitalize_first(name);
+            words.push(format!("{}{}", cap, year));
+            words.push(format!("{}{}!", cap, year));
+            words.push(format!("{}{}", name, year));
+            words.push(format!("{}{}", cap, short));
+            words.push(format!("{}{}!", cap, short));
+            words.push(format!("{}{}", name, short));
+        }
+        for month in &months {
+            let abbr: String = month.chars().take(3).collect();
+            let cap = mutations::capitalize_first(&abbr);
+            words.push(format!("{}{}", abbr, short));
+            words.push(format!("{}{}", cap, short));
+            words.push(format!("{}{}", cap, year));
+        }
+    }
+    words
 }
 
-/// Add items to candidates if they pass filters and haven't been seen.
-fn add_unique(
-    candidates: &mut Vec<String>,
-    seen: &mut HashSet<String>,
-    items: impl Iterator<Item = String>,
-    config: &GeneratorConfig,
-) {
-    for item in items {
-        if item.len() >= config.min_length
-            && item.len() <= config.max_length
-            && seen.insert(item.clone())
+/// Month and season names with a recent year, as they follow a seed word:
+/// capitalized with the full year and lowercase with the short one
+/// (`May2024`, `may24`).
+fn calendar_stamps(locales: &[String], years: RangeInclusive<u16>) -> Vec<String> {
+    let months = date::month_names(locales);
+    let seasons = date::season_names(locales);
+    let mut stamps = Vec::new();
+    for year in template::recent(years) {
+        let short = &year[year.len().saturating_sub(2)..];
+        for name in months.iter().chain(&seasons) {
+            stamps.push(format!("{}{}", mutations::capitalize_first(name), year));
+            stamps.push(format!("{}{}", name, short));
+        }
+    }
+    stamps
+}
+
+/// How many of the most common passwords get typo variants at depth 3.
+const TYPO_COMMON_PASSWORDS: usize = 100;
+
+/// Characters the mid-word insertions place between a seed's letters.
+const MID_WORD_INSERTS: [&str; 18] = [
+    "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "!", "@", "#", "$", ".", "_", "-", "*",
+];
+
+/// Short suffixes for candidates that are already more than one seed word
+/// long, or come from a base wordlist.
+const COMBO_SUFFIXES: [&str; 5] = ["123", "!", "1", "12", "1!"];
+
+/// The light mutation set for dictionary words that aren't about the
+/// target: as written and capitalized, then (from depth 2) with the
+/// profile's numbers and a few short suffixes. `words` is consumed lazily,
+/// so a huge dictionary never sits in memory.
+fn light_seed_groups(
+    words: impl IntoIterator<Item = String, IntoIter: Send + 'static>,
+    field: &'static str,
+    numbers: Vec<String>,
+    depth: u8,
+) -> GroupIter {
+    Box::new(words.into_iter().flat_map(move |word| {
+        let fields = [field];
+        let capitalized = mutations::capitalize_first(&word);
+        let mut groups = vec![Group {
+            origin: Origin::new(&fields, &["base_word"]),
+            items: vec![word.clone(), capitalized.clone()],
+        }];
+        if depth >= 2 {
+            groups.push(Group {
+                origin: Origin::new(&fields, &["base_word", "seed_number"]),
+                items: numbers
+                    .iter()
+                    .flat_map(|n| [format!("{}{}", word, n), format!("{}{}", capitalized, n)])
+                    .collect(),
+            });
+            groups.push(Group {
+                origin: Origin::new(&fields, &["base_word", "combo_suffix"]),
+                items: COMBO_SUFFIXES
+                    .iter()
+                    .map(|suffix| format!("{}{}", word, suffix))
+                    .collect(),
+            });
+        }
+        groups
+    }))
+}
+
+/// A tier of one fixed list, whose size is known up front.
+fn listed_tier(name: &'static str, origin: Origin, items: Vec<String>, ranked: bool) -> Tier {
+    Tier {
+        name,
+        size: Some(items.len() as u64),
+        groups: Box::new(std::iter::once(Group { origin, items })),
+        ranked,
+    }
+}
+
+/// Length, policy and charset filters plus first-seen deduplication shared
+/// by all tiers.
+pub struct Dedup {
+    seen: Seen,
+    min_length: usize,
+    max_length: usize,
+    policy: Option<Policy>,
+    charset: Option<Charset>,
+    exclude_chars: String,
+}
+
+impl Dedup {
+    pub fn new(config: &GeneratorConfig) -> Self {
+        let seen = match config.bloom {
+            Some(rate) => Seen::Bloom(Bloom::new(rate)),
+            None => Seen::Exact(Arena::new()),
+        };
+        Self {
+            seen,
+            min_length: config.min_length,
+            max_length: config.max_length,
+            policy: config.policy,
+            charset: config.charset.clone(),
+            exclude_chars: config.exclude_chars.clone(),
+        }
+    }
+
+    /// The candidates admitted so far, in the order they were admitted,
+    /// unless a Bloom filter dedups and keeps none.
+    pub fn admitted(&self) -> Option<&Arena> {
+        match self.seen {
+            Seen::Exact(ref arena) => Some(arena),
+            Seen::Bloom(_) => None,
+        }
+    }
+
+    /// Like [`admitted`](Self::admitted), taking the candidates.
+    pub fn into_admitted(self) -> Option<Arena> {
+        match self.seen {
+            Seen::Exact(arena) => Some(arena),
+            Seen::Bloom(_) => None,
+        }
+    }
+
+    /// Treat `item` as already produced, without counting or filtering it.
+    pub fn mark_seen(&mut self, item: &str) {
+        self.seen.insert(item);
+    }
+
+    /// Returns true if the candidate passes filters and hasn't been seen.
+    pub fn admit(&mut self, item: &str) -> bool {
+        self.check(item) == Verdict::Admitted
+    }
+
+    /// Like [`admit`](Self::admit), saying which filter turned the
+    /// candidate away.
+    pub fn check(&mut self, item: &str) -> Verdict {
+        let length = item.chars().count();
+        if length < self.min_length || length > self.max_length {
+            Verdict::Length
+        } else if !(self.policy.is_none_or(|p| p.allows(item))
+            && self.charset.as_ref().is_none_or(|c| c.allows(item))
+            && !item.contains(|c| self.exclude_chars.contains(c)))
         {
-            candidates.push(item);
+            Verdict::Policy
+        } else if !self.seen.insert(item) {
+            Verdict::Duplicate
+        } else {
+            Verdict::Admitted
+        }
+    }
+
+    /// [`check`](Self::check) for a source whose candidates go through the
+    /// filters, or only the duplicate check if `filtered` is false.
+    fn check_from(&mut self, filtered: bool, item: &str) -> Verdict {
+        if filtered {
+            self.check(item)
+        } else if self.seen.insert(item) {
+            Verdict::Admitted
+        } else {
+            Verdict::Duplicate
+        }
+    }
+}
+
+/// The candidates [`Dedup`] has admitted so far.
+enum Seen {
+    Exact(Arena),
+    Bloom(Bloom),
+}
+
+impl Seen {
+    /// Record `item`, returning false if it was already there.
+    fn insert(&mut self, item: &str) -> bool {
+        match self {
+            Seen::Exact(arena) => arena.insert(item),
+            Seen::Bloom(bloom) => bloom.insert(item),
+        }
+    }
+}
+
+/// What [`Dedup::check`] made of a candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Admitted,
+    /// Already produced by this or an earlier tier
+    Duplicate,
+    /// Outside the length range
+    Length,
+    /// Breaks the password policy, charset or excluded characters
+    Policy,
+}
+
+/// What one tier produced, and what the filters made of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TierStats {
+    pub name: &'static str,
+    pub admitted: u64,
+    pub duplicates: u64,
+    pub wrong_length: u64,
+    pub policy_rejected: u64,
+}
+
+impl TierStats {
+    fn record(&mut self, verdict: Verdict) {
+        match verdict {
+            Verdict::Admitted => self.admitted += 1,
+            Verdict::Duplicate => self.duplicates += 1,
+            Verdict::Length => self.wrong_length += 1,
+            Verdict::Policy => self.policy_rejected += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn sample_profile() -> Profile {
+        toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            pet_name = "Buddy"
+            birthdate = "1990-05-15"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stream_matches_collected() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 3, ..Default::default() };
+        let streamed: Vec<String> = candidate_stream(&profile, &config).collect();
+        assert_eq!(streamed, generate_candidates(&profile, &config));
+        assert!(streamed.contains(&"Buddy1990".to_string()));
+    }
+
+    #[test]
+    fn test_then() {
+        let profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let profile_only: Vec<String> = candidate_stream(&profile, &config).collect();
+        let lines = ["marmalade", "buddy1990", "zz9", "marmalade"].map(String::from);
+        let base = Lines::new("Base wordlist", lines.into_iter());
+        let mut stream = candidate_stream(&profile, &config).then([Box::new(base) as _]);
+        let all: Vec<String> = stream.by_ref().collect();
+        // Lines are deduplicated against the profile and each other, but
+        // not length-filtered
+        assert_eq!(all[..profile_only.len()], profile_only[..]);
+        assert_eq!(all[profile_only.len()..], ["marmalade", "zz9"]);
+        assert_eq!(stream.stats().last().unwrap().name, "Base wordlist");
+    }
+
+    #[test]
+    fn test_replay() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { markov: true, ..Default::default() };
+        let streamed: Vec<String> = candidate_stream(&profile, &config).collect();
+        let Ok(mut replay) = candidate_stream(&profile, &config).into_replay() else {
+            panic!("exact dedup replays");
+        };
+        assert_eq!(replay.len(), streamed.len());
+        assert_eq!(replay.nth(2).as_ref(), streamed.get(2));
+        assert_eq!(replay.collect::<Vec<_>>(), streamed[3..]);
+
+        let bloom = GeneratorConfig { bloom: Some(0.001), ..Default::default() };
+        let stream = candidate_stream(&profile, &bloom).into_replay().err().unwrap();
+        assert_eq!(stream.count(), generate_candidates(&profile, &bloom).len());
+    }
+
+    #[test]
+    fn test_tier_stats() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { min_length: 8, ..Default::default() };
+        let mut stream = candidate_stream(&profile, &config);
+        let written = stream.by_ref().count() as u64;
+        let stats = stream.stats();
+        assert_eq!(stats.len(), tiers(&profile, &config).len());
+        assert_eq!(stats.iter().map(|t| t.admitted).sum::<u64>(), written);
+        assert!(stats.iter().any(|t| t.duplicates > 0));
+        assert!(stats.iter().any(|t| t.wrong_length > 0));
+        assert!(stats.iter().all(|t| t.policy_rejected == 0));
+
+        let mut dedup = Dedup::new(&config);
+        assert_eq!(dedup.check("buddy"), Verdict::Length);
+        assert_eq!(dedup.check("buddy1990"), Verdict::Admitted);
+        assert_eq!(dedup.check("buddy1990"), Verdict::Duplicate);
+    }
+
+    #[test]
+    fn test_base_wordlist() {
+        let profile = sample_profile();
+        let path = std::env::temp_dir().join(format!("pg-base-{}", std::process::id()));
+        std::fs::write(&path, "marmalade\nbuddy\n").unwrap();
+
+        let config = GeneratorConfig { base_wordlist: Some(path.clone()), ..Default::default() };
+        let with_base = generate_candidates(&profile, &config);
+        let without = generate_candidates(&profile, &GeneratorConfig::default());
+        // Blended in after the profile's own candidates
+        assert_eq!(with_base[..without.len()], without[..]);
+        for word in ["marmalade", "Marmalade", "marmalade1990", "Marmalade1990", "marmalade1!"] {
+            assert!(with_base[without.len()..].contains(&word.to_string()), "{}", word);
         }
+
+        let shallow = GeneratorConfig { depth: 1, ..config };
+        let candidates = generate_candidates(&profile, &shallow);
+        assert!(candidates.contains(&"Marmalade".to_string()));
+        assert!(!candidates.contains(&"marmalade1990".to_string()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_language_words() {
+        let mut profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let english = generate_candidates(&profile, &config);
+        assert!(!english.contains(&"Teamo1990".to_string()));
+
+        profile.personal.language = Some("es".to_string());
+        let spanish = generate_candidates(&profile, &config);
+        for word in ["teamo1", "Teamo1990", "mivida123"] {
+            assert!(spanish.contains(&word.to_string()), "{}", word);
+        }
+        let shallow = GeneratorConfig { depth: 1, ..config };
+        assert!(!generate_candidates(&profile, &shallow).contains(&"mivida".to_string()));
+    }
+
+    #[test]
+    fn test_topic_words() {
+        let mut profile = sample_profile();
+        profile.interests.favorite_team = Some(crate::profile::Field::Plain("Liverpool".into()));
+        let config = GeneratorConfig::default();
+        let stages = tiers(&profile, &config);
+        assert!(!stages.iter().any(|t| t.name() == "Topic words"));
+
+        let topics = GeneratorConfig { topics: Some(Vec::new()), ..config.clone() };
+        let candidates = generate_candidates(&profile, &topics);
+        for word in ["anfield", "Gerrard1990", "champions123"] {
+            assert!(candidates.contains(&word.to_string()), "missing {}", word);
+        }
+        assert!(!candidates.contains(&"hallelujah".to_string()));
+
+        let listed = GeneratorConfig { topics: Some(vec!["religious".into()]), ..config };
+        let candidates = generate_candidates(&profile, &listed);
+        assert!(candidates.contains(&"anfield".to_string()));
+        assert!(candidates.contains(&"Hallelujah".to_string()));
+        // Nothing to bring in, no tier
+        let plain = sample_profile();
+        let stages = tiers(&plain, &topics);
+        assert!(!stages.iter().any(|t| t.name() == "Topic words"));
+    }
+
+    #[test]
+    fn test_position_case() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { min_length: 5, ..Default::default() };
+        let medium = generate_candidates(&profile, &config);
+        for expected in ["buddY", "BuddY", "johN1990"] {
+            assert_eq!(medium.contains(&expected.to_string()), expected.len() == 5);
+        }
+        assert!(!medium.contains(&"buDdy".to_string()));
+
+        let deep = generate_candidates(&profile, &GeneratorConfig { depth: 3, ..config });
+        for expected in ["buDdy", "bUddy", "johN1990", "buddY123"] {
+            assert!(deep.contains(&expected.to_string()), "missing {}", expected);
+        }
+    }
+
+    #[test]
+    fn test_duplications() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 1, ..Default::default() };
+        let fast = generate_candidates(&profile, &config);
+        assert!(fast.contains(&"Buddyy".to_string()));
+        assert!(!fast.contains(&"buuddy".to_string()));
+
+        let medium = GeneratorConfig { depth: 2, ..config };
+        let mut stream = candidate_stream(&profile, &medium);
+        assert!(stream.by_ref().any(|c| c == "Buuddy"));
+        assert_eq!(stream.tier(), "Mutating seed words");
+        assert_eq!(stream.origin().rules, ["duplicate_at"]);
+    }
+
+    #[test]
+    fn test_recent_years() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 1, ..Default::default() };
+        let candidates = generate_candidates(&profile, &config);
+        assert!(!candidates.contains(&"Buddy2026!".to_string()));
+
+        let config = GeneratorConfig { recent_year: Some(2026), ..config };
+        let candidates = generate_candidates(&profile, &config);
+        for expected in ["Buddy2026!", "buddy2025", "John2027"] {
+            assert!(candidates.contains(&expected.to_string()), "missing {}", expected);
+        }
+    }
+
+    #[test]
+    fn test_shifted_seed_numbers() {
+        let profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let candidates = generate_candidates(&profile, &config);
+        for expected in ["john!(()", "Buddy!(()", "!(()John"] {
+            assert!(candidates.contains(&expected.to_string()), "missing {}", expected);
+        }
+        let qwertz = GeneratorConfig { keyboard: Layout::Qwertz, ..config.clone() };
+        assert!(generate_candidates(&profile, &qwertz).contains(&"john!))=".to_string()));
+
+        let fast = GeneratorConfig { depth: 1, ..config };
+        assert!(!generate_candidates(&profile, &fast).contains(&"john!(()".to_string()));
+
+        // Digits already in a seed word are shifted too
+        let profile: Profile = toml::from_str("[custom]\nwords = [\"blink182\"]").unwrap();
+        let mut stream = candidate_stream(&profile, &GeneratorConfig::default());
+        assert!(stream.by_ref().any(|c| c == "Blink!*@"));
+        assert_eq!(stream.origin().rules, ["shift_digits"]);
+    }
+
+    #[test]
+    fn test_inflections() {
+        let profile: Profile = toml::from_str(
+            "[personal]\nlast_name = \"Smith\"\n[interests]\nhobbies = [\"fish\", \"dog\"]",
+        )
+        .unwrap();
+        let config = GeneratorConfig { min_length: 4, ..Default::default() };
+        let candidates = generate_candidates(&profile, &config);
+        for expected in ["dogs", "Doggy", "fishing", "smithy", "Smiths"] {
+            assert!(candidates.contains(&expected.to_string()), "missing {}", expected);
+        }
+        let fast = GeneratorConfig { depth: 1, ..config };
+        assert!(!generate_candidates(&profile, &fast).contains(&"smithy".to_string()));
+    }
+
+    #[test]
+    fn test_phonetic() {
+        let profile: Profile = toml::from_str("[interests]\nhobbies = [\"photography\"]").unwrap();
+        let config = GeneratorConfig { min_length: 4, ..Default::default() };
+        let candidates = generate_candidates(&profile, &config);
+        for expected in ["fotografy", "Fotografy"] {
+            assert!(candidates.contains(&expected.to_string()), "missing {}", expected);
+        }
+        let fast = GeneratorConfig { depth: 1, ..config };
+        assert!(!generate_candidates(&profile, &fast).contains(&"fotografy".to_string()));
+    }
+
+    #[test]
+    fn test_truncated_seeds() {
+        let profile: Profile =
+            toml::from_str("[personal]\nlast_name = \"Smithson\"\nbirthdate = \"1990-05-15\"")
+                .unwrap();
+        let config = GeneratorConfig { min_length: 5, ..Default::default() };
+        let medium = generate_candidates(&profile, &config);
+        for expected in ["smith", "Smith", "smiths", "thson", "smith1990", "Smit1990"] {
+            assert!(medium.contains(&expected.to_string()), "missing {}", expected);
+        }
+        assert!(!medium.contains(&"smith123".to_string()));
+        let deep = GeneratorConfig { depth: 3, ..config.clone() };
+        assert!(generate_candidates(&profile, &deep).contains(&"smith123".to_string()));
+
+        let fast = GeneratorConfig { depth: 1, ..config };
+        assert!(!generate_candidates(&profile, &fast).contains(&"smith".to_string()));
+    }
+
+    #[test]
+    fn test_profile_leet() {
+        let mut profile: Profile =
+            toml::from_str("[personal]\nlast_name = \"Smith\"\npet_name = \"Buddy\"").unwrap();
+        let config = GeneratorConfig {
+            depth: 1,
+            min_length: 5,
+            ..GeneratorConfig::default()
+        };
+        let candidates = generate_candidates(&profile, &config);
+        assert!(candidates.contains(&"5mith".to_string()));
+        assert!(!candidates.contains(&"bu60y".to_string()));
+
+        profile.leet.map.insert('d', vec!['6', '0']);
+        profile.leet.max_substitutions = 2;
+        let candidates = generate_candidates(&profile, &config);
+        assert!(candidates.contains(&"bu66y".to_string()));
+        assert!(candidates.contains(&"bu60y".to_string()));
+
+        profile.leet.style = mutations::LeetStyle::Symbols;
+        let candidates = generate_candidates(&profile, &config);
+        assert!(candidates.contains(&"$mith".to_string()));
+        assert!(!candidates.contains(&"5mith".to_string()));
+    }
+
+    #[test]
+    fn test_passphrases() {
+        let profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let medium = generate_candidates(&profile, &config);
+        for phrase in [
+            "ilovebuddy",
+            "I Love Buddy",
+            "buddy-forever",
+            "JohnAndBuddy",
+            "buddy_loves_john",
+        ] {
+            assert!(medium.contains(&phrase.to_string()), "missing {}", phrase);
+        }
+        assert!(!medium.contains(&"ilovebuddy1990".to_string()));
+
+        let deep_config = GeneratorConfig { depth: 3, ..config.clone() };
+        let deep = generate_candidates(&profile, &deep_config);
+        assert!(deep.contains(&"ilovebuddy1990".to_string()));
+
+        let short = GeneratorConfig { max_length: 12, ..config };
+        let candidates = generate_candidates(&profile, &short);
+        assert!(candidates.contains(&"buddyrocks".to_string()));
+        assert!(!candidates.contains(&"buddyisthebest".to_string()));
+    }
+
+    #[test]
+    fn test_typos() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { min_length: 4, ..GeneratorConfig::default() };
+        let medium = generate_candidates(&profile, &config);
+        for typo in ["budy", "Vuddy", "ubddy", "buddyy", "jhon"] {
+            assert!(medium.contains(&typo.to_string()), "missing {}", typo);
+        }
+        assert!(!medium.contains(&"psasword".to_string()));
+
+        let deep = generate_candidates(&profile, &GeneratorConfig { depth: 3, ..config });
+        assert!(deep.contains(&"psasword".to_string()));
+    }
+
+    #[test]
+    fn test_case_toggles() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 1, min_length: 4, ..Default::default() };
+        assert!(!generate_candidates(&profile, &config).contains(&"buDdy".to_string()));
+
+        let toggled = GeneratorConfig { case_toggles: 2, ..config };
+        let candidates = generate_candidates(&profile, &toggled);
+        for word in ["buDdy", "bUddY", "BuddY"] {
+            assert!(candidates.contains(&word.to_string()), "missing {}", word);
+        }
+        assert!(!candidates.contains(&"BUDdy".to_string()));
+    }
+
+    #[test]
+    fn test_rotations() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 1, min_length: 4, ..Default::default() };
+        assert!(!generate_candidates(&profile, &config).contains(&"uddyb".to_string()));
+
+        let rotated = GeneratorConfig { rotations: true, ..config };
+        let candidates = generate_candidates(&profile, &rotated);
+        for word in ["uddyb", "Uddyb", "ybudd", "dybud"] {
+            assert!(candidates.contains(&word.to_string()), "missing {}", word);
+        }
+    }
+
+    #[test]
+    fn test_insertions() {
+        let profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let medium = generate_candidates(&profile, &config);
+        for word in ["bud1dy", "Bud!dy", "bu.ddy", "Bu_ddy"] {
+            assert!(medium.contains(&word.to_string()), "missing {}", word);
+        }
+        assert!(!medium.contains(&"b!ud!dy".to_string()));
+
+        let two = GeneratorConfig { insertions: 2, ..config.clone() };
+        assert!(generate_candidates(&profile, &two).contains(&"b!ud!dy".to_string()));
+        let none = GeneratorConfig { insertions: 0, ..config.clone() };
+        assert!(!generate_candidates(&profile, &none).contains(&"bud1dy".to_string()));
+        let deep = GeneratorConfig { depth: 3, ..config };
+        assert!(generate_candidates(&profile, &deep).contains(&"bud1990dy".to_string()));
+    }
+
+    #[test]
+    fn test_weights() {
+        let profile = |weights: &str| -> Profile {
+            let toml = format!(
+                r#"
+                [personal]
+                first_name = "John"
+                pet_name = "Buddy"
+                [network]
+                isp = "Comcast"
+                [interests]
+                hobbies = ["chess", "golf"]
+                [weights]
+                {}
+                "#,
+                weights
+            );
+            toml::from_str(&toml).unwrap()
+        };
+        let config = GeneratorConfig::default();
+        let plain = generate_candidates(&profile(""), &config);
+        for pair in ["johnbuddy", "johncomcast"] {
+            assert!(plain.contains(&pair.to_string()), "missing {}", pair);
+        }
+        assert!(!plain.contains(&"chessgolf".to_string()));
+        let position = |list: &[String], word: &str| list.iter().position(|c| c == word);
+        assert!(position(&plain, "johnny!") < position(&plain, "buddy!"));
+
+        let weights = "pet_name = 10\nisp = 0\nhobbies = 8";
+        let weighed = generate_candidates(&profile(weights), &config);
+        assert!(!weighed.contains(&"johncomcast".to_string()));
+        assert!(weighed.contains(&"chessgolf".to_string()));
+        // The pet's affixes now come before the owner's
+        assert!(position(&weighed, "buddy!") < position(&weighed, "johnny!"));
+
+        let light = generate_candidates(&profile("first_name = 2\npet_name = 2"), &config);
+        assert!(!light.contains(&"johnbuddy".to_string()));
+        assert!(light.contains(&"johncomcast".to_string()));
+
+        let likely = likely_ordered(&profile("isp = 10"), &config);
+        let plain = likely_ordered(&profile(""), &config);
+        let rank =
+            |list: &[String]| position(list, "comcastjohn").unwrap() as f64 / list.len() as f64;
+        assert!(rank(&likely) < rank(&plain));
+    }
+
+    #[test]
+    fn test_excluding() {
+        let profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let all: Vec<String> = candidate_stream(&profile, &config).collect();
+        let (known, new) = all.split_at(100);
+        let mut stream = candidate_stream(&profile, &config).excluding(known.to_vec());
+        let rest: Vec<String> = stream.by_ref().collect();
+        assert_eq!(rest, new);
+        let duplicates: u64 = stream.stats().iter().map(|t| t.duplicates).sum();
+        assert!(duplicates >= 100);
+    }
+
+    #[test]
+    fn test_bloom_dedup() {
+        let profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let bloom = GeneratorConfig { bloom: Some(0.001), ..config.clone() };
+        // Far below the filter's first slice, where false positives are rare
+        assert_eq!(
+            generate_candidates(&profile, &bloom),
+            generate_candidates(&profile, &config)
+        );
+    }
+
+    #[test]
+    fn test_likely_ordered() {
+        let profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let ordered = likely_ordered(&profile, &config);
+        let mut sorted = ordered.clone();
+        sorted.sort();
+        let mut generated = generate_candidates(&profile, &config);
+        generated.sort();
+        assert_eq!(sorted, generated);
+
+        let position = |word: &str| ordered.iter().position(|c| c == word).unwrap();
+        // A common suffix beats a year suffix from the same tier
+        assert!(position("buddy1") < position("buddy2007"));
+        assert!(position("Buddy123") < position("buddy2007"));
+
+        assert_eq!(trailing_run("pass123!"), "123!");
+        assert_eq!(trailing_run("1234"), "1234");
+        assert_eq!(trailing_run("buddy"), "");
+    }
+
+    #[test]
+    fn test_within_budget() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 3, likely_first: true, ..Default::default() };
+        let all: Vec<String> = candidate_stream(&profile, &config).collect();
+
+        let (kept, total) = within_budget(&profile, &config, 500);
+        assert_eq!((kept.len(), total), (500, all.len()));
+        // Pruned from the bottom of the likelihood order, kept in stream order
+        let likely = likely_ordered(&profile, &config);
+        let mut expected: Vec<&String> = likely[..500].iter().collect();
+        expected.sort_by_key(|c| all.iter().position(|a| a == *c));
+        assert_eq!(kept.iter().collect::<Vec<_>>(), expected);
+
+        let (kept, _) = within_budget(&profile, &config, all.len() + 1);
+        assert_eq!(kept, all);
+    }
+
+    #[test]
+    fn test_stream_is_lazy() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 3, ..Default::default() };
+        let all = generate_candidates(&profile, &config);
+        let mut lazy = candidate_stream(&profile, &config);
+        let first: Vec<String> = lazy.by_ref().take(10).collect();
+        assert_eq!(first, all[..10]);
+        assert_eq!(lazy.count(), all.len() - 10);
+    }
+
+    #[test]
+    fn test_template_tier() {
+        let mut profile = sample_profile();
+        profile.work.company = Some(crate::profile::Field::Plain("Acme".into()));
+        let config = GeneratorConfig { years: Some(2016..=2018), ..Default::default() };
+        let corporate = GeneratorConfig { template: Template::Corporate, ..config.clone() };
+
+        let names: Vec<&str> = tiers(&profile, &corporate).iter().map(|t| t.name()).collect();
+        assert_eq!(names[..3], ["Corporate patterns", "Seasonal passwords", "Common passwords"]);
+        let candidates = generate_candidates(&profile, &corporate);
+        assert_eq!(candidates[0], "Acme2018!");
+        assert!(candidates.contains(&"Welcome1".to_string()));
+        assert!(candidates.contains(&"Summer2018!".to_string()));
+        // The company is a seed word under any template
+        let personal = generate_candidates(&profile, &config);
+        assert!(personal.contains(&"acme2017".to_string()));
+        assert!(!personal.contains(&"Acme2018!".to_string()));
+    }
+
+    #[test]
+    fn test_family_numbers() {
+        let mut profile = sample_profile();
+        profile.personal.children_names = vec![crate::profile::Field::Plain("Emma".into())];
+        profile.personal.children_birthdates = vec!["2016-08-21".into()];
+        let config = GeneratorConfig::default();
+        let names: Vec<&str> = tiers(&profile, &config).iter().map(|t| t.name()).collect();
+        assert!(names.contains(&"Family numbers"));
+
+        let candidates = generate_candidates(&profile, &config);
+        // The parent's name with the child's birthdate, and the other way round
+        for expected in ["John2016", "john0821", "emma1990", "Emma1505"] {
+            assert!(candidates.contains(&expected.to_string()), "{}", expected);
+        }
+        // The pet isn't family, and a family of one has no tier
+        assert!(!candidates.contains(&"buddy0821".to_string()));
+        let alone = sample_profile();
+        let names: Vec<&str> = tiers(&alone, &config).iter().map(|t| t.name()).collect();
+        assert!(!names.contains(&"Family numbers"));
+    }
+
+    #[test]
+    fn test_number_words() {
+        let mut profile = sample_profile();
+        profile.interests.favorite_number = Some("7".into());
+        profile.custom.words = vec![crate::profile::Field::Plain("OneLove".into())];
+        let config = GeneratorConfig { min_length: 5, ..Default::default() };
+        let candidates = generate_candidates(&profile, &config);
+        for word in [
+            "seven",
+            "Nineteenninety",
+            "buddyseven",
+            "JohnSeven",
+            "buddynineteenninety",
+            "1love",
+        ] {
+            assert!(candidates.contains(&word.to_string()), "missing {}", word);
+        }
+        let shallow = GeneratorConfig { depth: 1, ..config };
+        assert!(!generate_candidates(&profile, &shallow).contains(&"buddyseven".to_string()));
+    }
+
+    #[test]
+    fn test_month_and_season_names() {
+        let mut profile = sample_profile();
+        let config = GeneratorConfig {
+            min_length: 5,
+            years: Some(2020..=2024),
+            ..Default::default()
+        };
+        let english = generate_candidates(&profile, &config);
+        for word in ["October2023!", "january", "Summer24", "dec24", "Dec2024"] {
+            assert!(english.contains(&word.to_string()), "missing {}", word);
+        }
+        // Only recent years, and seeds get them at depth 3
+        assert!(!english.contains(&"October2019".to_string()));
+        assert!(!english.contains(&"buddyMay2024".to_string()));
+
+        profile.personal.locales = vec!["de".into(), "id".into()];
+        let local = generate_candidates(&profile, &config);
+        for word in ["Oktober2023!", "sommer24", "des24", "Desember2022"] {
+            assert!(local.contains(&word.to_string()), "missing {}", word);
+        }
+        assert!(!local.contains(&"October2023!".to_string()));
+        let deep = GeneratorConfig { depth: 3, ..config.clone() };
+        assert!(generate_candidates(&profile, &deep).contains(&"buddyMai2024".to_string()));
+
+        let shallow = GeneratorConfig { depth: 1, ..config };
+        assert!(!generate_candidates(&profile, &shallow).contains(&"Oktober2023!".to_string()));
+    }
+
+    #[test]
+    fn test_extra_sources() {
+        struct Corpus(Vec<String>);
+        impl CandidateSource for Corpus {
+            fn name(&self) -> &'static str {
+                "Company corpus"
+            }
+            fn estimated_size(&self) -> Option<u64> {
+                Some(self.0.len() as u64)
+            }
+            fn groups(self: Box<Self>) -> GroupIter {
+                Box::new(std::iter::once(Group {
+                    origin: Origin::default(),
+                    items: self.0,
+                }))
+            }
+        }
+        let corpus = |profile: &Profile, _: &GeneratorConfig| -> Option<Box<dyn CandidateSource>> {
+            let name = profile.personal.first_name.as_ref()?;
+            Some(Box::new(Corpus(vec![
+                format!("{}@Acme", name.value()),
+                "password".into(),
+            ])))
+        };
+
+        let profile = sample_profile();
+        let plain = GeneratorConfig { depth: 1, ..Default::default() };
+        let config = GeneratorConfig {
+            sources: vec![
+                ExtraSource::new(corpus).after("Common passwords"),
+                ExtraSource::new(corpus),
+            ],
+            ..plain.clone()
+        };
+        let stages = tiers(&profile, &config);
+        let names: Vec<&str> = stages.iter().map(|t| t.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "Common passwords",
+                "Company corpus",
+                "Mutating seed words",
+                "Company corpus"
+            ]
+        );
+        assert_eq!(
+            stages[0].estimated_size(),
+            Some(common::common_passwords().len() as u64)
+        );
+        assert_eq!(stages[1].estimated_size(), Some(2));
+
+        // Deduplicated against the built-in tiers like any other stage
+        let all = generate_candidates(&profile, &config);
+        let base = generate_candidates(&profile, &plain);
+        assert_eq!(all.len(), base.len() + 1);
+        assert!(all.contains(&"John@Acme".to_string()));
+        // A source with nothing to offer is left out
+        let mut nameless = sample_profile();
+        nameless.personal.first_name = None;
+        assert_eq!(
+            tiers(&nameless, &config).len(),
+            tiers(&nameless, &plain).len()
+        );
+    }
+
+    #[test]
+    fn test_markov_order_within_tiers() {
+        let profile = sample_profile();
+        let plain = GeneratorConfig::default();
+        let config = GeneratorConfig { markov: true, ..plain.clone() };
+        let ranked: Vec<String> = candidate_stream(&profile, &config).collect();
+        assert_eq!(ranked, generate_candidates(&profile, &config));
+
+        // Same candidates, reordered
+        let mut sorted = ranked.clone();
+        let mut unranked: Vec<String> = candidate_stream(&profile, &plain).collect();
+        sorted.sort();
+        unranked.sort();
+        assert_eq!(sorted, unranked);
+
+        // Common passwords keep their frequency order; the next tier opens
+        // with its most likely guess
+        let model = Markov::common();
+        let mut dedup = Dedup::new(&plain);
+        let mut tiers = tiers(&profile, &plain).into_iter();
+        let common = tiers.next().unwrap().admitted(&mut dedup, false);
+        assert_eq!(ranked[..common.len()], common[..]);
+        let mutated = tiers.next().unwrap().admitted(&mut dedup, false);
+        let best = mutated
+            .iter()
+            .max_by(|a, b| model.score(a).total_cmp(&model.score(b)))
+            .unwrap();
+        assert_eq!(model.score(&ranked[common.len()]), model.score(best));
+    }
+
+    #[test]
+    fn test_separators() {
+        let mut profile = sample_profile();
+        let config = GeneratorConfig::default();
+        let defaults: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(defaults.contains("john_buddy") && !defaults.contains("john-buddy"));
+
+        profile.custom.separators = vec!["-".into(), "!".into()];
+        let custom: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(custom.contains("john-buddy") && custom.contains("John!Buddy"));
+        assert!(!custom.contains("john_buddy") && !custom.contains("johnbuddy"));
+
+        // The config's separators win over the profile's
+        let config = GeneratorConfig { separators: vec!["@".into()], ..config };
+        let overridden: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(overridden.contains("john@buddy") && !overridden.contains("john-buddy"));
+    }
+
+    #[test]
+    fn test_year_range() {
+        // From the birth year in the sample profile to this year
+        let profile = sample_profile();
+        let this_year = date::current_year().to_string();
+        let config = GeneratorConfig::default();
+        let derived: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(derived.contains("buddy1991"));
+        assert!(derived.contains(&format!("buddy{}", this_year)));
+        assert!(derived.contains("buddy007"));
+        assert!(!derived.contains("buddy1989") && !derived.contains("buddy1950"));
+
+        let config = GeneratorConfig { years: Some(2016..=2018), ..config };
+        let set: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(set.contains("buddy2017") && set.contains("buddy17"));
+        assert!(!set.contains("buddy2019") && !set.contains("buddy19"));
+        // The profile's own year is a seed number, not a suffix
+        assert!(set.contains("buddy1990"));
+
+        assert_eq!(*default_years(&Profile::default(), 2026).start(), FIRST_YEAR);
+    }
+
+    #[test]
+    fn test_three_word_tier() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            last_name = "Smith"
+            pet_name = "Buddy"
+            birthdate = "1990-05-15"
+
+            [interests]
+            hobbies = ["chess"]
+
+            [network]
+            ssid = "HomeNet"
+            "#,
+        )
+        .unwrap();
+        let deep = GeneratorConfig { depth: 3, ..Default::default() };
+        let medium = GeneratorConfig { depth: 2, ..deep.clone() };
+        let at_depth = |config| candidate_stream(&profile, config).collect::<HashSet<String>>();
+        let (deep, medium) = (at_depth(&deep), at_depth(&medium));
+
+        for triple in [
+            "johnbuddysmith",
+            "JohnBuddySmith",
+            "JohnBuddy1990",
+            "smithchessjohn",
+        ] {
+            assert!(deep.contains(triple), "missing {}", triple);
+            assert!(!medium.contains(triple), "{} below depth 3", triple);
+        }
+        // Hobby + SSID rarely go together, so neither do triples with both
+        let mixed = |c: &&String| c.contains("chess") && c.contains("homenet");
+        assert!(!deep.iter().any(|c| mixed(&c)));
+    }
+
+    #[test]
+    fn test_likely_first_schedule() {
+        let profile = sample_profile();
+        let plain = GeneratorConfig::default();
+        let config = GeneratorConfig { likely_first: true, ..plain.clone() };
+        let names: Vec<&str> = tiers(&profile, &config).iter().map(|t| t.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "Common passwords",
+                "Mutating seed words",
+                "Seed numbers",
+                "Number words",
+                "Combining words",
+                "Applying affixes",
+                "Month and season names",
+                "Building passphrases",
+                "Keyboard patterns",
+                "Typos",
+                "Mid-word insertions",
+                "Truncated seeds"
+            ]
+        );
+
+        // Same candidates, with the profile's year and the combinations
+        // ahead of the generic year suffixes
+        let scheduled: Vec<String> = candidate_stream(&profile, &config).collect();
+        let wordlist: Vec<String> = candidate_stream(&profile, &plain).collect();
+        let (mut a, mut b) = (scheduled.clone(), wordlist.clone());
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+
+        let at = |list: &[String], c: &str| list.iter().position(|x| x == c).unwrap();
+        assert!(at(&scheduled, "Buddy1990") < at(&scheduled, "Buddy2005"));
+        assert!(at(&scheduled, "johnbuddy") < at(&scheduled, "Buddy2005"));
+        assert!(at(&wordlist, "Buddy2005") < at(&wordlist, "johnbuddy"));
+    }
+
+    #[test]
+    fn test_overlapping_seeds_not_combined() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            nickname = "Johnny"
+            pet_name = "Buddy"
+            [interests]
+            hobbies = ["New York"]
+            "#,
+        )
+        .unwrap();
+        let config = GeneratorConfig { depth: 3, ..Default::default() };
+        let candidates: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(candidates.contains("johnbuddy"));
+        assert!(candidates.contains("johnnybuddy"));
+        assert!(!candidates.contains("johnjohnny"));
+        assert!(!candidates.contains("JohnJohnny123"));
+        assert!(!candidates.contains("new yorkyork"));
+        assert!(candidates.contains("newjohn"));
+    }
+
+    #[test]
+    fn test_accented_seeds() {
+        let profile: Profile =
+            toml::from_str("[personal]\nfirst_name = \"Renée\"\npet_name = \"Buddy\"").unwrap();
+        let candidates: HashSet<String> =
+            candidate_stream(&profile, &GeneratorConfig::default()).collect();
+        let accented = ["Renée1", "Rene\u{301}e", "renéebuddy"];
+        for expected in accented.into_iter().chain(["renee123", "reneebuddy"]) {
+            assert!(candidates.contains(expected), "missing {}", expected);
+        }
+        assert!(!candidates.contains("renéerenee"));
+        // Five letters, however many bytes
+        assert!(!candidates.contains("renée"));
+    }
+
+    #[test]
+    fn test_class_pairs() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            [interests]
+            hobbies = ["chess", "golf"]
+            "#,
+        )
+        .unwrap();
+        let mut config = GeneratorConfig::default();
+        let candidates: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(candidates.contains("johnchess"));
+        assert!(!candidates.contains("chessgolf"));
+
+        config.all_pairs = true;
+        let candidates: HashSet<String> = candidate_stream(&profile, &config).collect();
+        assert!(candidates.contains("chessgolf"));
+    }
+
+    #[test]
+    fn test_field_transforms() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            [network]
+            ssid = { value = "CasaAzul", transforms = ["as-is", "no-mutations"] }
+            "#,
+        )
+        .unwrap();
+        let candidates: HashSet<String> =
+            candidate_stream(&profile, &GeneratorConfig::default()).collect();
+        assert!(candidates.contains("CasaAzul"));
+        assert!(candidates.contains("CasaAzul123"));
+        assert!(candidates.contains("johnCasaAzul"));
+        assert!(!candidates.contains("casaazul"));
+        assert!(!candidates.contains("CASAAZUL"));
+        assert!(!candidates.contains("luzaasac"));
+    }
+
+    #[test]
+    fn test_known_guesses_first() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            [guesses]
+            passwords = ["J0hn Smith!", "abc"]
+            "#,
+        )
+        .unwrap();
+        let candidates: Vec<String> =
+            candidate_stream(&profile, &GeneratorConfig::default()).collect();
+        assert_eq!(candidates[0], "J0hn Smith!");
+        assert!(!candidates.contains(&"abc".to_string()));
+    }
+
+    #[cfg(feature = "metadata")]
+    #[test]
+    fn test_metadata_provenance() {
+        let profile = sample_profile();
+        let config = GeneratorConfig { depth: 2, ..Default::default() };
+        let with_meta: Vec<Candidate> = candidate_stream_with_metadata(&profile, &config).collect();
+        let plain: Vec<String> = candidate_stream(&profile, &config).collect();
+        assert_eq!(with_meta.iter().map(|c| c.text.clone()).collect::<Vec<_>>(), plain);
+
+        let hit = with_meta.iter().find(|c| c.text == "Buddy1990").unwrap();
+        assert_eq!(hit.tier, "Applying affixes");
+        assert_eq!(hit.source_fields, vec!["personal.pet_name"]);
+        assert_eq!(hit.rules_applied, vec!["numeric_suffix"]);
     }
 }
@@ -0,0 +1,296 @@
+//! hashcat rule export: the profile's seed words as a base wordlist plus a
+//! `.rule` file that rebuilds the single-word tiers, so hashcat expands the
+//! keyspace on the GPU instead of reading a full wordlist.
+//!
+//! Only what one rule can do to one word is exported: the case, reversal,
+//! leet and doubling mutations, the affixes, the seed numbers, truncations
+//! and the `--rules` file. Common passwords, word combinations and keyboard
+//! walks stay in the regular wordlist. hashcat's `sXY` replaces every
+//! occurrence, so the single-position leet variants come out as the
+//! all-occurrence form, and the length and policy filters are left to
+//! hashcat.
+
+use std::collections::HashSet;
+
+use crate::common;
+use crate::generator::{self, GeneratorConfig};
+use crate::mutations::{self, Mutation};
+use crate::profile::Profile;
+
+/// Base words and the rules to run over them.
+pub struct RuleSet {
+    pub base: Vec<String>,
+    pub rules: Vec<String>,
+    /// `--rules` entries with no hashcat equivalent, left out
+    pub skipped: usize,
+}
+
+/// The built-in rules alone, as `export` writes them for a profile with no
+/// numbers of its own and no `--rules` or `--hashcat-rules`: what the
+/// mutation and affix tiers do to a seed word under `config`, as near as
+/// hashcat's syntax can say it.
+pub fn builtin(config: &GeneratorConfig) -> Vec<String> {
+    let config = GeneratorConfig {
+        rules: Vec::new(),
+        hashcat_rules: Vec::new(),
+        ..config.clone()
+    };
+    export(&Profile::default(), &config).rules
+}
+
+/// Export the single-word tiers `config` would generate for `profile`.
+pub fn export(profile: &Profile, config: &GeneratorConfig) -> RuleSet {
+    let mut base = Vec::new();
+    let mut seen = HashSet::new();
+    for seed in profile.seed_sources() {
+        if seen.insert(seed.word.clone()) {
+            base.push(seed.word);
+        }
+    }
+
+    let mut rules = Rules::default();
+    // Seeds as written, for the as-is fields
+    rules.push(":".to_string());
+
+    // Tier 2: Mutated seed words
+    for rule in mutations::builtin_rules(&profile.leet, config.max_length) {
+        rules.push(String::from(rule));
+    }
+
+    // The years around the run's, after every seed
+    if let Some(year) = config.recent_year {
+        for mutation in [Mutation::RecentYears(year), Mutation::RecentYearsCapitalized(year)] {
+            for rule in mutation.hashcat(&profile.leet, config.max_length) {
+                rules.push(String::from(rule));
+            }
+        }
+    }
+
+    // Digits in the seed word typed with Shift, from depth 2
+    if config.depth >= 2 {
+        let shifted = Mutation::ShiftDigits(config.keyboard);
+        for rule in shifted.hashcat(&profile.leet, config.max_length) {
+            let rule = String::from(rule);
+            rules.push(format!("{} c", rule));
+            rules.push(rule);
+        }
+    }
+
+    // Case flipped at each interior position, at depth 3
+    if config.depth >= 3 {
+        for n in 1..config.max_length.saturating_sub(1) {
+            for rule in Mutation::ToggleAt(n).hashcat(&profile.leet, config.max_length) {
+                rules.push(String::from(rule));
+            }
+        }
+    }
+
+    // Tier 2b: The user's own mutation rules
+    let mut skipped = 0;
+    for rule in &config.rules {
+        match rule.hashcat() {
+            Some(rule) => rules.push(rule),
+            None => skipped += 1,
+        }
+    }
+
+    // Tier 2b: hashcat rule sets, as they are
+    for rule in &config.hashcat_rules {
+        rules.push(String::from(rule.clone()));
+    }
+
+    // Tier 2d: Rotations, when asked for
+    if config.rotations {
+        for n in 1..config.max_length {
+            for mutation in [Mutation::RotateLeft(n), Mutation::RotateRight(n)] {
+                for rule in mutation.hashcat(&profile.leet, config.max_length) {
+                    let rule = String::from(rule);
+                    rules.push(format!("{} c", rule));
+                    rules.push(rule);
+                }
+            }
+        }
+    }
+
+    // Tier 3: Seeds + affixes
+    if config.depth >= 2 {
+        let years = match config.years {
+            Some(ref years) => years.clone(),
+            None => generator::default_years(profile, config.year()),
+        };
+        let numeric = common::numeric_suffixes(years);
+        let suffixes = numeric.iter().cloned().chain(common::symbol_suffixes());
+        for suffix in suffixes {
+            rules.push(format!("l {}", append(&suffix)));
+            rules.push(format!("c {}", append(&suffix)));
+        }
+        if config.depth >= 3 {
+            for suffix in &numeric {
+                rules.push(format!("l r T0 r {}", append(suffix)));
+            }
+        }
+        for prefix in common::common_prefixes() {
+            rules.push(format!("l {}", prepend(&prefix)));
+            rules.push(format!("c {}", prepend(&prefix)));
+        }
+        // The seed numbers, also typed with Shift: john!(() for john1990
+        let numbers = profile.seed_numbers_in(&config.date_formats, config.year());
+        let shifted = numbers
+            .iter()
+            .map(|number| mutations::shift_digits(number, config.keyboard));
+        for number in numbers.iter().cloned().chain(shifted) {
+            rules.push(format!("l {}", append(&number)));
+            rules.push(format!("c {}", append(&number)));
+            rules.push(format!("l {}", prepend(&number)));
+            rules.push(format!("c {}", prepend(&number)));
+        }
+
+        // Tier 5c: Seeds cut short, alone and with the seed numbers
+        for n in mutations::MIN_TRUNCATION..config.max_length {
+            for mutation in [Mutation::Prefix(n), Mutation::Suffix(n)] {
+                for rule in mutation.hashcat(&profile.leet, config.max_length) {
+                    let rule = String::from(rule);
+                    for cased in [rule.clone(), format!("{} c", rule)] {
+                        for number in &numbers {
+                            rules.push(format!("{} {}", cased, append(number)));
+                        }
+                        rules.push(cased);
+                    }
+                }
+            }
+        }
+    }
+
+    RuleSet {
+        base,
+        rules: rules.list,
+        skipped,
+    }
+}
+
+/// `$X` for every character of `text`.
+pub fn append(text: &str) -> String {
+    text.chars()
+        .map(|c| format!("${}", c))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `^X` for every character of `text`, last character first.
+pub fn prepend(text: &str) -> String {
+    text.chars()
+        .rev()
+        .map(|c| format!("^{}", c))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `sXY`: replace every `from` with `to`.
+pub fn substitute(from: char, to: char) -> String {
+    format!("s{}{}", from, to)
+}
+
+/// hashcat's one-character position: 0-9, then A-Z for 10 to 35.
+pub fn position(n: usize) -> Option<char> {
+    char::from_digit(u32::try_from(n).ok()?, 36).map(|c| c.to_ascii_uppercase())
+}
+
+/// Rule lines in order, without repeats.
+#[derive(Default)]
+struct Rules {
+    list: Vec<String>,
+    seen: HashSet<String>,
+}
+
+impl Rules {
+    fn push(&mut self, rule: String) {
+        if self.seen.insert(rule.clone()) {
+            self.list.push(rule);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_syntax() {
+        assert_eq!(append("1990"), "$1 $9 $9 $0");
+        assert_eq!(prepend("my"), "^y ^m");
+        assert_eq!(substitute('a', '@'), "sa@");
+        assert_eq!(position(9), Some('9'));
+        assert_eq!(position(10), Some('A'));
+        assert_eq!(position(35), Some('Z'));
+        assert_eq!(position(36), None);
+    }
+
+    #[test]
+    fn test_export() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            pet_name = "Buddy"
+
+            [custom]
+            numbers = ["42"]
+            "#,
+        )
+        .unwrap();
+        let config = GeneratorConfig {
+            rules: vec![
+                crate::rules::Rule::parse("toggle 0 | append 1987").unwrap(),
+                crate::rules::Rule::parse("substitute ph f").unwrap(),
+            ],
+            hashcat_rules: vec![crate::mutations::HashcatRule::parse("sa4 $!").unwrap()],
+            ..GeneratorConfig::default()
+        };
+        let set = export(&profile, &config);
+        assert!(set.base.contains(&"john".to_string()));
+        assert!(set.base.contains(&"buddy".to_string()));
+        let leet = "l sa@ se3 si1 sl1 so0 ss$ st7";
+        for rule in ["c", leet, "l ss5", "T0 $1 $9 $8 $7"] {
+            assert!(set.rules.contains(&rule.to_string()), "missing {}", rule);
+        }
+        for rule in ["sa4 $!", "c $!", "l ^y ^m", "c $4 $2", "l ^2 ^4", "l '5 c $4 $2", "c $$ $@"] {
+            assert!(set.rules.contains(&rule.to_string()), "missing {}", rule);
+        }
+        assert_eq!(set.skipped, 1);
+        let unique: HashSet<_> = set.rules.iter().collect();
+        assert_eq!(unique.len(), set.rules.len());
+        assert!(set.rules.contains(&"l sa@ so0".to_string()));
+
+        let one: Profile = toml::from_str("[leet]\nmax_substitutions = 1").unwrap();
+        let set = export(&one, &config);
+        assert!(!set.rules.contains(&"l sa@ so0".to_string()));
+
+        let builtin = builtin(&config);
+        for rule in [":", "l M $_ 4", "l sa@ so0", "c $!", "l $1 $2 $3"] {
+            assert!(builtin.contains(&rule.to_string()), "missing {}", rule);
+        }
+        for rule in ["sa4 $!", "T0 $1 $9 $8 $7", "c $4 $2"] {
+            assert!(!builtin.contains(&rule.to_string()), "{}", rule);
+        }
+
+        assert!(!set.rules.contains(&"l T2".to_string()));
+        let deep = export(&profile, &GeneratorConfig { depth: 3, ..config.clone() });
+        for rule in ["l T2", "l r T0 r $1 $2 $3", "l r T0 r"] {
+            assert!(deep.rules.contains(&rule.to_string()), "missing {}", rule);
+        }
+
+        let shifted = "l s0) s1! s2@ s3# s4$ s5% s6^ s7& s8* s9(";
+        assert!(set.rules.contains(&shifted.to_string()));
+        assert!(set.rules.contains(&format!("{} c", shifted)));
+
+        assert!(!set.rules.contains(&"l { { c".to_string()));
+        let rotated = export(&profile, &GeneratorConfig { rotations: true, ..config.clone() });
+        for rule in ["l {", "l } } } c"] {
+            assert!(rotated.rules.contains(&rule.to_string()), "missing {}", rule);
+        }
+
+        let shallow = GeneratorConfig { depth: 1, ..config };
+        let set = export(&profile, &shallow);
+        assert!(!set.rules.contains(&"c $!".to_string()));
+    }
+}
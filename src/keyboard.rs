@@ -0,0 +1,162 @@
+//! Keyboard layouts and the walks people type across them.
+
+use serde::{Deserialize, Serialize};
+
+/// A keyboard layout, for keyboard-walk candidates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    #[default]
+    Qwerty,
+    /// French and Belgian
+    Azerty,
+    /// German, Austrian, Swiss and most of Central Europe
+    Qwertz,
+    Dvorak,
+}
+
+/// Shortest row prefix worth trying as a walk.
+const MIN_WALK: usize = 4;
+
+impl Layout {
+    /// The number row, then the three letter rows, left to right (unshifted).
+    fn rows(self) -> [&'static str; 4] {
+        match self {
+            Layout::Qwerty => ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"],
+            Layout::Azerty => ["1234567890", "azertyuiop", "qsdfghjklm", "wxcvbn"],
+            Layout::Qwertz => ["1234567890", "qwertzuiop", "asdfghjkl", "yxcvbnm"],
+            Layout::Dvorak => ["1234567890", "',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"],
+        }
+    }
+
+    /// The symbols on the digit keys 0-9: typed with Shift, or on AZERTY,
+    /// where Shift gives the digit, without it.
+    fn digit_symbols(self) -> &'static str {
+        match self {
+            Layout::Qwerty | Layout::Dvorak => ")!@#$%^&*(",
+            Layout::Azerty => "à&é\"'(-è_ç",
+            Layout::Qwertz => "=!\"§$%&/()",
+        }
+    }
+
+    /// The symbol on the key of the digit `c` (`!` for `1` on QWERTY), as
+    /// people type a number with the other shift state for complexity.
+    pub fn shift_digit(self, c: char) -> Option<char> {
+        let digit = c.to_digit(10)?;
+        self.digit_symbols().chars().nth(digit as usize)
+    }
+
+    /// The keys either side of `c` on its row, for fat-finger typos.
+    pub fn neighbors(self, c: char) -> Vec<char> {
+        self.rows()
+            .iter()
+            .map(|row| row.chars().collect::<Vec<char>>())
+            .find_map(|row| {
+                let i = row.iter().position(|&k| k == c)?;
+                Some(
+                    [i.checked_sub(1), Some(i + 1)]
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|j| row.get(j).copied())
+                        .collect(),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Walks along each letter row from the left (qwer, qwert, qwerty, the
+    /// whole row), then down the leftmost columns (qazwsx), down them from
+    /// the number row (1qaz2wsx, 1qaz2wsx3edc) and back up (zaq1xsw2).
+    pub fn walks(self) -> Vec<String> {
+        let rows = self.rows().map(|row| row.chars().collect::<Vec<char>>());
+        let [numbers, letters @ ..] = &rows;
+        let mut walks: Vec<String> = Vec::new();
+        let mut push = |walk: String| {
+            if !walks.contains(&walk) {
+                walks.push(walk);
+            }
+        };
+
+        for row in letters {
+            for len in (MIN_WALK..=6)
+                .chain([row.len()])
+                .filter(|&len| len <= row.len())
+            {
+                push(row[..len].iter().collect());
+            }
+        }
+
+        let column = |i: usize| -> String { letters.iter().filter_map(|row| row.get(i)).collect() };
+        let down = |n: usize, from_numbers: bool| -> String {
+            (0..n)
+                .map(|i| match from_numbers {
+                    true => format!("{}{}", numbers[i], column(i)),
+                    false => column(i),
+                })
+                .collect()
+        };
+        push(down(2, false));
+        push(down(2, true));
+        push(down(3, true));
+        push(
+            (0..2)
+                .map(|i| {
+                    format!(
+                        "{}{}",
+                        column(i).chars().rev().collect::<String>(),
+                        numbers[i]
+                    )
+                })
+                .collect(),
+        );
+        walks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walks() {
+        let qwerty = Layout::Qwerty.walks();
+        for walk in [
+            "qwer",
+            "qwerty",
+            "qwertyuiop",
+            "asdfghjkl",
+            "zxcvbnm",
+            "qazwsx",
+            "1qaz2wsx",
+            "1qaz2wsx3edc",
+            "zaq1xsw2",
+        ] {
+            assert!(qwerty.contains(&walk.to_string()), "missing {}", walk);
+        }
+
+        let azerty = Layout::Azerty.walks();
+        assert!(azerty.contains(&"azerty".to_string()));
+        assert!(azerty.contains(&"1aqw2zsx".to_string()));
+        assert!(!azerty.contains(&"qwerty".to_string()));
+        assert!(Layout::Qwertz.walks().contains(&"qwertz".to_string()));
+        assert!(Layout::Dvorak.walks().contains(&"aoeu".to_string()));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        assert_eq!(Layout::Qwerty.neighbors('s'), ['a', 'd']);
+        assert_eq!(Layout::Qwerty.neighbors('q'), ['w']);
+        assert_eq!(Layout::Qwerty.neighbors('0'), ['9']);
+        assert_eq!(Layout::Qwertz.neighbors('t'), ['r', 'z']);
+        assert!(Layout::Qwerty.neighbors('!').is_empty());
+    }
+
+    #[test]
+    fn test_shift_digit() {
+        assert_eq!(Layout::Qwerty.shift_digit('1'), Some('!'));
+        assert_eq!(Layout::Qwerty.shift_digit('0'), Some(')'));
+        assert_eq!(Layout::Qwertz.shift_digit('7'), Some('/'));
+        assert_eq!(Layout::Azerty.shift_digit('2'), Some('é'));
+        assert_eq!(Layout::Qwerty.shift_digit('a'), None);
+    }
+}
@@ -0,0 +1,38 @@
+//! Smart, profile-driven password candidate generation and hash cracking
+//! for educational cybersecurity research.
+
+pub mod arena;
+pub mod audit;
+pub mod batch;
+pub mod bloom;
+pub mod brute;
+pub mod cache;
+pub mod combinator;
+pub mod common;
+pub mod console;
+pub mod cracker;
+pub mod date;
+pub mod estimate;
+pub mod explain;
+pub mod generator;
+pub mod hashcat;
+pub mod keyboard;
+pub mod loopback;
+pub mod markov;
+pub mod mask;
+pub mod metrics;
+pub mod mutations;
+pub mod names;
+pub mod pcfg;
+pub mod phone;
+pub mod policy;
+pub mod prince;
+pub mod profile;
+pub mod report;
+pub mod rules;
+pub mod sample;
+pub mod session;
+pub mod strength;
+pub mod template;
+pub mod unicode;
+pub mod wordlist;
@@ -0,0 +1,112 @@
+//! Loopback: recovered plaintexts become seeds for another pass.
+//!
+//! Accounts from the same dump often share patterns (one person reusing a
+//! base word, a team using the company name plus a year), so every cracked
+//! password is added to the profile exactly as found, along with its letter
+//! and digit runs. Generating again then mutates and recombines them.
+
+use crate::profile::{Field, Profile, Transform};
+
+/// Shortest letter run worth seeding on its own.
+const MIN_WORD: usize = 3;
+/// Shortest digit run worth seeding on its own.
+const MIN_NUMBER: usize = 2;
+
+/// Add cracked `plaintexts` to the profile's custom words and numbers,
+/// skipping anything it already has.
+pub fn add_plaintexts(profile: &mut Profile, plaintexts: &[String]) {
+    for plaintext in plaintexts {
+        let plaintext = plaintext.trim();
+        if plaintext.is_empty() {
+            continue;
+        }
+        // The password itself, spelled exactly as it was found
+        push_word(
+            profile,
+            Field::WithTransforms {
+                value: plaintext.to_string(),
+                transforms: vec![Transform::AsIs],
+            },
+        );
+        for run in runs(plaintext) {
+            if run.chars().all(|c| c.is_ascii_digit()) {
+                if run.len() >= MIN_NUMBER && !profile.custom.numbers.contains(&run) {
+                    profile.custom.numbers.push(run);
+                }
+            } else if run.chars().count() >= MIN_WORD && run != plaintext {
+                push_word(profile, Field::Plain(run));
+            }
+        }
+    }
+}
+
+fn push_word(profile: &mut Profile, field: Field) {
+    let words = &mut profile.custom.words;
+    if !words.iter().any(|w| w.value() == field.value()) {
+        words.push(field);
+    }
+}
+
+/// Maximal runs of letters and of digits, e.g. "Summer2024!" gives
+/// "Summer" and "2024".
+fn runs(text: &str) -> Vec<String> {
+    let mut runs = Vec::new();
+    let mut current = String::new();
+    let mut digits = false;
+    for c in text.chars() {
+        let kind = if c.is_ascii_digit() {
+            Some(true)
+        } else if c.is_alphabetic() {
+            Some(false)
+        } else {
+            None
+        };
+        match kind {
+            Some(d) if current.is_empty() || d == digits => {
+                digits = d;
+                current.push(c);
+            }
+            Some(d) => {
+                runs.push(std::mem::take(&mut current));
+                digits = d;
+                current.push(c);
+            }
+            None if !current.is_empty() => runs.push(std::mem::take(&mut current)),
+            None => {}
+        }
+    }
+    if !current.is_empty() {
+        runs.push(current);
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{self, GeneratorConfig};
+
+    #[test]
+    fn test_runs() {
+        assert_eq!(runs("Summer2024!"), ["Summer", "2024"]);
+        assert_eq!(runs("a.b"), ["a", "b"]);
+        assert!(runs("!!").is_empty());
+    }
+
+    #[test]
+    fn test_plaintexts_seed_new_candidates() {
+        let mut profile = Profile::default();
+        add_plaintexts(
+            &mut profile,
+            &["Acme2023!".to_string(), "acme2023".to_string()],
+        );
+        let words: Vec<&str> = profile.custom.words.iter().map(Field::value).collect();
+        assert_eq!(words, ["Acme2023!", "Acme", "acme2023", "acme"]);
+        assert_eq!(profile.custom.numbers, ["2023"]);
+
+        // A colleague's variation on the same pattern
+        let candidates: Vec<String> =
+            generator::candidate_stream(&profile, &GeneratorConfig::default()).collect();
+        assert!(candidates.iter().any(|c| c == "Acme2024"));
+    }
+}
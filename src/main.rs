@@ -1,16 +1,19 @@
-mod common;
-mod cracker;
-mod generator;
-mod mutations;
-mod profile;
-mod wordlist;
-
-use std::path::PathBuf;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 
+use password_guesser::{
+    audit, batch, brute, cache, combinator, common, console, cracker, date, estimate, explain,
+    generator, hashcat, keyboard, loopback, markov, mask, metrics, mutations, pcfg, policy, prince,
+    profile, report, rules, sample, session, status, strength, template, wordlist,
+};
+
 #[derive(Parser)]
 #[command(
     name = "password-guesser",
@@ -21,298 +24,3326 @@ use colored::Colorize;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Engagement file (TOML) with scope and authorization; enables the
+    /// authorization prompt and audit log for cracking commands
+    #[arg(long, global = true)]
+    engagement: Option<PathBuf>,
+
+    /// Skip the authorization prompt when the engagement file is complete
+    #[arg(long, global = true, requires = "engagement")]
+    authorized: bool,
+
+    /// Worker threads for hashing (default: one per CPU)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u16).range(1..))]
+    threads: Option<u16>,
+
+    /// Run at lower CPU priority (niceness 10) so the machine stays responsive
+    #[arg(long, global = true)]
+    nice: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Generate a wordlist from a target profile
-    Generate {
-        /// Path to the target profile TOML file
-        #[arg(short, long)]
-        profile: PathBuf,
+    Generate(Box<GenerateArgs>),
 
-        /// Output wordlist file
-        #[arg(short, long)]
-        output: PathBuf,
+    /// Estimate candidates per tier and run time before cracking
+    Estimate(EstimateArgs),
 
-        /// Generation depth (1=fast ~5K, 2=medium ~20-50K, 3=deep ~100-500K)
-        #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
-        depth: u8,
+    /// Report which tier and rules would have produced each of a list of
+    /// known passwords, to measure the generator's coverage
+    Explain(ExplainArgs),
 
-        /// Minimum password length
-        #[arg(long, default_value = "6")]
-        min_length: usize,
+    /// Crack hash(es) using a target profile
+    CrackHash(Box<CrackHashArgs>),
 
-        /// Maximum password length
-        #[arg(long, default_value = "32")]
-        max_length: usize,
-    },
+    /// Crack anything: a hash, a hash file or a handshake capture (auto-detected)
+    Crack(CrackArgs),
 
-    /// Crack hash(es) using a target profile
-    CrackHash {
-        /// Single hash to crack
-        #[arg(long)]
-        hash: Option<String>,
+    /// Run the profile + hash file jobs of a manifest, with one report at the end
+    CrackBatch(CrackBatchArgs),
 
-        /// File containing hashes (one per line)
-        #[arg(long)]
-        hash_file: Option<PathBuf>,
+    /// Crack a WiFi handshake using a target profile
+    CrackWifi(CrackWifiArgs),
 
-        /// Hash algorithm (md5, sha1, sha256, sha512, bcrypt, ntlm)
-        #[arg(short, long)]
-        algo: String,
+    /// Coordinate a distributed hash crack, handing candidate chunks to workers
+    Serve(ServeArgs),
 
-        /// Path to the target profile TOML file
-        #[arg(short, long)]
-        profile: PathBuf,
+    /// Join a distributed crack started with `serve`
+    Worker(WorkerArgs),
 
-        /// Generation depth (1-3)
-        #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
-        depth: u8,
+    /// Recompute a hash from a plaintext to confirm a crack result
+    Verify(VerifyArgs),
 
-        /// Minimum password length
-        #[arg(long, default_value = "6")]
-        min_length: usize,
+    /// Learn a PCFG password model from a leaked-password corpus, for
+    /// `generate --pcfg`
+    Train(TrainArgs),
 
-        /// Maximum password length
-        #[arg(long, default_value = "32")]
-        max_length: usize,
-    },
+    /// Learn a character Markov model from a leaked-password corpus, for
+    /// `generate --markov-model`
+    TrainMarkov(TrainArgs),
+}
 
-    /// Crack a WiFi handshake using a target profile
-    CrackWifi {
-        /// Path to the handshake capture file (.cap/.pcap/.hccapx)
-        #[arg(long)]
-        handshake: PathBuf,
+#[derive(Args)]
+struct GenerateArgs {
+    /// Path to the target profile TOML file
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["mask", "left", "prince_wordlist", "pcfg", "markov_model"]
+    )]
+    profile: Option<PathBuf>,
 
-        /// Path to the target profile TOML file
-        #[arg(short, long)]
-        profile: PathBuf,
+    #[command(flatten)]
+    mask: MaskArgs,
 
-        /// Use hashcat instead of aircrack-ng
-        #[arg(long)]
-        use_hashcat: bool,
+    #[command(flatten)]
+    combinator: CombinatorArgs,
 
-        /// Generation depth (1-3)
-        #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
-        depth: u8,
+    #[command(flatten)]
+    prince: PrinceArgs,
 
-        /// Minimum password length (WiFi requires 8+)
-        #[arg(long, default_value = "8")]
-        min_length: usize,
+    #[command(flatten)]
+    pcfg: PcfgArgs,
 
-        /// Maximum password length
-        #[arg(long, default_value = "63")]
-        max_length: usize,
-    },
+    #[command(flatten)]
+    markov_model: MarkovModelArgs,
+
+    /// Output wordlist file, or `-` to stream candidates to stdout
+    #[arg(short, long, required_unless_present_any = ["estimate", "emit_rules"])]
+    output: Option<PathBuf>,
+
+    /// Generation depth (1=fast ~5K, 2=medium ~20-50K, 3=deep ~100-500K)
+    /// [default: 2, or 3 with --max-candidates]
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=3))]
+    depth: Option<u8>,
+
+    /// Minimum password length
+    #[arg(long, default_value = "6")]
+    min_length: usize,
+
+    /// Maximum password length
+    #[arg(long, default_value = "32")]
+    max_length: usize,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+
+    /// Pause after each tier to show counts and a sample (for walkthroughs)
+    #[arg(long, conflicts_with = "attack")]
+    step: bool,
+
+    /// Write at most N candidates, pruning the least likely ones once the
+    /// tiers overflow the budget
+    #[arg(long, value_name = "N", conflicts_with_all = ["attack", "step"])]
+    max_candidates: Option<usize>,
+
+    /// Order of the written candidates
+    #[arg(long, value_enum, default_value_t = Order::Generated, conflicts_with_all = ["attack", "step"])]
+    order: Order,
+
+    /// Write the candidates in byte order, for wordlists that diff cleanly
+    /// between runs (holds the whole list in memory)
+    #[arg(long, visible_alias = "stable", conflicts_with_all = ["attack", "step", "order"])]
+    sort: bool,
+
+    /// Drop candidates with characters the target system rejects: ascii,
+    /// alnum or custom:<chars>
+    #[arg(long, value_name = "CHARSET", value_parser = parse_charset, conflicts_with = "attack")]
+    charset: Option<policy::Charset>,
+
+    /// Drop candidates containing any of these characters
+    #[arg(long, value_name = "CHARS", conflicts_with = "attack")]
+    exclude_chars: Option<String>,
+
+    /// Count the candidates and wordlist size each tier would produce,
+    /// without writing anything
+    #[arg(long, conflicts_with_all = ["attack", "step", "max_candidates", "order", "sort"])]
+    estimate: bool,
+
+    /// Write a hashcat rule set that rebuilds the single-word tiers to FILE,
+    /// in place of the wordlist (with --emit-base)
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "emit_base",
+        conflicts_with_all = ["output", "attack", "step", "max_candidates", "order", "sort", "estimate"]
+    )]
+    emit_rules: Option<PathBuf>,
+
+    /// Add only candidates not already in the output file to its end,
+    /// instead of overwriting it
+    #[arg(
+        long,
+        conflicts_with_all = ["attack", "step", "max_candidates", "order", "sort", "estimate", "emit_rules"]
+    )]
+    append: bool,
+
+    /// Write N candidates drawn at random from everything the run would
+    /// write, in the order it would write them (masks and combinator
+    /// attacks are sampled without expanding them)
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["step", "max_candidates", "order", "estimate", "emit_rules", "append"]
+    )]
+    sample: Option<usize>,
+
+    /// Random seed for --sample, to draw the same sample again [default:
+    /// from the clock, and shown]
+    #[arg(long, value_name = "S", requires = "sample")]
+    seed: Option<u64>,
+
+    /// Write each candidate as `word<TAB>score<TAB>tier`, with its
+    /// zxcvbn-style guessability score (0 weakest to 4) and the tier that
+    /// produced it
+    #[arg(
+        long,
+        conflicts_with_all = ["attack", "step", "max_candidates", "order", "sort", "estimate", "emit_rules", "append", "sample"]
+    )]
+    annotate: bool,
+
+    /// Write the seed words for the --emit-rules rule set to FILE
+    #[arg(long, value_name = "FILE", requires = "emit_rules")]
+    emit_base: Option<PathBuf>,
 }
 
-fn main() -> Result<()> {
-    print_banner();
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Order {
+    /// Tier by tier, as generated (streamed)
+    Generated,
+    /// Sorted by estimated likelihood: tier, mutation count and suffix frequency
+    LikelyFirst,
+}
 
-    let cli = Cli::parse();
+#[derive(Args)]
+struct CrackHashArgs {
+    /// Single hash to crack
+    #[arg(long)]
+    hash: Option<String>,
 
-    match cli.command {
-        Commands::Generate {
-            profile,
-            output,
-            depth,
-            min_length,
-            max_length,
-        } => cmd_generate(&profile, &output, depth, min_length, max_length),
+    /// File containing hashes (one per line)
+    #[arg(long)]
+    hash_file: Option<PathBuf>,
 
-        Commands::CrackHash {
-            hash,
-            hash_file,
-            algo,
-            profile,
-            depth,
-            min_length,
-            max_length,
-        } => cmd_crack_hash(hash, hash_file, &algo, &profile, depth, min_length, max_length),
+    /// Hashes are given as user:hash (as with hashcat --username)
+    #[arg(long, conflicts_with = "resume")]
+    username: bool,
+
+    /// Hash algorithm (md5, sha1, sha256, sha512, bcrypt, ntlm)
+    #[arg(short, long, required_unless_present = "resume")]
+    algo: Option<String>,
+
+    /// Path to the target profile TOML file
+    #[arg(short, long, required_unless_present_any = ["resume", "mask", "left", "prince_wordlist", "brute", "wordlist"])]
+    profile: Option<PathBuf>,
+
+    /// Test the lines of this wordlist instead of generating candidates, or
+    /// after them when --profile is also given; `-` reads them from stdin,
+    /// e.g. at the end of a pipe
+    #[arg(long, group = "attack")]
+    wordlist: Option<PathBuf>,
+
+    #[command(flatten)]
+    mask: MaskArgs,
+
+    #[command(flatten)]
+    combinator: CombinatorArgs,
+
+    #[command(flatten)]
+    prince: PrinceArgs,
 
-        Commands::CrackWifi {
-            handshake,
-            profile,
-            use_hashcat,
+    /// Last resort: brute-force every string over --charset in the --length range
+    #[arg(long, group = "attack")]
+    brute: bool,
+
+    /// Brute-force charset: lower, upper, digits, symbols, hex, all or literal
+    /// characters, joined with '+' (e.g. lower+digits+!@)
+    #[arg(long, default_value = "lower+digits", requires = "brute")]
+    charset: String,
+
+    /// Brute-force length or inclusive range (e.g. 6-8)
+    #[arg(long, default_value = "6-8", requires = "brute")]
+    length: String,
+
+    /// Generation depth (1-3)
+    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
+    depth: u8,
+
+    /// Minimum password length
+    #[arg(long, default_value = "6")]
+    min_length: usize,
+
+    /// Maximum password length
+    #[arg(long, default_value = "32")]
+    max_length: usize,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+
+    /// Save periodic checkpoints to this session file (profile, brute-force and
+    /// wordlist file runs)
+    #[arg(long, conflicts_with_all = ["mask", "right", "prince"])]
+    session: Option<PathBuf>,
+
+    /// Resume an interrupted run from a session file
+    #[arg(long, conflicts_with_all = ["hash", "hash_file", "session", "attack"])]
+    resume: Option<PathBuf>,
+
+    /// Re-verify each match with an independent byte-level recomputation
+    #[arg(long)]
+    double_check: bool,
+
+    /// Cracking engine: built-in CPU loop or an external hashcat process
+    #[arg(long, value_enum, default_value_t = Engine::Cpu)]
+    engine: Engine,
+
+    /// Look the hashes up in a cache of the profile's digests kept in this
+    /// directory, building it on the first run (fast hashes, profile runs)
+    #[arg(long, value_name = "DIR", requires = "profile", conflicts_with_all = ["session", "resume", "engine", "attack"])]
+    cache: Option<PathBuf>,
+
+    /// Seed cracked plaintexts back into the generator and retry the rest
+    #[arg(long)]
+    loopback: bool,
+
+    /// If hashes remain, retry them at each higher depth up to 3, skipping
+    /// candidates already tried (profile runs)
+    #[arg(long)]
+    auto_escalate: bool,
+
+    /// Start bcrypt runs without confirming the attack plan
+    #[arg(long)]
+    yes: bool,
+
+    #[command(flatten)]
+    budget: BudgetArgs,
+
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+/// Generator tuning shared by every command that builds candidates from a profile.
+#[derive(Args)]
+struct GenerationArgs {
+    /// Combine every pair of seed words, not just high-value class pairs
+    #[arg(long)]
+    all_pairs: bool,
+
+    /// Within each tier, try the most likely candidates first (Markov model
+    /// trained on common passwords)
+    #[arg(long)]
+    markov: bool,
+
+    /// Join seed words with this separator; repeatable, replacing the
+    /// profile's `separators` (default "", "_" and ".")
+    #[arg(long = "word-separator", value_name = "SEP")]
+    word_separators: Vec<String>,
+
+    /// Years to append as suffixes, e.g. 1985-2027 (default: from the
+    /// profile's birth year, or 1950, to this year)
+    #[arg(long, value_name = "FIRST-LAST", value_parser = parse_years)]
+    years: Option<RangeInclusive<u16>>,
+
+    /// Also write the birthdate, and name months and seasons, the way this
+    /// locale does (us, en, de, nl, fr, es, ...; or all); repeatable,
+    /// replacing the profile's `locales`
+    #[arg(long, value_name = "LOCALE", value_parser = parse_date_locale)]
+    date_format: Vec<String>,
+
+    /// Keyboard layout to generate keyboard walks, typos and shifted digits for
+    #[arg(long, value_enum, default_value_t = KeyboardLayout::Qwerty)]
+    keyboard: KeyboardLayout,
+
+    /// Drop candidates the target's password policy rejects, e.g.
+    /// "min-upper=1,min-digit=1,min-symbol=1" or windows-complexity
+    #[arg(long, value_name = "RULES", value_parser = parse_policy)]
+    policy: Option<policy::Policy>,
+
+    /// Drop candidates a standard password policy rejects: nist (8
+    /// characters), windows-complexity or pci (12 characters, letters and
+    /// digits); --policy rules tighten it further
+    #[arg(long, value_name = "PRESET", value_parser = parse_policy_preset)]
+    policy_preset: Option<policy::Policy>,
+
+    /// Blend in this dictionary's entries as extra seed words, lightly
+    /// mutated (case, the profile's numbers, short suffixes), after the
+    /// profile's own candidates
+    #[arg(long, value_name = "FILE", value_parser = parse_base_wordlist)]
+    base_wordlist: Option<PathBuf>,
+
+    /// Also apply the mutation rules in this file to every seed word, one
+    /// rule per line, e.g. "toggle 0 | append 2024"
+    #[arg(long, value_name = "FILE", value_parser = parse_rules)]
+    rules: Option<RuleFile>,
+
+    /// Also apply the rules in this hashcat rule file (best64.rule,
+    /// OneRuleToRuleThemAll.rule) to every seed word
+    #[arg(long, value_name = "FILE", value_parser = parse_hashcat_rules)]
+    hashcat_rules: Option<HashcatRuleFile>,
+
+    /// Most digits or symbols to insert inside a seed word at once
+    /// (pa55!word); 0 for none
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    insertions: usize,
+
+    /// Also flip the case of up to N letters of each seed word (joHn,
+    /// jOhN); 0 for none
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    case_toggles: usize,
+
+    /// Also rotate each seed word left and right by one or more characters
+    /// (john -> ohnj, njoh), as some people do at a forced change; for
+    /// deep runs
+    #[arg(long)]
+    rotations: bool,
+
+    /// Deduplicate with a Bloom filter instead of an exact set, for very
+    /// deep runs: a fraction of the memory, at the cost of dropping about
+    /// RATE of the candidates as false duplicates
+    #[arg(
+        long,
+        value_name = "RATE",
+        num_args = 0..=1,
+        default_missing_value = "0.001",
+        value_parser = parse_rate
+    )]
+    bloom: Option<f64>,
+
+    /// Blend in the topic dictionaries the profile's interests bring in (a
+    /// favorite team's mascots, players and chants), plus any listed:
+    /// sports, music, religious or gaming
+    #[arg(
+        long,
+        value_name = "TOPIC",
+        num_args = 0..,
+        value_delimiter = ',',
+        value_parser = parse_topic
+    )]
+    topics: Option<Vec<String>>,
+
+    /// Kind of account under attack: corporate adds helpdesk patterns
+    /// (Company2024!, Welcome1), router adds vendor defaults and SSID forms
+    #[arg(long, value_enum, default_value_t = TargetTemplate::Personal)]
+    template: TargetTemplate,
+}
+
+/// The rules of a `--rules` file, parsed as the arguments are.
+#[derive(Clone)]
+struct RuleFile(Vec<rules::Rule>);
+
+/// The rules of a `--hashcat-rules` file, parsed as the arguments are.
+#[derive(Clone)]
+struct HashcatRuleFile(Vec<mutations::HashcatRule>);
+
+#[derive(Clone, Copy, ValueEnum)]
+enum KeyboardLayout {
+    Qwerty,
+    /// French and Belgian
+    Azerty,
+    /// German and Central European
+    Qwertz,
+    Dvorak,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TargetTemplate {
+    /// A person's own account: the profile tiers alone
+    Personal,
+    /// A company account: company name and year, onboarding defaults
+    Corporate,
+    /// A router's admin or WiFi password: vendor defaults, SSID forms
+    Router,
+}
+
+impl From<TargetTemplate> for template::Template {
+    fn from(template: TargetTemplate) -> Self {
+        match template {
+            TargetTemplate::Personal => Self::Personal,
+            TargetTemplate::Corporate => Self::Corporate,
+            TargetTemplate::Router => Self::Router,
+        }
+    }
+}
+
+impl From<KeyboardLayout> for keyboard::Layout {
+    fn from(layout: KeyboardLayout) -> Self {
+        match layout {
+            KeyboardLayout::Qwerty => Self::Qwerty,
+            KeyboardLayout::Azerty => Self::Azerty,
+            KeyboardLayout::Qwertz => Self::Qwertz,
+            KeyboardLayout::Dvorak => Self::Dvorak,
+        }
+    }
+}
+
+impl GenerationArgs {
+    fn config(
+        &self,
+        depth: u8,
+        min_length: usize,
+        max_length: usize,
+    ) -> generator::GeneratorConfig {
+        generator::GeneratorConfig {
             depth,
             min_length,
             max_length,
-        } => cmd_crack_wifi(&handshake, &profile, use_hashcat, depth, min_length, max_length),
+            all_pairs: self.all_pairs,
+            markov: self.markov,
+            likely_first: false,
+            separators: self.word_separators.clone(),
+            years: self.years.clone(),
+            recent_year: Some(generator::this_year()),
+            date_formats: self.date_format.clone(),
+            keyboard: self.keyboard.into(),
+            policy: self
+                .policy_preset
+                .into_iter()
+                .chain(self.policy)
+                .reduce(policy::Policy::and),
+            charset: None,
+            exclude_chars: String::new(),
+            base_wordlist: self.base_wordlist.clone(),
+            rules: self.rules.clone().map(|r| r.0).unwrap_or_default(),
+            hashcat_rules: self.hashcat_rules.clone().map(|r| r.0).unwrap_or_default(),
+            insertions: self.insertions,
+            case_toggles: self.case_toggles,
+            rotations: self.rotations,
+            bloom: self.bloom,
+            topics: self.topics.clone(),
+            template: self.template.into(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Like [`config`](Self::config), with the tiers scheduled for cracking.
+    fn cracking(
+        &self,
+        depth: u8,
+        min_length: usize,
+        max_length: usize,
+    ) -> generator::GeneratorConfig {
+        generator::GeneratorConfig {
+            likely_first: true,
+            ..self.config(depth, min_length, max_length)
+        }
     }
 }
 
-fn print_banner() {
-    let banner = r#"
-  ╔═══════════════════════════════════════════╗
-  ║       Smart Password Guesser v0.1.0       ║
-  ║     Educational Cybersecurity Research     ║
-  ╚═══════════════════════════════════════════╝
-"#;
-    println!("{}", banner.cyan());
+/// How `crack-hash` reports what it cracked.
+#[derive(Args)]
+struct ReportArgs {
+    /// Result format: text, or one JSON document on stdout (status messages
+    /// then go to stderr)
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Append cracked results to this file as they are found
+    #[arg(long)]
+    outfile: Option<PathBuf>,
+
+    /// Outfile line layout, as in hashcat
+    #[arg(long, value_enum, default_value_t = OutfileFormat::HashPlain, requires = "outfile")]
+    outfile_format: OutfileFormat,
 }
 
-fn cmd_generate(
-    profile_path: &PathBuf,
-    output: &PathBuf,
-    depth: u8,
-    min_length: usize,
-    max_length: usize,
-) -> Result<()> {
-    let profile = profile::Profile::load(profile_path)?;
+impl ReportArgs {
+    /// Open the outfile, if any. `users` maps hashes to accounts for
+    /// `user:plain`.
+    fn output(&self, users: report::Users) -> Result<Output> {
+        let outfile = match self.outfile {
+            Some(ref path) => Some(report::Outfile::open(
+                path,
+                self.outfile_format.into(),
+                users,
+            )?),
+            None => None,
+        };
+        Ok(Output {
+            format: self.format,
+            outfile,
+        })
+    }
+}
 
-    let config = generator::GeneratorConfig {
-        depth,
-        min_length,
-        max_length,
-    };
+#[derive(Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+}
 
-    println!(
-        "{} Profile: {} | Depth: {} | Length: {}-{}",
-        ">>".cyan().bold(),
-        profile_path.display(),
-        depth,
-        min_length,
-        max_length,
-    );
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutfileFormat {
+    /// hash:plaintext
+    #[value(name = "hash:plain")]
+    HashPlain,
+    /// The plaintext alone
+    Plain,
+    /// username:plaintext, one line per account (needs --username)
+    #[value(name = "user:plain")]
+    UserPlain,
+}
 
-    let seeds = profile.seed_words();
-    println!(
-        "{} Seed words: {}",
-        ">>".cyan().bold(),
-        seeds.join(", ").dimmed()
-    );
+impl From<OutfileFormat> for report::OutfileFormat {
+    fn from(format: OutfileFormat) -> Self {
+        match format {
+            OutfileFormat::HashPlain => Self::HashPlain,
+            OutfileFormat::Plain => Self::Plain,
+            OutfileFormat::UserPlain => Self::UserPlain,
+        }
+    }
+}
 
-    let candidates = generator::generate_candidates(&profile, &config);
+/// Where a crack-hash run's results go besides the terminal.
+#[derive(Default)]
+struct Output {
+    format: Format,
+    outfile: Option<report::Outfile>,
+}
 
-    wordlist::write_wordlist(output, &candidates)?;
+impl Output {
+    /// Append results not yet in the outfile, if any; a failed write only warns.
+    fn record(&mut self, results: &[cracker::CrackResult]) {
+        if let Some(ref mut outfile) = self.outfile {
+            if let Err(e) = outfile.write(results) {
+                eprintln!("{} {:#}", "WARNING".yellow().bold(), e);
+            }
+        }
+    }
+}
 
-    println!(
-        "\n{} Wrote {} candidates to {}",
-        "SUCCESS".green().bold(),
-        candidates.len(),
-        output.display()
-    );
+/// Wall-clock limits for time-boxed runs, shared by `crack-hash` and `crack-wifi`.
+#[derive(Args)]
+struct BudgetArgs {
+    /// Stop cleanly after this many minutes, keeping a resumable checkpoint
+    #[arg(long, value_name = "MINUTES", value_parser = parse_minutes)]
+    max_duration: Option<Duration>,
 
-    Ok(())
+    /// Print a status snapshot every this many minutes
+    #[arg(long, value_name = "MINUTES", value_parser = parse_minutes)]
+    status_interval: Option<Duration>,
 }
 
-fn cmd_crack_hash(
-    hash: Option<String>,
-    hash_file: Option<PathBuf>,
-    algo_str: &str,
-    profile_path: &PathBuf,
-    depth: u8,
-    min_length: usize,
-    max_length: usize,
-) -> Result<()> {
-    let algo = cracker::HashAlgorithm::from_str(algo_str);
-    let algo = match algo {
-        Some(a) => a,
-        None => bail!(
-            "Unknown algorithm: {}. Supported: md5, sha1, sha256, sha512, bcrypt, ntlm",
-            algo_str
-        ),
-    };
+impl BudgetArgs {
+    fn crack_config(&self, double_check: bool) -> cracker::CrackConfig {
+        cracker::CrackConfig {
+            double_check,
+            deadline: self.max_duration.map(|d| Instant::now() + d),
+            status_interval: self.status_interval,
+            expected: None,
+        }
+    }
+}
 
-    // Collect hashes
-    let mut hashes = Vec::new();
-    if let Some(ref h) = hash {
-        hashes.push(h.clone());
+fn parse_minutes(s: &str) -> Result<Duration, String> {
+    match s.parse::<f64>() {
+        Ok(m) if m.is_finite() && m > 0.0 => Ok(Duration::from_secs_f64(m * 60.0)),
+        _ => Err(format!(
+            "expected a positive number of minutes, got '{}'",
+            s
+        )),
     }
-    if let Some(ref path) = hash_file {
-        let file_hashes = wordlist::read_wordlist(path)?;
-        hashes.extend(file_hashes);
+}
+
+fn parse_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate < 1.0 => Ok(rate),
+        _ => Err(format!(
+            "expected a false-positive rate between 0 and 1, like 0.001, got '{}'",
+            s
+        )),
     }
-    if hashes.is_empty() {
-        bail!("Provide --hash or --hash-file");
+}
+
+fn parse_topic(s: &str) -> Result<String, String> {
+    let topic = s.trim().to_lowercase();
+    if common::is_topic(&topic) {
+        Ok(topic)
+    } else {
+        Err(format!(
+            "no dictionary for topic '{}' ({})",
+            s,
+            common::topics().join(", ")
+        ))
     }
+}
 
-    // Generate candidates
-    let profile = profile::Profile::load(profile_path)?;
-    let config = generator::GeneratorConfig {
-        depth,
-        min_length,
-        max_length,
-    };
+fn parse_years(s: &str) -> Result<RangeInclusive<u16>, String> {
+    let (first, last) = s.split_once('-').unwrap_or((s, s));
+    let four_digits = 1000..=9999;
+    match (first.trim().parse::<u16>(), last.trim().parse::<u16>()) {
+        (Ok(first), Ok(last)) if four_digits.contains(&first) && four_digits.contains(&last) => {
+            Ok(first.min(last)..=first.max(last))
+        }
+        _ => Err(format!(
+            "expected a range of four-digit years like 1985-2027, got '{}'",
+            s
+        )),
+    }
+}
 
-    println!(
-        "{} Profile: {} | Algo: {} | Depth: {}",
-        ">>".cyan().bold(),
-        profile_path.display(),
-        algo,
-        depth,
-    );
+fn parse_policy(s: &str) -> Result<policy::Policy, String> {
+    policy::Policy::parse(s).map_err(|e| e.to_string())
+}
 
-    let seeds = profile.seed_words();
-    println!(
-        "{} Seed words: {}",
-        ">>".cyan().bold(),
-        seeds.join(", ").dimmed()
-    );
+fn parse_policy_preset(s: &str) -> Result<policy::Policy, String> {
+    policy::Policy::preset(s).map_err(|e| e.to_string())
+}
 
-    let candidates = generator::generate_candidates(&profile, &config);
+fn parse_base_wordlist(s: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    match std::fs::File::open(&path) {
+        Ok(_) if path.is_file() => Ok(path),
+        Ok(_) => Err(format!("{} is not a file", s)),
+        Err(e) => Err(format!("can't read {}: {}", s, e)),
+    }
+}
 
-    // Crack
-    let results = cracker::hash::crack_hashes(&hashes, algo, &candidates)?;
+fn parse_rules(s: &str) -> Result<RuleFile, String> {
+    rules::load(Path::new(s))
+        .map(RuleFile)
+        .map_err(|e| format!("{:#}", e))
+}
 
-    // Summary
-    println!();
-    if results.is_empty() {
-        println!(
-            "{} No hashes cracked. Try increasing --depth or enriching the profile.",
-            "RESULT".yellow().bold()
-        );
+fn parse_hashcat_rules(s: &str) -> Result<HashcatRuleFile, String> {
+    rules::load_hashcat(Path::new(s))
+        .map(HashcatRuleFile)
+        .map_err(|e| format!("{:#}", e))
+}
+
+fn parse_charset(s: &str) -> Result<policy::Charset, String> {
+    policy::Charset::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_date_locale(s: &str) -> Result<String, String> {
+    let locale = s.trim().to_lowercase();
+    if date::is_date_locale(&locale) {
+        Ok(locale)
     } else {
-        println!(
-            "{} Cracked {}/{} hash(es):",
-            "RESULT".green().bold(),
-            results.len(),
-            hashes.len()
-        );
-        for r in &results {
-            println!("  {} {}", "→".green(), r);
-        }
+        Err(format!("no date formats for locale '{}'", s))
     }
+}
 
-    Ok(())
+#[derive(Args)]
+struct PcfgArgs {
+    /// PCFG attack: candidates from a model built with `train`, most
+    /// probable first
+    #[arg(long, value_name = "MODEL", group = "attack")]
+    pcfg: Option<PathBuf>,
+
+    /// Write only the N most probable PCFG candidates
+    #[arg(long, value_name = "N", requires = "pcfg")]
+    pcfg_limit: Option<usize>,
 }
 
-fn cmd_crack_wifi(
-    handshake: &PathBuf,
-    profile_path: &PathBuf,
-    use_hashcat: bool,
+#[derive(Args)]
+struct MarkovModelArgs {
+    /// Markov attack: the most probable strings of a model built with
+    /// `train-markov`, within the length bounds
+    #[arg(long, value_name = "MODEL", group = "attack")]
+    markov_model: Option<PathBuf>,
+
+    /// Number of Markov candidates to write
+    #[arg(long, value_name = "N", default_value = "1000000", requires = "markov_model")]
+    markov_limit: usize,
+}
+
+#[derive(Args)]
+struct ExplainArgs {
+    /// Known passwords (say, ones cracked on an engagement), one per line,
+    /// or `-` for stdin
+    passwords: PathBuf,
+
+    /// Path to the target profile TOML file
+    #[arg(short, long)]
+    profile: PathBuf,
+
+    /// Generation depth (1-3)
+    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
     depth: u8,
+
+    /// Minimum password length
+    #[arg(long, default_value = "6")]
     min_length: usize,
+
+    /// Maximum password length
+    #[arg(long, default_value = "32")]
     max_length: usize,
-) -> Result<()> {
-    let profile = profile::Profile::load(profile_path)?;
-    let config = generator::GeneratorConfig {
-        depth,
-        min_length,
-        max_length,
-    };
 
-    println!(
-        "{} Profile: {} | Depth: {} | Tool: {}",
-        ">>".cyan().bold(),
-        profile_path.display(),
-        depth,
-        if use_hashcat { "hashcat" } else { "aircrack-ng" },
-    );
+    #[command(flatten)]
+    generation: GenerationArgs,
+}
+
+#[derive(Args)]
+struct TrainArgs {
+    /// Corpus of real passwords, one per line, or `-` for stdin
+    corpus: PathBuf,
+
+    /// Model file to write (TOML)
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+/// hashcat-style mask attack options, shared by `generate` and `crack-hash`.
+#[derive(Args)]
+struct MaskArgs {
+    /// Brute-force a mask instead of using the profile (e.g. ?u?l?l?l?d?d?d?d)
+    #[arg(long, group = "attack")]
+    mask: Option<String>,
+
+    /// Custom charset for ?1 in the mask (e.g. ?l?d)
+    #[arg(short = '1', long, requires = "mask")]
+    custom_charset1: Option<String>,
+
+    /// Custom charset for ?2 in the mask
+    #[arg(short = '2', long, requires = "mask")]
+    custom_charset2: Option<String>,
+
+    /// Custom charset for ?3 in the mask
+    #[arg(short = '3', long, requires = "mask")]
+    custom_charset3: Option<String>,
+
+    /// Custom charset for ?4 in the mask
+    #[arg(short = '4', long, requires = "mask")]
+    custom_charset4: Option<String>,
+}
+
+impl MaskArgs {
+    /// The parsed mask, if --mask was given.
+    fn parse(&self) -> Result<Option<mask::Mask>> {
+        let Some(ref mask) = self.mask else {
+            return Ok(None);
+        };
+        let custom = [
+            self.custom_charset1.clone(),
+            self.custom_charset2.clone(),
+            self.custom_charset3.clone(),
+            self.custom_charset4.clone(),
+        ];
+        Ok(Some(mask::Mask::parse(mask, &custom)?))
+    }
+}
+
+/// Combinator attack options, shared by `generate` and `crack-hash`.
+#[derive(Args)]
+struct CombinatorArgs {
+    /// Combinator attack: join every left word to every word in this wordlist
+    #[arg(long, group = "attack")]
+    right: Option<PathBuf>,
+
+    /// Left wordlist for the combinator attack (default: the profile's seed words)
+    #[arg(long, requires = "right")]
+    left: Option<PathBuf>,
+
+    /// Separator placed between left and right words; repeatable (default: none)
+    #[arg(long, requires = "right", allow_hyphen_values = true)]
+    separator: Vec<String>,
+}
+
+impl CombinatorArgs {
+    /// The combinator lists, if --right was given. Without --left the left
+    /// side is the seed words of `profile`.
+    fn build(&self, profile: Option<&Path>) -> Result<Option<combinator::Combinator>> {
+        let Some(ref right) = self.right else {
+            return Ok(None);
+        };
+        let left = match (&self.left, profile) {
+            (Some(left), _) => wordlist::read_wordlist(left)?,
+            (None, Some(profile)) => profile::Profile::load(profile)?.seed_words(),
+            (None, None) => bail!("Provide --left or --profile for the combinator attack"),
+        };
+        let right = wordlist::read_wordlist(right)?;
+        Ok(Some(combinator::Combinator::new(
+            left,
+            right,
+            self.separator.clone(),
+        )))
+    }
+}
+
+/// PRINCE attack options, shared by `generate` and `crack-hash`.
+#[derive(Args)]
+struct PrinceArgs {
+    /// PRINCE attack: chain profile seeds and numbers into passphrases, most likely first
+    #[arg(long, group = "attack")]
+    prince: bool,
+
+    /// Extra PRINCE elements, one per line (used alone when no profile is given)
+    #[arg(long, requires = "prince")]
+    prince_wordlist: Option<PathBuf>,
+
+    /// Fewest elements per PRINCE chain
+    #[arg(long, default_value = "2", requires = "prince")]
+    prince_min_elements: usize,
+
+    /// Most elements per PRINCE chain
+    #[arg(long, default_value = "4", requires = "prince")]
+    prince_max_elements: usize,
+}
+
+impl PrinceArgs {
+    /// The PRINCE chain builder, if --prince was given. Elements are the
+    /// profile's seed words, then its seed numbers, then --prince-wordlist.
+    fn build(
+        &self,
+        profile: Option<&Path>,
+        min_length: usize,
+        max_length: usize,
+    ) -> Result<Option<prince::Prince>> {
+        if !self.prince {
+            return Ok(None);
+        }
+        let mut elements = Vec::new();
+        if let Some(profile) = profile {
+            let profile = profile::Profile::load(profile)?;
+            elements.extend(profile.seed_words());
+            elements.extend(profile.seed_numbers(generator::this_year()));
+        }
+        if let Some(ref path) = self.prince_wordlist {
+            elements.extend(wordlist::read_wordlist(path)?);
+        }
+        Ok(Some(prince::Prince::new(
+            elements,
+            self.prince_min_elements,
+            self.prince_max_elements,
+            min_length,
+            max_length,
+        )))
+    }
+}
+
+/// A boxed candidate stream, as handed to the cracking loop.
+type Candidates = Box<dyn Iterator<Item = String> + Send>;
+
+/// Where crack-hash candidates come from.
+enum Source<'a> {
+    /// The tiered generator over a target profile, optionally followed by
+    /// a base wordlist
+    Profile {
+        path: &'a Path,
+        config: Box<generator::GeneratorConfig>,
+        wordlist: Option<&'a Path>,
+        /// Directory of digest caches to look the hashes up in, instead of
+        /// hashing the candidates again
+        cache: Option<&'a Path>,
+    },
+    /// Every expansion of a mask
+    Mask(mask::Mask),
+    /// The cross-product of two word lists
+    Combinator(combinator::Combinator),
+    /// Multi-element chains, most likely first
+    Prince(prince::Prince),
+    /// The exhaustive keyspace of a charset over a length range
+    Brute(brute::BruteForce),
+    /// Lines of a wordlist file, or of stdin for `-`
+    Wordlist(&'a Path),
+}
+
+#[derive(Args)]
+struct EstimateArgs {
+    /// Path to the target profile TOML file
+    #[arg(short, long)]
+    profile: PathBuf,
+
+    /// Algorithm to time (md5, sha1, sha256, sha512, bcrypt, ntlm, or wpa for handshakes)
+    #[arg(short, long)]
+    algo: String,
+
+    /// Target hash; bcrypt timing depends on its cost
+    #[arg(long)]
+    hash: Option<String>,
+
+    /// File containing target hashes (one per line)
+    #[arg(long)]
+    hash_file: Option<PathBuf>,
+
+    /// Generation depth (1-3)
+    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
+    depth: u8,
+
+    /// Minimum password length (default 6, or 8 for WPA)
+    #[arg(long)]
+    min_length: Option<usize>,
+
+    /// Maximum password length (default 32, or 63 for WPA)
+    #[arg(long)]
+    max_length: Option<usize>,
+
+    /// Seconds spent measuring throughput
+    #[arg(long, default_value = "2")]
+    calibration: f64,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+}
+
+/// Backend used to test candidates against hashes.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Engine {
+    /// Built-in parallel CPU cracker
+    Cpu,
+    /// Stream candidates into hashcat (GPU)
+    Hashcat,
+}
+
+#[derive(Args)]
+struct CrackArgs {
+    /// Hash string or path to a hash file / capture
+    target: String,
+
+    /// Path to the target profile TOML file
+    #[arg(short, long)]
+    profile: PathBuf,
+
+    /// Force the hash algorithm instead of guessing (e.g. ntlm)
+    #[arg(short, long)]
+    algo: Option<String>,
+
+    /// Use hashcat instead of aircrack-ng for handshakes
+    #[arg(long)]
+    use_hashcat: bool,
+
+    /// Generation depth (1-3)
+    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
+    depth: u8,
+
+    /// Minimum password length (default 6, or 8 for WiFi)
+    #[arg(long)]
+    min_length: Option<usize>,
+
+    /// Maximum password length (default 32, or 63 for WiFi)
+    #[arg(long)]
+    max_length: Option<usize>,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+
+    /// Re-verify each hash match with an independent byte-level recomputation
+    #[arg(long)]
+    double_check: bool,
+
+    /// Seed cracked plaintexts back into the generator and retry the rest
+    #[arg(long)]
+    loopback: bool,
+
+    /// Start bcrypt runs without confirming the attack plan
+    #[arg(long)]
+    yes: bool,
+}
+
+#[derive(Args)]
+struct CrackWifiArgs {
+    /// Path to the handshake capture file (.cap/.pcap/.hccapx)
+    #[arg(long, required_unless_present = "resume")]
+    handshake: Option<PathBuf>,
+
+    /// Path to the target profile TOML file
+    #[arg(short, long, required_unless_present = "resume")]
+    profile: Option<PathBuf>,
+
+    /// Use hashcat instead of aircrack-ng
+    #[arg(long)]
+    use_hashcat: bool,
+
+    /// Crack an .hccapx handshake in-process (no external tools)
+    #[arg(long, conflicts_with = "use_hashcat")]
+    native: bool,
+
+    /// Generation depth (1-3)
+    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
+    depth: u8,
+
+    /// Minimum password length (WiFi requires 8+)
+    #[arg(long, default_value = "8")]
+    min_length: usize,
+
+    /// Maximum password length
+    #[arg(long, default_value = "63")]
+    max_length: usize,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+
+    /// Save periodic checkpoints to this session file (--native only)
+    #[arg(long, requires = "native")]
+    session: Option<PathBuf>,
+
+    /// Resume an interrupted native run from a session file
+    #[arg(long, conflicts_with_all = ["handshake", "session", "use_hashcat"])]
+    resume: Option<PathBuf>,
+
+    #[command(flatten)]
+    budget: BudgetArgs,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Address to listen on; use 0.0.0.0:7878 to take workers from other
+    /// machines
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    bind: String,
+
+    /// Shared secret workers must present before they are sent any hash
+    #[arg(long)]
+    token: String,
+
+    /// Single hash to crack
+    #[arg(long)]
+    hash: Option<String>,
+
+    /// File containing hashes (one per line)
+    #[arg(long)]
+    hash_file: Option<PathBuf>,
+
+    /// Hash algorithm (md5, sha1, sha256, sha512, bcrypt, ntlm)
+    #[arg(short, long)]
+    algo: String,
+
+    /// Path to the target profile TOML file
+    #[arg(short, long)]
+    profile: PathBuf,
+
+    /// Generation depth (1-3)
+    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
+    depth: u8,
+
+    /// Minimum password length
+    #[arg(long, default_value = "6")]
+    min_length: usize,
+
+    /// Maximum password length
+    #[arg(long, default_value = "32")]
+    max_length: usize,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+
+    /// Candidates per chunk handed to a worker
+    #[arg(long, default_value = "50000")]
+    chunk_size: usize,
+
+    #[command(flatten)]
+    metrics: MetricsArgs,
+}
+
+#[derive(Args)]
+struct CrackBatchArgs {
+    /// Jobs manifest (TOML, or JSON by extension) with the profile, hash
+    /// file, algorithm and depth of each job
+    #[arg(short, long)]
+    manifest: PathBuf,
+
+    /// Jobs to run at once; side-by-side jobs run quietly and report as
+    /// they finish
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u16).range(1..))]
+    parallel: u16,
+
+    /// Result format: text, or one JSON document on stdout (status messages
+    /// then go to stderr)
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    #[command(flatten)]
+    metrics: MetricsArgs,
+}
+
+/// Monitoring for the long-running `serve` and `crack-batch` modes.
+#[derive(Args)]
+struct MetricsArgs {
+    /// Expose Prometheus metrics at http://<ADDR>/metrics (e.g. 127.0.0.1:9898)
+    #[arg(long, value_name = "ADDR")]
+    metrics: Option<String>,
+}
+
+impl MetricsArgs {
+    /// Counters for the run, served over HTTP if `--metrics` was given.
+    fn start(&self) -> Result<Arc<metrics::Metrics>> {
+        let counters = Arc::new(metrics::Metrics::default());
+        if let Some(ref bind) = self.metrics {
+            let addr = metrics::serve(Arc::clone(&counters), bind)?;
+            status!("{} Metrics on http://{}/metrics", ">>".cyan().bold(), addr);
+        }
+        Ok(counters)
+    }
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// Hash to check; a salted digest may be given as hash:salt
+    #[arg(long)]
+    hash: String,
+
+    /// Hash algorithm (md5, sha1, sha256, sha512, bcrypt, ntlm); guessed
+    /// from the hash's shape when omitted
+    #[arg(short, long)]
+    algo: Option<String>,
+
+    /// Plaintext the hash is claimed to come from
+    #[arg(long)]
+    plaintext: String,
+
+    /// Salt for salted digests, instead of hash:salt
+    #[arg(long)]
+    salt: Option<String>,
+
+    /// Which side of the password the salt goes on
+    #[arg(long, value_enum, default_value_t = SaltPosition::Append)]
+    salt_position: SaltPosition,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SaltPosition {
+    /// password then salt, as in hashcat -m 10, 110, 1410, 1710
+    Append,
+    /// salt then password, as in hashcat -m 20, 120, 1420, 1720
+    Prepend,
+}
+
+impl From<SaltPosition> for cracker::hash::SaltPosition {
+    fn from(position: SaltPosition) -> Self {
+        match position {
+            SaltPosition::Append => Self::Append,
+            SaltPosition::Prepend => Self::Prepend,
+        }
+    }
+}
+
+#[derive(Args)]
+struct WorkerArgs {
+    /// Server address (host:port)
+    #[arg(long)]
+    server: String,
+
+    /// Name reported to the server
+    #[arg(long, default_value = "worker")]
+    name: String,
+
+    /// Shared secret the server was started with
+    #[arg(long)]
+    token: String,
+}
+
+/// Niceness applied by `--nice`: well below interactive work, above idle.
+const NICENESS: i32 = 10;
+
+/// Where a time-boxed run without `--session` leaves its checkpoint.
+const DEFAULT_SESSION: &str = "password-guesser.session";
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Commands::Generate(ref args) => {
+            console::set_stdout_is_data(args.output.as_deref() == Some(Path::new("-")))
+        }
+        Commands::CrackHash(ref args) => {
+            console::set_stdout_is_data(args.report.format == Format::Json)
+        }
+        Commands::CrackBatch(ref args) => console::set_stdout_is_data(args.format == Format::Json),
+        _ => {}
+    }
+    print_banner();
+
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.into())
+            .build_global()?;
+    }
+    if cli.nice {
+        lower_priority(NICENESS);
+    }
+
+    if let Some(ref engagement) = cli.engagement {
+        let command = match cli.command {
+            Commands::Generate(_) | Commands::Estimate(_) | Commands::Explain(_) => None,
+            Commands::CrackHash(_) => Some("crack-hash"),
+            Commands::Crack(_) => Some("crack"),
+            Commands::CrackBatch(_) => Some("crack-batch"),
+            Commands::CrackWifi(_) => Some("crack-wifi"),
+            Commands::Serve(_) => Some("serve"),
+            Commands::Worker(_) => Some("worker"),
+            Commands::Verify(_) | Commands::Train(_) | Commands::TrainMarkov(_) => None,
+        };
+        if let Some(command) = command {
+            audit::gate(engagement, cli.authorized, command)?;
+        }
+    }
+
+    match cli.command {
+        Commands::Generate(args) => cmd_generate(&args),
+        Commands::Estimate(args) => cmd_estimate(&args),
+        Commands::Explain(args) => cmd_explain(&args),
+        Commands::CrackHash(args) => cmd_crack_hash(&args),
+        Commands::Crack(args) => cmd_crack(&args),
+        Commands::CrackBatch(args) => cmd_crack_batch(&args),
+        Commands::CrackWifi(args) => cmd_crack_wifi(&args),
+        Commands::Serve(args) => cmd_serve(&args),
+        Commands::Worker(args) => {
+            cracker::distributed::work(&args.server, &args.name, &args.token)
+        }
+        Commands::Verify(args) => cmd_verify(&args),
+        Commands::Train(args) => cmd_train(&args),
+        Commands::TrainMarkov(args) => cmd_train_markov(&args),
+    }
+}
+
+/// Set the process niceness to `niceness`; child processes such as
+/// hashcat and aircrack-ng inherit it. Failure only warns.
+#[cfg(unix)]
+fn lower_priority(niceness: i32) {
+    // SAFETY: setpriority only reads its integer arguments; 0 is this process
+    let rc = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) };
+    if rc != 0 {
+        eprintln!(
+            "{} Could not lower priority: {}",
+            "WARNING".yellow().bold(),
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn lower_priority(_niceness: i32) {
+    eprintln!(
+        "{} --nice is only supported on Unix; running at normal priority",
+        "WARNING".yellow().bold()
+    );
+}
+
+fn print_banner() {
+    let banner = r#"
+  ╔═══════════════════════════════════════════╗
+  ║       Smart Password Guesser v0.1.0       ║
+  ║     Educational Cybersecurity Research     ║
+  ╚═══════════════════════════════════════════╝
+"#;
+    status!("{}", banner.cyan());
+}
+
+fn cmd_generate(args: &GenerateArgs) -> Result<()> {
+    if args.estimate {
+        return estimate_generate(args);
+    }
+    if let Some(ref rules_path) = args.emit_rules {
+        return emit_rules(args, rules_path);
+    }
+    // Required by clap unless only estimating
+    let Some(ref output) = args.output else {
+        bail!("Provide --output, or --estimate to only count the candidates");
+    };
+    let mut sampler = args.sample.map(|size| {
+        let seed = args.seed.unwrap_or_else(clock_seed);
+        status!(
+            "{} Sampling {} candidates | Seed: {}",
+            ">>".cyan().bold(),
+            size,
+            seed
+        );
+        sample::Sampler::new(size, seed)
+    });
+
+    if let Some(mask) = args.mask.parse()? {
+        status!(
+            "{} Mask: {} | Keyspace: {}",
+            ">>".cyan().bold(),
+            args.mask.mask.as_deref().unwrap_or_default(),
+            mask.keyspace(),
+        );
+        if let Some(ref mut sampler) = sampler {
+            let indices = sampler.indices(mask.keyspace());
+            let candidates = indices.into_iter().filter_map(|i| mask.candidate(i));
+            return write_streamed(output, candidates);
+        }
+        return write_streamed(output, mask.candidates());
+    }
+    if let Some(combinator) = args.combinator.build(args.profile.as_deref())? {
+        status!(
+            "{} Combinator attack | Keyspace: {}",
+            ">>".cyan().bold(),
+            combinator.keyspace(),
+        );
+        if let Some(ref mut sampler) = sampler {
+            let indices = sampler.indices(combinator.keyspace());
+            let candidates = indices.into_iter().filter_map(|i| combinator.candidate(i));
+            return write_streamed(output, candidates);
+        }
+        return write_streamed(output, combinator.candidates());
+    }
+    let prince = args
+        .prince
+        .build(args.profile.as_deref(), args.min_length, args.max_length)?;
+    if let Some(prince) = prince {
+        status!(
+            "{} PRINCE attack | Keyspace: {}",
+            ">>".cyan().bold(),
+            prince.keyspace(),
+        );
+        return write_streamed(output, sampled(&mut sampler, prince.candidates()));
+    }
+    if let Some(ref model) = args.pcfg.pcfg {
+        let grammar = pcfg::Grammar::load(model)?;
+        status!(
+            "{} PCFG attack: {} | {} structures | Keyspace: {}",
+            ">>".cyan().bold(),
+            model.display(),
+            grammar.structures.len(),
+            grammar.keyspace(),
+        );
+        let candidates = grammar.candidates(args.min_length, args.max_length);
+        let limit = args.pcfg.pcfg_limit.unwrap_or(usize::MAX);
+        return write_streamed(output, sampled(&mut sampler, candidates.take(limit)));
+    }
+    if let Some(ref path) = args.markov_model.markov_model {
+        let model = markov::Markov::load(path)?;
+        status!(
+            "{} Markov attack: {} | Top {} candidates",
+            ">>".cyan().bold(),
+            path.display(),
+            args.markov_model.markov_limit,
+        );
+        let candidates = model.candidates(args.min_length, args.max_length);
+        let candidates = candidates.take(args.markov_model.markov_limit);
+        return write_streamed(output, sampled(&mut sampler, candidates));
+    }
+
+    // Required by clap unless an attack without a profile is chosen
+    let Some(ref profile_path) = args.profile else {
+        bail!("Provide --profile or a standalone attack (--mask, --left/--right, --pcfg, --markov-model)");
+    };
+    let profile = profile::Profile::load(profile_path)?;
+
+    // A budget draws on every tier unless told otherwise
+    let depth = args
+        .depth
+        .unwrap_or(if args.max_candidates.is_some() { 3 } else { 2 });
+    let config = generate_config(args, depth);
+
+    status!(
+        "{} Profile: {} | Depth: {} | Length: {}-{}",
+        ">>".cyan().bold(),
+        profile_path.display(),
+        config.depth,
+        config.min_length,
+        config.max_length,
+    );
+
+    print_seeds(&profile);
+
+    if let Some(ref mut sampler) = sampler {
+        let mut total = 0;
+        let stream = generator::candidate_stream(&profile, &config);
+        let mut candidates = sampler.sample(stream.inspect(|_| total += 1));
+        status!(
+            "{} Sampled {} of {} candidates",
+            ">>".cyan().bold(),
+            candidates.len(),
+            total
+        );
+        if args.sort {
+            candidates.sort_unstable();
+        }
+        return write_streamed(output, candidates.into_iter());
+    }
+    if let Some(max) = args.max_candidates {
+        let (mut candidates, total) = if args.order == Order::LikelyFirst {
+            let mut candidates = generator::likely_ordered(&profile, &config);
+            let total = candidates.len();
+            candidates.truncate(max);
+            (candidates, total)
+        } else {
+            generator::within_budget(&profile, &config, max)
+        };
+        if total > candidates.len() {
+            status!(
+                "{} Budget of {}: pruned the {} least likely of {} candidates",
+                ">>".cyan().bold(),
+                max,
+                total - candidates.len(),
+                total
+            );
+        }
+        if args.sort {
+            candidates.sort_unstable();
+        }
+        return write_streamed(output, candidates.into_iter());
+    }
+    if args.order == Order::LikelyFirst {
+        status!(
+            "{} Sorting candidates by estimated likelihood...",
+            ">>".cyan().bold()
+        );
+        let candidates = generator::likely_ordered(&profile, &config);
+        return write_streamed(output, candidates.into_iter());
+    }
+    if args.sort {
+        status!("{} Sorting candidates...", ">>".cyan().bold());
+        let mut stream = generator::candidate_stream(&profile, &config);
+        let mut candidates: Vec<String> = stream.by_ref().collect();
+        candidates.sort_unstable();
+        write_streamed(output, candidates.into_iter())?;
+        print_tier_stats(stream.stats());
+        return Ok(());
+    }
+    if args.append {
+        return append_new(output, &profile, &config);
+    }
+    if args.annotate {
+        let estimator = strength::Estimator::new();
+        let mut stream = generator::candidate_stream(&profile, &config);
+        let lines = std::iter::from_fn(|| {
+            let candidate = stream.next()?;
+            let score = estimator.score(&candidate);
+            Some(format!("{}\t{}\t{}", candidate, score, stream.tier()))
+        });
+        write_streamed(output, lines)?;
+        print_tier_stats(stream.stats());
+        return Ok(());
+    }
+    if !args.step {
+        let mut stream = generator::candidate_stream(&profile, &config);
+        write_streamed(output, stream.by_ref())?;
+        print_tier_stats(stream.stats());
+        return Ok(());
+    }
+    if output == Path::new("-") {
+        bail!("--step is interactive; write to a file with --output instead of '-'");
+    }
+
+    let Some(candidates) = generate_stepwise(&profile, config)? else {
+        status!("{} Aborted, nothing written.", "ABORT".yellow().bold());
+        return Ok(());
+    };
+
+    wordlist::write_wordlist(output, &candidates)?;
+
+    status!(
+        "\n{} Wrote {} candidates to {}",
+        "SUCCESS".green().bold(),
+        candidates.len(),
+        output.display()
+    );
+
+    Ok(())
+}
+
+/// `candidates`, or a `--sample` of them.
+fn sampled<'a>(
+    sampler: &mut Option<sample::Sampler>,
+    candidates: impl Iterator<Item = String> + 'a,
+) -> Box<dyn Iterator<Item = String> + 'a> {
+    match sampler {
+        Some(sampler) => Box::new(sampler.sample(candidates).into_iter()),
+        None => Box::new(candidates),
+    }
+}
+
+/// A `--sample` seed when none is given: the clock's nanoseconds.
+fn clock_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// `generate --append`: add the candidates `output` doesn't have yet to
+/// its end.
+fn append_new(
+    output: &Path,
+    profile: &profile::Profile,
+    config: &generator::GeneratorConfig,
+) -> Result<()> {
+    if output == Path::new("-") {
+        bail!("--append adds to a file; give --output a path instead of '-'");
+    }
+    let mut existing = 0;
+    let mut stream = generator::candidate_stream(profile, config);
+    if output.exists() {
+        let known = wordlist::stream_wordlist(output)?.inspect(|_| existing += 1);
+        stream = stream.excluding(known);
+    }
+    let count = wordlist::append_candidates(output, stream.by_ref())?;
+    status!(
+        "\n{} Appended {} new candidates to {} ({} already there)",
+        "SUCCESS".green().bold(),
+        count,
+        output.display(),
+        existing
+    );
+    print_tier_stats(stream.stats());
+    Ok(())
+}
+
+/// Generator settings for `generate` at `depth`.
+fn generate_config(args: &GenerateArgs, depth: u8) -> generator::GeneratorConfig {
+    let config = if args.order == Order::LikelyFirst || args.max_candidates.is_some() {
+        args.generation
+            .cracking(depth, args.min_length, args.max_length)
+    } else {
+        args.generation
+            .config(depth, args.min_length, args.max_length)
+    };
+    generator::GeneratorConfig {
+        charset: args.charset.clone(),
+        exclude_chars: args.exclude_chars.clone().unwrap_or_default(),
+        ..config
+    }
+}
+
+/// `generate --emit-rules`: the seed words and a hashcat rule set that
+/// expands them, instead of the expanded wordlist.
+fn emit_rules(args: &GenerateArgs, rules_path: &Path) -> Result<()> {
+    // Both required by clap, which rules out the attacks alongside --emit-rules
+    let (Some(ref profile_path), Some(ref base_path)) = (&args.profile, &args.emit_base) else {
+        bail!("--emit-rules needs --profile and --emit-base");
+    };
+    let profile = profile::Profile::load(profile_path)?;
+    let config = generate_config(args, args.depth.unwrap_or(2));
+    let set = hashcat::export(&profile, &config);
+
+    wordlist::write_wordlist(base_path, &set.base)?;
+    wordlist::write_wordlist(rules_path, &set.rules)?;
+    if set.skipped > 0 {
+        status!(
+            "{} {} of the --rules rules have no hashcat equivalent and were left out",
+            "WARNING".yellow().bold(),
+            set.skipped
+        );
+    }
+    status!(
+        "{} Wrote {} base words to {} and {} rules to {}",
+        "SUCCESS".green().bold(),
+        set.base.len(),
+        base_path.display(),
+        set.rules.len(),
+        rules_path.display()
+    );
+    status!(
+        "{} hashcat -a 0 -m <mode> hashes.txt {} -r {}",
+        ">>".cyan().bold(),
+        base_path.display(),
+        rules_path.display()
+    );
+    Ok(())
+}
+
+/// `generate --estimate`: what each tier would add to the wordlist, and the
+/// totals at every depth, without generating it.
+fn estimate_generate(args: &GenerateArgs) -> Result<()> {
+    // Required by clap, which rules out the attacks alongside --estimate
+    let Some(ref profile_path) = args.profile else {
+        bail!("--estimate needs --profile");
+    };
+    let profile = profile::Profile::load(profile_path)?;
+    let depth = args.depth.unwrap_or(2);
+    let config = generate_config(args, depth);
+
+    status!(
+        "{} Profile: {} | Depth: {} | Length: {}-{}",
+        ">>".cyan().bold(),
+        profile_path.display(),
+        config.depth,
+        config.min_length,
+        config.max_length,
+    );
+    print_seeds(&profile);
+
+    println!();
+    println!(
+        "  {:<32} {:>12} {:>12} {:>12}",
+        "Tier".bold(),
+        "Candidates".bold(),
+        "Cumulative".bold(),
+        "Size".bold()
+    );
+    let (mut total, mut bytes) = (0, 0);
+    for tier in estimate::tier_counts(&profile, &config) {
+        total += tier.candidates;
+        bytes += tier.bytes;
+        println!(
+            "  {:<32} {:>12} {:>12} {:>12}",
+            tier.name,
+            tier.candidates,
+            total,
+            estimate::format_size(bytes)
+        );
+    }
+
+    // Every depth, so the trade-off is visible at a glance
+    println!();
+    for d in 1..=3 {
+        let counts = estimate::tier_counts(&profile, &generate_config(args, d));
+        let line = format!(
+            "Depth {}: {} candidates, {}",
+            d,
+            counts.iter().map(|t| t.candidates).sum::<u64>(),
+            estimate::format_size(counts.iter().map(|t| t.bytes).sum())
+        );
+        if d == depth {
+            println!("{} {}", "→".green(), line.bold());
+        } else {
+            println!("  {}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Where the written candidates came from, and what the filters dropped.
+fn print_tier_stats(stats: &[generator::TierStats]) {
+    let row = |name: &str, s: &generator::TierStats| {
+        format!(
+            "  {:<32} {:>10} {:>10} {:>10} {:>10}",
+            name, s.admitted, s.duplicates, s.wrong_length, s.policy_rejected
+        )
+    };
+    status!();
+    status!(
+        "  {:<32} {:>10} {:>10} {:>10} {:>10}",
+        "Tier".bold(),
+        "Written".bold(),
+        "Duplicates".bold(),
+        "Length".bold(),
+        "Policy".bold()
+    );
+    let mut total = generator::TierStats::default();
+    for tier in stats {
+        status!("{}", row(tier.name, tier));
+        total.admitted += tier.admitted;
+        total.duplicates += tier.duplicates;
+        total.wrong_length += tier.wrong_length;
+        total.policy_rejected += tier.policy_rejected;
+    }
+    status!("{}", row("Total", &total).bold());
+}
+
+/// Show the profile's seed words, noting any kept with their original casing.
+fn print_seeds(profile: &profile::Profile) {
+    status!(
+        "{} Seed words: {}",
+        ">>".cyan().bold(),
+        profile.seed_words().join(", ").dimmed()
+    );
+    let cased = profile.cased_seeds();
+    if !cased.is_empty() {
+        status!(
+            "{} Kept original casing (lowercasing would lose it): {}",
+            ">>".cyan().bold(),
+            cased.join(", ").dimmed()
+        );
+    }
+}
+
+/// Stream an attack's candidates straight to the output file.
+fn write_streamed(output: &Path, candidates: impl Iterator<Item = String>) -> Result<()> {
+    let count = wordlist::write_candidates(output, candidates)?;
+    let output = match output.to_str() {
+        Some("-") => "stdout".into(),
+        _ => output.display().to_string(),
+    };
+    status!(
+        "\n{} Wrote {} candidates to {}",
+        "SUCCESS".green().bold(),
+        count,
+        output
+    );
+    Ok(())
+}
+
+/// Run the tiers one at a time, pausing after each for the user to continue,
+/// change depth, stop early, or abort. Returns None on abort.
+fn generate_stepwise(
+    profile: &profile::Profile,
+    mut config: generator::GeneratorConfig,
+) -> Result<Option<Vec<String>>> {
+    use std::io::Write;
+
+    const SAMPLE_SIZE: usize = 8;
+
+    let mut dedup = generator::Dedup::new(&config);
+    let mut candidates = Vec::new();
+    let mut done: Vec<&'static str> = Vec::new();
+
+    'tiers: loop {
+        let Some(tier) = generator::tiers(profile, &config)
+            .into_iter()
+            .find(|t| !done.contains(&t.name()))
+        else {
+            break;
+        };
+        let name = tier.name();
+        done.push(name);
+
+        let before = candidates.len();
+        candidates.extend(tier.admitted(&mut dedup, config.markov));
+        let added = &candidates[before..];
+
+        println!(
+            "\n{} Tier {}: {} — {} new, {} total",
+            ">>".cyan().bold(),
+            done.len(),
+            name.bold(),
+            added.len(),
+            candidates.len()
+        );
+        let stride = (added.len() / SAMPLE_SIZE).max(1);
+        let sample: Vec<&str> = added
+            .iter()
+            .step_by(stride)
+            .take(SAMPLE_SIZE)
+            .map(|s| s.as_str())
+            .collect();
+        if !sample.is_empty() {
+            println!("   sample: {}", sample.join(", ").dimmed());
+        }
+
+        loop {
+            print!("   [c]ontinue, [d]epth <1-3>, [s]top and write, [a]bort > ");
+            std::io::stdout().flush()?;
+            let mut line = String::new();
+            if std::io::stdin().read_line(&mut line)? == 0 {
+                break 'tiers;
+            }
+            let mut parts = line.split_whitespace();
+            match parts.next().unwrap_or("c") {
+                "c" => break,
+                "s" => break 'tiers,
+                "a" => return Ok(None),
+                "d" => match parts.next().and_then(|d| d.parse::<u8>().ok()) {
+                    Some(d @ 1..=3) => {
+                        config.depth = d;
+                        println!("   depth set to {}", d);
+                        break;
+                    }
+                    _ => println!("   depth must be 1, 2 or 3"),
+                },
+                other => println!("   unknown choice '{}'", other),
+            }
+        }
+    }
+
+    Ok(Some(candidates))
+}
+
+fn cmd_estimate(args: &EstimateArgs) -> Result<()> {
+    let target = match args.algo.to_lowercase().as_str() {
+        "wpa" => estimate::Target::Wpa,
+        algo => estimate::Target::Hash(parse_algorithm(algo)?),
+    };
+    let wpa = target == estimate::Target::Wpa;
+    if !args.calibration.is_finite() || args.calibration <= 0.0 {
+        bail!("--calibration must be a positive number of seconds");
+    }
+    let profile = profile::Profile::load(&args.profile)?;
+    let hashes = if args.hash.is_some() || args.hash_file.is_some() {
+        collect_hashes(&args.hash, &args.hash_file)?
+    } else {
+        if target == estimate::Target::Hash(cracker::HashAlgorithm::Bcrypt) {
+            println!(
+                "{} No --hash given: timing bcrypt at cost {}",
+                ">>".cyan().bold(),
+                bcrypt::DEFAULT_COST
+            );
+        }
+        estimate::placeholder_hashes(target)
+    };
+    let min_length = args.min_length.unwrap_or(if wpa { 8 } else { 6 });
+    let max_length = args.max_length.unwrap_or(if wpa { 63 } else { 32 });
+
+    println!(
+        "{} Calibrating {} for {}s...",
+        ">>".cyan().bold(),
+        target,
+        args.calibration
+    );
+    let rate = estimate::calibrate(target, &hashes, Duration::from_secs_f64(args.calibration));
+    println!(
+        "{} Throughput: {}",
+        ">>".cyan().bold(),
+        estimate::format_rate(rate)
+    );
+
+    let config = args.generation.cracking(args.depth, min_length, max_length);
+    println!();
+    println!(
+        "  {:<32} {:>12} {:>12} {:>12}",
+        "Tier".bold(),
+        "Candidates".bold(),
+        "Cumulative".bold(),
+        "ETA".bold()
+    );
+    let mut total = 0;
+    for tier in estimate::tier_counts(&profile, &config) {
+        total += tier.candidates;
+        println!(
+            "  {:<32} {:>12} {:>12} {:>12}",
+            tier.name,
+            tier.candidates,
+            total,
+            estimate::format_duration(total as f64 / rate)
+        );
+    }
+
+    // Every depth, so the trade-off is visible at a glance
+    println!();
+    for depth in 1..=3 {
+        let config = args.generation.cracking(depth, min_length, max_length);
+        let total: u64 = estimate::tier_counts(&profile, &config)
+            .iter()
+            .map(|t| t.candidates)
+            .sum();
+        let line = format!(
+            "Depth {}: {} candidates, worst case {}",
+            depth,
+            total,
+            estimate::format_duration(total as f64 / rate)
+        );
+        if depth == args.depth {
+            println!("{} {}", "→".green(), line.bold());
+        } else {
+            println!("  {}", line);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_crack_hash(args: &CrackHashArgs) -> Result<()> {
+    if let Some(ref path) = args.resume {
+        let session = session::Session::load(path)?;
+        let algo = parse_algorithm(&session.algorithm)?;
+        let targets = session.targets.clone();
+        status!(
+            "{} Resuming session {} at candidate {}",
+            ">>".cyan().bold(),
+            path.display(),
+            session.position
+        );
+        let crack_config = args.budget.crack_config(args.double_check);
+        let follow_up = FollowUp {
+            escalate: args.auto_escalate,
+            loopback: args.loopback.then(|| session.generator_config()),
+        };
+        let output = args.report.output(report::Users::new())?;
+        let profile_path;
+        let wordlist_path = session.wordlist.clone();
+        let source = match (&session.brute, &session.profile, &wordlist_path) {
+            (Some(spec), _, _) => Source::Brute(brute::BruteForce::new(spec)?),
+            (None, None, Some(wordlist)) => Source::Wordlist(wordlist),
+            _ => {
+                profile_path = session.profile_path()?;
+                Source::Profile {
+                    path: &profile_path,
+                    config: Box::new(session.generator_config()),
+                    wordlist: wordlist_path.as_deref(),
+                    cache: None,
+                }
+            }
+        };
+        return crack_hash_list(
+            &targets,
+            algo,
+            &source,
+            &crack_config,
+            args.engine,
+            Some(session),
+            follow_up,
+            output,
+            args.yes,
+        );
+    }
+
+    // Required by clap unless --resume is given
+    let Some(ref algo_str) = args.algo else {
+        bail!("Provide --algo");
+    };
+    let algo = parse_algorithm(algo_str)?;
+
+    let hashes = collect_hashes(&args.hash, &args.hash_file)?;
+    let (hashes, users) = if args.username {
+        report::split_usernames(&hashes)?
+    } else {
+        (hashes, report::Users::new())
+    };
+    let output = args.report.output(users)?;
+
+    let crack_config = args.budget.crack_config(args.double_check);
+    let follow_up = FollowUp {
+        escalate: args.auto_escalate,
+        // Loopback rounds generate with the run's own depth and lengths
+        loopback: args.loopback.then(|| {
+            args.generation
+                .cracking(args.depth, args.min_length, args.max_length)
+        }),
+    };
+
+    if args.brute {
+        let spec = brute::BruteSpec {
+            charset: args.charset.clone(),
+            length: args.length.clone(),
+        };
+        let source = Source::Brute(brute::BruteForce::new(&spec)?);
+        let session = session_path(&args.session, &args.budget)?
+            .map(|path| session::Session::new_brute(&path, spec, algo_str, hashes.clone()));
+        return crack_hash_list(
+            &hashes,
+            algo,
+            &source,
+            &crack_config,
+            args.engine,
+            session,
+            follow_up,
+            output,
+            args.yes,
+        );
+    }
+    let stdin = args.wordlist.as_deref() == Some(Path::new("-"));
+    if stdin && args.session.is_some() {
+        bail!("A wordlist read from stdin can't be resumed; drop --session");
+    }
+    // With a profile too, the list is tried after the generated candidates
+    if let (Some(ref path), None) = (&args.wordlist, &args.profile) {
+        let session_file = if stdin {
+            None
+        } else {
+            session_path(&args.session, &args.budget)?
+        };
+        let session = match session_file {
+            Some(file) => Some(session::Session::new_wordlist(
+                &file,
+                path,
+                algo_str,
+                hashes.clone(),
+            )?),
+            None => None,
+        };
+        let source = Source::Wordlist(path);
+        return crack_hash_list(
+            &hashes,
+            algo,
+            &source,
+            &crack_config,
+            args.engine,
+            session,
+            follow_up,
+            output,
+            args.yes,
+        );
+    }
+    if let Some(mask) = args.mask.parse()? {
+        let source = Source::Mask(mask);
+        return crack_hash_list(
+            &hashes,
+            algo,
+            &source,
+            &crack_config,
+            args.engine,
+            None,
+            follow_up,
+            output,
+            args.yes,
+        );
+    }
+    if let Some(combinator) = args.combinator.build(args.profile.as_deref())? {
+        let source = Source::Combinator(combinator);
+        return crack_hash_list(
+            &hashes,
+            algo,
+            &source,
+            &crack_config,
+            args.engine,
+            None,
+            follow_up,
+            output,
+            args.yes,
+        );
+    }
+    let prince = args
+        .prince
+        .build(args.profile.as_deref(), args.min_length, args.max_length)?;
+    if let Some(prince) = prince {
+        let source = Source::Prince(prince);
+        return crack_hash_list(
+            &hashes,
+            algo,
+            &source,
+            &crack_config,
+            args.engine,
+            None,
+            follow_up,
+            output,
+            args.yes,
+        );
+    }
+
+    // Required by clap unless --resume or an attack without a profile is chosen
+    let Some(ref profile_path) = args.profile else {
+        bail!(
+            "Provide --profile or a standalone attack (--wordlist, --mask, --left/--right, --brute)"
+        );
+    };
+    let config = args
+        .generation
+        .cracking(args.depth, args.min_length, args.max_length);
+    let session_file = if stdin {
+        None
+    } else {
+        session_path(&args.session, &args.budget)?
+    };
+    let session = match session_file {
+        Some(path) => {
+            let session =
+                session::Session::new(&path, profile_path, algo_str, hashes.clone(), &config)?;
+            match args.wordlist {
+                Some(ref wordlist) => Some(session.with_wordlist(wordlist)?),
+                None => Some(session),
+            }
+        }
+        None => None,
+    };
+
+    let source = Source::Profile {
+        path: profile_path,
+        config: Box::new(config),
+        wordlist: args.wordlist.as_deref(),
+        cache: args.cache.as_deref(),
+    };
+    crack_hash_list(
+        &hashes,
+        algo,
+        &source,
+        &crack_config,
+        args.engine,
+        session,
+        follow_up,
+        output,
+        args.yes,
+    )
+}
+
+/// Session file for a run: `--session`, or a default one when a time budget
+/// needs somewhere to leave its checkpoint.
+fn session_path(session: &Option<PathBuf>, budget: &BudgetArgs) -> Result<Option<PathBuf>> {
+    if session.is_some() || budget.max_duration.is_none() {
+        return Ok(session.clone());
+    }
+    let path = PathBuf::from(DEFAULT_SESSION);
+    if path.exists() {
+        bail!(
+            "{} already exists; --resume it, or pass --session to checkpoint elsewhere",
+            DEFAULT_SESSION
+        );
+    }
+    status!(
+        "{} Checkpointing to {}",
+        ">>".cyan().bold(),
+        DEFAULT_SESSION
+    );
+    Ok(Some(path))
+}
 
-    let candidates = generator::generate_candidates(&profile, &config);
+/// Gather hashes from --hash and --hash-file.
+fn collect_hashes(hash: &Option<String>, hash_file: &Option<PathBuf>) -> Result<Vec<String>> {
+    let mut hashes = Vec::new();
+    if let Some(ref h) = hash {
+        hashes.push(h.clone());
+    }
+    if let Some(ref path) = hash_file {
+        let file_hashes = wordlist::read_wordlist(path)?;
+        hashes.extend(file_hashes);
+    }
+    if hashes.is_empty() {
+        bail!("Provide --hash or --hash-file");
+    }
+    Ok(hashes)
+}
+
+fn parse_algorithm(algo_str: &str) -> Result<cracker::HashAlgorithm> {
+    match cracker::HashAlgorithm::from_str(algo_str) {
+        Some(a) => Ok(a),
+        None => bail!(
+            "Unknown algorithm: {}. Supported: md5, sha1, sha256, sha512, bcrypt, ntlm",
+            algo_str
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn crack_hash_list(
+    hashes: &[String],
+    algo: cracker::HashAlgorithm,
+    source: &Source,
+    crack_config: &cracker::CrackConfig,
+    engine: Engine,
+    mut session: Option<session::Session>,
+    follow_up: FollowUp,
+    mut output: Output,
+    yes: bool,
+) -> Result<()> {
+    if follow_up.escalate && !matches!(source, Source::Profile { .. }) {
+        bail!("--auto-escalate needs a profile to generate from");
+    }
+    // Set up the candidate source, skipping what an earlier run already tested
+    let start = session.as_ref().map_or(0, |s| s.position);
+    let mut digests = None;
+    let (keyspace, candidates): (Option<u128>, Candidates) = match source {
+        Source::Profile {
+            path,
+            config,
+            wordlist: None,
+            cache: Some(dir),
+        } => {
+            let profile = print_profile(path, algo, config)?;
+            let cached = cache::DigestCache::open(dir, path, config, algo, || {
+                generator::candidate_stream(&profile, config)
+            })?;
+            let keyspace = u128::from(cached.len());
+            digests = Some(cached);
+            (Some(keyspace), Box::new(std::iter::empty()))
+        }
+        Source::Profile {
+            path,
+            config,
+            wordlist: None,
+            ..
+        } => {
+            let profile = print_profile(path, algo, config)?;
+            let stream = generator::candidate_stream(&profile, config);
+            let (keyspace, candidates) = sized(stream, start);
+            (keyspace.map(u128::from), candidates)
+        }
+        Source::Profile {
+            path,
+            config,
+            wordlist: Some(wordlist),
+            ..
+        } => {
+            let profile = print_profile(path, algo, config)?;
+            with_base_wordlist(&profile, config, wordlist, start)?
+        }
+        Source::Mask(mask) => {
+            status!(
+                "{} Mask attack | Algo: {} | Length: {} | Keyspace: {}",
+                ">>".cyan().bold(),
+                algo,
+                mask.len(),
+                mask.keyspace(),
+            );
+            let candidates = mask.candidates_from(u128::from(start));
+            (Some(mask.keyspace()), Box::new(candidates))
+        }
+        Source::Combinator(combinator) => {
+            status!(
+                "{} Combinator attack | Algo: {} | Keyspace: {}",
+                ">>".cyan().bold(),
+                algo,
+                combinator.keyspace(),
+            );
+            let candidates = combinator.candidates().skip(start as usize);
+            (Some(combinator.keyspace()), Box::new(candidates))
+        }
+        Source::Prince(prince) => {
+            status!(
+                "{} PRINCE attack | Algo: {} | Keyspace: {}",
+                ">>".cyan().bold(),
+                algo,
+                prince.keyspace(),
+            );
+            let candidates = prince.candidates().skip(start as usize);
+            (Some(prince.keyspace()), Box::new(candidates))
+        }
+        Source::Brute(brute) => {
+            status!(
+                "{} Brute force | Algo: {} | Keyspace: {}",
+                ">>".cyan().bold(),
+                algo,
+                brute.keyspace(),
+            );
+            let candidates = brute.candidates_from(u128::from(start));
+            (Some(brute.keyspace()), Box::new(candidates))
+        }
+        Source::Wordlist(path) if *path == Path::new("-") => {
+            status!("{} Wordlist: stdin | Algo: {}", ">>".cyan().bold(), algo);
+            (None, Box::new(wordlist::stream_wordlist(path)?))
+        }
+        Source::Wordlist(path) => {
+            // SAFETY: a wordlist being cracked is input the run owns; one
+            // truncated mid-run would end it with SIGBUS, which is the
+            // documented risk of --wordlist
+            let keyspace = unsafe { wordlist::count_wordlist(path)? };
+            status!(
+                "{} Wordlist: {} | Algo: {} | Keyspace: {}",
+                ">>".cyan().bold(),
+                path.display(),
+                algo,
+                keyspace
+            );
+            // SAFETY: as for the count above
+            let candidates = unsafe { wordlist::map_wordlist(path)? }.skip(start as usize);
+            (Some(u128::from(keyspace)), Box::new(candidates))
+        }
+    };
+
+    // Hashes already cracked in an earlier run of this session
+    let mut results: Vec<cracker::CrackResult> = session
+        .iter()
+        .flat_map(|s| &s.cracked)
+        .map(|c| cracker::CrackResult {
+            hash: c.hash.clone(),
+            plaintext: c.plaintext.clone(),
+            algorithm: algo,
+            found: None,
+        })
+        .collect();
+    let remaining = match session {
+        Some(ref s) => s.remaining_targets(),
+        None => hashes.to_vec(),
+    };
+    // Wordlists read from stdin have no known size up front, and cached
+    // digests take no time to look up
+    let timed = keyspace.filter(|_| !remaining.is_empty() && digests.is_none());
+    if let Some(keyspace) = timed {
+        let left = keyspace.saturating_sub(u128::from(start));
+        if algo == cracker::HashAlgorithm::Bcrypt && engine == Engine::Cpu {
+            bcrypt_plan(&remaining, source, left)?;
+            if !yes && !confirm("Start this run?")? {
+                status!("{} Aborted before cracking", "ABORT".yellow().bold());
+                return Ok(());
+            }
+        } else {
+            preflight(&remaining, algo, left, engine);
+        }
+    }
+
+    let mut tested = 0;
+    if !remaining.is_empty() && engine == Engine::Hashcat {
+        // hashcat has no checkpoint to honor, so the budget just ends its input
+        let candidates: Candidates = match crack_config.deadline {
+            Some(deadline) => Box::new(candidates.take_while(move |_| Instant::now() < deadline)),
+            None => candidates,
+        };
+        let found = cracker::hashcat::crack_hashes(&remaining, algo, candidates)?;
+        for r in found {
+            if crack_config.double_check
+                && !cracker::hash::verify_digest(algo, &r.plaintext, &r.hash)
+            {
+                eprintln!(
+                    "{} Rejected unverifiable hashcat match: {} -> {}",
+                    "WARNING".yellow().bold(),
+                    r.hash,
+                    r.plaintext
+                );
+                continue;
+            }
+            results.push(r);
+        }
+        output.record(&results);
+    } else if let Some(ref cached) = digests {
+        let found = cached.lookup(&remaining);
+        status!(
+            "{} Looked up {} hash(es) among {} cached digests: {} cracked",
+            ">>".cyan().bold(),
+            remaining.len(),
+            cached.len(),
+            found.len()
+        );
+        output.record(&found);
+        results.extend(found);
+    } else if !remaining.is_empty() {
+        // Crack
+        let config = cracker::CrackConfig {
+            expected: keyspace.map(|k| k.saturating_sub(u128::from(start)) as u64),
+            ..*crack_config
+        };
+        let found =
+            cracker::hash::crack_hashes(&remaining, algo, candidates, &config, |n, found| {
+                tested = n;
+                checkpoint(&mut session, start, n, found);
+                output.record(found);
+            })?;
+        // Count indices from the start of the stream, not of this run
+        results.extend(found.into_iter().map(|mut r| {
+            if let Some(ref mut f) = r.found {
+                f.index += start;
+            }
+            r
+        }));
+    }
+    // Results from `start` on came from the named follow-up pass
+    let mut passes: Vec<(usize, String)> = Vec::new();
+    if follow_up.escalate && !crack_config.out_of_time() {
+        for (start, depth) in escalate(
+            hashes,
+            algo,
+            source,
+            crack_config,
+            &mut results,
+            &mut session,
+            &mut output,
+        )? {
+            passes.push((start, format!("Depth {} escalation", depth)));
+        }
+    }
+    let timed_out = crack_config.out_of_time() && results.len() < hashes.len();
+    if let Some(ref config) = follow_up.loopback.filter(|_| !timed_out) {
+        passes.push((results.len(), "Loopback".to_string()));
+        loopback_rounds(
+            hashes,
+            algo,
+            source,
+            config,
+            crack_config,
+            &mut results,
+            &mut session,
+            &mut output,
+        )?;
+    }
+    if let Some(ref mut s) = session {
+        s.save()?;
+    }
+
+    // Summary
+    status!();
+    if timed_out {
+        print_budget_spent(tested, session.as_ref(), "crack-hash");
+    }
+    if results.is_empty() {
+        status!(
+            "{} No hashes cracked. Try increasing --depth or enriching the profile.",
+            "RESULT".yellow().bold()
+        );
+    } else {
+        status!(
+            "{} Cracked {}/{} hash(es):",
+            "RESULT".green().bold(),
+            results.len(),
+            hashes.len()
+        );
+        for r in &results {
+            status!("  {} {}", "→".green(), r);
+        }
+    }
+
+    if output.format == Format::Json {
+        let tiers = source_tiers(source)?;
+        let entries: Vec<report::Entry> = results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| report::Entry {
+                result: r,
+                tier: match passes.iter().rev().find(|&&(start, _)| i >= start) {
+                    Some((_, pass)) => Some(pass),
+                    None => r.found.and_then(|f| tiers.at(f.index)),
+                },
+            })
+            .collect();
+        println!("{}", report::to_json(algo, hashes, &entries));
+    }
+    Ok(())
+}
+
+/// The tiers of a profile source, or the attack as one open-ended stretch.
+fn source_tiers(source: &Source) -> Result<report::Tiers> {
+    let mut tiers = report::Tiers::default();
+    match source {
+        Source::Profile {
+            path,
+            config,
+            wordlist,
+            ..
+        } => {
+            let profile = profile::Profile::load(path)?;
+            for tier in estimate::tier_counts(&profile, config) {
+                tiers.push(tier.name, Some(tier.candidates));
+            }
+            if wordlist.is_some() {
+                tiers.push("Base wordlist", None);
+            }
+        }
+        Source::Mask(_) => tiers.push("Mask", None),
+        Source::Combinator(_) => tiers.push("Combinator", None),
+        Source::Prince(_) => tiers.push("PRINCE", None),
+        Source::Brute(_) => tiers.push("Brute force", None),
+        Source::Wordlist(_) => tiers.push("Wordlist", None),
+    }
+    Ok(tiers)
+}
+
+/// Load the profile and print the run header and its seeds.
+fn print_profile(
+    path: &Path,
+    algo: cracker::HashAlgorithm,
+    config: &generator::GeneratorConfig,
+) -> Result<profile::Profile> {
+    let profile = profile::Profile::load(path)?;
+    status!(
+        "{} Profile: {} | Algo: {} | Depth: {}",
+        ">>".cyan().bold(),
+        path.display(),
+        algo,
+        config.depth,
+    );
+    print_seeds(&profile);
+    Ok(profile)
+}
+
+/// `stream` from `start` on, and how many candidates it has in all. The
+/// stream is generated once and replayed to know that, unless a Bloom
+/// filter dedups and keeps nothing to replay; those runs go unsized.
+fn sized(stream: generator::CandidateStream, start: u64) -> (Option<u64>, Candidates) {
+    match stream.into_replay() {
+        Ok(replay) => (Some(replay.len() as u64), Box::new(replay.skip(start as usize))),
+        Err(stream) => (None, Box::new(stream.skip(start as usize))),
+    }
+}
+
+/// The profile's candidates from `start` on, then the lines of a base
+/// wordlist they didn't already cover, through one dedup. The size is known
+/// unless the list comes from stdin or a Bloom filter dedups.
+fn with_base_wordlist(
+    profile: &profile::Profile,
+    config: &generator::GeneratorConfig,
+    wordlist: &Path,
+    start: u64,
+) -> Result<(Option<u128>, Candidates)> {
+    let stdin = wordlist == Path::new("-");
+    let lines: Candidates = if stdin {
+        Box::new(wordlist::stream_wordlist(wordlist)?)
+    } else {
+        // SAFETY: the base wordlist is read-only input for the run; truncating
+        // it mid-run ends the process with SIGBUS, as the README warns
+        Box::new(unsafe { wordlist::map_wordlist(wordlist)? })
+    };
+    let base = generator::Lines::new("Base wordlist", lines);
+    let stream = generator::candidate_stream(profile, config).then([Box::new(base) as _]);
+    let name = if stdin {
+        "stdin".to_string()
+    } else {
+        wordlist.display().to_string()
+    };
+    let replay = if stdin { Err(Box::new(stream)) } else { stream.into_replay() };
+    Ok(match replay {
+        Ok(replay) => {
+            let new = replay.stats().last().map_or(0, |t| t.admitted) as usize;
+            status!(
+                "{} Base wordlist: {} | {} new after the {} generated",
+                ">>".cyan().bold(),
+                name,
+                new,
+                replay.len() - new
+            );
+            (Some(replay.len() as u128), Box::new(replay.skip(start as usize)))
+        }
+        Err(stream) => {
+            status!(
+                "{} Base wordlist: {} | streamed after the generated candidates",
+                ">>".cyan().bold(),
+                name
+            );
+            (None, Box::new(stream.skip(start as usize)))
+        }
+    })
+}
+
+/// Passes that follow the main one, for the hashes still standing.
+#[derive(Default)]
+struct FollowUp {
+    /// Retry at each higher depth (profile runs only)
+    escalate: bool,
+    /// Loopback rounds, generating with this config
+    loopback: Option<generator::GeneratorConfig>,
+}
+
+/// Retry the hashes still standing at each depth above the run's, up to 3,
+/// skipping candidates an earlier depth already tried. Returns where each
+/// depth's finds start in `results`. Passes run on the CPU engine and stop
+/// early once everything falls or the time budget runs out.
+fn escalate(
+    hashes: &[String],
+    algo: cracker::HashAlgorithm,
+    source: &Source,
+    crack_config: &cracker::CrackConfig,
+    results: &mut Vec<cracker::CrackResult>,
+    session: &mut Option<session::Session>,
+    output: &mut Output,
+) -> Result<Vec<(usize, u8)>> {
+    let Source::Profile { path, config, .. } = source else {
+        return Ok(Vec::new());
+    };
+    let profile = profile::Profile::load(path)?;
+    // Fill the dedup set with what the main pass tried; each depth's tiers
+    // then carry on from the last, skipping everything before them
+    let mut tried = generator::candidate_stream(&profile, config);
+    tried.by_ref().for_each(drop);
+    let mut passes = Vec::new();
+    for depth in config.depth + 1..=3 {
+        let remaining: Vec<String> = hashes
+            .iter()
+            .filter(|h| !results.iter().any(|r| r.hash.eq_ignore_ascii_case(h)))
+            .cloned()
+            .collect();
+        if remaining.is_empty() || crack_config.out_of_time() {
+            break;
+        }
+        status!();
+        status!(
+            "{} Escalating to depth {}: {} hash(es) left",
+            ">>".cyan().bold(),
+            depth,
+            remaining.len()
+        );
+        let config = generator::GeneratorConfig {
+            depth,
+            ..(**config).clone()
+        };
+        tried = tried.then(generator::tiers(&profile, &config));
+        let found =
+            cracker::hash::crack_hashes(&remaining, algo, tried.by_ref(), crack_config, |_, found| {
+                output.record(found)
+            })?;
+        // Keep the position: the main pass's stream hasn't moved
+        let position = session.as_ref().map_or(0, |s| s.position);
+        checkpoint(session, position, 0, &found);
+        passes.push((results.len(), depth));
+        results.extend(found);
+    }
+    Ok(passes)
+}
+
+/// Feed cracked plaintexts back in as seeds and retry the hashes still
+/// standing, until a round finds nothing new. Rounds run on the CPU engine
+/// and skip candidates the profile pass or an earlier round already tested.
+#[allow(clippy::too_many_arguments)]
+fn loopback_rounds(
+    hashes: &[String],
+    algo: cracker::HashAlgorithm,
+    source: &Source,
+    config: &generator::GeneratorConfig,
+    crack_config: &cracker::CrackConfig,
+    results: &mut Vec<cracker::CrackResult>,
+    session: &mut Option<session::Session>,
+    output: &mut Output,
+) -> Result<()> {
+    // Rounds build on the target profile, when there is one, and carry on
+    // from its dedup set filled with what the profile pass tested
+    let (mut profile, mut tested) = match source {
+        Source::Profile { path, config, .. } => {
+            let profile = profile::Profile::load(path)?;
+            let mut tested = generator::candidate_stream(&profile, config);
+            tested.by_ref().for_each(drop);
+            (profile, Some(tested))
+        }
+        _ => (profile::Profile::default(), None),
+    };
+
+    let mut seeds: Vec<String> = results.iter().map(|r| r.plaintext.clone()).collect();
+    let mut round = 1;
+    loop {
+        let remaining: Vec<String> = hashes
+            .iter()
+            .filter(|h| !results.iter().any(|r| r.hash.eq_ignore_ascii_case(h)))
+            .cloned()
+            .collect();
+        if seeds.is_empty() || remaining.is_empty() {
+            return Ok(());
+        }
+        status!();
+        status!(
+            "{} Loopback round {}: {} new seed(s), {} hash(es) left",
+            ">>".cyan().bold(),
+            round,
+            seeds.len(),
+            remaining.len()
+        );
+        loopback::add_plaintexts(&mut profile, &seeds);
+        let mut stream = match tested.take() {
+            Some(stream) => stream.then(generator::tiers(&profile, config)),
+            None => generator::candidate_stream(&profile, config),
+        };
+        let candidates = stream.by_ref();
+        let found =
+            cracker::hash::crack_hashes(&remaining, algo, candidates, crack_config, |_, found| {
+                output.record(found)
+            })?;
+        tested = Some(stream);
+        // Keep the position: the main pass's stream hasn't moved
+        let position = session.as_ref().map_or(0, |s| s.position);
+        checkpoint(session, position, 0, &found);
+        seeds = found.iter().map(|r| r.plaintext.clone()).collect();
+        results.extend(found);
+        round += 1;
+    }
+}
+
+/// Say that `--max-duration` ended the run, and how to pick it up again.
+fn print_budget_spent(tested: u64, session: Option<&session::Session>, command: &str) {
+    status!(
+        "{} Time budget reached after {} candidates; results below are partial",
+        "STOPPED".yellow().bold(),
+        tested
+    );
+    if let Some(s) = session {
+        status!(
+            "{} Continue with: password-guesser {} --resume {}",
+            ">>".cyan().bold(),
+            command,
+            s.path().display()
+        );
+    }
+}
+
+/// Print what a bcrypt run will cost: the verify rate against every target,
+/// measured on the cheapest and scaled by cost, and the time for `left`
+/// candidates. Profile runs also get the other depths for comparison.
+fn bcrypt_plan(hashes: &[String], source: &Source, left: u128) -> Result<()> {
+    // bcrypt is slow, so give the measurement a little longer
+    const CALIBRATION: Duration = Duration::from_secs(1);
+
+    let mut costs: Vec<u32> = hashes
+        .iter()
+        .filter_map(|h| estimate::bcrypt_cost(h))
+        .collect();
+    costs.sort_unstable();
+    costs.dedup();
+    let costs: Vec<String> = costs.iter().map(u32::to_string).collect();
+    let per_candidate = estimate::bcrypt_seconds_per_candidate(hashes, CALIBRATION);
+    status!(
+        "{} bcrypt attack plan: {} hash(es), cost {} | ~{}",
+        ">>".cyan().bold(),
+        hashes.len(),
+        if costs.is_empty() {
+            "?".to_string()
+        } else {
+            costs.join(", ")
+        },
+        estimate::format_rate(1.0 / per_candidate)
+    );
+
+    let line = |candidates: u128| {
+        format!(
+            "{} candidates, worst case {}",
+            candidates,
+            estimate::format_duration(candidates as f64 * per_candidate)
+        )
+    };
+    let Source::Profile {
+        path,
+        config,
+        wordlist: None,
+        ..
+    } = source
+    else {
+        status!("{} {}", "→".green(), line(left).bold());
+        return Ok(());
+    };
+    let profile = profile::Profile::load(path)?;
+    for depth in 1..=3 {
+        if depth == config.depth {
+            let text = format!("Depth {}: {}", depth, line(left));
+            status!("{} {}", "→".green(), text.bold());
+        } else {
+            let config = generator::GeneratorConfig {
+                depth,
+                ..(**config).clone()
+            };
+            let total: u64 = estimate::tier_counts(&profile, &config)
+                .iter()
+                .map(|t| t.candidates)
+                .sum();
+            status!("  Depth {}: {}", depth, line(u128::from(total)));
+        }
+    }
+    Ok(())
+}
+
+/// Ask a yes/no question, defaulting to no. A closed stdin counts as no.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::Write;
+
+    let prompt = format!("{} {} [y/N] ", "?".yellow().bold(), question);
+    if console::stdout_is_data() {
+        eprint!("{}", prompt);
+    } else {
+        print!("{}", prompt);
+        std::io::stdout().flush()?;
+    }
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer)? == 0 {
+        status!();
+        status!("No answer on stdin; pass --yes to start without confirming");
+        return Ok(false);
+    }
+    let answer = answer.trim();
+    Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
+/// Print how many candidates are left and, for the CPU engine, how long
+/// they take at a briefly measured rate.
+fn preflight(hashes: &[String], algo: cracker::HashAlgorithm, left: u128, engine: Engine) {
+    // Short enough not to delay the run noticeably
+    const CALIBRATION: Duration = Duration::from_millis(500);
+
+    if engine == Engine::Hashcat {
+        status!(
+            "{} Pre-flight: {} candidates to test",
+            ">>".cyan().bold(),
+            left
+        );
+        return;
+    }
+    let rate = estimate::calibrate(estimate::Target::Hash(algo), hashes, CALIBRATION);
+    status!(
+        "{} Pre-flight: {} candidates to test at ~{}, worst case {}",
+        ">>".cyan().bold(),
+        left,
+        estimate::format_rate(rate),
+        estimate::format_duration(left as f64 / rate)
+    );
+}
+
+/// Record progress in the session, if any; a failed save only warns.
+fn checkpoint(
+    session: &mut Option<session::Session>,
+    start: u64,
+    tested: u64,
+    found: &[cracker::CrackResult],
+) {
+    if let Some(s) = session {
+        if let Err(e) = s.checkpoint(start, tested, found) {
+            eprintln!("{} {:#}", "WARNING".yellow().bold(), e);
+        }
+    }
+}
+
+fn cmd_serve(args: &ServeArgs) -> Result<()> {
+    if args.token.trim().is_empty() {
+        bail!("--token must not be empty");
+    }
+    let algo = parse_algorithm(&args.algo)?;
+    let hashes = collect_hashes(&args.hash, &args.hash_file)?;
+    let profile = profile::Profile::load(&args.profile)?;
+    let config = args
+        .generation
+        .cracking(args.depth, args.min_length, args.max_length);
+
+    let metrics = args.metrics.start()?;
+    let candidates = Box::new(generator::candidate_stream(&profile, &config));
+    let results = cracker::distributed::serve(
+        &args.bind,
+        &args.token,
+        &hashes,
+        algo,
+        candidates,
+        args.chunk_size.max(1),
+        &metrics,
+    )?;
+
+    println!();
+    println!(
+        "{} Cracked {}/{} hash(es):",
+        "RESULT".green().bold(),
+        results.len(),
+        hashes.len()
+    );
+    for r in &results {
+        println!("  {} {}", "→".green(), r);
+    }
+    Ok(())
+}
+
+fn cmd_explain(args: &ExplainArgs) -> Result<()> {
+    let profile = profile::Profile::load(&args.profile)?;
+    let passwords: Vec<String> = wordlist::stream_wordlist(&args.passwords)?
+        .quiet()
+        .collect();
+    if passwords.is_empty() {
+        bail!("No passwords in {}", args.passwords.display());
+    }
+    let config = args
+        .generation
+        .config(args.depth, args.min_length, args.max_length);
+    status!(
+        "{} Explaining {} passwords against the depth {} candidates of {}...",
+        ">>".cyan().bold(),
+        passwords.len(),
+        args.depth,
+        args.profile.display()
+    );
+
+    let explained = explain::explain(&profile, &config, &passwords);
+    println!();
+    println!(
+        "  {:<24} {:>10}  {:<28} {:<28} {}",
+        "Password".bold(),
+        "Candidate".bold(),
+        "Tier".bold(),
+        "Fields".bold(),
+        "Rules".bold()
+    );
+    for explanation in &explained {
+        match explanation.verdict {
+            explain::Verdict::Generated {
+                position,
+                tier,
+                ref fields,
+                ref rules,
+            } => println!(
+                "  {:<24} {:>10}  {:<28} {:<28} {}",
+                explanation.password,
+                position,
+                tier,
+                if fields.is_empty() {
+                    "-".to_string()
+                } else {
+                    fields.join(",")
+                },
+                rules.join(" > ")
+            ),
+            ref missed => {
+                let why = match missed {
+                    explain::Verdict::WrongLength => format!(
+                        "outside --min-length {} / --max-length {}",
+                        args.min_length, args.max_length
+                    ),
+                    explain::Verdict::Rejected => {
+                        "rejected by --policy, --charset or --exclude-chars".to_string()
+                    }
+                    _ => "not generated by any tier".to_string(),
+                };
+                println!("  {:<24} {:>10}  {}", explanation.password, "-", why.red());
+            }
+        }
+    }
+
+    let covered: usize = explain::by_tier(&explained).iter().map(|(_, n)| n).sum();
+    println!();
+    println!(
+        "{} Covered {} of {} passwords ({:.1}%)",
+        ">>".cyan().bold(),
+        covered,
+        explained.len(),
+        100.0 * covered as f64 / explained.len() as f64
+    );
+    for (tier, count) in explain::by_tier(&explained) {
+        println!("  {:<32} {:>6}", tier, count);
+    }
+    Ok(())
+}
+
+fn cmd_train(args: &TrainArgs) -> Result<()> {
+    status!(
+        "{} Training PCFG on {}",
+        ">>".cyan().bold(),
+        args.corpus.display()
+    );
+    let grammar = pcfg::Grammar::train(wordlist::stream_wordlist(&args.corpus)?);
+    if grammar.passwords == 0 {
+        bail!("No passwords in {}", args.corpus.display());
+    }
+    grammar.save(&args.output)?;
+    let terminals: usize = grammar.terminals.values().map(|t| t.len()).sum();
+    status!(
+        "\n{} Learned {} structures and {} terminals from {} passwords; wrote {}",
+        "SUCCESS".green().bold(),
+        grammar.structures.len(),
+        terminals,
+        grammar.passwords,
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn cmd_train_markov(args: &TrainArgs) -> Result<()> {
+    status!(
+        "{} Training Markov model on {}",
+        ">>".cyan().bold(),
+        args.corpus.display()
+    );
+    let mut model = markov::Markov::default();
+    for password in wordlist::stream_wordlist(&args.corpus)? {
+        model.add(&password);
+    }
+    if model.passwords() == 0 {
+        bail!("No passwords in {}", args.corpus.display());
+    }
+    model.save(&args.output)?;
+    status!(
+        "\n{} Learned {} contexts from {} passwords; wrote {}",
+        "SUCCESS".green().bold(),
+        model.contexts(),
+        model.passwords(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn cmd_verify(args: &VerifyArgs) -> Result<()> {
+    let (hash, salt) = match (&args.salt, args.hash.split_once(':')) {
+        (Some(salt), _) => (args.hash.as_str(), Some(salt.as_str())),
+        // bcrypt hashes never contain ':', so this is hashcat's hash:salt
+        (None, Some((hash, salt))) => (hash, Some(salt)),
+        (None, None) => (args.hash.as_str(), None),
+    };
+    let algo = match args.algo {
+        Some(ref algo) => parse_algorithm(algo)?,
+        None => {
+            let Some(algo) = cracker::detect::guess_algorithm(hash) else {
+                bail!("Could not tell the algorithm from the hash; pass --algo");
+            };
+            println!(
+                "{} Algorithm guessed from the hash: {}",
+                ">>".cyan().bold(),
+                algo
+            );
+            algo
+        }
+    };
+
+    let salted = salt.map(|s| (s, args.salt_position.into()));
+    let described = match salted {
+        None => format!("{}({:?})", algo, args.plaintext),
+        Some((salt, cracker::hash::SaltPosition::Append)) => {
+            format!("{}({:?} . {:?})", algo, args.plaintext, salt)
+        }
+        Some((salt, cracker::hash::SaltPosition::Prepend)) => {
+            format!("{}({:?} . {:?})", algo, salt, args.plaintext)
+        }
+    };
+    if !cracker::hash::verify(algo, hash, &args.plaintext, salted)? {
+        bail!("MISMATCH: {} does not produce {}", described, hash);
+    }
+    println!("{} {} = {}", "VERIFIED".green().bold(), described, hash);
+    Ok(())
+}
+
+fn cmd_crack(args: &CrackArgs) -> Result<()> {
+    let algo = args.algo.as_deref().map(parse_algorithm).transpose()?;
+
+    match cracker::detect::detect(&args.target, algo)? {
+        cracker::detect::Target::Hashes { hashes, algorithm } => {
+            println!(
+                "{} Detected {} {} hash(es)",
+                ">>".cyan().bold(),
+                hashes.len(),
+                algorithm
+            );
+            let config = args.generation.cracking(
+                args.depth,
+                args.min_length.unwrap_or(6),
+                args.max_length.unwrap_or(32),
+            );
+            let crack_config = cracker::CrackConfig {
+                double_check: args.double_check,
+                ..Default::default()
+            };
+            let follow_up = FollowUp {
+                escalate: false,
+                loopback: args.loopback.then(|| config.clone()),
+            };
+            let source = Source::Profile {
+                path: &args.profile,
+                config: Box::new(config),
+                wordlist: None,
+                cache: None,
+            };
+            crack_hash_list(
+                &hashes,
+                algorithm,
+                &source,
+                &crack_config,
+                Engine::Cpu,
+                None,
+                follow_up,
+                Output::default(),
+                args.yes,
+            )
+        }
+        cracker::detect::Target::Handshake(path) => {
+            println!("{} Detected WiFi handshake capture", ">>".cyan().bold());
+            let native = !args.use_hashcat && path.extension().is_some_and(|e| e == "hccapx");
+            let config = args.generation.cracking(
+                args.depth,
+                args.min_length.unwrap_or(8),
+                args.max_length.unwrap_or(63),
+            );
+            let crack_config = cracker::CrackConfig::default();
+            crack_wifi(
+                &path,
+                &args.profile,
+                args.use_hashcat,
+                native,
+                &config,
+                &crack_config,
+                None,
+            )
+        }
+        cracker::detect::Target::Unsupported(kind) => {
+            bail!("Detected a {}, which is not supported yet", kind)
+        }
+    }
+}
+
+fn cmd_crack_batch(args: &CrackBatchArgs) -> Result<()> {
+    let manifest = batch::Manifest::load(&args.manifest)?;
+    let jobs = &manifest.jobs;
+    let parallel = usize::from(args.parallel).min(jobs.len());
+    status!(
+        "{} Batch: {} job(s) from {}, {} at a time",
+        ">>".cyan().bold(),
+        jobs.len(),
+        args.manifest.display(),
+        parallel
+    );
+    let metrics = args.metrics.start()?;
+    metrics.set_jobs(jobs.len() as u64);
+
+    // A failed job is reported with the rest instead of ending the batch
+    let run = |i: usize| {
+        let job = &jobs[i];
+        let quiet = parallel > 1;
+        if !quiet {
+            status!();
+            status!(
+                "{} Job {}/{}: {} ({}, {}, depth {})",
+                ">>".cyan().bold(),
+                i + 1,
+                jobs.len(),
+                job.name(),
+                job.profile.display(),
+                job.hash_file.display(),
+                job.depth
+            );
+        }
+        let outcome = batch::run(job, quiet, &metrics).map_err(|e| format!("{:#}", e));
+        metrics.job_finished();
+        match outcome {
+            Ok(ref o) if quiet => status!(
+                "  {} {}: {}/{} cracked, {} tested in {}",
+                "✓".green(),
+                job.name(),
+                o.results.len(),
+                o.targets.len(),
+                o.tested,
+                estimate::format_duration(o.elapsed.as_secs_f64())
+            ),
+            Err(ref e) => eprintln!("{} {}: {}", "ERROR".red().bold(), job.name(), e),
+            Ok(_) => {}
+        }
+        (job.name(), outcome)
+    };
+    let outcomes: Vec<(String, Result<batch::Outcome, String>)> = if parallel == 1 {
+        (0..jobs.len()).map(run).collect()
+    } else {
+        // Workers take the next job in manifest order until none are left
+        let next = AtomicUsize::new(0);
+        let finished = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for _ in 0..parallel {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= jobs.len() {
+                        break;
+                    }
+                    let outcome = run(i);
+                    finished.lock().unwrap().push((i, outcome));
+                });
+            }
+        });
+        let mut finished = finished.into_inner().unwrap();
+        finished.sort_by_key(|&(i, _)| i);
+        finished.into_iter().map(|(_, outcome)| outcome).collect()
+    };
+
+    // Summary
+    status!();
+    status!("{} Batch summary:", "RESULT".green().bold());
+    status!(
+        "  {:<20} {:<8} {:>9} {:>12} {:>10}",
+        "Job",
+        "Algo",
+        "Cracked",
+        "Tested",
+        "Time"
+    );
+    let (mut cracked, mut targets) = (0, 0);
+    for (name, outcome) in &outcomes {
+        match outcome {
+            Ok(o) => {
+                cracked += o.results.len();
+                targets += o.targets.len();
+                status!(
+                    "  {:<20} {:<8} {:>9} {:>12} {:>10}",
+                    name,
+                    o.algorithm.to_string(),
+                    format!("{}/{}", o.results.len(), o.targets.len()),
+                    o.tested,
+                    estimate::format_duration(o.elapsed.as_secs_f64())
+                );
+            }
+            Err(e) => status!("  {:<20} {} {}", name, "failed:".red(), e),
+        }
+    }
+    for (name, outcome) in &outcomes {
+        for r in outcome.iter().flat_map(|o| &o.results) {
+            status!("  {} {}: {}", "→".green(), name, r);
+        }
+    }
+    let failed = outcomes.iter().filter(|(_, o)| o.is_err()).count();
+    status!(
+        "{} Cracked {}/{} hash(es) across {} job(s){}",
+        "RESULT".green().bold(),
+        cracked,
+        targets,
+        jobs.len(),
+        if failed > 0 {
+            format!(", {} failed", failed)
+        } else {
+            String::new()
+        }
+    );
+
+    if args.format == Format::Json {
+        println!("{}", report::batch_to_json(&outcomes));
+    }
+    Ok(())
+}
+
+fn cmd_crack_wifi(args: &CrackWifiArgs) -> Result<()> {
+    if let Some(ref path) = args.resume {
+        let session = session::Session::load(path)?;
+        if session.algorithm != "wpa" {
+            bail!("{} is not a WiFi session", path.display());
+        }
+        let handshake = PathBuf::from(&session.targets[0]);
+        let profile_path = session.profile_path()?;
+        let config = session.generator_config();
+        println!(
+            "{} Resuming session {} at candidate {}",
+            ">>".cyan().bold(),
+            path.display(),
+            session.position
+        );
+        let crack_config = args.budget.crack_config(false);
+        return crack_wifi(
+            &handshake,
+            &profile_path,
+            false,
+            true,
+            &config,
+            &crack_config,
+            Some(session),
+        );
+    }
+
+    // Both are required by clap unless --resume is given
+    let (Some(handshake), Some(profile_path)) = (&args.handshake, &args.profile) else {
+        bail!("Provide --handshake and --profile");
+    };
+    let config = args
+        .generation
+        .cracking(args.depth, args.min_length, args.max_length);
+    let budgeted = args.budget.max_duration.is_some() || args.budget.status_interval.is_some();
+    if budgeted && !args.native {
+        bail!("--max-duration and --status-interval need --native");
+    }
+    let session = match session_path(&args.session, &args.budget)? {
+        Some(path) => Some(session::Session::new(
+            &path,
+            profile_path,
+            "wpa",
+            vec![handshake.display().to_string()],
+            &config,
+        )?),
+        None => None,
+    };
+
+    let crack_config = args.budget.crack_config(false);
+    crack_wifi(
+        handshake,
+        profile_path,
+        args.use_hashcat,
+        args.native,
+        &config,
+        &crack_config,
+        session,
+    )
+}
+
+fn crack_wifi(
+    handshake: &Path,
+    profile_path: &Path,
+    use_hashcat: bool,
+    native: bool,
+    config: &generator::GeneratorConfig,
+    crack_config: &cracker::CrackConfig,
+    mut session: Option<session::Session>,
+) -> Result<()> {
+    let profile = profile::Profile::load(profile_path)?;
+
+    println!(
+        "{} Profile: {} | Depth: {} | Tool: {}",
+        ">>".cyan().bold(),
+        profile_path.display(),
+        config.depth,
+        if native {
+            "native"
+        } else if use_hashcat {
+            "hashcat"
+        } else {
+            "aircrack-ng"
+        },
+    );
+
+    let candidates = generator::candidate_stream(&profile, config);
+
+    if native {
+        if let Some(c) = session.as_ref().and_then(|s| s.cracked.first()) {
+            println!("{} WiFi key cracked: {}", "SUCCESS".green().bold(), c.plaintext);
+            return Ok(());
+        }
+        let start = session.as_ref().map_or(0, |s| s.position);
+        let (keyspace, candidates) = sized(candidates, start);
+        let crack_config = cracker::CrackConfig {
+            expected: keyspace.map(|k| k.saturating_sub(start)),
+            ..*crack_config
+        };
+
+        let mut tested = 0;
+        let key = cracker::wpa::crack_hccapx(handshake, candidates, &crack_config, |n| {
+            tested = n;
+            checkpoint(&mut session, start, n, &[])
+        })?;
+        if let Some(ref mut s) = session {
+            if let Some(ref key) = key {
+                s.cracked.push(session::CrackedEntry {
+                    hash: handshake.display().to_string(),
+                    plaintext: key.clone(),
+                });
+            }
+            s.save()?;
+        }
+
+        if key.is_none() && crack_config.out_of_time() {
+            print_budget_spent(tested, session.as_ref(), "crack-wifi");
+            return Ok(());
+        }
+        match key {
+            Some(key) => println!("{} WiFi key cracked: {}", "SUCCESS".green().bold(), key),
+            None => println!(
+                "{} Key not found. Try increasing --depth or adding more profile data.",
+                "FAILED".red().bold()
+            ),
+        }
+        return Ok(());
+    }
 
     // Write to temp file
     let tmp_dir = std::env::temp_dir();
     let wordlist_path = tmp_dir.join("password_guesser_wordlist.txt");
-    wordlist::write_wordlist(&wordlist_path, &candidates)?;
+    let count = wordlist::write_candidates(&wordlist_path, candidates)?;
 
     println!(
         "{} Wordlist written to {} ({} candidates)",
         ">>".cyan().bold(),
         wordlist_path.display(),
-        candidates.len()
+        count
     );
 
     if use_hashcat {
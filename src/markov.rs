@@ -0,0 +1,348 @@
+//! Character-level Markov model for ranking candidates by likelihood.
+//!
+//! The model is trained once on the embedded common-password corpus: every
+//! character is predicted from the two before it, with add-one smoothing so
+//! unseen transitions stay possible but expensive. A candidate's score is the
+//! log-probability of its characters plus the end of the string, so
+//! `password1` outranks `p4Ssw0rd!` and tiers can be tried most-likely first.
+//!
+//! A model can also be trained on any corpus, saved to a file, and walked
+//! to generate the strings it rates most likely, without a profile.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::common;
+
+/// Characters of context used to predict the next one.
+const ORDER: usize = 2;
+
+/// Pads the context before the first character.
+const START: char = '\u{2}';
+/// Marks the end of a password, so length is part of the likelihood.
+const END: char = '\u{3}';
+
+/// Outcomes the smoothing spreads probability over: printable ASCII plus END.
+const VOCABULARY: f64 = 96.0;
+
+/// Transition counts out of one context.
+#[derive(Debug, Default)]
+struct Transitions {
+    total: u32,
+    next: HashMap<char, u32>,
+}
+
+/// A trained character model.
+#[derive(Debug, Default)]
+pub struct Markov {
+    contexts: HashMap<[char; ORDER], Transitions>,
+}
+
+/// A model as saved in a file: for each context (the characters before,
+/// START-padded), how often each character followed it.
+#[derive(Serialize, Deserialize)]
+struct ModelFile {
+    order: usize,
+    contexts: BTreeMap<String, BTreeMap<String, u32>>,
+}
+
+impl Markov {
+    /// Train on a list of passwords.
+    pub fn train<'a>(passwords: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut model = Self::default();
+        for password in passwords {
+            model.add(password);
+        }
+        model
+    }
+
+    /// Count the transitions of one more password.
+    pub fn add(&mut self, password: &str) {
+        if password.is_empty() {
+            return;
+        }
+        let mut context = [START; ORDER];
+        for c in password.chars().chain(std::iter::once(END)) {
+            let t = self.contexts.entry(context).or_default();
+            t.total += 1;
+            *t.next.entry(c).or_default() += 1;
+            context.rotate_left(1);
+            context[ORDER - 1] = c;
+        }
+    }
+
+    /// Passwords trained on.
+    pub fn passwords(&self) -> u32 {
+        self.contexts.get(&[START; ORDER]).map_or(0, |t| t.total)
+    }
+
+    /// Contexts seen in training.
+    pub fn contexts(&self) -> usize {
+        self.contexts.len()
+    }
+
+    /// Read a model written by [`save`](Self::save).
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read Markov model: {}", path.display()))?;
+        let file: ModelFile =
+            toml::from_str(&content).with_context(|| "Failed to parse Markov model TOML")?;
+        if file.order != ORDER {
+            bail!(
+                "Markov model has order {}; only order {} is supported",
+                file.order,
+                ORDER
+            );
+        }
+        let mut model = Self::default();
+        for (context, next) in file.contexts {
+            let Ok(key) = <[char; ORDER]>::try_from(context.chars().collect::<Vec<_>>()) else {
+                bail!(
+                    "Markov model context {:?} is not {} characters",
+                    context,
+                    ORDER
+                );
+            };
+            let mut transitions = Transitions::default();
+            for (c, count) in next {
+                let mut chars = c.chars();
+                let (Some(c), None) = (chars.next(), chars.next()) else {
+                    bail!("Markov model transition {:?} is not one character", c);
+                };
+                transitions.total += count;
+                transitions.next.insert(c, count);
+            }
+            model.contexts.insert(key, transitions);
+        }
+        Ok(model)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contexts = self
+            .contexts
+            .iter()
+            .map(|(context, t)| {
+                let next = t.next.iter().map(|(c, &n)| (c.to_string(), n)).collect();
+                (context.iter().collect(), next)
+            })
+            .collect();
+        let file = ModelFile {
+            order: ORDER,
+            contexts,
+        };
+        let content = toml::to_string(&file).context("Failed to serialize Markov model")?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write Markov model: {}", path.display()))
+    }
+
+    /// The model trained on the embedded common passwords, built on first use.
+    pub fn common() -> &'static Self {
+        static MODEL: OnceLock<Markov> = OnceLock::new();
+        MODEL.get_or_init(|| Self::train(common::COMMON_PASSWORDS.lines().map(str::trim)))
+    }
+
+    /// Log-probability of `password` (always negative; higher is more likely).
+    pub fn score(&self, password: &str) -> f64 {
+        let mut score = 0.0;
+        let mut context = [START; ORDER];
+        for c in password.chars().chain(std::iter::once(END)) {
+            score += self.transition(&context, c);
+            context.rotate_left(1);
+            context[ORDER - 1] = c;
+        }
+        score
+    }
+
+    /// Log-probability of `c` following `context`, smoothed as in
+    /// [`score`](Self::score).
+    fn transition(&self, context: &[char; ORDER], c: char) -> f64 {
+        let (seen, total) = match self.contexts.get(context) {
+            Some(t) => (t.next.get(&c).copied().unwrap_or(0), t.total),
+            None => (0, 0),
+        };
+        ((seen as f64 + 1.0) / (total as f64 + VOCABULARY)).ln()
+    }
+
+    /// Strings of `min_length..=max_length` bytes, most likely first by
+    /// [`score`](Self::score). Only transitions seen in training are
+    /// followed, so every string is built from the corpus's own habits.
+    ///
+    /// Each character lowers a string's score, so a best-first search over
+    /// prefixes finishes strings in score order; the frontier grows with
+    /// the number of strings taken, so take only as many as needed.
+    pub fn candidates(&self, min_length: usize, max_length: usize) -> MarkovIter<'_> {
+        let mut frontier = BinaryHeap::new();
+        frontier.push(Prefix {
+            score: 0.0,
+            text: String::new(),
+            context: [START; ORDER],
+            done: false,
+        });
+        MarkovIter {
+            model: self,
+            frontier,
+            min_length,
+            max_length,
+        }
+    }
+
+    /// Reorder `items` most likely first; ties keep their original order.
+    pub fn rank<T>(&self, items: Vec<T>, text: impl Fn(&T) -> &str) -> Vec<T> {
+        let mut scored: Vec<(f64, T)> = items
+            .into_iter()
+            .map(|item| (self.score(text(&item)), item))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+/// Iterator returned by [`Markov::candidates`].
+pub struct MarkovIter<'a> {
+    model: &'a Markov,
+    frontier: BinaryHeap<Prefix>,
+    min_length: usize,
+    max_length: usize,
+}
+
+impl Iterator for MarkovIter<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some(prefix) = self.frontier.pop() {
+            if prefix.done {
+                return Some(prefix.text);
+            }
+            let Some(t) = self.model.contexts.get(&prefix.context) else {
+                continue;
+            };
+            for &c in t.next.keys() {
+                let score = prefix.score + self.model.transition(&prefix.context, c);
+                if c == END {
+                    if prefix.text.len() >= self.min_length {
+                        self.frontier.push(Prefix {
+                            score,
+                            text: prefix.text.clone(),
+                            context: prefix.context,
+                            done: true,
+                        });
+                    }
+                } else if prefix.text.len() + c.len_utf8() <= self.max_length {
+                    let mut context = prefix.context;
+                    context.rotate_left(1);
+                    context[ORDER - 1] = c;
+                    let mut text = prefix.text.clone();
+                    text.push(c);
+                    self.frontier.push(Prefix {
+                        score,
+                        text,
+                        context,
+                        done: false,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A string being built, or a finished one, with its log-probability so far.
+struct Prefix {
+    score: f64,
+    text: String,
+    context: [char; ORDER],
+    done: bool,
+}
+
+// BinaryHeap is a max-heap, and the most likely prefix must come out first.
+// Ties go to finished strings, then to the shorter and the lexically
+// smaller text, keeping output stable.
+impl Ord for Prefix {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| self.done.cmp(&other.done))
+            .then_with(|| other.text.len().cmp(&self.text.len()))
+            .then_with(|| other.text.cmp(&self.text))
+    }
+}
+
+impl PartialOrd for Prefix {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Prefix {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Prefix {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_patterns_score_higher() {
+        let model = Markov::common();
+        assert!(model.score("password1") > model.score("p4Ssw0rd!"));
+        assert!(model.score("monkey12") > model.score("mqnkxz12"));
+        assert!(model.score("abc") < 0.0);
+    }
+
+    #[test]
+    fn test_candidates_most_likely_first() {
+        let mut corpus = vec!["abc1"; 20];
+        corpus.extend(["abc2", "abd1", "xyz"]);
+        let model = Markov::train(corpus);
+        let all: Vec<String> = model.candidates(0, 8).collect();
+        assert_eq!(all[0], "abc1");
+        assert!(all.contains(&"xyz".to_string()));
+        assert!(all
+            .windows(2)
+            .all(|w| model.score(&w[0]) >= model.score(&w[1])));
+        let unique: std::collections::HashSet<&String> = all.iter().collect();
+        assert_eq!(unique.len(), all.len());
+
+        let four: Vec<String> = model.candidates(4, 4).collect();
+        assert!(!four.is_empty() && four.iter().all(|c| c.len() == 4));
+        assert!(!four.contains(&"xyz".to_string()));
+        assert_eq!(model.candidates(9, 16).count(), 0);
+
+        // Strings the corpus never had, joined where the contexts overlap
+        let mut all: Vec<String> = Markov::train(["abcd", "xbcy"]).candidates(0, 8).collect();
+        all.sort();
+        assert_eq!(all, ["abcd", "abcy", "xbcd", "xbcy"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("pg-markov-{}.toml", std::process::id()));
+        let model = Markov::train(["monkey12", "José!", "a\"b"]);
+        model.save(&path).unwrap();
+        let loaded = Markov::load(&path).unwrap();
+        assert_eq!(loaded.passwords(), 3);
+        for word in ["monkey12", "José!", "a\"b", "zebra"] {
+            assert_eq!(loaded.score(word), model.score(word));
+        }
+
+        std::fs::write(&path, "order = 3\n[contexts]\n").unwrap();
+        assert!(Markov::load(&path).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_rank_is_stable() {
+        let model = Markov::train(["aaaa", "aaab"]);
+        let ranked = model.rank(vec!["zzzz", "aaaa", "yyyy"], |s| s);
+        assert_eq!(ranked, ["aaaa", "zzzz", "yyyy"]);
+    }
+}
@@ -0,0 +1,215 @@
+//! hashcat-style mask attack: `?u?l?l?l?d?d?d?d` and friends.
+//!
+//! Built-in charsets: `?l` a-z, `?u` A-Z, `?d` 0-9, `?h` 0-9a-f, `?H` 0-9A-F,
+//! `?s` printable symbols (including space), `?a` = `?l?u?d?s`, `??` a literal
+//! question mark. `?1`-`?4` refer to custom charsets, which may themselves
+//! use the built-ins (`-1 ?l?d`). Any other character is a literal.
+
+use anyhow::{bail, Result};
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const HEX_LOWER: &str = "0123456789abcdef";
+const HEX_UPPER: &str = "0123456789ABCDEF";
+const SYMBOLS: &str = " !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// A parsed mask: one charset per output position.
+#[derive(Debug, Clone)]
+pub struct Mask {
+    positions: Vec<Vec<char>>,
+}
+
+impl Mask {
+    /// Parse `mask`, resolving `?1`-`?4` against `custom` charsets.
+    pub fn parse(mask: &str, custom: &[Option<String>; 4]) -> Result<Self> {
+        let mut resolved: [Option<Vec<char>>; 4] = Default::default();
+        for (i, charset) in custom.iter().enumerate() {
+            if let Some(charset) = charset {
+                let mut chars = Vec::new();
+                for set in tokenize(charset, &Default::default())? {
+                    chars.extend(set);
+                }
+                dedup_in_order(&mut chars);
+                if chars.is_empty() {
+                    bail!("Custom charset {} is empty", i + 1);
+                }
+                resolved[i] = Some(chars);
+            }
+        }
+
+        let positions = tokenize(mask, &resolved)?;
+        if positions.is_empty() {
+            bail!("Mask is empty");
+        }
+        Ok(Self { positions })
+    }
+
+    /// Output length of every candidate.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Number of candidates the mask expands to (saturating).
+    pub fn keyspace(&self) -> u128 {
+        self.positions
+            .iter()
+            .fold(1u128, |acc, p| acc.saturating_mul(p.len() as u128))
+    }
+
+    /// Every candidate, with the rightmost position changing fastest.
+    pub fn candidates(&self) -> MaskIter {
+        self.candidates_from(0)
+    }
+
+    /// The candidate at `index` of [`Mask::candidates`], if there is one.
+    pub fn candidate(&self, index: u128) -> Option<String> {
+        self.candidates_from(index).next()
+    }
+
+    /// Candidates starting at index `start` of [`Mask::candidates`], without
+    /// walking the ones before it.
+    pub fn candidates_from(&self, start: u128) -> MaskIter {
+        let mut odometer = vec![0; self.positions.len()];
+        let mut rest = start;
+        for (i, set) in odometer.iter_mut().zip(&self.positions).rev() {
+            let radix = set.len() as u128;
+            *i = (rest % radix) as usize;
+            rest /= radix;
+        }
+        MaskIter {
+            positions: self.positions.clone(),
+            odometer,
+            done: rest > 0,
+        }
+    }
+}
+
+/// Iterator returned by [`Mask::candidates`].
+pub struct MaskIter {
+    positions: Vec<Vec<char>>,
+    odometer: Vec<usize>,
+    done: bool,
+}
+
+impl Iterator for MaskIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        let candidate = self
+            .odometer
+            .iter()
+            .zip(&self.positions)
+            .map(|(&i, set)| set[i])
+            .collect();
+
+        // Advance, carrying leftwards
+        self.done = true;
+        for (i, set) in self.odometer.iter_mut().zip(&self.positions).rev() {
+            *i += 1;
+            if *i < set.len() {
+                self.done = false;
+                break;
+            }
+            *i = 0;
+        }
+        Some(candidate)
+    }
+}
+
+/// Split a mask into one charset per position.
+fn tokenize(mask: &str, custom: &[Option<Vec<char>>; 4]) -> Result<Vec<Vec<char>>> {
+    let mut positions = Vec::new();
+    let mut chars = mask.chars();
+    while let Some(c) = chars.next() {
+        if c != '?' {
+            positions.push(vec![c]);
+            continue;
+        }
+        let set: Vec<char> = match chars.next() {
+            Some('l') => LOWER.chars().collect(),
+            Some('u') => UPPER.chars().collect(),
+            Some('d') => DIGITS.chars().collect(),
+            Some('h') => HEX_LOWER.chars().collect(),
+            Some('H') => HEX_UPPER.chars().collect(),
+            Some('s') => SYMBOLS.chars().collect(),
+            Some('a') => [LOWER, UPPER, DIGITS, SYMBOLS].concat().chars().collect(),
+            Some('?') => vec!['?'],
+            Some(n @ '1'..='4') => {
+                let i = n as usize - '1' as usize;
+                match custom[i] {
+                    Some(ref set) => set.clone(),
+                    None => bail!("Mask uses ?{} but custom charset {} is not set", n, n),
+                }
+            }
+            Some('b') => bail!("?b (raw bytes) is not supported; candidates must be text"),
+            Some(other) => bail!("Unknown mask placeholder ?{}", other),
+            None => bail!("Mask ends with a lone '?'; use ?? for a literal"),
+        };
+        positions.push(set);
+    }
+    Ok(positions)
+}
+
+fn dedup_in_order(chars: &mut Vec<char>) {
+    let mut seen = std::collections::HashSet::new();
+    chars.retain(|c| seen.insert(*c));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_expansion() {
+        let mask = Mask::parse("?u?d!", &Default::default()).unwrap();
+        assert_eq!(mask.len(), 3);
+        assert_eq!(mask.keyspace(), 260);
+        let all: Vec<String> = mask.candidates().collect();
+        assert_eq!(all.len(), 260);
+        assert_eq!(all[0], "A0!");
+        assert_eq!(all[1], "A1!");
+        assert_eq!(all[259], "Z9!");
+    }
+
+    #[test]
+    fn test_candidates_from() {
+        let mask = Mask::parse("?d?l?d", &Default::default()).unwrap();
+        let all: Vec<String> = mask.candidates().collect();
+        for start in [0, 1, 259, 2600 - 1] {
+            let rest: Vec<String> = mask.candidates_from(start).collect();
+            assert_eq!(rest, all[start as usize..]);
+        }
+        assert_eq!(mask.candidates_from(2600).count(), 0);
+    }
+
+    #[test]
+    fn test_candidate() {
+        let mask = Mask::parse("?d?l", &Default::default()).unwrap();
+        let all: Vec<String> = mask.candidates().collect();
+        assert_eq!(mask.candidate(0).as_deref(), Some("0a"));
+        assert_eq!(mask.candidate(27), Some(all[27].clone()));
+        assert_eq!(mask.candidate(259).as_deref(), Some("9z"));
+        assert_eq!(mask.candidate(260), None);
+    }
+
+    #[test]
+    fn test_custom_charsets() {
+        let custom = [Some("?dx".to_string()), None, None, None];
+        let mask = Mask::parse("?1??", &custom).unwrap();
+        let all: Vec<String> = mask.candidates().collect();
+        assert_eq!(all.len(), 11);
+        assert_eq!(all[10], "x?");
+
+        assert!(Mask::parse("?2", &custom).is_err());
+        assert!(Mask::parse("abc?", &custom).is_err());
+        assert!(Mask::parse("?b", &custom).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! Prometheus metrics for long `serve` and `crack-batch` runs, so
+//! multi-hour jobs can be watched from an existing dashboard.
+//!
+//! The text exposition format is a handful of lines, so the endpoint is a
+//! bare-bones HTTP responder on its own thread rather than a web framework.
+
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+/// Most bytes of a request read, headers included; a scrape needs a few
+/// hundred.
+const MAX_REQUEST: u64 = 8192;
+
+/// Progress counters shared between the cracking loop and the endpoint.
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    tested: AtomicU64,
+    cracked: AtomicU64,
+    targets: AtomicU64,
+    workers: AtomicU64,
+    jobs: AtomicU64,
+    jobs_finished: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            tested: AtomicU64::new(0),
+            cracked: AtomicU64::new(0),
+            targets: AtomicU64::new(0),
+            workers: AtomicU64::new(0),
+            jobs: AtomicU64::new(0),
+            jobs_finished: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn add_tested(&self, n: u64) {
+        self.tested.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn add_cracked(&self, n: u64) {
+        self.cracked.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn add_targets(&self, n: u64) {
+        self.targets.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Distributed workers currently connected.
+    pub fn set_workers(&self, n: u64) {
+        self.workers.store(n, Ordering::Relaxed);
+    }
+
+    /// Jobs in the batch.
+    pub fn set_jobs(&self, n: u64) {
+        self.jobs.store(n, Ordering::Relaxed);
+    }
+
+    pub fn job_finished(&self) {
+        self.jobs_finished.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The current values in Prometheus' text format. Rendering changes
+    /// nothing, so any number of scrapers see the same counters; rates are
+    /// left to `rate()` on the Prometheus side.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = writeln!(out, "# HELP password_guesser_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE password_guesser_{} {}", name, kind);
+            let _ = writeln!(out, "password_guesser_{} {}", name, value);
+        };
+        metric(
+            "candidates_tested_total",
+            "counter",
+            "Candidates hashed and compared.",
+            self.tested.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "hashes_cracked_total",
+            "counter",
+            "Target hashes cracked.",
+            self.cracked.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "hashes_targeted",
+            "gauge",
+            "Target hashes loaded.",
+            self.targets.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "workers",
+            "gauge",
+            "Distributed workers connected.",
+            self.workers.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "jobs",
+            "gauge",
+            "Jobs in the batch.",
+            self.jobs.load(Ordering::Relaxed).to_string(),
+        );
+        metric(
+            "jobs_finished_total",
+            "counter",
+            "Batch jobs finished, cracked or not.",
+            self.jobs_finished.load(Ordering::Relaxed).to_string(),
+        );
+        if let Some(bytes) = resident_bytes() {
+            metric(
+                "resident_memory_bytes",
+                "gauge",
+                "Resident set size of the process.",
+                bytes.to_string(),
+            );
+        }
+        metric(
+            "uptime_seconds",
+            "gauge",
+            "Seconds since the run started.",
+            format!("{:.1}", self.started.elapsed().as_secs_f64()),
+        );
+        out
+    }
+}
+
+/// Serve `metrics` at `/metrics` on `bind` from a background thread, for
+/// as long as the process runs. Returns the address actually bound.
+pub fn serve(metrics: Arc<Metrics>, bind: &str) -> Result<SocketAddr> {
+    let listener =
+        TcpListener::bind(bind).with_context(|| format!("Failed to bind metrics on {}", bind))?;
+    let addr = listener.local_addr()?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // One scrape at a time is plenty; a stalled client only holds
+            // up the next scrape until the timeout
+            let _ = respond(stream, &metrics);
+        }
+    });
+    Ok(addr)
+}
+
+fn respond(stream: TcpStream, metrics: &Metrics) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let path = request_path(stream.try_clone()?)?;
+    let (status, body) = if path == "/metrics" {
+        ("200 OK", metrics.render())
+    } else {
+        ("404 Not Found", "Metrics are at /metrics\n".to_string())
+    };
+    let mut writer = stream;
+    write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// The path of an HTTP request, reading no more than [`MAX_REQUEST`] bytes
+/// of it; the rest of a longer one is left unread.
+fn request_path(stream: impl Read) -> Result<String> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST));
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // Skip the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && !line.trim().is_empty() {
+        line.clear();
+    }
+    Ok(request.split_whitespace().nth(1).unwrap_or("").to_string())
+}
+
+/// Resident set size, from `/proc/self/statm`.
+#[cfg(target_os = "linux")]
+fn resident_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    // SAFETY: sysconf only reads its integer argument
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * u64::try_from(page_size).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_scrape() {
+        let metrics = Arc::new(Metrics::default());
+        metrics.add_targets(3);
+        metrics.add_tested(5000);
+        metrics.add_cracked(1);
+        let text = metrics.render();
+        assert!(text.contains("# TYPE password_guesser_candidates_tested_total counter\n"));
+        assert!(text.contains("\npassword_guesser_candidates_tested_total 5000\n"));
+        assert!(text.contains("\npassword_guesser_hashes_cracked_total 1\n"));
+        assert!(text.contains("\npassword_guesser_hashes_targeted 3\n"));
+        #[cfg(target_os = "linux")]
+        assert!(text.contains("\npassword_guesser_resident_memory_bytes "));
+
+        // Scraping changes nothing another scraper would see
+        let counters = |text: &str| -> Vec<String> {
+            text.lines()
+                .filter(|line| !line.contains("uptime") && !line.contains("memory"))
+                .map(String::from)
+                .collect()
+        };
+        assert_eq!(counters(&metrics.render()), counters(&text));
+
+        let addr = serve(Arc::clone(&metrics), "127.0.0.1:0").unwrap();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("password_guesser_candidates_tested_total 5000"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+
+        // A request line that never ends is cut off, not buffered
+        let mut endless = std::io::Cursor::new(vec![b'a'; 64 * 1024]);
+        assert_eq!(request_path(&mut endless).unwrap(), "");
+        assert!(endless.position() <= MAX_REQUEST);
+    }
+}
@@ -1,48 +1,638 @@
-/// Mutation and mangling rules engine.
+//! Mutation and mangling rules engine.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::hashcat;
+use crate::keyboard::Layout;
+use crate::unicode;
 
 /// Apply all basic mutations to a word, returning new variants.
 pub fn mutate_word(word: &str) -> Vec<String> {
-    let mut results = Vec::new();
-    let lower = word.to_lowercase();
+    mutate_word_with(word, &Leet::default())
+}
+
+/// Like [`mutate_word`], with the leet substitutions of `leet`.
+pub fn mutate_word_with(word: &str, leet: &Leet) -> Vec<String> {
+    apply_all(&WORD_MUTATIONS, word, leet)
+}
+
+/// Apply mutations suitable for combined words (lighter set).
+pub fn mutate_combined(word: &str) -> Vec<String> {
+    mutate_combined_with(word, &Leet::default())
+}
 
-    // Original
-    results.push(lower.clone());
+/// Like [`mutate_combined`], with the leet substitutions of `leet`.
+pub fn mutate_combined_with(word: &str, leet: &Leet) -> Vec<String> {
+    apply_all(&COMBINED_MUTATIONS, word, leet)
+}
 
-    // Capitalize first letter
-    results.push(capitalize_first(&lower));
+fn apply_all(mutations: &[Mutation], word: &str, leet: &Leet) -> Vec<String> {
+    mutations
+        .iter()
+        .flat_map(|mutation| mutation.apply(word, leet))
+        .collect()
+}
 
-    // ALL CAPS
-    results.push(lower.to_uppercase());
+/// The mutations of [`mutate_word`], in order.
+pub const WORD_MUTATIONS: [Mutation; 16] = [
+    Mutation::Lowercase,
+    Mutation::Capitalize,
+    Mutation::Decomposed,
+    Mutation::DecomposedCapitalized,
+    Mutation::Uppercase,
+    Mutation::AlternatingCase,
+    Mutation::CapitalizeLast,
+    Mutation::CapitalizeFirstAndLast,
+    Mutation::CapitalizeAfterDigits,
+    Mutation::Reverse,
+    Mutation::ReverseCapitalized,
+    Mutation::Leet,
+    Mutation::LeetCapitalized,
+    Mutation::PartialLeet,
+    Mutation::DuplicateLast,
+    Mutation::DuplicateLastCapitalized,
+];
 
-    // aLtErNaTiNg case
-    results.push(alternating_case(&lower));
+/// The mutations of [`mutate_combined`], in order.
+pub const COMBINED_MUTATIONS: [Mutation; 4] = [
+    Mutation::Lowercase,
+    Mutation::Capitalize,
+    Mutation::Uppercase,
+    Mutation::Leet,
+];
 
-    // Reverse
-    let reversed: String = lower.chars().rev().collect();
-    results.push(reversed.clone());
-    results.push(capitalize_first(&reversed));
+/// The mutations of [`double_word`], in order.
+pub const DOUBLING_MUTATIONS: [Mutation; 3] = [
+    Mutation::Doubled,
+    Mutation::DoubledUnderscore,
+    Mutation::DoubledCapitalized,
+];
 
-    // Full leet speak
-    results.push(full_leet(&lower));
-    results.push(capitalize_first(&full_leet(&lower)));
+/// An English word ending, for [`Mutation::Inflect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ending {
+    /// `-s`, or `-es` after a hissing sound (`dogs`, `fishes`, `puppies`)
+    Plural,
+    /// `runner`, `dancer`
+    Er,
+    /// `fishing`, `running`
+    Ing,
+    /// The pet-name endings: `smithy`, `bobby`
+    Y,
+    /// `bobbie`, `katie`
+    Ie,
+}
 
-    // Single-position leet substitutions (to avoid exponential blowup)
-    results.extend(single_leet_variants(&lower));
+/// Every [`Ending`], in the order [`inflections`] tries them.
+pub const ENDINGS: [Ending; 5] = [Ending::Plural, Ending::Er, Ending::Ing, Ending::Y, Ending::Ie];
 
-    results
+/// One built-in single-word mutation, as a rule object that can be stored
+/// by name or turned into hashcat rules. Every mutation lowercases the
+/// word first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mutation {
+    /// `john`
+    Lowercase,
+    /// `John`
+    Capitalize,
+    /// Accents as base letter + combining mark (NFD), as some systems store
+    /// them; nothing for unaccented words
+    Decomposed,
+    /// `Decomposed`, capitalized
+    DecomposedCapitalized,
+    /// `JOHN`
+    Uppercase,
+    /// `jOhN`
+    AlternatingCase,
+    /// `johN`; nothing when the word doesn't end in a letter
+    CapitalizeLast,
+    /// `JohN`
+    CapitalizeFirstAndLast,
+    /// The letter after each digit (`john1Smith`); nothing when there is
+    /// none
+    CapitalizeAfterDigits,
+    /// The case of the character at an index flipped (`jOhn` for 1), as
+    /// hashcat's `TN`; nothing past the end or on a non-letter
+    ToggleAt(usize),
+    /// The first N characters (`smith` from `smithson` for 5); nothing
+    /// unless the word is longer
+    Prefix(usize),
+    /// The last N characters (`son` from `smithson` for 3); nothing unless
+    /// the word is longer
+    Suffix(usize),
+    /// The last character typed twice (`buddyy`), as people lengthen an
+    /// old password to meet a new minimum
+    DuplicateLast,
+    /// `Buddyy`
+    DuplicateLastCapitalized,
+    /// The character at an index typed twice (`buuddy` for 1); nothing
+    /// past the end
+    DuplicateAt(usize),
+    /// A year, the one before and the one after appended, each also
+    /// followed by `!` (`john2026`, `john2026!`, `john2025` for 2026): the
+    /// years of passwords rotated every January
+    RecentYears(u16),
+    /// `John2026`, `John2026!`, `John2025`
+    RecentYearsCapitalized(u16),
+    /// The first N characters moved to the end (`ohnj` from `john` for 1),
+    /// as hashcat's `{` N times; nothing when that leaves the word as it is
+    RotateLeft(usize),
+    /// The last N characters moved to the front (`njoh` for 1), as `}`
+    RotateRight(usize),
+    /// Every digit typed with the other shift state on a layout
+    /// (`john!(()` for `john1990` on QWERTY); nothing without digits
+    ShiftDigits(Layout),
+    /// A grammatical ending, spelled the English way (`dogs`, `fishing`,
+    /// `smithy`); nothing unless the word is all letters
+    Inflect(Ending),
+    /// Letters swapped for others that sound the same, one place at a time,
+    /// then every usual spelling at once, then each respelling back
+    /// (`fotografy`, `4ever`, `luv`, `phun`, `rock` from `rok`); see
+    /// [`PHONETIC`]
+    Phonetic,
+    /// `nhoj`
+    Reverse,
+    /// `Nhoj`
+    ReverseCapitalized,
+    /// Every letter in leet (`j0hn`)
+    Leet,
+    /// `J0hn`
+    LeetCapitalized,
+    /// Leet at up to `max_substitutions` positions at once (`j0hnsmith`,
+    /// `j0hn$mith`)
+    PartialLeet,
+    /// `johnjohn`
+    Doubled,
+    /// `john_john`
+    DoubledUnderscore,
+    /// `JohnJohn`
+    DoubledCapitalized,
 }
 
-/// Apply mutations suitable for combined words (lighter set).
-pub fn mutate_combined(word: &str) -> Vec<String> {
-    let mut results = Vec::new();
-    let lower = word.to_lowercase();
+impl Mutation {
+    /// The variants of `word`: one for most mutations, several for
+    /// `PartialLeet`.
+    pub fn apply(self, word: &str, leet: &Leet) -> Vec<String> {
+        let lower = word.to_lowercase();
+        match self {
+            Mutation::Lowercase => vec![lower],
+            Mutation::Capitalize => vec![capitalize_first(&lower)],
+            Mutation::Decomposed | Mutation::DecomposedCapitalized if lower.is_ascii() => {
+                Vec::new()
+            }
+            Mutation::Decomposed => vec![unicode::nfd(&lower)],
+            Mutation::DecomposedCapitalized => vec![unicode::nfd(&capitalize_first(&lower))],
+            Mutation::Uppercase => vec![lower.to_uppercase()],
+            Mutation::AlternatingCase => vec![alternating_case(&lower)],
+            Mutation::CapitalizeLast => changed(&lower, capitalize_last(&lower)),
+            Mutation::CapitalizeFirstAndLast => {
+                changed(&lower, capitalize_last(&capitalize_first(&lower)))
+            }
+            Mutation::CapitalizeAfterDigits => {
+                let mut after_digit = false;
+                let capitalized = lower
+                    .chars()
+                    .map(|c| {
+                        let c = if after_digit { toggle_char(c) } else { c };
+                        after_digit = c.is_ascii_digit();
+                        c
+                    })
+                    .collect();
+                changed(&lower, capitalized)
+            }
+            Mutation::ToggleAt(n) => {
+                let mut chars: Vec<char> = lower.chars().collect();
+                if let Some(c) = chars.get_mut(n) {
+                    *c = toggle_char(*c);
+                }
+                changed(&lower, chars.into_iter().collect())
+            }
+            Mutation::Prefix(n) => changed(&lower, lower.chars().take(n).collect()),
+            Mutation::Suffix(n) => {
+                let skip = lower.chars().count().saturating_sub(n);
+                changed(&lower, lower.chars().skip(skip).collect())
+            }
+            Mutation::DuplicateLast => duplicate_last(&lower).into_iter().collect(),
+            Mutation::DuplicateLastCapitalized => duplicate_last(&lower)
+                .map(|w| capitalize_first(&w))
+                .into_iter()
+                .collect(),
+            Mutation::DuplicateAt(n) => duplicate_at(&lower, n).into_iter().collect(),
+            Mutation::RecentYears(year) => recent_year_suffixes(year)
+                .iter()
+                .map(|suffix| format!("{}{}", lower, suffix))
+                .collect(),
+            Mutation::RecentYearsCapitalized(year) => recent_year_suffixes(year)
+                .iter()
+                .map(|suffix| format!("{}{}", capitalize_first(&lower), suffix))
+                .collect(),
+            Mutation::RotateLeft(n) => changed(&lower, rotate(&lower, n, true)),
+            Mutation::RotateRight(n) => changed(&lower, rotate(&lower, n, false)),
+            Mutation::ShiftDigits(layout) => changed(&lower, shift_digits(&lower, layout)),
+            Mutation::Inflect(ending) => inflect(&lower, ending).into_iter().collect(),
+            Mutation::Phonetic => respellings(&lower),
+            Mutation::Reverse => vec![lower.chars().rev().collect()],
+            Mutation::ReverseCapitalized => {
+                vec![capitalize_first(&lower.chars().rev().collect::<String>())]
+            }
+            Mutation::Leet => vec![leet.full(&lower)],
+            Mutation::LeetCapitalized => vec![capitalize_first(&leet.full(&lower))],
+            Mutation::PartialLeet => leet.variants(&lower),
+            Mutation::Doubled => vec![lower.repeat(2)],
+            Mutation::DoubledUnderscore => vec![format!("{}_{}", lower, lower)],
+            Mutation::DoubledCapitalized => vec![capitalize_first(&lower).repeat(2)],
+        }
+    }
+
+    /// The same mutation as hashcat rules, for words up to `max_length`
+    /// long; none for the decomposed accents and the letters after digits,
+    /// which hashcat can't write.
+    /// hashcat's `sXY` replaces every occurrence, so `PartialLeet` comes out
+    /// as one rule per set of substituted letters rather than positions.
+    pub fn hashcat(self, leet: &Leet, max_length: usize) -> Vec<HashcatRule> {
+        let letters: Vec<(char, Vec<char>)> = leet
+            .map
+            .keys()
+            .map(|&c| (c, leet.replacements(c).collect::<Vec<_>>()))
+            .filter(|(_, replacements)| !replacements.is_empty())
+            .collect();
+        let full = || {
+            let substitutions = letters
+                .iter()
+                .map(|(from, to)| hashcat::substitute(*from, to[0]));
+            std::iter::once("l".to_string())
+                .chain(substitutions)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let rules = match self {
+            Mutation::Lowercase => vec!["l".to_string()],
+            Mutation::Capitalize => vec!["c".to_string()],
+            Mutation::Decomposed | Mutation::DecomposedCapitalized => Vec::new(),
+            Mutation::Uppercase => vec!["u".to_string()],
+            Mutation::AlternatingCase => {
+                let toggles = (1..max_length)
+                    .step_by(2)
+                    .map_while(|i| hashcat::position(i).map(|p| format!(" T{}", p)));
+                vec![std::iter::once("l".to_string()).chain(toggles).collect()]
+            }
+            // Reversed, first letter toggled and reversed back
+            Mutation::CapitalizeLast => vec!["l r T0 r".to_string()],
+            Mutation::CapitalizeFirstAndLast => vec!["c r T0 r".to_string()],
+            Mutation::CapitalizeAfterDigits => Vec::new(),
+            Mutation::ToggleAt(n) => hashcat::position(n)
+                .map(|p| format!("l T{}", p))
+                .into_iter()
+                .collect(),
+            Mutation::Prefix(n) => hashcat::position(n)
+                .map(|p| format!("l '{}", p))
+                .into_iter()
+                .collect(),
+            // Reversed, truncated and reversed back
+            Mutation::Suffix(n) => hashcat::position(n)
+                .map(|p| format!("l r '{} r", p))
+                .into_iter()
+                .collect(),
+            Mutation::DuplicateLast => vec!["l Z1".to_string()],
+            Mutation::DuplicateLastCapitalized => vec!["c Z1".to_string()],
+            // hashcat can only duplicate at the ends
+            Mutation::DuplicateAt(_) => Vec::new(),
+            Mutation::RecentYears(year) => recent_year_suffixes(year)
+                .iter()
+                .map(|suffix| format!("l {}", hashcat::append(suffix)))
+                .collect(),
+            Mutation::RecentYearsCapitalized(year) => recent_year_suffixes(year)
+                .iter()
+                .map(|suffix| format!("c {}", hashcat::append(suffix)))
+                .collect(),
+            Mutation::RotateLeft(n) => vec![format!("l{}", " {".repeat(n))],
+            Mutation::RotateRight(n) => vec![format!("l{}", " }".repeat(n))],
+            // hashcat substitutes bytes, so only layouts with ASCII symbols
+            Mutation::ShiftDigits(layout) => {
+                let digits = "0123456789";
+                let symbols = shift_digits(digits, layout);
+                if !symbols.is_ascii() {
+                    return Vec::new();
+                }
+                let subs: Vec<String> = digits
+                    .chars()
+                    .zip(symbols.chars())
+                    .map(|(digit, symbol)| hashcat::substitute(digit, symbol))
+                    .collect();
+                vec![format!("l {}", subs.join(" "))]
+            }
+            // The spelling depends on how the word ends, which one rule
+            // can't test for
+            Mutation::Inflect(_) => Vec::new(),
+            // hashcat only substitutes single characters
+            Mutation::Phonetic => Vec::new(),
+            Mutation::Reverse => vec!["l r".to_string()],
+            Mutation::ReverseCapitalized => vec!["r c".to_string()],
+            Mutation::Leet => vec![full()],
+            Mutation::LeetCapitalized => vec![format!("{} c", full())],
+            Mutation::PartialLeet => {
+                let mut rules = Vec::new();
+                leet_rules(&letters, 0, leet.max_substitutions, "l", &mut rules);
+                rules
+            }
+            Mutation::Doubled => vec!["l d".to_string()],
+            Mutation::DoubledUnderscore => vec!["l M $_ 4".to_string()],
+            Mutation::DoubledCapitalized => vec!["c d".to_string()],
+        };
+        rules
+            .iter()
+            .map(|rule| HashcatRule::parse(rule).expect("built-in hashcat rules parse"))
+            .collect()
+    }
+}
+
+/// [`Mutation::ToggleAt`] at each interior position of `word`, lowercased
+/// (`jOhn`, `joHn`): case slips the first-letter and alternating forms miss.
+pub fn position_toggles(word: &str) -> Vec<String> {
+    let len = word.chars().count();
+    (1..len.saturating_sub(1))
+        .flat_map(|n| Mutation::ToggleAt(n).apply(word, &Leet::default()))
+        .collect()
+}
+
+/// [`Mutation::DuplicateAt`] at each interior position of `word`,
+/// lowercase and capitalized (`buuddy`, `Buuddy`), without repeats.
+pub fn duplications(word: &str) -> Vec<String> {
+    let len = word.chars().count();
+    let mut out: Vec<String> = Vec::new();
+    for n in 1..len.saturating_sub(1) {
+        for duplicated in Mutation::DuplicateAt(n).apply(word, &Leet::default()) {
+            if !out.contains(&duplicated) {
+                out.push(duplicated);
+            }
+        }
+    }
+    let capitalized: Vec<String> = out.iter().map(|w| capitalize_first(w)).collect();
+    out.extend(capitalized);
+    out
+}
+
+/// `word` with the character at index `n` typed twice; `None` past the
+/// end.
+fn duplicate_at(word: &str, n: usize) -> Option<String> {
+    let chars: Vec<char> = word.chars().collect();
+    if n >= chars.len() {
+        return None;
+    }
+    Some(chars[..=n].iter().chain(&chars[n..]).collect())
+}
+
+/// `word` with its last character typed twice; `None` when empty.
+fn duplicate_last(word: &str) -> Option<String> {
+    word.chars()
+        .count()
+        .checked_sub(1)
+        .and_then(|n| duplicate_at(word, n))
+}
+
+/// The endings of [`Mutation::RecentYears`]: `year`, then the one before
+/// and the one after, each bare and followed by `!`.
+pub fn recent_year_suffixes(year: u16) -> Vec<String> {
+    [year, year.saturating_sub(1), year.saturating_add(1)]
+        .iter()
+        .flat_map(|year| [year.to_string(), format!("{}!", year)])
+        .collect()
+}
+
+/// `word` lowercased and rotated by each amount, nearest first: one left,
+/// one right, two left... (`ohnj`, `njoh`, `hnjo`), without repeats.
+pub fn rotations(word: &str) -> Vec<String> {
+    let len = word.chars().count();
+    let leet = Leet::default();
+    let mut out: Vec<String> = Vec::new();
+    let rotated = (1..len)
+        .flat_map(|n| [Mutation::RotateLeft(n), Mutation::RotateRight(n)])
+        .flat_map(|mutation| mutation.apply(word, &leet));
+    for word in rotated {
+        if !out.contains(&word) {
+            out.push(word);
+        }
+    }
+    out
+}
+
+/// `word` rotated `n` characters left, or right.
+fn rotate(word: &str, n: usize, left: bool) -> String {
+    let mut chars: Vec<char> = word.chars().collect();
+    if !chars.is_empty() {
+        let n = n % chars.len();
+        if left {
+            chars.rotate_left(n);
+        } else {
+            chars.rotate_right(n);
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// `text` with every digit replaced by the symbol on its key on `layout`
+/// (`!@#$` for `1234` on QWERTY).
+pub fn shift_digits(text: &str, layout: Layout) -> String {
+    text.chars()
+        .map(|c| layout.shift_digit(c).unwrap_or(c))
+        .collect()
+}
+
+/// `word` lowercased with each of [`ENDINGS`] (`dogs`, `doggy`, `doggie`),
+/// without repeats: hobbies and pets as people make them into passwords.
+pub fn inflections(word: &str) -> Vec<String> {
+    let leet = Leet::default();
+    let mut out: Vec<String> = Vec::new();
+    for inflected in ENDINGS
+        .iter()
+        .flat_map(|&ending| Mutation::Inflect(ending).apply(word, &leet))
+    {
+        if !out.contains(&inflected) {
+            out.push(inflected);
+        }
+    }
+    out
+}
 
-    results.push(lower.clone());
-    results.push(capitalize_first(&lower));
-    results.push(lower.to_uppercase());
-    results.push(full_leet(&lower));
+/// `word` (lowercase) with `ending`, by the usual spelling rules: a final
+/// silent `e` dropped (`dancer`), a final consonant doubled after a short
+/// vowel (`running`, `bobby`), `y` after a consonant turned to `i`
+/// (`puppies`, `happier`). `None` unless `word` is all ASCII letters, or
+/// for a pet-name ending on a word that has one.
+fn inflect(word: &str, ending: Ending) -> Option<String> {
+    if word.len() < 2 || !word.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let vowel = |c: u8| b"aeiou".contains(&c);
+    let bytes = word.as_bytes();
+    let [.., before, last] = bytes else {
+        return None;
+    };
+    let consonant_y = last == &b'y' && !vowel(*before);
+    let hissing = ["s", "x", "z", "ch", "sh"].iter().any(|end| word.ends_with(end));
+    let suffix = match ending {
+        Ending::Plural if consonant_y => return Some(format!("{}ies", &word[..word.len() - 1])),
+        Ending::Plural if hissing => return Some(format!("{}es", word)),
+        Ending::Plural => return Some(format!("{}s", word)),
+        Ending::Er => "er",
+        Ending::Ing => "ing",
+        Ending::Y | Ending::Ie if *last == b'y' || word.ends_with("ie") => return None,
+        Ending::Y => "y",
+        Ending::Ie => "ie",
+    };
+    let short_vowel = matches!(bytes, [.., c1, v, c2]
+        if !vowel(*c1) && vowel(*v) && !vowel(*c2) && !b"wxy".contains(c2))
+        && word.len() <= 4;
+    let stem = if *last == b'e' && !word.ends_with("ee") {
+        word[..word.len() - 1].to_string()
+    } else if consonant_y && ending == Ending::Er {
+        format!("{}i", &word[..word.len() - 1])
+    } else if short_vowel {
+        format!("{}{}", word, *last as char)
+    } else {
+        word.to_string()
+    };
+    Some(format!("{}{}", stem, suffix))
+}
+
+/// Spellings and the ones that sound the same, for [`Mutation::Phonetic`].
+/// Each pair works both ways (`photo` -> `foto`, `fun` -> `phun`), one
+/// occurrence at a time.
+pub const PHONETIC: [(&str, &str); 7] = [
+    ("ph", "f"),
+    ("qu", "kw"),
+    ("ck", "k"),
+    ("love", "luv"),
+    ("too", "2"),
+    ("for", "4"),
+    ("you", "u"),
+];
+
+/// `word` (lowercase) with each occurrence of each of [`PHONETIC`] respelled
+/// on its own, then with every usual spelling respelled at once, then with
+/// each respelling spelled back, again one occurrence at a time.
+fn respellings(word: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut push = |variant: String| {
+        if variant != word && !out.contains(&variant) {
+            out.push(variant);
+        }
+    };
+    let swapped = |at: usize, from: &str, to: &str| {
+        format!("{}{}{}", &word[..at], to, &word[at + from.len()..])
+    };
+    let mut all = word.to_string();
+    for (spelling, sound) in PHONETIC {
+        for (at, _) in word.match_indices(spelling) {
+            push(swapped(at, spelling, sound));
+        }
+        all = all.replace(spelling, sound);
+    }
+    push(all);
+    for (spelling, sound) in PHONETIC {
+        for (at, _) in word.match_indices(sound) {
+            if spells_back(word, at, sound) {
+                push(swapped(at, sound, spelling));
+            }
+        }
+    }
+    out
+}
 
-    results
+/// Whether the `sound` at byte `at` of `word` stands for its usual
+/// spelling. A lone `k` or `u` is usually just a letter, so `k` counts only
+/// after a vowel and not before one (`rok`, `rokstar`, not `kate` or
+/// `joke`), and `u` only after a consonant at the end of a word or before a
+/// digit (`iluvu`, `thanku2`, not `buddy` or `you`); the rest count anywhere.
+fn spells_back(word: &str, at: usize, sound: &str) -> bool {
+    let bytes = word.as_bytes();
+    let vowel = |i: Option<usize>| {
+        i.and_then(|i| bytes.get(i))
+            .is_some_and(|b| b"aeiou".contains(b))
+    };
+    let before = at.checked_sub(1);
+    let after = Some(at + sound.len());
+    match sound {
+        "k" => vowel(before) && !vowel(after),
+        "u" => {
+            let ends = bytes.get(at + 1).is_none_or(|b| b.is_ascii_digit());
+            ends && before.is_some() && !vowel(before)
+        }
+        _ => true,
+    }
+}
+
+/// Shortest part of a word kept by [`truncations`].
+pub const MIN_TRUNCATION: usize = 3;
+
+/// `word` lowercased and cut short, as people abbreviate long names: its
+/// prefixes (`smith` from `smithson`, `joh`), then its suffixes (`son`,
+/// `ohn`), longest first and at least [`MIN_TRUNCATION`] long.
+pub fn truncations(word: &str) -> Vec<String> {
+    let len = word.chars().count();
+    let leet = Leet::default();
+    let mut out: Vec<String> = Vec::new();
+    let lengths = || (MIN_TRUNCATION..len).rev();
+    let parts = lengths()
+        .map(Mutation::Prefix)
+        .chain(lengths().map(Mutation::Suffix))
+        .flat_map(|mutation| mutation.apply(word, &leet));
+    for part in parts {
+        if !out.contains(&part) {
+            out.push(part);
+        }
+    }
+    out
+}
+
+/// `variant`, unless it's `word` unchanged.
+fn changed(word: &str, variant: String) -> Vec<String> {
+    if variant == word {
+        Vec::new()
+    } else {
+        vec![variant]
+    }
+}
+
+/// Every built-in mutation as hashcat rules, in the order the generator
+/// applies them and without repeats: what the mutation tier does, for
+/// hashcat or for the record.
+pub fn builtin_rules(leet: &Leet, max_length: usize) -> Vec<HashcatRule> {
+    let mut rules: Vec<HashcatRule> = Vec::new();
+    for mutation in WORD_MUTATIONS.iter().chain(&DOUBLING_MUTATIONS) {
+        for rule in mutation.hashcat(leet, max_length) {
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
+    }
+    rules
+}
+
+/// Record `rule` followed by each substitution of up to `left` more
+/// letters from `letters[start..]`, one replacement per letter.
+fn leet_rules(
+    letters: &[(char, Vec<char>)],
+    start: usize,
+    left: usize,
+    rule: &str,
+    rules: &mut Vec<String>,
+) {
+    if left == 0 {
+        return;
+    }
+    for (i, (from, replacements)) in letters.iter().enumerate().skip(start) {
+        for &to in replacements {
+            let rule = format!("{} {}", rule, hashcat::substitute(*from, to));
+            rules.push(rule.clone());
+            leet_rules(letters, i + 1, left - 1, &rule, rules);
+        }
+    }
 }
 
 /// Capitalize the first letter of a string.
@@ -54,6 +644,14 @@ pub fn capitalize_first(s: &str) -> String {
     }
 }
 
+/// The last character uppercased.
+fn capitalize_last(s: &str) -> String {
+    match s.char_indices().last() {
+        None => String::new(),
+        Some((i, c)) => s[..i].to_string() + &c.to_uppercase().to_string(),
+    }
+}
+
 /// aLtErNaTiNg case.
 fn alternating_case(s: &str) -> String {
     s.chars()
@@ -68,50 +666,317 @@ fn alternating_case(s: &str) -> String {
         .collect()
 }
 
-/// Full leet speak substitution.
-pub fn full_leet(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            'a' => '@',
-            'e' => '3',
-            'i' => '1',
-            'o' => '0',
-            's' => '$',
-            't' => '7',
-            'l' => '1',
-            _ => c,
-        })
-        .collect()
+/// Leet speak habits, from the profile's `[leet]` section:
+///
+/// ```toml
+/// [leet]
+/// map = { a = ["4"], e = ["3"], o = ["0"] }
+/// max_substitutions = 3
+/// max_variants = 100
+/// style = "digits"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Leet {
+    /// Replacements for each letter, most common first; full leet uses the
+    /// first allowed one.
+    pub map: BTreeMap<char, Vec<char>>,
+    /// Most positions substituted at once in the partial variants (0 for
+    /// none, leaving only full leet).
+    pub max_substitutions: usize,
+    /// Most partial variants kept per word, so long words with many
+    /// substitutable letters don't blow up the keyspace.
+    pub max_variants: usize,
+    /// Which replacements are allowed.
+    pub style: LeetStyle,
+}
+
+/// Kinds of characters a letter may be replaced with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeetStyle {
+    /// Digits and symbols (`p@55w0rd`)
+    #[default]
+    Any,
+    /// Digits only (`p455w0rd`)
+    Digits,
+    /// Symbols only (`p@$$word`)
+    Symbols,
+}
+
+impl LeetStyle {
+    fn allows(self, c: char) -> bool {
+        match self {
+            LeetStyle::Any => true,
+            LeetStyle::Digits => c.is_ascii_digit(),
+            LeetStyle::Symbols => !c.is_alphanumeric(),
+        }
+    }
+}
+
+impl Default for Leet {
+    fn default() -> Self {
+        let map: [(char, &[char]); 7] = [
+            ('a', &['@', '4']),
+            ('e', &['3']),
+            ('i', &['1', '!']),
+            ('o', &['0']),
+            ('s', &['$', '5']),
+            ('t', &['7', '+']),
+            ('l', &['1']),
+        ];
+        Self {
+            map: map.into_iter().map(|(c, to)| (c, to.to_vec())).collect(),
+            max_substitutions: 2,
+            max_variants: 64,
+            style: LeetStyle::Any,
+        }
+    }
 }
 
-/// Single-position leet variants: replace one character at a time.
-fn single_leet_variants(s: &str) -> Vec<String> {
-    let leet_map: &[(char, &[char])] = &[
-        ('a', &['@', '4']),
-        ('e', &['3']),
-        ('i', &['1', '!']),
-        ('o', &['0']),
-        ('s', &['$', '5']),
-        ('t', &['7', '+']),
-        ('l', &['1']),
-    ];
+impl Leet {
+    /// The allowed replacements for `c`, most common first.
+    pub fn replacements(&self, c: char) -> impl Iterator<Item = char> + '_ {
+        let style = self.style;
+        self.map
+            .get(&c)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |&to| style.allows(to))
+    }
 
-    let chars: Vec<char> = s.chars().collect();
-    let mut variants = Vec::new();
+    /// Every letter replaced with its first allowed replacement.
+    pub fn full(&self, s: &str) -> String {
+        s.chars()
+            .map(|c| self.replacements(c).next().unwrap_or(c))
+            .collect()
+    }
 
-    for (i, &ch) in chars.iter().enumerate() {
-        for &(from, replacements) in leet_map {
-            if ch == from {
-                for &replacement in replacements {
-                    let mut new_chars = chars.clone();
-                    new_chars[i] = replacement;
-                    variants.push(new_chars.into_iter().collect());
+    /// Variants with from one to `max_substitutions` positions replaced,
+    /// fewer substitutions first, stopping at `max_variants`.
+    pub fn variants(&self, s: &str) -> Vec<String> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut variants = Vec::new();
+        for count in 1..=self.max_substitutions.min(chars.len()) {
+            self.substitute_from(&chars, 0, count, &mut chars.clone(), &mut variants);
+            if variants.len() >= self.max_variants {
+                variants.truncate(self.max_variants);
+                break;
+            }
+        }
+        variants
+    }
+
+    /// Replace `left` more positions of `current` at or after `start`,
+    /// recording each variant with every replacement made, until the word
+    /// reaches `max_variants`.
+    fn substitute_from(
+        &self,
+        original: &[char],
+        start: usize,
+        left: usize,
+        current: &mut Vec<char>,
+        variants: &mut Vec<String>,
+    ) {
+        if left == 0 {
+            variants.push(current.iter().collect());
+            return;
+        }
+        for i in start..original.len() {
+            for replacement in self.replacements(original[i]) {
+                if variants.len() >= self.max_variants {
+                    return;
                 }
+                current[i] = replacement;
+                self.substitute_from(original, i + 1, left - 1, current, variants);
             }
+            current[i] = original[i];
+        }
+    }
+}
+
+/// Full leet speak substitution.
+pub fn full_leet(s: &str) -> String {
+    Leet::default().full(s)
+}
+
+/// Common typing slips on `word`: each character dropped, doubled, swapped
+/// with the next one, or replaced by a key beside it on `layout`.
+pub fn typos(word: &str, layout: Layout) -> Vec<String> {
+    let chars: Vec<char> = word.to_lowercase().chars().collect();
+    let mut typos = Vec::new();
+    for i in 0..chars.len() {
+        // Omission
+        typos.push(chars[..i].iter().chain(&chars[i + 1..]).collect());
+        // Duplication
+        typos.push(chars[..=i].iter().chain(&chars[i..]).collect());
+        // Transposition
+        if i + 1 < chars.len() && chars[i] != chars[i + 1] {
+            let mut swapped = chars.clone();
+            swapped.swap(i, i + 1);
+            typos.push(swapped.into_iter().collect());
+        }
+        // Adjacent key
+        for key in layout.neighbors(chars[i]) {
+            let mut slipped = chars.clone();
+            slipped[i] = key;
+            typos.push(slipped.into_iter().collect());
         }
     }
+    typos
+}
+
+/// `word` lowercased with the case of up to `max` of its letters flipped
+/// (`joHn`, `jOhN`), fewest flips first.
+pub fn case_toggles(word: &str, max: usize) -> Vec<String> {
+    let chars: Vec<char> = word.to_lowercase().chars().collect();
+    let letters: Vec<usize> = (0..chars.len())
+        .filter(|&i| chars[i].is_alphabetic())
+        .collect();
+    let mut toggled = Vec::new();
+    for count in 1..=max.min(letters.len()) {
+        toggle_from(&chars, &letters, count, &mut chars.clone(), &mut toggled);
+    }
+    toggled
+}
 
-    variants
+/// Record `current` with `count` more of `letters` uppercased, in order.
+fn toggle_from(
+    chars: &[char],
+    letters: &[usize],
+    count: usize,
+    current: &mut Vec<char>,
+    out: &mut Vec<String>,
+) {
+    if count == 0 {
+        out.push(current.iter().collect());
+        return;
+    }
+    for (n, &i) in letters.iter().enumerate() {
+        if letters.len() - n < count {
+            break;
+        }
+        let flipped: Vec<char> = chars[i].to_uppercase().collect();
+        if flipped.len() != 1 || flipped[0] == chars[i] {
+            continue;
+        }
+        current[i] = flipped[0];
+        toggle_from(chars, &letters[n + 1..], count - 1, current, out);
+        current[i] = chars[i];
+    }
+}
+
+/// `word` lowercased with up to `max` of `inserts` placed between its
+/// letters (pa55!word), never at the ends and at most one per gap.
+pub fn insertions(word: &str, inserts: &[String], max: usize) -> Vec<String> {
+    let chars: Vec<char> = word.to_lowercase().chars().collect();
+    let mut out = Vec::new();
+    insert_from(&chars, 0, String::new(), inserts, max, &mut out);
+    out
+}
+
+/// Record `built` followed by `chars[done..]` with one insert in each
+/// later interior gap, then recurse for up to `left - 1` more.
+fn insert_from(
+    chars: &[char],
+    done: usize,
+    built: String,
+    inserts: &[String],
+    left: usize,
+    out: &mut Vec<String>,
+) {
+    if left == 0 {
+        return;
+    }
+    for gap in (done + 1)..chars.len() {
+        let head: String = built.clone() + &chars[done..gap].iter().collect::<String>();
+        let tail: String = chars[gap..].iter().collect();
+        for insert in inserts {
+            out.push(format!("{}{}{}", head, insert, tail));
+            insert_from(chars, gap, head.clone() + insert, inserts, left - 1, out);
+        }
+    }
+}
+
+/// Number words below twenty, by value.
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+
+/// Number words for the tens, by value (none below twenty).
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// A number below 100 run together as words (`21` → `twentyone`).
+fn below_hundred(n: usize) -> String {
+    match (n, n % 10) {
+        (0..=19, _) => ONES[n].to_string(),
+        (_, 0) => TENS[n / 10].to_string(),
+        (_, ones) => format!("{}{}", TENS[n / 10], ONES[ones]),
+    }
+}
+
+/// `number` spelled out as people type it into passwords, if it is a
+/// number below 100 (`7` → `seven`, `21` → `twentyone`) or a year
+/// (`1990` → `nineteenninety`, `1905` → `nineteenohfive`, `2005` →
+/// `twothousandfive`). Numbers with a leading zero aren't spelled.
+pub fn spell_number(number: &str) -> Option<String> {
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if number.len() > 1 && number.starts_with('0') {
+        return None;
+    }
+    let n: usize = number.parse().ok()?;
+    match (number.len(), n / 100, n % 100) {
+        (1..=2, _, _) => Some(below_hundred(n)),
+        (4, 20, 0) => Some("twothousand".to_string()),
+        (4, 20, 1..=9) => Some(format!("twothousand{}", ONES[n % 100])),
+        (4, 11..=20, 0) => Some(format!("{}hundred", below_hundred(n / 100))),
+        (4, 11..=20, 1..=9) => Some(format!("{}oh{}", below_hundred(n / 100), ONES[n % 100])),
+        (4, 11..=20, rest) => Some(format!("{}{}", below_hundred(n / 100), below_hundred(rest))),
+        _ => None,
+    }
+}
+
+/// `word` lowercased with a spelled-out number at its start or end, or
+/// the whole of it, written in digits (`onelove` → `1love`, `someone` →
+/// `some1`, `seven` → `7`); empty if it has none. The rest of the word
+/// has to be at least three letters, so `stone` stays a stone.
+pub fn number_words_to_digits(word: &str) -> Vec<String> {
+    let lower = word.to_lowercase();
+    let words: Vec<(String, usize)> = (0..100).map(|n| (below_hundred(n), n)).collect();
+    let longest = |fits: &dyn Fn(&str) -> bool| {
+        words
+            .iter()
+            .filter(|(w, _)| fits(w))
+            .max_by_key(|(w, _)| w.len())
+            .cloned()
+    };
+    if let Some((_, n)) = longest(&|w| w == lower) {
+        return vec![n.to_string()];
+    }
+    let rest_ok = |w: &str| lower.len() >= w.len() + 3;
+    let prefix = longest(&|w| lower.starts_with(w) && rest_ok(w));
+    let suffix = longest(&|w| lower.ends_with(w) && rest_ok(w));
+
+    let mut out = Vec::new();
+    if let Some((ref w, n)) = prefix {
+        out.push(format!("{}{}", n, &lower[w.len()..]));
+    }
+    if let Some((ref w, n)) = suffix {
+        out.push(format!("{}{}", &lower[..lower.len() - w.len()], n));
+    }
+    if let (Some((p, a)), Some((q, b))) = (prefix, suffix) {
+        if p.len() + q.len() < lower.len() {
+            out.push(format!("{}{}{}", a, &lower[p.len()..lower.len() - q.len()], b));
+        }
+    }
+    out
 }
 
 /// Generate combined forms of two words.
@@ -133,6 +998,50 @@ pub fn combine_words(a: &str, b: &str) -> Vec<String> {
     ]
 }
 
+/// Like [`combine_words`], joining the words with each of `separators`
+/// instead of "", "_" and ".".
+pub fn combine_words_with(a: &str, b: &str, separators: &[String]) -> Vec<String> {
+    let a_lower = a.to_lowercase();
+    let b_lower = b.to_lowercase();
+    let a_cap = capitalize_first(&a_lower);
+    let b_cap = capitalize_first(&b_lower);
+
+    let mut combos = Vec::new();
+    for sep in separators {
+        combos.push(format!("{}{}{}", a_lower, sep, b_lower)); // john-smith
+        combos.push(format!("{}{}{}", a_cap, sep, b_cap)); // John-Smith
+        if sep.is_empty() {
+            combos.push(format!("{}{}", a_cap, b_lower)); // Johnsmith
+        }
+    }
+    combos.push(format!("{}{}", b_lower, a_lower)); // smithjohn
+    combos.push(format!("{}{}", b_cap, a_cap)); // SmithJohn
+    combos
+}
+
+/// Index orders of three words, as written.
+pub const ORDERINGS_OF_THREE: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// Generate every ordering of three words, all lowercase and with each word
+/// capitalized.
+pub fn combine_three(a: &str, b: &str, c: &str) -> Vec<String> {
+    let lower = [a, b, c].map(str::to_lowercase);
+    let cap = lower.clone().map(|w| capitalize_first(&w));
+    let mut combos = Vec::new();
+    for [x, y, z] in ORDERINGS_OF_THREE {
+        combos.push(format!("{}{}{}", lower[x], lower[y], lower[z])); // johnbuddysmith
+        combos.push(format!("{}{}{}", cap[x], cap[y], cap[z])); // JohnBuddySmith
+    }
+    combos
+}
+
 /// Generate word + number combinations.
 pub fn combine_word_number(word: &str, number: &str) -> Vec<String> {
     let lower = word.to_lowercase();
@@ -170,14 +1079,412 @@ pub fn apply_prefix(prefix: &str, word: &str) -> Vec<String> {
 
 /// Double a word.
 pub fn double_word(word: &str) -> Vec<String> {
-    let lower = word.to_lowercase();
-    let cap = capitalize_first(&lower);
+    apply_all(&DOUBLING_MUTATIONS, word, &Leet::default())
+}
 
-    vec![
-        format!("{}{}", lower, lower),       // johnjohn
-        format!("{}_{}", lower, lower),      // john_john
-        format!("{}{}", cap, cap),           // JohnJohn
-    ]
+/// One function of a hashcat rule, with its positions decoded (`0`-`9`
+/// then `A`-`Z` for 10-35).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleFunction {
+    /// `:` leaves the word alone
+    Noop,
+    /// `l`
+    Lower,
+    /// `u`
+    Upper,
+    /// `c` uppercases the first letter and lowercases the rest
+    Capitalize,
+    /// `C` lowercases the first letter and uppercases the rest
+    InvertCapitalize,
+    /// `t`
+    ToggleAll,
+    /// `TN`
+    Toggle(usize),
+    /// `r`
+    Reverse,
+    /// `d`
+    Duplicate,
+    /// `pN` appends the word N more times
+    DuplicateTimes(usize),
+    /// `f` appends the word reversed
+    Reflect,
+    /// `{`
+    RotateLeft,
+    /// `}`
+    RotateRight,
+    /// `$X`
+    Append(char),
+    /// `^X`
+    Prepend(char),
+    /// `[`
+    DeleteFirst,
+    /// `]`
+    DeleteLast,
+    /// `DN`
+    Delete(usize),
+    /// `xNM` keeps M characters from N
+    Extract(usize, usize),
+    /// `ONM` deletes M characters from N
+    Omit(usize, usize),
+    /// `iNX`
+    Insert(usize, char),
+    /// `oNX`
+    Overwrite(usize, char),
+    /// `'N` keeps the first N characters
+    Truncate(usize),
+    /// `sXY` replaces every X with Y
+    Replace(char, char),
+    /// `@X` deletes every X
+    Purge(char),
+    /// `zN` repeats the first character N times
+    DuplicateFirst(usize),
+    /// `ZN` repeats the last character N times
+    DuplicateLast(usize),
+    /// `q` doubles every character
+    DuplicateAll,
+    /// `k`
+    SwapFront,
+    /// `K`
+    SwapBack,
+    /// `*NM`
+    Swap(usize, usize),
+    /// `LN` shifts the bits of the character at N left
+    ShiftLeft(usize),
+    /// `RN` shifts the bits of the character at N right
+    ShiftRight(usize),
+    /// `+N` adds one to the character at N
+    Increment(usize),
+    /// `-N` subtracts one from the character at N
+    Decrement(usize),
+    /// `.N` replaces the character at N with the one after it
+    ReplaceNext(usize),
+    /// `,N` replaces the character at N with the one before it
+    ReplacePrevious(usize),
+    /// `yN` prepends the first N characters
+    DuplicateBlockFront(usize),
+    /// `YN` appends the last N characters
+    DuplicateBlockBack(usize),
+    /// `E` lowercases the word and capitalizes each space-separated part
+    Title,
+    /// `eX` is `E` with X as the separator
+    TitleSeparated(char),
+    /// `3NX` toggles the case of the character after the Nth X (from 0)
+    ToggleAfter(usize, char),
+    /// `M` memorizes the word
+    Memorize,
+    /// `4` appends the memorized word
+    AppendMemory,
+    /// `6` prepends the memorized word
+    PrependMemory,
+    /// `XNMI` inserts M memorized characters from N at I
+    ExtractMemory(usize, usize, usize),
+    /// `<N` rejects words longer than N
+    RejectLonger(usize),
+    /// `>N` rejects words shorter than N
+    RejectShorter(usize),
+    /// `_N` rejects words not N long
+    RejectLength(usize),
+    /// `!X` rejects words containing X
+    RejectContains(char),
+    /// `/X` rejects words without X
+    RejectMissing(char),
+    /// `(X` rejects words not starting with X
+    RejectFirst(char),
+    /// `)X` rejects words not ending with X
+    RejectLast(char),
+    /// `=NX` rejects words without X at N
+    RejectAt(usize, char),
+    /// `%NX` rejects words with fewer than N Xs
+    RejectFewer(usize, char),
+    /// `Q` rejects words equal to the memorized one
+    RejectMemory,
+}
+
+/// A rule in hashcat's syntax (`c $1 $9 $8 $7`, `sa@ so0`), as found in
+/// community rule sets like best64. Kept in sessions as the line it was
+/// parsed from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HashcatRule {
+    text: String,
+    functions: Vec<RuleFunction>,
+}
+
+impl HashcatRule {
+    /// Parse one rule line. Spaces between functions are ignored, but not
+    /// as arguments: `$ ` appends a space.
+    pub fn parse(line: &str) -> Result<Self> {
+        let text = line.trim_end_matches(['\r', '\n']);
+        let mut chars = text.chars();
+        let mut functions = Vec::new();
+        while let Some(name) = chars.next() {
+            let chars = &mut chars;
+            functions.push(match name {
+                ' ' => continue,
+                ':' => RuleFunction::Noop,
+                'l' => RuleFunction::Lower,
+                'u' => RuleFunction::Upper,
+                'c' => RuleFunction::Capitalize,
+                'C' => RuleFunction::InvertCapitalize,
+                't' => RuleFunction::ToggleAll,
+                'T' => RuleFunction::Toggle(rule_position(name, chars)?),
+                'r' => RuleFunction::Reverse,
+                'd' => RuleFunction::Duplicate,
+                'p' => RuleFunction::DuplicateTimes(rule_position(name, chars)?),
+                'f' => RuleFunction::Reflect,
+                '{' => RuleFunction::RotateLeft,
+                '}' => RuleFunction::RotateRight,
+                '$' => RuleFunction::Append(rule_char(name, chars)?),
+                '^' => RuleFunction::Prepend(rule_char(name, chars)?),
+                '[' => RuleFunction::DeleteFirst,
+                ']' => RuleFunction::DeleteLast,
+                'D' => RuleFunction::Delete(rule_position(name, chars)?),
+                'x' => {
+                    RuleFunction::Extract(rule_position(name, chars)?, rule_position(name, chars)?)
+                }
+                'O' => RuleFunction::Omit(rule_position(name, chars)?, rule_position(name, chars)?),
+                'i' => RuleFunction::Insert(rule_position(name, chars)?, rule_char(name, chars)?),
+                'o' => {
+                    RuleFunction::Overwrite(rule_position(name, chars)?, rule_char(name, chars)?)
+                }
+                '\'' => RuleFunction::Truncate(rule_position(name, chars)?),
+                's' => RuleFunction::Replace(rule_char(name, chars)?, rule_char(name, chars)?),
+                '@' => RuleFunction::Purge(rule_char(name, chars)?),
+                'z' => RuleFunction::DuplicateFirst(rule_position(name, chars)?),
+                'Z' => RuleFunction::DuplicateLast(rule_position(name, chars)?),
+                'q' => RuleFunction::DuplicateAll,
+                'k' => RuleFunction::SwapFront,
+                'K' => RuleFunction::SwapBack,
+                '*' => RuleFunction::Swap(rule_position(name, chars)?, rule_position(name, chars)?),
+                'L' => RuleFunction::ShiftLeft(rule_position(name, chars)?),
+                'R' => RuleFunction::ShiftRight(rule_position(name, chars)?),
+                '+' => RuleFunction::Increment(rule_position(name, chars)?),
+                '-' => RuleFunction::Decrement(rule_position(name, chars)?),
+                '.' => RuleFunction::ReplaceNext(rule_position(name, chars)?),
+                ',' => RuleFunction::ReplacePrevious(rule_position(name, chars)?),
+                'y' => RuleFunction::DuplicateBlockFront(rule_position(name, chars)?),
+                'Y' => RuleFunction::DuplicateBlockBack(rule_position(name, chars)?),
+                'E' => RuleFunction::Title,
+                'e' => RuleFunction::TitleSeparated(rule_char(name, chars)?),
+                '3' => {
+                    RuleFunction::ToggleAfter(rule_position(name, chars)?, rule_char(name, chars)?)
+                }
+                'M' => RuleFunction::Memorize,
+                '4' => RuleFunction::AppendMemory,
+                '6' => RuleFunction::PrependMemory,
+                'X' => RuleFunction::ExtractMemory(
+                    rule_position(name, chars)?,
+                    rule_position(name, chars)?,
+                    rule_position(name, chars)?,
+                ),
+                '<' => RuleFunction::RejectLonger(rule_position(name, chars)?),
+                '>' => RuleFunction::RejectShorter(rule_position(name, chars)?),
+                '_' => RuleFunction::RejectLength(rule_position(name, chars)?),
+                '!' => RuleFunction::RejectContains(rule_char(name, chars)?),
+                '/' => RuleFunction::RejectMissing(rule_char(name, chars)?),
+                '(' => RuleFunction::RejectFirst(rule_char(name, chars)?),
+                ')' => RuleFunction::RejectLast(rule_char(name, chars)?),
+                '=' => RuleFunction::RejectAt(rule_position(name, chars)?, rule_char(name, chars)?),
+                '%' => {
+                    RuleFunction::RejectFewer(rule_position(name, chars)?, rule_char(name, chars)?)
+                }
+                'Q' => RuleFunction::RejectMemory,
+                _ => bail!("Unknown rule function '{}'", name),
+            });
+        }
+        Ok(Self {
+            text: text.to_string(),
+            functions,
+        })
+    }
+
+    /// The functions of the rule, in order.
+    pub fn functions(&self) -> &[RuleFunction] {
+        &self.functions
+    }
+
+    /// Apply the rule to `word`, or `None` if a rejection function drops
+    /// it. As in hashcat, a position past the end of the word leaves it
+    /// unchanged.
+    pub fn apply(&self, word: &str) -> Option<String> {
+        let mut w: Vec<char> = word.chars().collect();
+        let mut memory = w.clone();
+        for &function in &self.functions {
+            let len = w.len();
+            match function {
+                RuleFunction::Noop => {}
+                RuleFunction::Lower => w = w.iter().flat_map(|c| c.to_lowercase()).collect(),
+                RuleFunction::Upper => w = w.iter().flat_map(|c| c.to_uppercase()).collect(),
+                RuleFunction::Capitalize => {
+                    w = w.iter().flat_map(|c| c.to_lowercase()).collect();
+                    if let Some(first) = w.first_mut() {
+                        *first = toggle_char(*first);
+                    }
+                }
+                RuleFunction::InvertCapitalize => {
+                    w = w.iter().flat_map(|c| c.to_uppercase()).collect();
+                    if let Some(first) = w.first_mut() {
+                        *first = toggle_char(*first);
+                    }
+                }
+                RuleFunction::ToggleAll => w.iter_mut().for_each(|c| *c = toggle_char(*c)),
+                RuleFunction::Toggle(n) if n < len => w[n] = toggle_char(w[n]),
+                RuleFunction::Reverse => w.reverse(),
+                RuleFunction::Duplicate => w = w.repeat(2),
+                RuleFunction::DuplicateTimes(n) => w = w.repeat(n + 1),
+                RuleFunction::Reflect => w.extend(w.clone().into_iter().rev()),
+                RuleFunction::RotateLeft if len > 0 => w.rotate_left(1),
+                RuleFunction::RotateRight if len > 0 => w.rotate_right(1),
+                RuleFunction::Append(c) => w.push(c),
+                RuleFunction::Prepend(c) => w.insert(0, c),
+                RuleFunction::DeleteFirst if len > 0 => {
+                    w.remove(0);
+                }
+                RuleFunction::DeleteLast => {
+                    w.pop();
+                }
+                RuleFunction::Delete(n) if n < len => {
+                    w.remove(n);
+                }
+                RuleFunction::Extract(n, m) if n + m <= len => w = w[n..n + m].to_vec(),
+                RuleFunction::Omit(n, m) if n + m <= len => {
+                    w.drain(n..n + m);
+                }
+                RuleFunction::Insert(n, c) if n <= len => w.insert(n, c),
+                RuleFunction::Overwrite(n, c) if n < len => w[n] = c,
+                RuleFunction::Truncate(n) => w.truncate(n),
+                RuleFunction::Replace(from, to) => {
+                    w.iter_mut().filter(|c| **c == from).for_each(|c| *c = to)
+                }
+                RuleFunction::Purge(c) => w.retain(|&x| x != c),
+                RuleFunction::DuplicateFirst(n) if len > 0 => {
+                    w.splice(0..0, std::iter::repeat_n(w[0], n));
+                }
+                RuleFunction::DuplicateLast(n) if len > 0 => w.extend(vec![w[len - 1]; n]),
+                RuleFunction::DuplicateAll => w = w.iter().flat_map(|&c| [c, c]).collect(),
+                RuleFunction::SwapFront if len > 1 => w.swap(0, 1),
+                RuleFunction::SwapBack if len > 1 => w.swap(len - 2, len - 1),
+                RuleFunction::Swap(n, m) if n < len && m < len => w.swap(n, m),
+                RuleFunction::ShiftLeft(n) if n < len => w[n] = map_byte(w[n], |b| b << 1),
+                RuleFunction::ShiftRight(n) if n < len => w[n] = map_byte(w[n], |b| b >> 1),
+                RuleFunction::Increment(n) if n < len => {
+                    w[n] = map_byte(w[n], |b| b.wrapping_add(1))
+                }
+                RuleFunction::Decrement(n) if n < len => {
+                    w[n] = map_byte(w[n], |b| b.wrapping_sub(1))
+                }
+                RuleFunction::ReplaceNext(n) if n + 1 < len => w[n] = w[n + 1],
+                RuleFunction::ReplacePrevious(n) if n > 0 && n < len => w[n] = w[n - 1],
+                RuleFunction::DuplicateBlockFront(n) if n <= len => {
+                    w.splice(0..0, w[..n].to_vec());
+                }
+                RuleFunction::DuplicateBlockBack(n) if n <= len => w.extend(w[len - n..].to_vec()),
+                RuleFunction::Title => w = title(&w, ' '),
+                RuleFunction::TitleSeparated(separator) => w = title(&w, separator),
+                RuleFunction::ToggleAfter(n, separator) => {
+                    let after = (0..len)
+                        .filter(|&i| w[i] == separator)
+                        .nth(n)
+                        .map(|i| i + 1);
+                    if let Some(i) = after.filter(|&i| i < len) {
+                        w[i] = toggle_char(w[i]);
+                    }
+                }
+                RuleFunction::Memorize => memory = w.clone(),
+                RuleFunction::AppendMemory => w.extend(memory.iter().copied()),
+                RuleFunction::PrependMemory => {
+                    w.splice(0..0, memory.iter().copied());
+                }
+                RuleFunction::ExtractMemory(n, m, at) if n + m <= memory.len() && at <= len => {
+                    w.splice(at..at, memory[n..n + m].iter().copied());
+                }
+                RuleFunction::RejectLonger(n) if len > n => return None,
+                RuleFunction::RejectShorter(n) if len < n => return None,
+                RuleFunction::RejectLength(n) if len != n => return None,
+                RuleFunction::RejectContains(c) if w.contains(&c) => return None,
+                RuleFunction::RejectMissing(c) if !w.contains(&c) => return None,
+                RuleFunction::RejectFirst(c) if w.first() != Some(&c) => return None,
+                RuleFunction::RejectLast(c) if w.last() != Some(&c) => return None,
+                RuleFunction::RejectAt(n, c) if w.get(n) != Some(&c) => return None,
+                RuleFunction::RejectFewer(n, c) if w.iter().filter(|&&x| x == c).count() < n => {
+                    return None
+                }
+                RuleFunction::RejectMemory if w == memory => return None,
+                _ => {}
+            }
+        }
+        Some(w.into_iter().collect())
+    }
+}
+
+impl TryFrom<String> for HashcatRule {
+    type Error = anyhow::Error;
+
+    fn try_from(line: String) -> Result<Self> {
+        HashcatRule::parse(&line)
+    }
+}
+
+impl From<HashcatRule> for String {
+    fn from(rule: HashcatRule) -> Self {
+        rule.text
+    }
+}
+
+/// The position argument of `name`: `0`-`9`, then `A`-`Z` for 10-35.
+fn rule_position(name: char, chars: &mut std::str::Chars) -> Result<usize> {
+    let c = chars
+        .next()
+        .with_context(|| format!("'{}' needs a position", name))?;
+    match c {
+        '0'..='9' => Ok(c as usize - '0' as usize),
+        'A'..='Z' => Ok(c as usize - 'A' as usize + 10),
+        _ => bail!("'{}' needs a position (0-9, A-Z), got '{}'", name, c),
+    }
+}
+
+/// The character argument of `name`.
+fn rule_char(name: char, chars: &mut std::str::Chars) -> Result<char> {
+    chars
+        .next()
+        .with_context(|| format!("'{}' needs a character", name))
+}
+
+/// `c` with its case flipped, if that keeps it one character.
+fn toggle_char(c: char) -> char {
+    let flipped: Vec<char> = if c.is_uppercase() {
+        c.to_lowercase().collect()
+    } else {
+        c.to_uppercase().collect()
+    };
+    match flipped[..] {
+        [flipped] => flipped,
+        _ => c,
+    }
+}
+
+/// `c` with `f` applied to its byte, for ASCII characters only.
+fn map_byte(c: char, f: impl Fn(u8) -> u8) -> char {
+    match u8::try_from(c) {
+        Ok(b) if b.is_ascii() => char::from(f(b)),
+        _ => c,
+    }
+}
+
+/// `word` lowercased with the first letter of each `separator`-separated
+/// part uppercased.
+fn title(word: &[char], separator: char) -> Vec<char> {
+    let mut out = Vec::with_capacity(word.len());
+    let mut start = true;
+    for &c in word {
+        if start {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        start = c == separator;
+    }
+    out
 }
 
 #[cfg(test)]
@@ -196,6 +1503,383 @@ mod tests {
         assert_eq!(full_leet("leet"), "1337");
     }
 
+    #[test]
+    fn test_leet() {
+        let leet: Leet =
+            toml::from_str("map = { a = ['4', '@'], o = ['0'] }\nmax_substitutions = 1").unwrap();
+        assert_eq!(leet.full("carol"), "c4r0l");
+        assert_eq!(leet.variants("carol"), ["c4rol", "c@rol", "car0l"]);
+
+        let two = Leet {
+            max_substitutions: 2,
+            ..leet.clone()
+        };
+        let variants = two.variants("carol");
+        assert!(variants.contains(&"c4r0l".to_string()));
+        assert!(variants.contains(&"c@r0l".to_string()));
+        assert_eq!(variants, ["c4rol", "c@rol", "car0l", "c4r0l", "c@r0l"]);
+
+        // j0hn$mith takes two substitutions; the cap keeps the fewest
+        let variants = Leet::default().variants("johnsmith");
+        assert!(variants.contains(&"j0hn$mith".to_string()));
+        let capped = Leet {
+            max_substitutions: 3,
+            max_variants: 4,
+            ..Leet::default()
+        };
+        assert_eq!(
+            capped.variants("johnsmith"),
+            ["j0hnsmith", "john$mith", "john5mith", "johnsm1th"]
+        );
+        assert_eq!(capped.variants("a"), ["@", "4"]);
+
+        let symbols = Leet {
+            style: LeetStyle::Symbols,
+            ..leet
+        };
+        assert_eq!(symbols.full("carol"), "c@rol");
+        assert_eq!(symbols.variants("carol"), ["c@rol"]);
+        assert!(toml::from_str::<Leet>("style = \"emoji\"").is_err());
+    }
+
+    #[test]
+    fn test_mutation_rules() {
+        let leet = Leet::default();
+        for mutation in WORD_MUTATIONS.iter().chain(&DOUBLING_MUTATIONS) {
+            let rules = mutation.hashcat(&leet, 32);
+            for word in ["Buddy", "JohnSmith", "carol"] {
+                let mut expected = mutation.apply(word, &leet);
+                let mut applied: Vec<String> =
+                    rules.iter().filter_map(|rule| rule.apply(word)).collect();
+                match mutation {
+                    Mutation::Decomposed
+                    | Mutation::DecomposedCapitalized
+                    | Mutation::CapitalizeAfterDigits => {
+                        assert!(rules.is_empty());
+                        continue;
+                    }
+                    // Rules for letters the word lacks leave it as it is, and
+                    // sXY substitutes repeated letters together
+                    Mutation::PartialLeet if word == "carol" => {
+                        applied.retain(|w| w != "carol");
+                        applied.sort();
+                        applied.dedup();
+                        expected.sort();
+                    }
+                    Mutation::PartialLeet => continue,
+                    _ => {}
+                }
+                assert_eq!(applied, expected, "{:?} on {}", mutation, word);
+            }
+        }
+        assert_eq!(Mutation::Decomposed.apply("josé", &leet), ["jose\u{301}"]);
+        let toggle = Mutation::ToggleAt(2);
+        assert_eq!(toggle.apply("John", &leet), ["joHn"]);
+        assert_eq!(toggle.hashcat(&leet, 32)[0].apply("John").unwrap(), "joHn");
+
+        let rules: Vec<String> = builtin_rules(&leet, 6).into_iter().map(String::from).collect();
+        assert_eq!(rules[..4], ["l", "c", "u", "l T1 T3 T5"]);
+        assert!(rules.contains(&"l M $_ 4".to_string()));
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        struct Listed {
+            mutations: Vec<Mutation>,
+        }
+        let listed = Listed {
+            mutations: COMBINED_MUTATIONS.to_vec(),
+        };
+        let text = toml::to_string(&listed).unwrap();
+        assert!(text.contains("\"leet\""), "{}", text);
+        assert_eq!(toml::from_str::<Listed>(&text).unwrap(), listed);
+    }
+
+    #[test]
+    fn test_position_case() {
+        let leet = Leet::default();
+        let apply = |mutation: Mutation, word: &str| mutation.apply(word, &leet);
+        assert_eq!(apply(Mutation::CapitalizeLast, "password"), ["passworD"]);
+        assert_eq!(apply(Mutation::CapitalizeFirstAndLast, "john"), ["JohN"]);
+        assert!(apply(Mutation::CapitalizeLast, "buddy1").is_empty());
+        assert_eq!(apply(Mutation::CapitalizeAfterDigits, "john1smith2x"), ["john1Smith2X"]);
+        assert!(apply(Mutation::CapitalizeAfterDigits, "john12").is_empty());
+        assert!(apply(Mutation::ToggleAt(9), "john").is_empty());
+        assert_eq!(position_toggles("John"), ["jOhn", "joHn"]);
+        assert!(mutate_word("password").contains(&"passworD".to_string()));
+    }
+
+    #[test]
+    fn test_truncations() {
+        assert_eq!(
+            truncations("SmithSon"),
+            [
+                "smithso", "smiths", "smith", "smit", "smi", "mithson", "ithson", "thson", "hson",
+                "son"
+            ]
+        );
+        assert_eq!(truncations("john"), ["joh", "ohn"]);
+        assert!(truncations("bob").is_empty());
+        let leet = Leet::default();
+        for mutation in [Mutation::Prefix(5), Mutation::Suffix(3)] {
+            let rule = &mutation.hashcat(&leet, 32)[0];
+            assert_eq!(
+                Some(rule.apply("SmithSon").unwrap()),
+                mutation.apply("SmithSon", &leet).pop()
+            );
+        }
+    }
+
+    #[test]
+    fn test_recent_years() {
+        let leet = Leet::default();
+        assert_eq!(
+            Mutation::RecentYears(2026).apply("Summer", &leet),
+            ["summer2026", "summer2026!", "summer2025", "summer2025!", "summer2027", "summer2027!"]
+        );
+        let capitalized = Mutation::RecentYearsCapitalized(2026).apply("summer", &leet);
+        assert_eq!(capitalized[1], "Summer2026!");
+        let rules = Mutation::RecentYearsCapitalized(2026).hashcat(&leet, 32);
+        assert_eq!(rules[1].apply("summer").unwrap(), "Summer2026!");
+        assert!(!mutate_word("summer").contains(&"Summer2026".to_string()));
+    }
+
+    #[test]
+    fn test_rotations() {
+        let leet = Leet::default();
+        assert_eq!(Mutation::RotateLeft(1).apply("John", &leet), ["ohnj"]);
+        assert_eq!(Mutation::RotateRight(1).apply("John", &leet), ["njoh"]);
+        assert!(Mutation::RotateLeft(4).apply("john", &leet).is_empty());
+        assert!(Mutation::RotateRight(1).apply("aaa", &leet).is_empty());
+        assert_eq!(rotations("John"), ["ohnj", "njoh", "hnjo"]);
+        assert!(rotations("j").is_empty());
+        for mutation in [Mutation::RotateLeft(2), Mutation::RotateRight(3)] {
+            let rule = &mutation.hashcat(&leet, 32)[0];
+            assert_eq!(rule.apply("Smith"), mutation.apply("Smith", &leet).pop());
+        }
+    }
+
+    #[test]
+    fn test_shift_digits() {
+        assert_eq!(shift_digits("1234", Layout::Qwerty), "!@#$");
+        assert_eq!(shift_digits("john1990", Layout::Qwerty), "john!(()");
+        assert_eq!(shift_digits("15/05", Layout::Qwertz), "!%/=%");
+        let leet = Leet::default();
+        let shifted = Mutation::ShiftDigits(Layout::Qwerty);
+        assert_eq!(shifted.apply("John1990", &leet), ["john!(()"]);
+        assert!(shifted.apply("john", &leet).is_empty());
+        let rule = &shifted.hashcat(&leet, 32)[0];
+        assert_eq!(rule.apply("John1990").unwrap(), "john!(()");
+        assert!(Mutation::ShiftDigits(Layout::Azerty).hashcat(&leet, 32).is_empty());
+    }
+
+    #[test]
+    fn test_inflections() {
+        let inflected = |word: &str, ending: Ending| inflect(word, ending).unwrap_or_default();
+        assert_eq!(inflected("dog", Ending::Plural), "dogs");
+        assert_eq!(inflected("fish", Ending::Plural), "fishes");
+        assert_eq!(inflected("puppy", Ending::Plural), "puppies");
+        assert_eq!(inflected("fish", Ending::Ing), "fishing");
+        assert_eq!(inflected("run", Ending::Ing), "running");
+        assert_eq!(inflected("dance", Ending::Er), "dancer");
+        assert_eq!(inflected("happy", Ending::Er), "happier");
+        assert_eq!(inflected("smith", Ending::Y), "smithy");
+        assert_eq!(inflected("bob", Ending::Ie), "bobbie");
+        assert_eq!(inflected("kate", Ending::Ie), "katie");
+        assert_eq!(inflect("buddy", Ending::Y), None);
+        assert_eq!(inflect("r2d2", Ending::Plural), None);
+        assert_eq!(inflections("Bob"), ["bobs", "bobber", "bobbing", "bobby", "bobbie"]);
+        assert!(Mutation::Inflect(Ending::Ing).hashcat(&Leet::default(), 32).is_empty());
+    }
+
+    #[test]
+    fn test_phonetic() {
+        let leet = Leet::default();
+        let respelled = |word: &str| Mutation::Phonetic.apply(word, &leet);
+        // One place at a time, then all at once
+        assert_eq!(respelled("Photography"), ["fotography", "photografy", "fotografy"]);
+        assert_eq!(respelled("forever"), ["4ever", "phorever"]);
+        assert_eq!(respelled("iloveyou"), ["iluvyou", "iloveu", "iluvu"]);
+        assert_eq!(respelled("quick"), ["kwick", "quik", "kwik"]);
+        // And back
+        assert_eq!(respelled("fun"), ["phun"]);
+        assert_eq!(respelled("fifa"), ["phifa", "fipha"]);
+        assert_eq!(respelled("kwik"), ["quik", "kwick"]);
+        assert_eq!(respelled("rok"), ["rock"]);
+        assert_eq!(respelled("iluvu"), ["iloveu", "iluvyou"]);
+        assert_eq!(respelled("4ever"), ["forever"]);
+        assert_eq!(respelled("me2"), ["metoo"]);
+        assert!(respelled("buddy").is_empty());
+        assert!(respelled("kate").is_empty() && respelled("joke").is_empty());
+        assert!(Mutation::Phonetic.hashcat(&leet, 32).is_empty());
+    }
+
+    #[test]
+    fn test_duplications() {
+        let leet = Leet::default();
+        assert_eq!(Mutation::DuplicateLast.apply("Buddy", &leet), ["buddyy"]);
+        assert_eq!(Mutation::DuplicateLastCapitalized.apply("buddy", &leet), ["Buddyy"]);
+        assert!(Mutation::DuplicateLast.apply("", &leet).is_empty());
+        assert!(Mutation::DuplicateAt(5).apply("buddy", &leet).is_empty());
+        assert_eq!(duplications("Buddy"), ["buuddy", "budddy", "Buuddy", "Budddy"]);
+        assert!(duplications("ab").is_empty());
+        assert!(mutate_word("buddy").contains(&"buddyy".to_string()));
+        assert!(Mutation::DuplicateAt(1).hashcat(&leet, 32).is_empty());
+    }
+
+    #[test]
+    fn test_hashcat_rule() {
+        let apply = |rule: &str, word: &str| HashcatRule::parse(rule).unwrap().apply(word);
+        // The examples of hashcat's rule reference
+        for (rule, expected) in [
+            (":", "p@ssW0rd"),
+            ("l", "p@ssw0rd"),
+            ("u", "P@SSW0RD"),
+            ("c", "P@ssw0rd"),
+            ("C", "p@SSW0RD"),
+            ("t", "P@SSw0RD"),
+            ("T3", "p@sSW0rd"),
+            ("r", "dr0Wss@p"),
+            ("d", "p@ssW0rdp@ssW0rd"),
+            ("p2", "p@ssW0rdp@ssW0rdp@ssW0rd"),
+            ("f", "p@ssW0rddr0Wss@p"),
+            ("{", "@ssW0rdp"),
+            ("}", "dp@ssW0r"),
+            ("$1 $2", "p@ssW0rd12"),
+            ("^2^1", "12p@ssW0rd"),
+            ("[", "@ssW0rd"),
+            ("]", "p@ssW0r"),
+            ("D3", "p@sW0rd"),
+            ("x04", "p@ss"),
+            ("O12", "psW0rd"),
+            ("i4!", "p@ss!W0rd"),
+            ("o3$", "p@s$W0rd"),
+            ("'6", "p@ssW0"),
+            ("ss$", "p@$$W0rd"),
+            ("@s", "p@W0rd"),
+            ("z2", "ppp@ssW0rd"),
+            ("Z2", "p@ssW0rddd"),
+            ("q", "pp@@ssssWW00rrdd"),
+            ("k", "@pssW0rd"),
+            ("K", "p@ssW0dr"),
+            ("*34", "p@sWs0rd"),
+            ("R2", "p@9sW0rd"),
+            ("+2", "p@tsW0rd"),
+            ("-1", "p?ssW0rd"),
+            (".1", "psssW0rd"),
+            (",1", "ppssW0rd"),
+            ("y2", "p@p@ssW0rd"),
+            ("Y2", "p@ssW0rdrd"),
+            ("uMl4", "p@ssw0rdP@SSW0RD"),
+            ("uMl6", "P@SSW0RDp@ssw0rd"),
+            ("lMX428", "p@ssw0rdw0"),
+            ("T9", "p@ssW0rd"),
+            ("$ ", "p@ssW0rd "),
+        ] {
+            let applied = apply(rule, "p@ssW0rd");
+            assert_eq!(applied.as_deref(), Some(expected), "{}", rule);
+        }
+        assert_eq!(apply("E", "p@ssW0rd w0rld").unwrap(), "P@ssw0rd W0rld");
+        assert_eq!(apply("e-", "pass-word").unwrap(), "Pass-Word");
+        assert_eq!(apply("30-", "pass-word").unwrap(), "pass-Word");
+
+        for rule in ["<7", "_7", "!@", "/z", ")p", "=1a", "%3s", "MQ"] {
+            assert_eq!(apply(rule, "p@ssW0rd"), None, "{}", rule);
+        }
+        for rule in [">7", "<8", "_8", "!z", "/@", "(p", "=1@", "%2s", "M$1Q"] {
+            assert!(apply(rule, "p@ssW0rd").is_some(), "{}", rule);
+        }
+
+        let rule = HashcatRule::parse("c $1").unwrap();
+        assert_eq!(
+            rule.functions(),
+            [RuleFunction::Capitalize, RuleFunction::Append('1')]
+        );
+        assert_eq!(String::from(rule), "c $1");
+        assert!(HashcatRule::parse("T").is_err());
+        assert!(HashcatRule::parse("Tz").is_err());
+        assert!(HashcatRule::parse("sa").is_err());
+        assert!(HashcatRule::parse("&").is_err());
+    }
+
+    #[test]
+    fn test_mutate_accented_word() {
+        let variants = mutate_word("José");
+        for expected in ["josé", "José", "JOSÉ", "ésoj", "jose\u{301}", "Jose\u{301}"] {
+            assert!(variants.contains(&expected.to_string()), "{}", expected);
+        }
+        assert!(!mutate_word("jose").iter().any(|v| !v.is_ascii()));
+    }
+
+    #[test]
+    fn test_typos() {
+        let typos = typos("Buddy", Layout::Qwerty);
+        for typo in ["uddy", "budy", "bbuddy", "buddyy", "ubddy", "budyd", "vuddy", "byddy"] {
+            assert!(typos.contains(&typo.to_string()), "missing {}", typo);
+        }
+        assert!(!typos.contains(&"buddy".to_string()));
+        assert!(typos.iter().all(|t| t.chars().count().abs_diff(5) <= 1));
+    }
+
+    #[test]
+    fn test_case_toggles() {
+        let one = case_toggles("John", 1);
+        assert_eq!(one, ["John", "jOhn", "joHn", "johN"]);
+
+        let two = case_toggles("john", 2);
+        assert_eq!(two.len(), 4 + 6);
+        assert!(two.contains(&"jOhN".to_string()));
+        assert!(!two.contains(&"JOHn".to_string()));
+        assert_eq!(case_toggles("j0hn", 9).len(), 7);
+        assert!(case_toggles("john", 0).is_empty());
+    }
+
+    #[test]
+    fn test_insertions() {
+        let inserts = ["!".to_string(), "2020".to_string()];
+        let one = insertions("John", &inserts, 1);
+        for word in ["j!ohn", "jo!hn", "joh!n", "jo2020hn"] {
+            assert!(one.contains(&word.to_string()), "missing {}", word);
+        }
+        assert_eq!(one.len(), 6);
+        assert!(!one.contains(&"!john".to_string()));
+        assert!(!one.contains(&"john!".to_string()));
+
+        let two = insertions("pass", &inserts, 2);
+        assert!(two.contains(&"p!a!ss".to_string()));
+        assert!(two.contains(&"pa2020s!s".to_string()));
+        assert!(!two.contains(&"p!!ass".to_string()));
+        assert!(insertions("a", &inserts, 2).is_empty());
+        assert!(insertions("pass", &inserts, 0).is_empty());
+    }
+
+    #[test]
+    fn test_spell_number() {
+        let spelled = |n: &str| spell_number(n);
+        assert_eq!(spelled("7").as_deref(), Some("seven"));
+        assert_eq!(spelled("21").as_deref(), Some("twentyone"));
+        assert_eq!(spelled("40").as_deref(), Some("forty"));
+        assert_eq!(spelled("1990").as_deref(), Some("nineteenninety"));
+        assert_eq!(spelled("1905").as_deref(), Some("nineteenohfive"));
+        assert_eq!(spelled("1900").as_deref(), Some("nineteenhundred"));
+        assert_eq!(spelled("2000").as_deref(), Some("twothousand"));
+        assert_eq!(spelled("2005").as_deref(), Some("twothousandfive"));
+        assert_eq!(spelled("2016").as_deref(), Some("twentysixteen"));
+        for none in ["05", "123", "0515", "3000", "", "7a"] {
+            assert_eq!(spelled(none), None, "{}", none);
+        }
+    }
+
+    #[test]
+    fn test_number_words_to_digits() {
+        assert_eq!(number_words_to_digits("OneLove"), ["1love"]);
+        assert_eq!(number_words_to_digits("someone"), ["some1"]);
+        assert_eq!(number_words_to_digits("twentyone"), ["21"]);
+        // The longest number word wins
+        assert_eq!(number_words_to_digits("sevenfortyseven"), ["7fortyseven", "seven47"]);
+        assert_eq!(number_words_to_digits("onetwothree"), ["1twothree", "onetwo3", "1two3"]);
+        assert!(number_words_to_digits("stone").is_empty());
+        assert!(number_words_to_digits("buddy").is_empty());
+    }
+
     #[test]
     fn test_mutate_word() {
         let variants = mutate_word("test");
@@ -206,6 +1890,26 @@ mod tests {
         assert!(variants.contains(&"7es7".to_string())); // leet variants
     }
 
+    #[test]
+    fn test_combine_words_with() {
+        let separators = ["-".to_string(), "@".to_string()];
+        let combos = combine_words_with("john", "Smith", &separators);
+        assert_eq!(combos[..2], ["john-smith", "John-Smith"]);
+        assert_eq!(combos[2..4], ["john@smith", "John@Smith"]);
+        assert_eq!(combos[4..], ["smithjohn", "SmithJohn"]);
+
+        let joined = combine_words_with("john", "smith", &["".to_string()]);
+        assert!(joined.contains(&"Johnsmith".to_string()));
+    }
+
+    #[test]
+    fn test_combine_three() {
+        let combos = combine_three("john", "Buddy", "smith");
+        assert_eq!(combos.len(), 12);
+        assert!(combos.contains(&"johnbuddysmith".to_string()));
+        assert!(combos.contains(&"SmithBuddyJohn".to_string()));
+    }
+
     #[test]
     fn test_combine_words() {
         let combos = combine_words("john", "smith");
@@ -0,0 +1,104 @@
+//! Embedded name datasets used to expand personal names into variants.
+
+/// Common given-name nicknames, one `name: nick nick ...` line per name.
+const NICKNAMES: &str = include_str!("../data/nicknames.txt");
+
+/// Alternate spellings grouped by locale, one `locale: name name ...` line
+/// per group of equivalent spellings.
+const NAME_VARIANTS: &str = include_str!("../data/name_variants.txt");
+
+/// Nicknames for a (lowercase) given name, e.g. robert -> rob, bob, bobby.
+pub fn nicknames(name: &str) -> Vec<String> {
+    NICKNAMES
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once(':'))
+        .find(|(n, _)| n.trim() == name)
+        .map(|(_, nicks)| nicks.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Alternate spellings of a (lowercase) name from the given locales, e.g.
+/// sergey -> sergei, serguei for `ru`. `all` selects every locale.
+pub fn spelling_variants(name: &str, locales: &[String]) -> Vec<String> {
+    let mut variants = Vec::new();
+    for (locale, group) in NAME_VARIANTS
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once(':'))
+    {
+        let locale = locale.trim();
+        if !locales.iter().any(|l| l == locale || l == "all") {
+            continue;
+        }
+        let group: Vec<&str> = group.split_whitespace().collect();
+        if group.contains(&name) {
+            for v in group {
+                if v != name && !variants.iter().any(|e| e == v) {
+                    variants.push(v.to_string());
+                }
+            }
+        }
+    }
+    variants
+}
+
+/// Gendered surname forms for locales that inflect them (Ivanov/Ivanova,
+/// Kowalski/Kowalska). With no gender given, both forms are produced.
+pub fn surname_forms(surname: &str, locales: &[String], gender: Option<&str>) -> Vec<String> {
+    const SLAVIC: &[(&str, &str, &str)] = &[
+        ("ru", "ov", "ova"),
+        ("ru", "ev", "eva"),
+        ("ru", "in", "ina"),
+        ("ru", "sky", "skaya"),
+        ("pl", "ski", "ska"),
+        ("pl", "cki", "cka"),
+        ("pl", "dzki", "dzka"),
+    ];
+    let wants = |g: &str| gender.is_none_or(|want| want.starts_with(g));
+
+    let mut forms = Vec::new();
+    for (locale, masculine, feminine) in SLAVIC {
+        if !locales.iter().any(|l| l == locale || l == "all") {
+            continue;
+        }
+        let stem = surname
+            .strip_suffix(feminine)
+            .or_else(|| surname.strip_suffix(masculine));
+        let Some(stem) = stem else {
+            continue;
+        };
+        for (g, suffix) in [("m", masculine), ("f", feminine)] {
+            let form = format!("{}{}", stem, suffix);
+            if wants(g) && form != surname && !forms.contains(&form) {
+                forms.push(form);
+            }
+        }
+    }
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spelling_variants() {
+        let ru = vec!["ru".to_string()];
+        assert_eq!(spelling_variants("sergey", &ru), vec!["sergei", "serguei", "sergej"]);
+        assert!(spelling_variants("mohammed", &ru).is_empty());
+        let all = vec!["all".to_string()];
+        assert!(spelling_variants("mohammed", &all).contains(&"muhammad".to_string()));
+    }
+
+    #[test]
+    fn test_surname_forms() {
+        let ru = vec!["ru".to_string()];
+        assert_eq!(surname_forms("ivanov", &ru, None), vec!["ivanova"]);
+        assert_eq!(surname_forms("ivanova", &ru, None), vec!["ivanov"]);
+        assert!(surname_forms("ivanov", &ru, Some("m")).is_empty());
+        let pl = vec!["pl".to_string()];
+        assert_eq!(surname_forms("kowalski", &pl, Some("female")), vec!["kowalska"]);
+        assert!(surname_forms("smith", &pl, None).is_empty());
+    }
+}
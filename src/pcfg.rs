@@ -0,0 +1,395 @@
+//! Probabilistic context-free grammar (PCFG) password model, after Weir et
+//! al.: a password is split into runs of letters (L), digits (D) and other
+//! characters (S), giving its structure, e.g. `L8D4S1` for `password2024!`,
+//! and one terminal per run (`password`, `2024`, `!`).
+//!
+//! Training counts structures and, for each run of a class and length, its
+//! terminals. A candidate's probability is that of its structure times those
+//! of its terminals. Generation walks the candidates in decreasing
+//! probability with a best-first search over terminal ranks, as
+//! [`crate::prince`] does over element ranks, keeping only the frontier in
+//! memory.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Counts learned from a corpus, as saved in a model file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Grammar {
+    /// Passwords trained on.
+    pub passwords: u64,
+    /// Times each structure was seen, e.g. `L8D4S1`.
+    pub structures: BTreeMap<String, u64>,
+    /// For each run (class and length, e.g. `D4`), times each terminal was
+    /// seen.
+    pub terminals: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl Grammar {
+    /// Learn structures and terminals from a list of passwords.
+    pub fn train(passwords: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let mut grammar = Self::default();
+        for password in passwords {
+            grammar.add(password.as_ref());
+        }
+        grammar
+    }
+
+    /// Count one more password.
+    pub fn add(&mut self, password: &str) {
+        if password.is_empty() {
+            return;
+        }
+        let segments = segments(password);
+        let structure: String = segments.iter().map(|(run, _)| run.as_str()).collect();
+        *self.structures.entry(structure).or_default() += 1;
+        for (run, text) in segments {
+            let terminals = self.terminals.entry(run).or_default();
+            *terminals.entry(text.to_string()).or_default() += 1;
+        }
+        self.passwords += 1;
+    }
+
+    /// Read a model written by [`save`](Self::save).
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read PCFG model: {}", path.display()))?;
+        let grammar: Grammar =
+            toml::from_str(&content).with_context(|| "Failed to parse PCFG model TOML")?;
+        for structure in grammar.structures.keys() {
+            let Some(runs) = runs(structure) else {
+                bail!("Malformed structure '{}' in PCFG model", structure);
+            };
+            if let Some(run) = runs
+                .iter()
+                .find(|run| !grammar.terminals.contains_key(*run))
+            {
+                bail!(
+                    "Structure '{}' in PCFG model uses run '{}', which has no terminals",
+                    structure,
+                    run
+                );
+            }
+        }
+        Ok(grammar)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self).context("Failed to serialize PCFG model")?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write PCFG model: {}", path.display()))
+    }
+
+    /// Number of candidates the model can produce (saturating).
+    pub fn keyspace(&self) -> u128 {
+        self.structures
+            .keys()
+            .filter_map(|structure| runs(structure))
+            .map(|runs| {
+                runs.iter()
+                    .map(|run| self.terminals.get(run).map_or(0, |t| t.len() as u128))
+                    .fold(1u128, |acc, n| acc.saturating_mul(n))
+            })
+            .fold(0u128, |acc, n| acc.saturating_add(n))
+    }
+
+    /// Candidates of `min_length..=max_length` bytes, most probable first.
+    pub fn candidates(&self, min_length: usize, max_length: usize) -> PcfgIter {
+        // Each run's terminals, most frequent first
+        let mut ids = HashMap::new();
+        let mut terminals = Vec::new();
+        for (run, counts) in &self.terminals {
+            let total: u64 = counts.values().sum();
+            let mut ranked: Vec<(&String, &u64)> = counts.iter().collect();
+            ranked.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            let ranked = ranked
+                .into_iter()
+                .map(|(text, &count)| (text.clone(), cost(count, total)))
+                .collect::<Vec<_>>();
+            ids.insert(run.as_str(), terminals.len());
+            terminals.push(ranked);
+        }
+
+        let mut structures = Vec::new();
+        let mut frontier = BinaryHeap::new();
+        for (structure, &count) in &self.structures {
+            let Some(runs) = runs(structure) else {
+                continue;
+            };
+            let Some(runs) = runs
+                .iter()
+                .map(|run| ids.get(run.as_str()).copied())
+                .collect::<Option<Vec<usize>>>()
+            else {
+                continue;
+            };
+            // Never fewer bytes than characters
+            if run_lengths(structure) > max_length {
+                continue;
+            }
+            let base = cost(count, self.passwords);
+            let cost = base + runs.iter().map(|&run| terminals[run][0].1).sum::<f64>();
+            frontier.push(Node {
+                cost,
+                structure: structures.len(),
+                indices: vec![0; runs.len()],
+                pivot: 0,
+            });
+            structures.push(runs);
+        }
+
+        PcfgIter {
+            terminals,
+            structures,
+            frontier,
+            min_length,
+            max_length,
+        }
+    }
+}
+
+/// Iterator returned by [`Grammar::candidates`].
+pub struct PcfgIter {
+    /// Per run, terminals and their costs, cheapest first
+    terminals: Vec<Vec<(String, f64)>>,
+    /// Per structure, the runs it is made of
+    structures: Vec<Vec<usize>>,
+    frontier: BinaryHeap<Node>,
+    min_length: usize,
+    max_length: usize,
+}
+
+impl Iterator for PcfgIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some(node) = self.frontier.pop() {
+            let runs = &self.structures[node.structure];
+            // Successors take the next terminal of one run at or after the
+            // last one advanced, so every combination is reached once.
+            for i in node.pivot..runs.len() {
+                let terminals = &self.terminals[runs[i]];
+                let next = node.indices[i] + 1;
+                if next < terminals.len() {
+                    let mut indices = node.indices.clone();
+                    indices[i] = next;
+                    self.frontier.push(Node {
+                        cost: node.cost - terminals[next - 1].1 + terminals[next].1,
+                        structure: node.structure,
+                        indices,
+                        pivot: i,
+                    });
+                }
+            }
+
+            let text: String = runs
+                .iter()
+                .zip(&node.indices)
+                .map(|(&run, &i)| self.terminals[run][i].0.as_str())
+                .collect();
+            if text.len() >= self.min_length && text.len() <= self.max_length {
+                return Some(text);
+            }
+        }
+        None
+    }
+}
+
+/// A structure with a choice of terminal for each run, and its cost
+/// (negative log-probability).
+struct Node {
+    cost: f64,
+    structure: usize,
+    indices: Vec<usize>,
+    pivot: usize,
+}
+
+// BinaryHeap is a max-heap, so the cheapest node must compare greatest.
+// Ties go to the earlier structure, then to lower ranks, keeping output
+// stable.
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.structure.cmp(&self.structure))
+            .then_with(|| other.indices.cmp(&self.indices))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+fn cost(count: u64, total: u64) -> f64 {
+    -(count as f64 / total.max(1) as f64).ln()
+}
+
+/// Class letter of a character: L, D or S.
+fn class(c: char) -> char {
+    if c.is_ascii_digit() {
+        'D'
+    } else if c.is_alphabetic() {
+        'L'
+    } else {
+        'S'
+    }
+}
+
+/// The runs of `password`: each run's key (class and length in characters,
+/// e.g. `L8`) and text.
+pub fn segments(password: &str) -> Vec<(String, &str)> {
+    let mut segments = Vec::new();
+    let mut chars = password.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let kind = class(c);
+        let mut len = 1;
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, next)) = chars.peek() {
+            if class(next) != kind {
+                break;
+            }
+            len += 1;
+            end = i + next.len_utf8();
+            chars.next();
+        }
+        segments.push((format!("{}{}", kind, len), &password[start..end]));
+    }
+    segments
+}
+
+/// The run keys of a structure, e.g. `L8D4S1` to `L8`, `D4`, `S1`; `None`
+/// if it is malformed.
+fn runs(structure: &str) -> Option<Vec<String>> {
+    let mut runs: Vec<String> = Vec::new();
+    for c in structure.chars() {
+        match c {
+            'L' | 'D' | 'S' => runs.push(c.to_string()),
+            '0'..='9' => runs.last_mut()?.push(c),
+            _ => return None,
+        }
+    }
+    let well_formed = runs
+        .iter()
+        .all(|run| run.len() > 1 && !run[1..].starts_with('0'));
+    (well_formed && !runs.is_empty()).then_some(runs)
+}
+
+/// Characters in a candidate of this structure.
+fn run_lengths(structure: &str) -> usize {
+    runs(structure)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|run| run[1..].parse::<usize>().ok())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn corpus() -> Grammar {
+        Grammar::train([
+            "password1",
+            "password1",
+            "password2",
+            "monkey12",
+            "dragon12",
+            "dragon12!",
+            "iloveyou",
+            "123456",
+        ])
+    }
+
+    #[test]
+    fn test_segments() {
+        let split = segments("password2024!");
+        assert_eq!(
+            split,
+            [
+                ("L8".to_string(), "password"),
+                ("D4".to_string(), "2024"),
+                ("S1".to_string(), "!"),
+            ]
+        );
+        let keys: Vec<String> = segments("José_99").into_iter().map(|(r, _)| r).collect();
+        assert_eq!(keys, ["L4", "S1", "D2"]);
+        assert_eq!(runs("L8D4S1").unwrap(), ["L8", "D4", "S1"]);
+        assert_eq!(runs("L08"), None);
+        assert_eq!(runs("8L"), None);
+        assert_eq!(runs("LD4"), None);
+    }
+
+    #[test]
+    fn test_train() {
+        let grammar = corpus();
+        assert_eq!(grammar.passwords, 8);
+        assert_eq!(grammar.structures["L8D1"], 3);
+        assert_eq!(grammar.structures["L6D2S1"], 1);
+        assert_eq!(grammar.terminals["L8"]["password"], 3);
+        assert_eq!(grammar.terminals["D2"]["12"], 3);
+        assert_eq!(grammar.terminals["L6"].len(), 2);
+    }
+
+    #[test]
+    fn test_probability_order() {
+        let grammar = corpus();
+        let all: Vec<String> = grammar.candidates(0, 32).collect();
+        // The most common structure with the most common terminals
+        assert_eq!(all[0], "password1");
+        assert_eq!(all[1], "dragon12");
+        let rank = |s: &str| all.iter().position(|c| c == s).unwrap();
+        assert!(rank("password2") < rank("monkey12!"));
+        // Unseen combinations of seen parts come up too, and a common
+        // structure can put one ahead of a password seen as is
+        assert!(rank("iloveyou1") < rank("iloveyou"));
+        assert_eq!(all.len() as u128, grammar.keyspace());
+        let unique: HashSet<&String> = all.iter().collect();
+        assert_eq!(unique.len(), all.len());
+    }
+
+    #[test]
+    fn test_length_bounds() {
+        let grammar = corpus();
+        let all: Vec<String> = grammar.candidates(8, 8).collect();
+        assert!(all.iter().all(|c| c.len() == 8));
+        assert!(all.contains(&"iloveyou".to_string()));
+        assert!(all.contains(&"monkey12".to_string()));
+        assert!(!all.contains(&"password1".to_string()));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("pg-pcfg-{}.toml", std::process::id()));
+        let grammar = Grammar::train(["p@ss=1", "héllo 2"]);
+        grammar.save(&path).unwrap();
+        let loaded = Grammar::load(&path).unwrap();
+        assert_eq!(loaded.passwords, 2);
+        assert_eq!(loaded.structures, grammar.structures);
+        assert_eq!(loaded.terminals, grammar.terminals);
+
+        std::fs::write(
+            &path,
+            "passwords = 1\n[structures]\nL4 = 1\n[terminals.D4]\n1234 = 1\n",
+        )
+        .unwrap();
+        let err = Grammar::load(&path).unwrap_err().to_string();
+        assert!(err.contains("'L4'"), "{}", err);
+        std::fs::remove_file(&path).ok();
+    }
+}
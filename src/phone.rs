@@ -0,0 +1,245 @@
+//! Phone numbers as people reuse them in passwords, by country: with and
+//! without the country code or trunk prefix, the area code alone, the
+//! subscriber number, the last six digits and the groups the number is
+//! written in.
+
+/// How a country writes its phone numbers.
+struct Convention {
+    /// ISO 3166 alpha-2 codes of the countries sharing the plan
+    countries: &'static [&'static str],
+    /// International calling code
+    calling: &'static str,
+    /// Prefix dialled before the national number within the country
+    trunk: &'static str,
+    /// Digit groups of the national number as written, the area (or mobile
+    /// operator) code first
+    groups: &'static [usize],
+}
+
+/// Numbering plans, for the commonest way each writes a mobile number.
+const CONVENTIONS: &[Convention] = &[
+    Convention {
+        countries: &["us", "ca"],
+        calling: "1",
+        trunk: "",
+        groups: &[3, 3, 4],
+    },
+    Convention {
+        countries: &["gb", "uk"],
+        calling: "44",
+        trunk: "0",
+        groups: &[4, 3, 3],
+    },
+    Convention {
+        countries: &["de"],
+        calling: "49",
+        trunk: "0",
+        groups: &[3, 8],
+    },
+    Convention {
+        countries: &["fr"],
+        calling: "33",
+        trunk: "0",
+        groups: &[1, 2, 2, 2, 2],
+    },
+    Convention {
+        countries: &["nl"],
+        calling: "31",
+        trunk: "0",
+        groups: &[1, 8],
+    },
+    Convention {
+        countries: &["es"],
+        calling: "34",
+        trunk: "",
+        groups: &[3, 3, 3],
+    },
+    Convention {
+        countries: &["it"],
+        calling: "39",
+        trunk: "",
+        groups: &[3, 3, 4],
+    },
+    Convention {
+        countries: &["id"],
+        calling: "62",
+        trunk: "0",
+        groups: &[3, 4, 4],
+    },
+    Convention {
+        countries: &["in"],
+        calling: "91",
+        trunk: "0",
+        groups: &[5, 5],
+    },
+    Convention {
+        countries: &["au"],
+        calling: "61",
+        trunk: "0",
+        groups: &[3, 3, 3],
+    },
+    Convention {
+        countries: &["br"],
+        calling: "55",
+        trunk: "0",
+        groups: &[2, 5, 4],
+    },
+    Convention {
+        countries: &["ru"],
+        calling: "7",
+        trunk: "8",
+        groups: &[3, 3, 2, 2],
+    },
+];
+
+/// Shortest digit group worth trying on its own.
+const MIN_GROUP: usize = 3;
+
+/// Countries with a known numbering plan.
+pub fn countries() -> Vec<&'static str> {
+    CONVENTIONS
+        .iter()
+        .flat_map(|c| c.countries)
+        .copied()
+        .collect()
+}
+
+/// Whether `country` has a known numbering plan.
+pub fn is_country(country: &str) -> bool {
+    convention(country).is_some()
+}
+
+/// The numbers `phone` yields, most used first: the digits as written and
+/// the last four, then with a known `country` the national number with and
+/// without the trunk prefix, with the country code, the area code (with
+/// the trunk prefix too), the subscriber number, the last six digits and
+/// each written group of three or more digits.
+pub fn numbers(phone: &str, country: Option<&str>) -> Vec<String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        return Vec::new();
+    }
+    let mut numbers = vec![digits.clone()];
+    if digits.len() >= 4 {
+        numbers.push(digits[digits.len() - 4..].to_string());
+    }
+    let Some(plan) = country.and_then(convention) else {
+        return numbers;
+    };
+
+    let national = national(phone, &digits, plan);
+    let area_len = plan.groups[0].min(national.len());
+    let (area, subscriber) = national.split_at(area_len);
+    numbers.push(national.to_string());
+    if !plan.trunk.is_empty() {
+        numbers.push(format!("{}{}", plan.trunk, national));
+    }
+    numbers.push(format!("{}{}", plan.calling, national));
+    if area.len() >= 2 {
+        numbers.push(area.to_string());
+        if !plan.trunk.is_empty() {
+            numbers.push(format!("{}{}", plan.trunk, area));
+        }
+    }
+    numbers.push(subscriber.to_string());
+    if national.len() >= 6 {
+        numbers.push(national[national.len() - 6..].to_string());
+    }
+    let mut rest = national;
+    for &len in plan.groups {
+        let (group, tail) = rest.split_at(len.min(rest.len()));
+        if group.len() >= MIN_GROUP {
+            numbers.push(group.to_string());
+        }
+        rest = tail;
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    numbers
+        .into_iter()
+        .filter(|n| !n.is_empty() && seen.insert(n.clone()))
+        .collect()
+}
+
+fn convention(country: &str) -> Option<&'static Convention> {
+    let country = country.trim().to_lowercase();
+    CONVENTIONS
+        .iter()
+        .find(|c| c.countries.contains(&country.as_str()))
+}
+
+/// The national significant number: `digits` without the country code
+/// (when written international style, or as long as a number with one) or
+/// the trunk prefix.
+fn national<'a>(phone: &str, digits: &'a str, plan: &Convention) -> &'a str {
+    let length: usize = plan.groups.iter().sum();
+    let written = phone.trim_start();
+    let international = written.starts_with('+') || written.starts_with("00");
+    let digits = match digits.strip_prefix("00") {
+        Some(rest) if international => rest,
+        _ => digits,
+    };
+    if let Some(rest) = digits.strip_prefix(plan.calling) {
+        if international || rest.len() == length {
+            return rest;
+        }
+    }
+    match digits.strip_prefix(plan.trunk) {
+        Some(rest) if !plan.trunk.is_empty() && rest.len() == length => rest,
+        _ => digits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_without_country() {
+        assert_eq!(numbers("+1-555-867-5309", None), ["15558675309", "5309"]);
+        assert_eq!(numbers("123", None), ["123"]);
+        assert!(numbers("n/a", Some("us")).is_empty());
+    }
+
+    #[test]
+    fn test_numbers_by_country() {
+        let us = numbers("+1-555-867-5309", Some("US"));
+        assert_eq!(us[..2], ["15558675309", "5309"]);
+        for expected in ["5558675309", "555", "8675309", "675309", "867"] {
+            assert!(us.contains(&expected.to_string()), "{}", expected);
+        }
+
+        // Written nationally, with the trunk prefix
+        let gb = numbers("07911 123456", Some("gb"));
+        for expected in ["7911123456", "447911123456", "7911", "07911", "123456"] {
+            assert!(gb.contains(&expected.to_string()), "{}", expected);
+        }
+        // The same number written internationally
+        let international = numbers("+44 7911 123456", Some("gb"));
+        assert!(international.contains(&"07911123456".to_string()));
+        assert!(international.contains(&"7911".to_string()));
+
+        let fr = numbers("06 12 34 56 78", Some("fr"));
+        for expected in [
+            "612345678",
+            "0612345678",
+            "33612345678",
+            "12345678",
+            "345678",
+        ] {
+            assert!(fr.contains(&expected.to_string()), "{}", expected);
+        }
+        // Two-digit groups are too common to be worth a seed of their own
+        assert!(!fr.contains(&"12".to_string()));
+
+        let unique: std::collections::HashSet<&String> = fr.iter().collect();
+        assert_eq!(unique.len(), fr.len());
+    }
+
+    #[test]
+    fn test_countries() {
+        assert!(is_country("de") && is_country("UK"));
+        assert!(!is_country("xx"));
+        assert!(countries().contains(&"br"));
+    }
+}
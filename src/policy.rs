@@ -0,0 +1,266 @@
+//! Target password policies: composition rules that candidates must meet.
+//!
+//! A policy is written as comma-separated `rule=count` pairs, e.g.
+//! `min-upper=1,min-digit=1,min-symbol=1`, or given by a preset name
+//! (`nist`, `windows-complexity`, `pci`).
+//! Candidates the target would reject can never be the password, so the
+//! generator drops them, as it does candidates outside the [`Charset`] a
+//! system accepts.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Named policies, as the rules they stand for.
+const PRESETS: &[(&str, &str)] = &[
+    // NIST SP 800-63B: eight characters, and no composition rules at all
+    ("nist", "min-length=8"),
+    // Windows "Password must meet complexity requirements": three of the four
+    // character classes and at least six characters
+    ("windows-complexity", "min-length=6,min-classes=3"),
+    // PCI DSS 4.0 requirement 8.3.6: twelve characters, letters and digits
+    ("pci", "min-length=12,min-letter=1,min-digit=1"),
+];
+
+/// Minimum composition rules, kept in sessions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    pub min_length: usize,
+    pub min_upper: usize,
+    pub min_lower: usize,
+    /// Letters of either case
+    pub min_letter: usize,
+    pub min_digit: usize,
+    /// Anything that isn't a letter or digit
+    pub min_symbol: usize,
+    /// Distinct classes out of upper, lower, digit and symbol
+    pub min_classes: usize,
+}
+
+impl Policy {
+    /// Parse `rule=count` pairs or a preset name.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let rules = PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(spec))
+            .map_or(spec, |(_, rules)| rules);
+
+        let mut policy = Policy::default();
+        for rule in rules.split(',').map(str::trim) {
+            let Some((name, count)) = rule.split_once('=') else {
+                bail!("Expected rule=count or a preset name, got '{}'", rule);
+            };
+            let count: usize = count
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid count in policy rule '{}'", rule))?;
+            let field = match name.trim() {
+                "min-length" => &mut policy.min_length,
+                "min-upper" => &mut policy.min_upper,
+                "min-lower" => &mut policy.min_lower,
+                "min-letter" => &mut policy.min_letter,
+                "min-digit" => &mut policy.min_digit,
+                "min-symbol" => &mut policy.min_symbol,
+                "min-classes" if count <= 4 => &mut policy.min_classes,
+                "min-classes" => bail!("min-classes is at most 4, got {}", count),
+                other => bail!(
+                    "Unknown policy rule '{}' (min-length, min-upper, min-lower, min-letter, \
+                     min-digit, min-symbol or min-classes; presets: {})",
+                    other,
+                    preset_names().join(", ")
+                ),
+            };
+            *field = count;
+        }
+        Ok(policy)
+    }
+
+    /// The preset called `name`, and nothing else.
+    pub fn preset(name: &str) -> Result<Self> {
+        if !preset_names().contains(&name.trim().to_lowercase().as_str()) {
+            bail!(
+                "Unknown policy preset '{}' ({})",
+                name,
+                preset_names().join(", ")
+            );
+        }
+        Policy::parse(name)
+    }
+
+    /// A policy with the stricter of each rule, for a preset tightened
+    /// with rules of its own.
+    pub fn and(self, other: Policy) -> Policy {
+        Policy {
+            min_length: self.min_length.max(other.min_length),
+            min_upper: self.min_upper.max(other.min_upper),
+            min_lower: self.min_lower.max(other.min_lower),
+            min_letter: self.min_letter.max(other.min_letter),
+            min_digit: self.min_digit.max(other.min_digit),
+            min_symbol: self.min_symbol.max(other.min_symbol),
+            min_classes: self.min_classes.max(other.min_classes),
+        }
+    }
+
+    /// Whether the target would accept `candidate`.
+    pub fn allows(&self, candidate: &str) -> bool {
+        let (mut upper, mut lower, mut digit, mut symbol) = (0, 0, 0, 0);
+        for c in candidate.chars() {
+            match c {
+                c if c.is_uppercase() => upper += 1,
+                c if c.is_lowercase() => lower += 1,
+                c if c.is_numeric() => digit += 1,
+                _ => symbol += 1,
+            }
+        }
+        let classes = [upper, lower, digit, symbol]
+            .iter()
+            .filter(|&&n| n > 0)
+            .count();
+        candidate.chars().count() >= self.min_length
+            && upper >= self.min_upper
+            && lower >= self.min_lower
+            && upper + lower >= self.min_letter
+            && digit >= self.min_digit
+            && symbol >= self.min_symbol
+            && classes >= self.min_classes
+    }
+}
+
+/// Characters a system accepts in passwords, kept in sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Charset {
+    /// Printable ASCII, space included
+    Ascii,
+    /// ASCII letters and digits
+    Alnum,
+    /// Only these characters
+    Custom(String),
+}
+
+impl Charset {
+    /// Parse `ascii`, `alnum` or `custom:<chars>`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once(':') {
+            Some(("custom", "")) => bail!("custom: charset has no characters"),
+            Some(("custom", chars)) => Ok(Charset::Custom(chars.to_string())),
+            _ => match spec.trim() {
+                "ascii" => Ok(Charset::Ascii),
+                "alnum" => Ok(Charset::Alnum),
+                _ => bail!(
+                    "Unknown charset '{}' (ascii, alnum or custom:<chars>)",
+                    spec
+                ),
+            },
+        }
+    }
+
+    /// Whether every character of `candidate` is in the charset.
+    pub fn allows(&self, candidate: &str) -> bool {
+        match self {
+            Charset::Ascii => candidate.chars().all(|c| c == ' ' || c.is_ascii_graphic()),
+            Charset::Alnum => candidate.chars().all(|c| c.is_ascii_alphanumeric()),
+            Charset::Custom(chars) => candidate.chars().all(|c| chars.contains(c)),
+        }
+    }
+}
+
+/// Names accepted by [`Policy::parse`] in place of rules.
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let policy = Policy::parse("min-upper=1, min-digit=2,min-symbol=1").unwrap();
+        assert_eq!(
+            policy,
+            Policy {
+                min_upper: 1,
+                min_digit: 2,
+                min_symbol: 1,
+                ..Default::default()
+            }
+        );
+        let windows = Policy::parse("Windows-Complexity").unwrap();
+        assert_eq!(
+            windows,
+            Policy {
+                min_length: 6,
+                min_classes: 3,
+                ..Default::default()
+            }
+        );
+
+        let pci = Policy::preset("pci").unwrap();
+        assert_eq!(
+            pci,
+            Policy {
+                min_length: 12,
+                min_letter: 1,
+                min_digit: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(Policy::preset("NIST").unwrap().min_length, 8);
+        assert!(Policy::preset("min-length=8").is_err());
+        assert!(Policy::preset("hipaa").is_err());
+
+        assert!(Policy::parse("min-upper").is_err());
+        assert!(Policy::parse("min-upper=x").is_err());
+        assert!(Policy::parse("max-upper=1").is_err());
+        assert!(Policy::parse("min-classes=5").is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let policy = Policy::parse("min-upper=1,min-digit=1,min-symbol=1").unwrap();
+        assert!(policy.allows("Buddy1990!"));
+        assert!(!policy.allows("Buddy1990"));
+        assert!(!policy.allows("buddy1990!"));
+
+        let windows = Policy::parse("windows-complexity").unwrap();
+        assert!(windows.allows("Buddy1990"));
+        assert!(windows.allows("buddy_1990"));
+        assert!(!windows.allows("buddy1990"));
+        assert!(!windows.allows("Bud1!"));
+        assert!(Policy::default().allows("x"));
+
+        let pci = Policy::preset("pci").unwrap();
+        assert!(pci.allows("correcthorse1"));
+        assert!(pci.allows("123456789ABC"));
+        assert!(!pci.allows("123456789012"));
+        assert!(!pci.allows("buddy1990"));
+
+        // A preset tightened with a rule keeps the stricter of each
+        let strict = pci.and(Policy::parse("min-symbol=1,min-length=8").unwrap());
+        assert_eq!(strict.min_length, 12);
+        assert!(strict.allows("correcthorse1!"));
+        assert!(!strict.allows("correcthorse1"));
+    }
+
+    #[test]
+    fn test_charset() {
+        let ascii = Charset::parse("ascii").unwrap();
+        assert!(ascii.allows("Buddy 1990!"));
+        assert!(!ascii.allows("Büddy1990"));
+        let alnum = Charset::parse("alnum").unwrap();
+        assert!(alnum.allows("Buddy1990"));
+        assert!(!alnum.allows("Buddy_1990"));
+        let custom = Charset::parse("custom:bduy0129").unwrap();
+        assert!(custom.allows("buddy1990"));
+        assert!(!custom.allows("Buddy1990"));
+        assert_eq!(
+            Charset::parse("custom::").unwrap(),
+            Charset::Custom(":".into())
+        );
+
+        assert!(Charset::parse("custom:").is_err());
+        assert!(Charset::parse("utf8").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! PRINCE-style chains: passphrases built by concatenating several elements
+//! from a word pool.
+//!
+//! Elements are ranked by their position in the pool (profile seeds come
+//! first, so they count as most likely) and an element of rank `r` is given
+//! a Zipf-like probability proportional to `1 / (r + 2)`. Chains are emitted
+//! in order of decreasing estimated probability, i.e. increasing
+//! `sum(ln(r + 2))`, which naturally favours short chains of high-ranked
+//! elements. The walk is lazy: a best-first search over index tuples that
+//! only keeps the current frontier in memory.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::sync::Arc;
+
+/// A chain generator over a pool of elements.
+#[derive(Debug, Clone)]
+pub struct Prince {
+    elements: Arc<[String]>,
+    min_elements: usize,
+    max_elements: usize,
+    min_length: usize,
+    max_length: usize,
+}
+
+impl Prince {
+    /// Chains of `min_elements..=max_elements` elements whose total length
+    /// falls within `min_length..=max_length`. Repeated elements are dropped.
+    pub fn new(
+        elements: Vec<String>,
+        min_elements: usize,
+        max_elements: usize,
+        min_length: usize,
+        max_length: usize,
+    ) -> Self {
+        let mut seen = HashSet::new();
+        let elements: Vec<String> = elements
+            .into_iter()
+            .filter(|e| !e.is_empty() && seen.insert(e.clone()))
+            .collect();
+        Self {
+            elements: elements.into(),
+            min_elements: min_elements.max(1),
+            max_elements: max_elements.max(min_elements.max(1)),
+            min_length,
+            max_length,
+        }
+    }
+
+    /// Number of chains before the length filter (saturating).
+    pub fn keyspace(&self) -> u128 {
+        let n = self.elements.len() as u128;
+        (self.min_elements..=self.max_elements)
+            .map(|k| n.saturating_pow(k as u32))
+            .fold(0u128, |acc, c| acc.saturating_add(c))
+    }
+
+    /// Chains in order of decreasing estimated probability.
+    pub fn candidates(&self) -> PrinceIter {
+        let mut frontier = BinaryHeap::new();
+        if !self.elements.is_empty() {
+            for k in self.min_elements..=self.max_elements {
+                frontier.push(Chain::new(vec![0; k], 0));
+            }
+        }
+        PrinceIter {
+            prince: self.clone(),
+            frontier,
+        }
+    }
+}
+
+/// Iterator returned by [`Prince::candidates`].
+pub struct PrinceIter {
+    prince: Prince,
+    frontier: BinaryHeap<Chain>,
+}
+
+impl Iterator for PrinceIter {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let prince = &self.prince;
+        while let Some(chain) = self.frontier.pop() {
+            // Successors bump one index at or after the last one bumped, so
+            // every tuple is reached along exactly one path.
+            for i in chain.pivot..chain.indices.len() {
+                if chain.indices[i] + 1 < prince.elements.len() {
+                    let mut indices = chain.indices.clone();
+                    indices[i] += 1;
+                    self.frontier.push(Chain::new(indices, i));
+                }
+            }
+
+            let text: String = chain
+                .indices
+                .iter()
+                .map(|&i| prince.elements[i].as_str())
+                .collect();
+            if text.len() >= prince.min_length && text.len() <= prince.max_length {
+                return Some(text);
+            }
+        }
+        None
+    }
+}
+
+/// Element indices plus their cost (negative log-probability).
+struct Chain {
+    cost: f64,
+    indices: Vec<usize>,
+    pivot: usize,
+}
+
+impl Chain {
+    fn new(indices: Vec<usize>, pivot: usize) -> Self {
+        let cost = indices.iter().map(|&r| ((r + 2) as f64).ln()).sum();
+        Self {
+            cost,
+            indices,
+            pivot,
+        }
+    }
+}
+
+// BinaryHeap is a max-heap, so the cheapest chain must compare greatest.
+// Ties go to fewer elements, then to lower indices, keeping output stable.
+impl Ord for Chain {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.indices.len().cmp(&self.indices.len()))
+            .then_with(|| other.indices.cmp(&self.indices))
+    }
+}
+
+impl PartialOrd for Chain {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Chain {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Chain {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(words: &[&str]) -> Vec<String> {
+        words.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_every_chain_once() {
+        let prince = Prince::new(pool(&["a", "b", "c"]), 2, 3, 0, 32);
+        assert_eq!(prince.keyspace(), 9 + 27);
+        let chains: Vec<String> = prince.candidates().collect();
+        assert_eq!(chains.len(), 36);
+        let unique: HashSet<&String> = chains.iter().collect();
+        assert_eq!(unique.len(), 36);
+    }
+
+    #[test]
+    fn test_probability_order() {
+        let prince = Prince::new(pool(&["john", "buddy", "1990", "blue"]), 2, 4, 0, 64);
+        let chains: Vec<String> = prince.candidates().collect();
+        assert_eq!(chains[0], "johnjohn");
+        assert_eq!(chains[1], "johnbuddy");
+        assert_eq!(chains[2], "buddyjohn");
+        let rank = |s: &str| chains.iter().position(|c| c == s).unwrap();
+        assert!(rank("johnjohnjohn") < rank("blueblue"));
+        assert!(rank("johnbuddy1990") < rank("blueblueblue"));
+    }
+
+    #[test]
+    fn test_length_bounds() {
+        let prince = Prince::new(pool(&["ab", "cdef"]), 2, 2, 5, 6);
+        let chains: Vec<String> = prince.candidates().collect();
+        assert_eq!(chains, pool(&["abcdef", "cdefab"]));
+    }
+}
@@ -1,9 +1,17 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::path::Path;
 
+use crate::common;
+use crate::date;
+use crate::mutations::Leet;
+use crate::names;
+use crate::phone;
+use crate::unicode;
+
 /// A target profile loaded from TOML.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Profile {
     #[serde(default)]
     pub personal: Personal,
@@ -12,59 +20,177 @@ pub struct Profile {
     #[serde(default)]
     pub interests: Interests,
     #[serde(default)]
+    pub work: Work,
+    #[serde(default)]
     pub custom: Custom,
+    #[serde(default)]
+    pub guesses: Guesses,
+    #[serde(default)]
+    pub leet: Leet,
+    /// How much each seed field counts, from 0 to [`MAX_WEIGHT`] and
+    /// [`DEFAULT_WEIGHT`] when unset, keyed by field name (`pet_name = 10`).
+    #[serde(default)]
+    pub weights: BTreeMap<String, u32>,
 }
 
+/// Weight of a seed field the profile doesn't weigh.
+pub const DEFAULT_WEIGHT: u32 = 5;
+
+/// Heaviest weight a profile may give a field.
+pub const MAX_WEIGHT: u32 = 10;
+
+/// The profile fields seed words come from, in seed order.
+pub const SEED_FIELDS: [&str; 15] = [
+    "personal.first_name",
+    "personal.last_name",
+    "personal.nickname",
+    "personal.partner_name",
+    "personal.pet_name",
+    "personal.children_names",
+    "network.ssid",
+    "network.router_brand",
+    "network.isp",
+    "interests.favorite_team",
+    "interests.favorite_band",
+    "interests.hobbies",
+    "interests.favorite_color",
+    "work.company",
+    "custom.words",
+];
+
 #[derive(Debug, Default, Deserialize)]
 pub struct Personal {
     #[serde(default)]
-    pub first_name: Option<String>,
+    pub first_name: Option<Field>,
     #[serde(default)]
-    pub last_name: Option<String>,
+    pub last_name: Option<Field>,
     #[serde(default)]
-    pub nickname: Option<String>,
+    pub nickname: Option<Field>,
     #[serde(default)]
     pub birthdate: Option<String>, // YYYY-MM-DD
+    /// Age in years, used to infer birth years when the birthdate is unknown.
+    #[serde(default)]
+    pub age: Option<u32>,
+    #[serde(default)]
+    pub partner_name: Option<Field>,
     #[serde(default)]
-    pub partner_name: Option<String>,
+    pub partner_birthdate: Option<String>, // YYYY-MM-DD
     #[serde(default)]
-    pub pet_name: Option<String>,
+    pub pet_name: Option<Field>,
     #[serde(default)]
-    pub children_names: Vec<String>,
+    pub children_names: Vec<Field>,
+    /// Birthdates of the children, in the order of `children_names`.
+    #[serde(default)]
+    pub children_birthdates: Vec<String>,
     #[serde(default)]
     pub phone: Option<String>,
+    /// Country the phone number is from (ISO code such as "us", "gb" or
+    /// "de"), for the ways that country writes it.
+    #[serde(default)]
+    pub country: Option<String>,
+    /// Locales whose alternate name spellings to add (e.g. "ru", "ar", or "all").
+    #[serde(default)]
+    pub locales: Vec<String>,
+    /// "m" or "f"; picks the surname form in locales that inflect surnames.
+    #[serde(default)]
+    pub gender: Option<String>,
+    /// Language the target thinks in ("en", "es", "id", "de" or "fr"), for
+    /// that language's everyday words.
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize)]
 pub struct Network {
     #[serde(default)]
-    pub ssid: Option<String>,
+    pub ssid: Option<Field>,
     #[serde(default)]
-    pub router_brand: Option<String>,
+    pub router_brand: Option<Field>,
     #[serde(default)]
-    pub isp: Option<String>,
+    pub isp: Option<Field>,
 }
 
 #[derive(Debug, Default, Deserialize)]
 pub struct Interests {
     #[serde(default)]
-    pub favorite_team: Option<String>,
+    pub favorite_team: Option<Field>,
     #[serde(default)]
-    pub favorite_band: Option<String>,
+    pub favorite_band: Option<Field>,
     #[serde(default)]
-    pub hobbies: Vec<String>,
+    pub hobbies: Vec<Field>,
     #[serde(default)]
-    pub favorite_color: Option<String>,
+    pub favorite_color: Option<Field>,
     #[serde(default)]
     pub favorite_number: Option<String>,
 }
 
+/// Where the target works, for the corporate template's patterns.
+#[derive(Debug, Default, Deserialize)]
+pub struct Work {
+    #[serde(default)]
+    pub company: Option<Field>,
+}
+
 #[derive(Debug, Default, Deserialize)]
 pub struct Custom {
     #[serde(default)]
-    pub words: Vec<String>,
+    pub words: Vec<Field>,
     #[serde(default)]
     pub numbers: Vec<String>,
+    /// Separators for joining seed words, in place of "", "_" and ".".
+    #[serde(default)]
+    pub separators: Vec<String>,
+}
+
+/// Specific high-confidence guesses, tried first and exactly as written.
+#[derive(Debug, Default, Deserialize)]
+pub struct Guesses {
+    #[serde(default)]
+    pub passwords: Vec<String>,
+}
+
+/// A text field: either a plain string or a table carrying per-field
+/// transformation presets, e.g.
+/// `ssid = { value = "CasaAzul", transforms = ["as-is", "no-mutations"] }`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Field {
+    Plain(String),
+    WithTransforms {
+        value: String,
+        #[serde(default)]
+        transforms: Vec<Transform>,
+    },
+}
+
+impl Field {
+    pub fn value(&self) -> &str {
+        match self {
+            Field::Plain(value) | Field::WithTransforms { value, .. } => value,
+        }
+    }
+
+    pub fn transforms(&self) -> &[Transform] {
+        match self {
+            Field::Plain(_) => &[],
+            Field::WithTransforms { transforms, .. } => transforms,
+        }
+    }
+}
+
+/// Per-field presets restricting which generation rules touch a seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transform {
+    /// Keep the exact spelling: no lowercasing, no splitting into parts, no
+    /// derived names, and affixes/combinations join the verbatim text.
+    AsIs,
+    /// Skip case, leet, reversal and doubling mutations.
+    NoMutations,
+    /// Skip numeric/symbol suffixes, prefixes and seed numbers.
+    NoAffixes,
+    /// Never combine this seed with other seeds.
+    NoCombinations,
 }
 
 impl Profile {
@@ -74,62 +200,246 @@ impl Profile {
             .with_context(|| format!("Failed to read profile: {}", path.display()))?;
         let profile: Profile =
             toml::from_str(&content).with_context(|| "Failed to parse profile TOML")?;
+        if let Some(ref language) = profile.personal.language {
+            if !common::is_language(language) {
+                bail!(
+                    "No dictionary for language '{}' (en, es, id, de or fr)",
+                    language
+                );
+            }
+        }
+        if let Some(ref country) = profile.personal.country {
+            if !phone::is_country(country) {
+                bail!(
+                    "No phone conventions for country '{}' ({})",
+                    country,
+                    phone::countries().join(", ")
+                );
+            }
+        }
+        for (field, &weight) in &profile.weights {
+            if !SEED_FIELDS.iter().any(|f| field_name(f) == field) {
+                let names: Vec<&str> = SEED_FIELDS.iter().map(|f| field_name(f)).collect();
+                bail!(
+                    "Unknown field '{}' in [weights] ({})",
+                    field,
+                    names.join(", ")
+                );
+            }
+            if weight > MAX_WEIGHT {
+                bail!(
+                    "Weight {} for '{}' is out of range (0 to {})",
+                    weight,
+                    field,
+                    MAX_WEIGHT
+                );
+            }
+        }
         Ok(profile)
     }
 
+    /// The weight of the seed field `field` (e.g. "personal.pet_name").
+    pub fn weight(&self, field: &str) -> u32 {
+        self.weights
+            .get(field_name(field))
+            .copied()
+            .unwrap_or(DEFAULT_WEIGHT)
+    }
+
     /// Extract all seed words from the profile (lowercased, non-empty).
     pub fn seed_words(&self) -> Vec<String> {
+        self.seed_sources().into_iter().map(|s| s.word).collect()
+    }
+
+    /// Seed words paired with the profile field each came from, in the same
+    /// order as [`Profile::seed_words`].
+    pub fn seed_sources(&self) -> Vec<SeedSource> {
         let mut words = Vec::new();
 
         let p = &self.personal;
-        push_opt(&mut words, &p.first_name);
-        push_opt(&mut words, &p.last_name);
-        push_opt(&mut words, &p.nickname);
-        push_opt(&mut words, &p.partner_name);
-        push_opt(&mut words, &p.pet_name);
+        if let Some(ref name) = p.first_name {
+            push_name(&mut words, "personal.first_name", name, &p.locales);
+        }
+        push_opt(&mut words, "personal.last_name", &p.last_name);
+        if let Some(ref surname) = p.last_name {
+            if !surname.transforms().contains(&Transform::AsIs) {
+                let lower = surname.value().trim().to_lowercase();
+                for form in names::surname_forms(&lower, &p.locales, p.gender.as_deref()) {
+                    push_derived(&mut words, "personal.last_name", surname, form);
+                }
+            }
+        }
+        push_opt(&mut words, "personal.nickname", &p.nickname);
+        if let Some(ref name) = p.partner_name {
+            push_name(&mut words, "personal.partner_name", name, &p.locales);
+        }
+        push_opt(&mut words, "personal.pet_name", &p.pet_name);
         for name in &p.children_names {
-            push_word(&mut words, name);
+            push_name(&mut words, "personal.children_names", name, &p.locales);
         }
 
         let n = &self.network;
-        push_opt(&mut words, &n.ssid);
-        push_opt(&mut words, &n.router_brand);
-        push_opt(&mut words, &n.isp);
+        push_opt(&mut words, "network.ssid", &n.ssid);
+        push_opt(&mut words, "network.router_brand", &n.router_brand);
+        push_opt(&mut words, "network.isp", &n.isp);
 
         let i = &self.interests;
-        push_opt(&mut words, &i.favorite_team);
-        push_opt(&mut words, &i.favorite_band);
+        push_opt(&mut words, "interests.favorite_team", &i.favorite_team);
+        push_opt(&mut words, "interests.favorite_band", &i.favorite_band);
         for h in &i.hobbies {
-            push_word(&mut words, h);
+            push_word(&mut words, "interests.hobbies", h);
         }
-        push_opt(&mut words, &i.favorite_color);
+        push_opt(&mut words, "interests.favorite_color", &i.favorite_color);
+
+        push_opt(&mut words, "work.company", &self.work.company);
 
         for w in &self.custom.words {
-            push_word(&mut words, w);
+            push_word(&mut words, "custom.words", w);
         }
 
         words
     }
 
-    /// Extract seed numbers from the profile.
-    pub fn seed_numbers(&self) -> Vec<String> {
+    /// Seeds kept with their original capitalization, which plain
+    /// lowercasing would have lost (e.g. "McDonald", "iPhone").
+    pub fn cased_seeds(&self) -> Vec<String> {
+        self.seed_sources()
+            .into_iter()
+            .map(|s| s.word)
+            .filter(|w| w.chars().any(char::is_uppercase))
+            .collect()
+    }
+
+    /// The favorite team and band and the hobbies, unaccented and reduced
+    /// to lowercase letters and digits, for matching topic dictionaries.
+    pub fn interests(&self) -> Vec<String> {
+        let i = &self.interests;
+        i.favorite_team
+            .iter()
+            .chain(&i.favorite_band)
+            .chain(&i.hobbies)
+            .map(|f| {
+                unicode::to_ascii(f.value())
+                    .chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The topics the interests bring in: sports for a favorite team, music
+    /// for a favorite band, and any topic whose triggers an interest
+    /// contains (`chess` → none, `Minecraft` → gaming).
+    pub fn topics(&self) -> Vec<&'static str> {
+        let mut topics = Vec::new();
+        if self.interests.favorite_team.is_some() {
+            topics.push("sports");
+        }
+        if self.interests.favorite_band.is_some() {
+            topics.push("music");
+        }
+        for topic in common::matching_topics(&self.interests()) {
+            if !topics.contains(&topic) {
+                topics.push(topic);
+            }
+        }
+        topics
+    }
+
+    /// The target's birth year, or the likely ones given only their age,
+    /// counted back from `year`, the run's current year.
+    pub fn birth_years(&self, year: u16) -> Vec<i64> {
+        if let Some(ref bd) = self.personal.birthdate {
+            let year = bd.split('-').next().and_then(|y| y.parse().ok());
+            year.into_iter().collect()
+        } else if let Some(age) = self.personal.age {
+            birth_years(age, year.into())
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The target, their partner and each child, with the numbers of their
+    /// birthdates (the target's also from `age`, counted back from `year`).
+    /// Members without a name are left out.
+    pub fn family(&self, year: u16) -> Vec<FamilyMember> {
+        let p = &self.personal;
+        let dates = |date: Option<&String>| date.map_or_else(Vec::new, |d| decompose_date(d));
+        let mut family = Vec::new();
+        let mut push = |fields: &[(&'static str, Option<&Field>)], numbers: Vec<String>| {
+            let mut names = Vec::new();
+            for &(field, name) in fields {
+                if let Some(name) = name {
+                    push_word(&mut names, field, name);
+                }
+            }
+            if !names.is_empty() {
+                family.push(FamilyMember { names, numbers });
+            }
+        };
+
+        let own = match (&p.birthdate, p.age) {
+            (Some(birthdate), _) => decompose_date(birthdate),
+            (None, Some(age)) => birth_years(age, year.into())
+                .iter()
+                .flat_map(|year| decompose_year(&year.to_string()))
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        push(
+            &[
+                ("personal.first_name", p.first_name.as_ref()),
+                ("personal.nickname", p.nickname.as_ref()),
+            ],
+            own,
+        );
+        push(
+            &[("personal.partner_name", p.partner_name.as_ref())],
+            dates(p.partner_birthdate.as_ref()),
+        );
+        for (i, child) in p.children_names.iter().enumerate() {
+            push(
+                &[("personal.children_names", Some(child))],
+                dates(p.children_birthdates.get(i)),
+            );
+        }
+        family
+    }
+
+    /// Extract seed numbers from the profile, with an `age` counted back
+    /// from `year`, the run's current year.
+    pub fn seed_numbers(&self, year: u16) -> Vec<String> {
+        self.seed_numbers_in(&[], year)
+    }
+
+    /// Like [`Profile::seed_numbers`], writing the birthdate the ways
+    /// `date_locales` write dates, or the profile's `locales` if empty.
+    pub fn seed_numbers_in(&self, date_locales: &[String], year: u16) -> Vec<String> {
         let mut numbers = Vec::new();
 
         // Birthdate decomposition
         if let Some(ref bd) = self.personal.birthdate {
             numbers.extend(decompose_date(bd));
-        }
-
-        // Phone digits
-        if let Some(ref phone) = self.personal.phone {
-            let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
-            if !digits.is_empty() {
-                numbers.push(digits.clone());
-                // Last 4 digits
-                if digits.len() >= 4 {
-                    numbers.push(digits[digits.len() - 4..].to_string());
+            let locales = if date_locales.is_empty() {
+                &self.personal.locales
+            } else {
+                date_locales
+            };
+            for format in regional_dates(bd, locales) {
+                if !numbers.contains(&format) {
+                    numbers.push(format);
                 }
             }
+        } else if let Some(age) = self.personal.age {
+            for born in birth_years(age, year.into()) {
+                numbers.extend(decompose_year(&born.to_string()));
+            }
+        }
+
+        // Phone digits, and the country's ways of writing them
+        if let Some(ref number) = self.personal.phone {
+            numbers.extend(phone::numbers(number, self.personal.country.as_deref()));
         }
 
         // Favorite number
@@ -148,25 +458,190 @@ impl Profile {
     }
 }
 
-fn push_opt(words: &mut Vec<String>, opt: &Option<String>) {
-    if let Some(ref s) = opt {
-        push_word(words, s);
+/// A person in the target's household: their name seeds and the fragments
+/// of their own birthdate, for pairing one person's name with another's
+/// numbers.
+#[derive(Debug, Clone)]
+pub struct FamilyMember {
+    pub names: Vec<SeedSource>,
+    pub numbers: Vec<String>,
+}
+
+/// A seed word and the profile field it was extracted from.
+#[derive(Debug, Clone)]
+pub struct SeedSource {
+    pub word: String,
+    pub field: &'static str,
+    /// Presets from the field, restricting which rules apply.
+    pub transforms: Vec<Transform>,
+}
+
+/// Broad kind of a seed word, used to decide which seeds are worth combining.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedClass {
+    /// Names of the target and their family
+    Name,
+    Pet,
+    /// Teams, bands, hobbies and colors
+    Interest,
+    /// SSID, router brand and ISP
+    Network,
+    /// Free-form words from the `[custom]` section
+    Custom,
+}
+
+impl SeedClass {
+    /// Whether seeds of these two classes commonly appear together in a
+    /// password. Anything paired with a name or pet qualifies, as do custom
+    /// words with each other; interest and network pairs rarely do.
+    pub fn pairs_well_with(self, other: SeedClass) -> bool {
+        use SeedClass::*;
+        matches!(
+            (self, other),
+            (Name | Pet, _) | (_, Name | Pet) | (Custom, Custom)
+        )
     }
 }
 
-fn push_word(words: &mut Vec<String>, s: &str) {
-    let trimmed = s.trim();
-    if !trimmed.is_empty() {
-        // Add the whole thing lowercased
-        words.push(trimmed.to_lowercase());
-        // If it contains spaces/hyphens, also add individual parts
-        for part in trimmed.split(|c: char| c == ' ' || c == '-' || c == '_') {
-            let p = part.trim().to_lowercase();
-            if !p.is_empty() && p != trimmed.to_lowercase() {
-                words.push(p);
-            }
+impl SeedSource {
+    /// Whether the field this seed came from carries `transform`.
+    pub fn has(&self, transform: Transform) -> bool {
+        self.transforms.contains(&transform)
+    }
+
+    /// The class of the profile field this seed came from.
+    pub fn class(&self) -> SeedClass {
+        match self.field {
+            "personal.pet_name" => SeedClass::Pet,
+            f if f.starts_with("personal.") => SeedClass::Name,
+            f if f.starts_with("interests.") => SeedClass::Interest,
+            f if f.starts_with("network.") => SeedClass::Network,
+            _ => SeedClass::Custom,
+        }
+    }
+}
+
+/// `field` without its section: "personal.pet_name" -> "pet_name".
+fn field_name(field: &str) -> &str {
+    field.rsplit('.').next().unwrap_or(field)
+}
+
+fn push_opt(words: &mut Vec<SeedSource>, field: &'static str, opt: &Option<Field>) {
+    if let Some(ref f) = opt {
+        push_word(words, field, f);
+    }
+}
+
+/// Push a given name plus its common nicknames (Robert -> rob, bob, bobby)
+/// and its alternate spellings in the profile's locales.
+fn push_name(words: &mut Vec<SeedSource>, field: &'static str, name: &Field, locales: &[String]) {
+    push_word(words, field, name);
+    if name.transforms().contains(&Transform::AsIs) {
+        return;
+    }
+    let lower = unicode::nfc(name.value().trim()).to_lowercase();
+    for nick in names::nicknames(&lower) {
+        push_derived(words, field, name, nick);
+    }
+    for variant in names::spelling_variants(&lower, locales) {
+        push_derived(words, field, name, variant);
+    }
+}
+
+/// Push a seed derived from `from` unless it is already present.
+fn push_derived(words: &mut Vec<SeedSource>, field: &'static str, from: &Field, word: String) {
+    if !words.iter().any(|w| w.word == word) {
+        words.push(SeedSource {
+            word,
+            field,
+            transforms: from.transforms().to_vec(),
+        });
+    }
+}
+
+fn push_word(words: &mut Vec<SeedSource>, field: &'static str, f: &Field) {
+    let transforms = f.transforms();
+    if f.value().trim().is_empty() {
+        return;
+    }
+    if transforms.contains(&Transform::AsIs) {
+        words.push(SeedSource {
+            word: f.value().trim().to_string(),
+            field,
+            transforms: transforms.to_vec(),
+        });
+        return;
+    }
+    // One spelling of accented letters, whichever way they were typed
+    let normalized = unicode::nfc(f.value());
+    let trimmed = normalized.trim();
+    let first = words.len();
+    // Add the whole thing lowercased
+    words.push(SeedSource {
+        word: trimmed.to_lowercase(),
+        field,
+        transforms: transforms.to_vec(),
+    });
+    // If it contains spaces/hyphens, also add individual parts
+    let parts: Vec<&str> = trimmed
+        .split([' ', '-', '_'])
+        .map(str::trim)
+        .filter(|p| !p.is_empty() && p.to_lowercase() != trimmed.to_lowercase())
+        .collect();
+    for part in &parts {
+        words.push(SeedSource {
+            word: part.to_lowercase(),
+            field,
+            transforms: transforms.to_vec(),
+        });
+    }
+    // Keep intentional capitalization (McDonald, iPhone) as a verbatim base,
+    // since lowercasing and the case mutations can't recover it
+    let mut preserved = transforms.to_vec();
+    preserved.extend([Transform::AsIs, Transform::NoMutations]);
+    for original in std::iter::once(trimmed).chain(parts) {
+        if has_intentional_case(original) && !words.iter().any(|w| w.word == original) {
+            words.push(SeedSource {
+                word: original.to_string(),
+                field,
+                transforms: preserved.clone(),
+            });
         }
     }
+    // The same words without their accents (josé -> jose)
+    let accented: Vec<String> = words[first..]
+        .iter()
+        .filter(|w| !w.word.is_ascii() && !w.has(Transform::AsIs))
+        .map(|w| unicode::to_ascii(&w.word))
+        .collect();
+    for word in accented {
+        push_derived(words, field, f, word);
+    }
+}
+
+/// Whether `s` is cased in a way the case mutations wouldn't reproduce: an
+/// uppercase letter inside a word that isn't all caps.
+fn has_intentional_case(s: &str) -> bool {
+    s.split([' ', '-', '_']).any(|part| {
+        part.chars().skip(1).any(char::is_uppercase)
+            && part.chars().any(char::is_lowercase)
+    })
+}
+
+/// Plausible birth years for someone `age` years old: the birthday may or
+/// may not have passed yet this year, plus one year of slack for stale ages.
+fn birth_years(age: u32, current_year: i64) -> Vec<i64> {
+    let year = current_year - i64::from(age);
+    vec![year, year - 1, year + 1]
+}
+
+/// Year fragments usable without month and day: 1990, 90.
+fn decompose_year(year: &str) -> Vec<String> {
+    let mut frags = vec![year.to_string()];
+    if year.len() == 4 {
+        frags.push(year[2..].to_string());
+    }
+    frags
 }
 
 /// Decompose a date string (YYYY-MM-DD) into useful number fragments.
@@ -178,10 +653,7 @@ fn decompose_date(date: &str) -> Vec<String> {
         let month = parts[1];
         let day = parts[2];
 
-        frags.push(year.to_string()); // 1990
-        if year.len() == 4 {
-            frags.push(year[2..].to_string()); // 90
-        }
+        frags.extend(decompose_year(year)); // 1990, 90
         frags.push(month.to_string()); // 05
         frags.push(day.to_string()); // 15
         frags.push(format!("{}{}", month, day)); // 0515
@@ -196,10 +668,47 @@ fn decompose_date(date: &str) -> Vec<String> {
     frags
 }
 
+/// A YYYY-MM-DD date in the formats of `locales`.
+fn regional_dates(date: &str, locales: &[String]) -> Vec<String> {
+    let mut parts = date.split('-');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(year), Some(month), Some(day)) => match (month.parse(), day.parse()) {
+            (Ok(month @ 1..=12), Ok(day)) => date::regional_formats(year, month, day, locales),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_topics() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [interests]
+            favorite_team = "Manchester United"
+            hobbies = ["chess", "Minecraft"]
+            "#,
+        )
+        .unwrap();
+        let interests = profile.interests();
+        assert_eq!(interests, ["manchesterunited", "chess", "minecraft"]);
+        assert_eq!(profile.topics(), ["sports", "gaming"]);
+
+        let sports = common::topic_words("sports", &interests);
+        assert_eq!(sports[0], "reddevils");
+        assert!(sports.contains(&"champions".to_string()));
+        assert!(!sports.contains(&"ynwa".to_string()));
+        let gaming = common::topic_words("gaming", &interests);
+        assert!(gaming.contains(&"creeper".to_string()));
+        assert!(!gaming.contains(&"pikachu".to_string()));
+        assert!(Profile::default().topics().is_empty());
+        assert!(common::is_topic("religious") && !common::is_topic("cooking"));
+    }
+
     #[test]
     fn test_decompose_date() {
         let frags = decompose_date("1990-05-15");
@@ -208,4 +717,176 @@ mod tests {
         assert!(frags.contains(&"0515".to_string()));
         assert!(frags.contains(&"051590".to_string()));
     }
+
+    #[test]
+    fn test_regional_birthdate() {
+        let profile: Profile =
+            toml::from_str("[personal]\nbirthdate = \"1990-05-15\"\nlocales = [\"nl\"]").unwrap();
+        let numbers = profile.seed_numbers(2026);
+        assert!(numbers.contains(&"15-05-1990".to_string()));
+        assert!(numbers.contains(&"15mei1990".to_string()));
+        // Fragments shared with the default set aren't repeated
+        assert_eq!(numbers.iter().filter(|n| *n == "15051990").count(), 1);
+
+        let german = profile.seed_numbers_in(&["de".to_string()], 2026);
+        assert!(german.contains(&"15.05.1990".to_string()));
+        assert!(!german.contains(&"15mei1990".to_string()));
+    }
+
+    #[test]
+    fn test_phone_numbers() {
+        let profile: Profile = toml::from_str("[personal]\nphone = \"07911 123456\"").unwrap();
+        assert_eq!(profile.seed_numbers(2026), ["07911123456", "3456"]);
+
+        let profile: Profile =
+            toml::from_str("[personal]\nphone = \"07911 123456\"\ncountry = \"gb\"").unwrap();
+        let numbers = profile.seed_numbers(2026);
+        assert!(numbers.contains(&"447911123456".to_string()));
+        assert!(numbers.contains(&"123456".to_string()));
+    }
+
+    #[test]
+    fn test_family() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            nickname = "Johnny"
+            birthdate = "1985-03-02"
+            partner_name = "Jane"
+            children_names = ["Emma", "Liam"]
+            children_birthdates = ["2015-07-04"]
+            "#,
+        )
+        .unwrap();
+        let family = profile.family(2026);
+        assert_eq!(family.len(), 4);
+        let words: Vec<&str> = family[0].names.iter().map(|n| n.word.as_str()).collect();
+        assert_eq!(words, ["john", "johnny"]);
+        assert!(family[0].numbers.contains(&"1985".to_string()));
+        // No birthdate for the partner or the second child
+        assert!(family[1].numbers.is_empty());
+        assert_eq!(family[2].names[0].word, "emma");
+        assert!(family[2].numbers.contains(&"0407".to_string()));
+        assert!(family[3].numbers.is_empty());
+    }
+
+    #[test]
+    fn test_birth_years_from_age() {
+        assert_eq!(birth_years(34, 2024), vec![1990, 1989, 1991]);
+
+        let profile: Profile = toml::from_str("[personal]\nage = 30").unwrap();
+        // Counted from the run's year, not the clock
+        let numbers = profile.seed_numbers(2026);
+        assert!(numbers.contains(&"1996".to_string()));
+        assert!(numbers.contains(&"96".to_string()));
+        assert!(!profile.seed_numbers(2030).contains(&"1996".to_string()));
+    }
+
+    #[test]
+    fn test_nicknames_derived() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "Robert"
+            partner_name = "Jonathan"
+            children_names = ["Nathaniel"]
+            "#,
+        )
+        .unwrap();
+        let words = profile.seed_words();
+        assert_eq!(words.iter().filter(|w| *w == "nate").count(), 1);
+        assert!(words.contains(&"bobby".to_string()));
+        assert!(words.contains(&"rob".to_string()));
+    }
+
+    #[test]
+    fn test_field_transforms() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = { value = "Robert", transforms = ["no-mutations"] }
+            [network]
+            ssid = { value = "Casa Azul", transforms = ["as-is"] }
+            "#,
+        )
+        .unwrap();
+        let seeds = profile.seed_sources();
+        let words: Vec<&str> = seeds.iter().map(|s| s.word.as_str()).collect();
+        assert_eq!(words.iter().filter(|w| **w == "Casa Azul").count(), 1);
+        assert!(!words.contains(&"casa"));
+        // Derived nicknames inherit the field's presets
+        let bob = seeds.iter().find(|s| s.word == "bob").unwrap();
+        assert!(bob.has(Transform::NoMutations));
+
+        let bogus = "[network]\nssid = { value = \"x\", transforms = [\"bogus\"] }";
+        assert!(toml::from_str::<Profile>(bogus).is_err());
+    }
+
+    #[test]
+    fn test_original_case_kept() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "John"
+            last_name = "McDonald"
+            [network]
+            ssid = "New York"
+            [custom]
+            words = ["my iPhone", "NASA"]
+            "#,
+        )
+        .unwrap();
+        let words = profile.seed_words();
+        assert!(words.contains(&"mcdonald".to_string()));
+        assert!(words.contains(&"McDonald".to_string()));
+        assert!(words.contains(&"my iPhone".to_string()));
+        assert!(words.contains(&"iPhone".to_string()));
+        assert_eq!(profile.cased_seeds(), ["McDonald", "my iPhone", "iPhone"]);
+
+        let mcdonald = profile
+            .seed_sources()
+            .into_iter()
+            .find(|s| s.word == "McDonald")
+            .unwrap();
+        assert!(mcdonald.has(Transform::AsIs));
+    }
+
+    #[test]
+    fn test_locale_variants() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "Sergey"
+            last_name = "Ivanov"
+            locales = ["ru"]
+            "#,
+        )
+        .unwrap();
+        let words = profile.seed_words();
+        assert!(words.contains(&"sergei".to_string()));
+        assert!(words.contains(&"ivanova".to_string()));
+    }
+
+    #[test]
+    fn test_accented_seeds() {
+        // José typed with a combining accent, Muñoz precomposed
+        let profile: Profile = toml::from_str(
+            r#"
+            [personal]
+            first_name = "Jose\u0301"
+            last_name = "Muñoz"
+            [custom]
+            words = ["Straße", { value = "Café", transforms = ["as-is"] }]
+            "#,
+        )
+        .unwrap();
+        let words = profile.seed_words();
+        let accented = ["josé", "muñoz", "straße", "Café"];
+        for expected in accented.into_iter().chain(["jose", "munoz", "strasse"]) {
+            assert!(words.contains(&expected.to_string()), "{}", expected);
+        }
+        assert!(!words.contains(&"jose\u{301}".to_string()));
+        assert!(!words.contains(&"cafe".to_string()));
+    }
 }
@@ -0,0 +1,388 @@
+//! Cracking results for other tools: the `crack-hash --format json`
+//! document (and `crack-batch`'s, one per job), and `--outfile` lines in
+//! hashcat's layouts.
+//!
+//! The JSON schema is small and flat, so it is written by hand.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{BufWriter, Write as _};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::batch::Outcome;
+use crate::cracker::{CrackResult, HashAlgorithm};
+
+/// Usernames by lowercased hash, from `user:hash` lines.
+pub type Users = HashMap<String, Vec<String>>;
+
+/// Split `user:hash` lines (hashcat's `--username` layout) into the unique
+/// hashes to crack and who uses each.
+pub fn split_usernames(lines: &[String]) -> Result<(Vec<String>, Users)> {
+    let mut hashes = Vec::new();
+    let mut users = Users::new();
+    for (i, line) in lines.iter().enumerate() {
+        let Some((user, hash)) = line.split_once(':') else {
+            bail!("Hash {} is not in user:hash form: {}", i + 1, line);
+        };
+        let key = hash.to_lowercase();
+        if !users.contains_key(&key) {
+            hashes.push(hash.to_string());
+        }
+        users.entry(key).or_default().push(user.to_string());
+    }
+    Ok((hashes, users))
+}
+
+/// Line layout of an outfile, after hashcat's `--outfile-format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutfileFormat {
+    HashPlain,
+    Plain,
+    /// One line per account using the hash; needs [`Users`].
+    UserPlain,
+}
+
+/// Cracked results appended to a file as they fall, flushed after every
+/// batch so they survive a scrolled terminal or a killed process.
+pub struct Outfile {
+    writer: BufWriter<File>,
+    format: OutfileFormat,
+    users: Users,
+    /// Hashes already written, so cumulative result lists can be passed in.
+    written: HashSet<String>,
+}
+
+impl Outfile {
+    /// Open `path` for appending, like hashcat, so reruns add to it.
+    pub fn open(path: &Path, format: OutfileFormat, users: Users) -> Result<Self> {
+        if format == OutfileFormat::UserPlain && users.is_empty() {
+            bail!("The user:plain outfile format needs user:hash input (--username)");
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open outfile: {}", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            format,
+            users,
+            written: HashSet::new(),
+        })
+    }
+
+    /// Append the results not written yet.
+    pub fn write(&mut self, results: &[CrackResult]) -> Result<()> {
+        for r in results {
+            let key = r.hash.to_lowercase();
+            if self.written.contains(&key) {
+                continue;
+            }
+            match self.format {
+                OutfileFormat::HashPlain => writeln!(self.writer, "{}:{}", r.hash, r.plaintext)?,
+                OutfileFormat::Plain => writeln!(self.writer, "{}", r.plaintext)?,
+                OutfileFormat::UserPlain => {
+                    for user in self.users.get(&key).into_iter().flatten() {
+                        writeln!(self.writer, "{}:{}", user, r.plaintext)?;
+                    }
+                }
+            }
+            self.written.insert(key);
+        }
+        self.writer
+            .flush()
+            .with_context(|| "Failed to flush outfile")
+    }
+}
+
+/// Which tier (or attack) each stretch of a candidate stream came from.
+#[derive(Debug, Default)]
+pub struct Tiers {
+    /// (end of the stretch, exclusive; name), in stream order.
+    spans: Vec<(u64, String)>,
+}
+
+impl Tiers {
+    /// Append a stretch of `candidates`, or one running to the end of the
+    /// stream when that is `None`.
+    pub fn push(&mut self, name: &str, candidates: Option<u64>) {
+        let start = self.spans.last().map_or(0, |&(end, _)| end);
+        let end = candidates.map_or(u64::MAX, |n| start.saturating_add(n));
+        self.spans.push((end, name.to_string()));
+    }
+
+    /// Candidates in all stretches, if none runs open-ended.
+    pub fn total(&self) -> Option<u64> {
+        self.spans
+            .last()
+            .and_then(|&(end, _)| (end != u64::MAX).then_some(end))
+    }
+
+    /// The tier the candidate at `index` came from.
+    pub fn at(&self, index: u64) -> Option<&str> {
+        self.spans
+            .iter()
+            .find(|&&(end, _)| index < end)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+/// One cracked hash and the tier its plaintext came from, if known.
+pub struct Entry<'a> {
+    pub result: &'a CrackResult,
+    pub tier: Option<&'a str>,
+}
+
+/// The run's results as a JSON document: every cracked hash with where
+/// and when it fell, then the targets still standing.
+pub fn to_json(algorithm: HashAlgorithm, targets: &[String], cracked: &[Entry]) -> String {
+    let algorithm = algorithm.to_string().to_lowercase();
+    let mut out = String::from("{\n");
+    let _ = writeln!(out, "  \"algorithm\": {},", string(&algorithm));
+    let _ = writeln!(out, "  \"targets\": {},", targets.len());
+
+    out += "  \"cracked\": [";
+    for (i, entry) in cracked.iter().enumerate() {
+        let r = entry.result;
+        let found = r.found.as_ref();
+        let fields = [
+            ("hash", string(&r.hash)),
+            ("plaintext", string(&r.plaintext)),
+            ("algorithm", string(&algorithm)),
+            ("index", optional(found.map(|f| f.index.to_string()))),
+            (
+                "elapsed_secs",
+                optional(found.map(|f| format!("{:.3}", f.elapsed.as_secs_f64()))),
+            ),
+            ("tier", optional(entry.tier.map(string))),
+        ];
+        out += if i == 0 { "\n    {" } else { ",\n    {" };
+        for (j, (key, value)) in fields.iter().enumerate() {
+            let sep = if j == 0 { "" } else { ", " };
+            let _ = write!(out, "{}\"{}\": {}", sep, key, value);
+        }
+        out += "}";
+    }
+    out += if cracked.is_empty() {
+        "],\n"
+    } else {
+        "\n  ],\n"
+    };
+
+    let uncracked: Vec<String> = targets
+        .iter()
+        .filter(|t| {
+            !cracked
+                .iter()
+                .any(|e| e.result.hash.eq_ignore_ascii_case(t))
+        })
+        .map(|t| string(t))
+        .collect();
+    let _ = writeln!(out, "  \"uncracked\": [{}]", uncracked.join(", "));
+    out += "}";
+    out
+}
+
+/// Several runs as one document, for `crack-batch`: per job its name, the
+/// candidates tested and the fields of [`to_json`], or the error it failed
+/// with.
+pub fn batch_to_json(jobs: &[(String, Result<Outcome, String>)]) -> String {
+    let mut out = String::from("{\n  \"jobs\": [");
+    for (i, (name, outcome)) in jobs.iter().enumerate() {
+        out += if i == 0 { "\n    {\n" } else { ",\n    {\n" };
+        let _ = write!(out, "      \"name\": {}", string(name));
+        match outcome {
+            Ok(outcome) => {
+                let _ = write!(
+                    out,
+                    ",\n      \"tested\": {},\n      \"elapsed_secs\": {:.3},\n",
+                    outcome.tested,
+                    outcome.elapsed.as_secs_f64()
+                );
+                let entries: Vec<Entry> = outcome
+                    .results
+                    .iter()
+                    .map(|result| Entry {
+                        result,
+                        tier: result.found.and_then(|f| outcome.tiers.at(f.index)),
+                    })
+                    .collect();
+                // The job's own document, one level in, without its braces
+                let document = to_json(outcome.algorithm, &outcome.targets, &entries);
+                let lines: Vec<&str> = document.lines().collect();
+                for line in &lines[1..lines.len() - 1] {
+                    let _ = writeln!(out, "    {}", line);
+                }
+            }
+            Err(error) => {
+                let _ = writeln!(out, ",\n      \"error\": {}", string(error));
+            }
+        }
+        out += "    }";
+    }
+    out += if jobs.is_empty() { "]\n}" } else { "\n  ]\n}" };
+    out
+}
+
+fn optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "null".to_string())
+}
+
+/// A JSON string literal.
+fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cracker::Found;
+    use std::time::Duration;
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(string("tab\there\u{1}"), r#""tab\there\u0001""#);
+        assert_eq!(string("pässwörd"), "\"pässwörd\"");
+    }
+
+    #[test]
+    fn test_split_usernames() {
+        let lines: Vec<String> = ["alice:AA", "bob:bb", "carol:aa"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let (hashes, users) = split_usernames(&lines).unwrap();
+        assert_eq!(hashes, ["AA", "bb"]);
+        assert_eq!(users["aa"], ["alice", "carol"]);
+        assert!(split_usernames(&["no-user".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_outfile_formats() {
+        let path = std::env::temp_dir().join(format!("pg-outfile-{}", std::process::id()));
+        let result = |hash: &str, plaintext: &str| CrackResult {
+            hash: hash.to_string(),
+            plaintext: plaintext.to_string(),
+            algorithm: HashAlgorithm::Md5,
+            found: None,
+        };
+        let (_, users) =
+            split_usernames(&["alice:aa".to_string(), "carol:AA".to_string()]).unwrap();
+        for (format, expected) in [
+            (OutfileFormat::HashPlain, "aa:x:y\n"),
+            (OutfileFormat::Plain, "x:y\n"),
+            (OutfileFormat::UserPlain, "alice:x:y\ncarol:x:y\n"),
+        ] {
+            let _ = std::fs::remove_file(&path);
+            let mut outfile = Outfile::open(&path, format, users.clone()).unwrap();
+            outfile.write(&[result("aa", "x:y")]).unwrap();
+            // Already written, so passing it again adds nothing
+            outfile.write(&[result("aa", "x:y")]).unwrap();
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+        }
+        assert!(Outfile::open(&path, OutfileFormat::UserPlain, Users::new()).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tiers() {
+        let mut tiers = Tiers::default();
+        tiers.push("Known guesses", Some(2));
+        tiers.push("Common passwords", Some(0));
+        tiers.push("Mutating seed words", Some(3));
+        tiers.push("Base wordlist", None);
+        assert_eq!(tiers.at(1), Some("Known guesses"));
+        assert_eq!(tiers.at(2), Some("Mutating seed words"));
+        assert_eq!(tiers.at(1_000_000), Some("Base wordlist"));
+        assert_eq!(Tiers::default().at(0), None);
+        assert_eq!(tiers.total(), None);
+        tiers.spans.pop();
+        assert_eq!(tiers.total(), Some(5));
+    }
+
+    #[test]
+    fn test_json_document() {
+        let targets = vec!["AA".to_string(), "bb".to_string()];
+        let result = CrackResult {
+            hash: "aa".to_string(),
+            plaintext: "pass\"word".to_string(),
+            algorithm: HashAlgorithm::Md5,
+            found: Some(Found {
+                index: 41,
+                elapsed: Duration::from_millis(1500),
+            }),
+        };
+        let json = to_json(
+            HashAlgorithm::Md5,
+            &targets,
+            &[Entry {
+                result: &result,
+                tier: Some("Known guesses"),
+            }],
+        );
+        assert!(json.contains(
+            r#"{"hash": "aa", "plaintext": "pass\"word", "algorithm": "md5", "index": 41, "elapsed_secs": 1.500, "tier": "Known guesses"}"#
+        ));
+        assert!(json.contains(r#""uncracked": ["bb"]"#));
+
+        let empty = to_json(HashAlgorithm::Ntlm, &targets, &[]);
+        assert!(empty.contains(r#""cracked": [],"#));
+        assert!(empty.contains(r#""uncracked": ["AA", "bb"]"#));
+    }
+
+    #[test]
+    fn test_batch_document() {
+        let mut tiers = Tiers::default();
+        tiers.push("Common passwords", Some(100));
+        let outcome = Outcome {
+            algorithm: HashAlgorithm::Md5,
+            targets: vec!["aa".to_string()],
+            results: vec![CrackResult {
+                hash: "aa".to_string(),
+                plaintext: "password".to_string(),
+                algorithm: HashAlgorithm::Md5,
+                found: Some(Found {
+                    index: 1,
+                    elapsed: Duration::ZERO,
+                }),
+            }],
+            tiers,
+            tested: 2,
+            elapsed: Duration::from_secs(1),
+        };
+        let json = batch_to_json(&[
+            ("alice".to_string(), Ok(outcome)),
+            ("bob".to_string(), Err("No hashes".to_string())),
+        ]);
+        assert!(json.starts_with(
+            "{\n  \"jobs\": [\n    {\n      \"name\": \"alice\",\n      \"tested\": 2,"
+        ));
+        assert!(json.contains("\n      \"algorithm\": \"md5\",\n"));
+        assert!(json.contains(r#""tier": "Common passwords"}"#));
+        assert!(json.contains("\n      \"uncracked\": []\n    },\n"));
+        assert!(json.ends_with(
+            "{\n      \"name\": \"bob\",\n      \"error\": \"No hashes\"\n    }\n  ]\n}"
+        ));
+        assert_eq!(batch_to_json(&[]), "{\n  \"jobs\": []\n}");
+    }
+}
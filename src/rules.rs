@@ -0,0 +1,232 @@
+//! User-defined mutation rules, read from a `--rules` file and applied to
+//! every seed word.
+//!
+//! One rule per line; a rule is a chain of operations separated by `|`,
+//! applied left to right. Blank lines and lines starting with `#` are
+//! skipped:
+//!
+//! ```text
+//! # Capitalize, then add the company's founding year
+//! toggle 0 | append 1987
+//! substitute a @ | substitute o 0
+//! truncate 4 | prepend !
+//! ```
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::hashcat;
+use crate::mutations::HashcatRule;
+
+/// One operation of a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// `append TEXT`
+    Append(String),
+    /// `prepend TEXT`
+    Prepend(String),
+    /// `substitute FROM TO`: every occurrence of FROM becomes TO
+    Substitute(String, String),
+    /// `toggle N` flips the case of the character at N (from 0); a bare
+    /// `toggle` flips every letter
+    Toggle(Option<usize>),
+    /// `truncate N` keeps the first N characters
+    Truncate(usize),
+}
+
+/// A chain of operations, kept in sessions as the line it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rule {
+    text: String,
+    ops: Vec<Op>,
+}
+
+impl Rule {
+    /// Parse one rule line, e.g. `toggle 0 | append 123`.
+    pub fn parse(line: &str) -> Result<Self> {
+        let text = line.trim();
+        let ops = text.split('|').map(parse_op).collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            text: text.to_string(),
+            ops,
+        })
+    }
+
+    /// Apply the operations to `word` in order.
+    pub fn apply(&self, word: &str) -> String {
+        self.ops.iter().fold(word.to_string(), |word, op| match op {
+            Op::Append(text) => word + text,
+            Op::Prepend(text) => format!("{}{}", text, word),
+            Op::Substitute(from, to) => word.replace(from.as_str(), to),
+            Op::Toggle(at) => word
+                .chars()
+                .enumerate()
+                .map(|(i, c)| match at {
+                    Some(n) if *n != i => c.to_string(),
+                    _ => toggle_case(c),
+                })
+                .collect(),
+            Op::Truncate(n) => word.chars().take(*n).collect(),
+        })
+    }
+
+    /// The rule in hashcat syntax, if it has one: `substitute` needs single
+    /// characters and positions stop at 35.
+    pub fn hashcat(&self) -> Option<String> {
+        let ops = self
+            .ops
+            .iter()
+            .map(|op| match op {
+                Op::Append(text) => Some(hashcat::append(text)),
+                Op::Prepend(text) => Some(hashcat::prepend(text)),
+                Op::Substitute(from, to) => {
+                    let (mut from, mut to) = (from.chars(), to.chars());
+                    match (from.next(), from.next(), to.next(), to.next()) {
+                        (Some(from), None, Some(to), None) => Some(hashcat::substitute(from, to)),
+                        _ => None,
+                    }
+                }
+                Op::Toggle(None) => Some("t".to_string()),
+                Op::Toggle(Some(n)) => hashcat::position(*n).map(|p| format!("T{}", p)),
+                Op::Truncate(n) => hashcat::position(*n).map(|p| format!("'{}", p)),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(ops.join(" "))
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = anyhow::Error;
+
+    fn try_from(line: String) -> Result<Self> {
+        Rule::parse(&line)
+    }
+}
+
+impl From<Rule> for String {
+    fn from(rule: Rule) -> Self {
+        rule.text
+    }
+}
+
+/// Read the rules in `path`, naming the line of the first bad one.
+pub fn load(path: &Path) -> Result<Vec<Rule>> {
+    load_lines(path, Rule::parse)
+}
+
+/// Read the hashcat rules in `path` (best64.rule and the like), naming the
+/// line of the first bad one.
+pub fn load_hashcat(path: &Path) -> Result<Vec<HashcatRule>> {
+    load_lines(path, HashcatRule::parse)
+}
+
+fn load_lines<T>(path: &Path, parse: impl Fn(&str) -> Result<T>) -> Result<Vec<T>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rules: {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| parse(l).with_context(|| format!("{} line {}", path.display(), i + 1)))
+        .collect()
+}
+
+fn parse_op(op: &str) -> Result<Op> {
+    let mut args = op.split_whitespace();
+    let name = args.next().unwrap_or_default();
+    let args: Vec<&str> = args.collect();
+    let count = |arg: &str| -> Result<usize> {
+        arg.parse()
+            .with_context(|| format!("'{}' needs a number, got '{}'", name, arg))
+    };
+    Ok(match (name, args.as_slice()) {
+        ("append", [text]) => Op::Append(text.to_string()),
+        ("prepend", [text]) => Op::Prepend(text.to_string()),
+        ("substitute", [from, to]) => Op::Substitute(from.to_string(), to.to_string()),
+        ("toggle", []) => Op::Toggle(None),
+        ("toggle", [n]) => Op::Toggle(Some(count(n)?)),
+        ("truncate", [n]) => Op::Truncate(count(n)?),
+        ("append" | "prepend" | "truncate", _) => bail!("'{}' takes one argument", name),
+        ("substitute", _) => bail!("'substitute' takes two arguments, FROM and TO"),
+        ("toggle", _) => bail!("'toggle' takes at most one argument"),
+        ("", _) => bail!("Empty operation"),
+        _ => bail!(
+            "Unknown operation '{}' (append, prepend, substitute, toggle or truncate)",
+            name
+        ),
+    })
+}
+
+fn toggle_case(c: char) -> String {
+    if c.is_uppercase() {
+        c.to_lowercase().collect()
+    } else {
+        c.to_uppercase().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(rule: &str, word: &str) -> String {
+        Rule::parse(rule).unwrap().apply(word)
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(apply("append 123", "buddy"), "buddy123");
+        assert_eq!(apply("prepend #", "buddy"), "#buddy");
+        assert_eq!(apply("substitute d 7", "buddy"), "bu77y");
+        assert_eq!(apply("toggle 0 | append 1987", "buddy"), "Buddy1987");
+        assert_eq!(apply("toggle", "Buddy"), "bUDDY");
+        assert_eq!(apply("toggle 9", "buddy"), "buddy");
+        assert_eq!(apply("truncate 3 | append 90", "buddy"), "bud90");
+        assert_eq!(apply("truncate 9", "buddy"), "buddy");
+    }
+
+    #[test]
+    fn test_hashcat() {
+        let hashcat = |rule: &str| Rule::parse(rule).unwrap().hashcat();
+        assert_eq!(hashcat("toggle 0 | append 1987").unwrap(), "T0 $1 $9 $8 $7");
+        assert_eq!(hashcat("prepend !# | toggle").unwrap(), "^# ^! t");
+        assert_eq!(hashcat("substitute a @ | truncate 12").unwrap(), "sa@ 'C");
+        assert_eq!(hashcat("substitute ph f"), None);
+        assert_eq!(hashcat("toggle 40"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Rule::parse("append").is_err());
+        assert!(Rule::parse("substitute a").is_err());
+        assert!(Rule::parse("truncate x").is_err());
+        assert!(Rule::parse("append 1 |").is_err());
+        assert!(Rule::parse("reverse").is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("pg-rules-{}", std::process::id()));
+        std::fs::write(&path, "# comment\n\ntoggle 0 | append !\nappend 1\n").unwrap();
+        let rules = load(&path).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(String::from(rules[0].clone()), "toggle 0 | append !");
+
+        std::fs::write(&path, "append 1\nbogus\n").unwrap();
+        let err = format!("{:#}", load(&path).unwrap_err());
+        assert!(err.contains("line 2"), "{}", err);
+
+        std::fs::write(&path, "## best64\n:\nc $1\n$ \nsa@ ]\n").unwrap();
+        let rules = load_hashcat(&path).unwrap();
+        assert_eq!(rules.len(), 4);
+        assert_eq!(rules[2].apply("john").unwrap(), "john ");
+        std::fs::write(&path, "c $1\nT\n").unwrap();
+        let err = format!("{:#}", load_hashcat(&path).unwrap_err());
+        assert!(err.contains("line 2"), "{}", err);
+        assert!(err.contains("'T' needs a position"), "{}", err);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+//! Random samples of a keyspace, for strength research and test fixtures.
+//!
+//! Keyspaces with random access (masks, combinator attacks) are sampled by
+//! drawing indices, so a sample of a 10^15 mask costs as much as the sample
+//! itself. Streams are sampled with a reservoir, which walks the stream but
+//! holds only the sample. Either way a sample keeps the order the full run
+//! would have written it in, and a seed reproduces it exactly.
+
+use std::collections::HashSet;
+
+/// A seeded SplitMix64 generator: fast, and the same sequence everywhere.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number below `n`, which must be positive.
+    pub fn below(&mut self, n: u128) -> u128 {
+        let wide = (self.next_u64() as u128) << 64 | self.next_u64() as u128;
+        wide % n
+    }
+
+    /// A number strictly between 0 and 1.
+    fn unit(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+}
+
+/// Draws a fixed number of candidates from a keyspace.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    size: usize,
+    rng: Rng,
+}
+
+impl Sampler {
+    pub fn new(size: usize, seed: u64) -> Self {
+        Self {
+            size,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// `size` distinct indices below `keyspace`, ascending (all of them if
+    /// the keyspace is smaller).
+    pub fn indices(&mut self, keyspace: u128) -> Vec<u128> {
+        if keyspace <= self.size as u128 {
+            return (0..keyspace).collect();
+        }
+        // Floyd's algorithm: one draw per index, whatever the keyspace
+        let mut picked = HashSet::with_capacity(self.size);
+        for j in keyspace - self.size as u128..keyspace {
+            let t = self.rng.below(j + 1);
+            if !picked.insert(t) {
+                picked.insert(j);
+            }
+        }
+        let mut indices: Vec<u128> = picked.into_iter().collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    /// `size` of `items` chosen uniformly at random, in stream order.
+    pub fn sample<I: Iterator<Item = String>>(&mut self, items: I) -> Vec<String> {
+        if self.size == 0 {
+            return Vec::new();
+        }
+        let mut items = items.enumerate();
+        let mut reservoir: Vec<(usize, String)> = items.by_ref().take(self.size).collect();
+
+        // Algorithm L: skip ahead by a random gap instead of drawing per item
+        let n = self.size as f64;
+        let mut w = (self.rng.unit().ln() / n).exp();
+        if reservoir.len() == self.size {
+            loop {
+                let skip = (self.rng.unit().ln() / (1.0 - w).ln()).floor() as usize;
+                let Some(item) = items.nth(skip) else {
+                    break;
+                };
+                let slot = self.rng.below(self.size as u128) as usize;
+                reservoir[slot] = item;
+                w *= (self.rng.unit().ln() / n).exp();
+            }
+        }
+        reservoir.sort_unstable_by_key(|&(i, _)| i);
+        reservoir.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indices() {
+        let indices = Sampler::new(100, 7).indices(1_000_000_000_000);
+        assert_eq!(indices.len(), 100);
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        assert!(indices.iter().all(|&i| i < 1_000_000_000_000));
+        assert_eq!(indices, Sampler::new(100, 7).indices(1_000_000_000_000));
+        assert_ne!(indices, Sampler::new(100, 8).indices(1_000_000_000_000));
+
+        assert_eq!(Sampler::new(10, 1).indices(4), [0, 1, 2, 3]);
+        assert_eq!(Sampler::new(5, 1).indices(5).len(), 5);
+    }
+
+    #[test]
+    fn test_sample() {
+        let items = || (0..10_000).map(|i| format!("{:05}", i));
+        let sample = Sampler::new(50, 42).sample(items());
+        assert_eq!(sample.len(), 50);
+        // Stream order, no repeats, and the same again with the same seed
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sample, Sampler::new(50, 42).sample(items()));
+
+        // Spread over the whole stream, not bunched at its start
+        let late = sample.iter().filter(|s| s.as_str() >= "05000").count();
+        assert!((10..=40).contains(&late), "{}", late);
+
+        assert_eq!(Sampler::new(50, 1).sample(items().take(3)).len(), 3);
+        assert!(Sampler::new(0, 1).sample(items()).is_empty());
+    }
+}
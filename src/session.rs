@@ -0,0 +1,376 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+
+use crate::brute::BruteSpec;
+use crate::cracker::CrackResult;
+use crate::generator::{self, GeneratorConfig};
+use crate::keyboard::Layout;
+use crate::mutations::HashcatRule;
+use crate::policy::Policy;
+use crate::profile::Profile;
+use crate::rules::Rule;
+use crate::template::Template;
+
+/// How often checkpoints are flushed to disk during a run.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A resumable cracking session, saved as TOML.
+///
+/// The candidate stream is deterministic for a given profile and config (or
+/// brute-force spec, or wordlist file), so the number of candidates already
+/// tested is enough to pick up where a run stopped.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    /// Profile the candidates are generated from; unset for brute-force runs.
+    #[serde(default)]
+    pub profile: Option<PathBuf>,
+    /// SHA256 of the profile file, to refuse resuming against an edited profile.
+    #[serde(default)]
+    pub profile_hash: Option<String>,
+    /// Brute-force settings, for runs that don't use a profile.
+    #[serde(default)]
+    pub brute: Option<BruteSpec>,
+    /// Wordlist file the candidates are read from, for wordlist runs.
+    #[serde(default)]
+    pub wordlist: Option<PathBuf>,
+    /// Size of the wordlist in bytes, to refuse resuming against a changed file.
+    #[serde(default)]
+    pub wordlist_size: Option<u64>,
+    /// Hash algorithm name, or "wpa" for handshakes.
+    pub algorithm: String,
+    /// Target hashes, or the handshake path for WPA.
+    pub targets: Vec<String>,
+    pub depth: u8,
+    pub min_length: usize,
+    pub max_length: usize,
+    /// Sessions from before seed-class filtering combined every pair.
+    #[serde(default = "default_all_pairs")]
+    pub all_pairs: bool,
+    /// Candidates within each tier were ordered by Markov likelihood.
+    #[serde(default)]
+    pub markov: bool,
+    /// Tiers were scheduled likelihood-first for cracking; older sessions
+    /// walked them in wordlist order.
+    #[serde(default)]
+    pub likely_first: bool,
+    /// Separators the seed words were joined with, when set on the command
+    /// line.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub separators: Vec<String>,
+    /// Years suffixed, as resolved when the session started; older sessions
+    /// always used 1950-2026.
+    #[serde(default = "default_first_year")]
+    pub first_year: u16,
+    #[serde(default = "default_last_year")]
+    pub last_year: u16,
+    /// Year the recent years were appended around, as resolved when the
+    /// session started; older sessions appended none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent_year: Option<u16>,
+    /// Locales the birthdate was written for, when set on the command line.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub date_formats: Vec<String>,
+    /// Layout the keyboard walks were typed on; older sessions used QWERTY.
+    #[serde(default)]
+    pub keyboard: Layout,
+    /// Password policy the candidates were filtered by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<Policy>,
+    /// Dictionary blended in as extra seed words, and its size in bytes, to
+    /// refuse resuming against a changed file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_wordlist: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_wordlist_size: Option<u64>,
+    /// User-defined mutation rules, as written in the rules file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// hashcat rules, as written in the rule file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashcat_rules: Vec<HashcatRule>,
+    /// Most mid-word insertions; older sessions made none.
+    #[serde(default)]
+    pub insertions: usize,
+    /// Most letters case-toggled at once; older sessions toggled none.
+    #[serde(default)]
+    pub case_toggles: usize,
+    /// Seed words were rotated; older sessions rotated none.
+    #[serde(default)]
+    pub rotations: bool,
+    /// False-positive rate of the Bloom filter dedup, if one was used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bloom: Option<f64>,
+    /// Topic dictionaries blended in, if any; older sessions had none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topics: Option<Vec<String>>,
+    /// Target type whose tier was generated; older sessions had none.
+    #[serde(default)]
+    pub template: Template,
+    /// Number of candidates fully tested so far.
+    pub position: u64,
+    #[serde(default)]
+    pub cracked: Vec<CrackedEntry>,
+
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    last_saved: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrackedEntry {
+    pub hash: String,
+    pub plaintext: String,
+}
+
+impl Session {
+    /// A session with `config`'s generator settings and no candidate
+    /// source yet; the constructors below fill in their own.
+    fn base(
+        path: &Path,
+        algorithm: &str,
+        targets: Vec<String>,
+        config: &GeneratorConfig,
+    ) -> Self {
+        Self {
+            profile: None,
+            profile_hash: None,
+            brute: None,
+            wordlist: None,
+            wordlist_size: None,
+            algorithm: algorithm.to_lowercase(),
+            targets,
+            depth: config.depth,
+            min_length: config.min_length,
+            max_length: config.max_length,
+            all_pairs: config.all_pairs,
+            markov: config.markov,
+            likely_first: config.likely_first,
+            separators: config.separators.clone(),
+            date_formats: config.date_formats.clone(),
+            keyboard: config.keyboard,
+            policy: config.policy,
+            base_wordlist: config.base_wordlist.clone(),
+            rules: config.rules.clone(),
+            hashcat_rules: config.hashcat_rules.clone(),
+            insertions: config.insertions,
+            case_toggles: config.case_toggles,
+            rotations: config.rotations,
+            bloom: config.bloom,
+            topics: config.topics.clone(),
+            template: config.template,
+            base_wordlist_size: None,
+            first_year: default_first_year(),
+            last_year: default_last_year(),
+            recent_year: config.recent_year,
+            position: 0,
+            cracked: Vec::new(),
+            path: path.to_path_buf(),
+            last_saved: None,
+        }
+    }
+
+    /// Start a new session that will be checkpointed to `path`.
+    pub fn new(
+        path: &Path,
+        profile: &Path,
+        algorithm: &str,
+        targets: Vec<String>,
+        config: &GeneratorConfig,
+    ) -> Result<Self> {
+        let years = match config.years {
+            Some(ref years) => years.clone(),
+            None => generator::default_years(&Profile::load(profile)?, config.year()),
+        };
+        let base_wordlist_size = match config.base_wordlist {
+            Some(ref base) => Some(wordlist_size(base)?),
+            None => None,
+        };
+        Ok(Self {
+            profile: Some(profile.to_path_buf()),
+            profile_hash: Some(profile_hash(profile)?),
+            base_wordlist_size,
+            first_year: *years.start(),
+            last_year: *years.end(),
+            ..Self::base(path, algorithm, targets, config)
+        })
+    }
+
+    /// Start a new brute-force session that will be checkpointed to `path`.
+    pub fn new_brute(path: &Path, brute: BruteSpec, algorithm: &str, targets: Vec<String>) -> Self {
+        Self {
+            brute: Some(brute),
+            ..Self::base(path, algorithm, targets, &GeneratorConfig::default())
+        }
+    }
+
+    /// Start a new wordlist session that will be checkpointed to `path`.
+    pub fn new_wordlist(
+        path: &Path,
+        wordlist: &Path,
+        algorithm: &str,
+        targets: Vec<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            wordlist: Some(wordlist.to_path_buf()),
+            wordlist_size: Some(wordlist_size(wordlist)?),
+            ..Self::base(path, algorithm, targets, &GeneratorConfig::default())
+        })
+    }
+
+    /// Add a base wordlist, tried after the profile's candidates.
+    pub fn with_wordlist(mut self, wordlist: &Path) -> Result<Self> {
+        self.wordlist_size = Some(wordlist_size(wordlist)?);
+        self.wordlist = Some(wordlist.to_path_buf());
+        Ok(self)
+    }
+
+    /// Load a session file and check that its profile or wordlist hasn't
+    /// changed.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read session: {}", path.display()))?;
+        let mut session: Session =
+            toml::from_str(&content).with_context(|| "Failed to parse session file")?;
+
+        // A profile, a wordlist or both; or brute-force settings alone
+        match (&session.profile, &session.profile_hash, &session.brute) {
+            (Some(profile), Some(hash), None) => {
+                if profile_hash(profile)? != *hash {
+                    bail!(
+                        "Profile {} changed since the session was created; start a new session",
+                        profile.display()
+                    );
+                }
+            }
+            (None, None, Some(_)) if session.wordlist.is_none() => {}
+            (None, None, None) if session.wordlist.is_some() => {}
+            _ => bail!(
+                "Session {} has no profile, brute-force settings or wordlist",
+                path.display()
+            ),
+        }
+        for (wordlist, size) in [
+            (&session.wordlist, session.wordlist_size),
+            (&session.base_wordlist, session.base_wordlist_size),
+        ] {
+            if let Some(wordlist) = wordlist {
+                if Some(wordlist_size(wordlist)?) != size {
+                    bail!(
+                        "Wordlist {} changed since the session was created; start a new session",
+                        wordlist.display()
+                    );
+                }
+            }
+        }
+        session.path = path.to_path_buf();
+        Ok(session)
+    }
+
+    /// Profile the session was started with, for profile-based runs.
+    pub fn profile_path(&self) -> Result<PathBuf> {
+        match self.profile {
+            Some(ref profile) => Ok(profile.clone()),
+            None => bail!("{} is not a profile session", self.path.display()),
+        }
+    }
+
+    /// File the session is checkpointed to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Generator settings the session was started with.
+    pub fn generator_config(&self) -> GeneratorConfig {
+        GeneratorConfig {
+            depth: self.depth,
+            min_length: self.min_length,
+            max_length: self.max_length,
+            all_pairs: self.all_pairs,
+            markov: self.markov,
+            likely_first: self.likely_first,
+            separators: self.separators.clone(),
+            years: Some(self.first_year..=self.last_year),
+            recent_year: self.recent_year,
+            date_formats: self.date_formats.clone(),
+            keyboard: self.keyboard,
+            policy: self.policy,
+            base_wordlist: self.base_wordlist.clone(),
+            rules: self.rules.clone(),
+            hashcat_rules: self.hashcat_rules.clone(),
+            insertions: self.insertions,
+            case_toggles: self.case_toggles,
+            rotations: self.rotations,
+            bloom: self.bloom,
+            topics: self.topics.clone(),
+            template: self.template,
+            ..GeneratorConfig::default()
+        }
+    }
+
+    /// Targets not yet cracked in an earlier run.
+    pub fn remaining_targets(&self) -> Vec<String> {
+        self.targets
+            .iter()
+            .filter(|t| !self.cracked.iter().any(|c| c.hash.eq_ignore_ascii_case(t)))
+            .cloned()
+            .collect()
+    }
+
+    /// Record progress at `start + tested` and save if the interval elapsed.
+    pub fn checkpoint(&mut self, start: u64, tested: u64, results: &[CrackResult]) -> Result<()> {
+        self.position = start + tested;
+        for r in results {
+            if !self.cracked.iter().any(|c| c.hash == r.hash) {
+                self.cracked.push(CrackedEntry {
+                    hash: r.hash.clone(),
+                    plaintext: r.plaintext.clone(),
+                });
+            }
+        }
+        if self.last_saved.is_none_or(|t| t.elapsed() >= CHECKPOINT_INTERVAL) {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Write the session atomically (temp file + rename).
+    pub fn save(&mut self) -> Result<()> {
+        let content = toml::to_string_pretty(self).with_context(|| "Failed to serialize session")?;
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, content)
+            .with_context(|| format!("Failed to write session: {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write session: {}", self.path.display()))?;
+        self.last_saved = Some(Instant::now());
+        Ok(())
+    }
+}
+
+fn default_all_pairs() -> bool {
+    true
+}
+
+fn default_first_year() -> u16 {
+    generator::FIRST_YEAR
+}
+
+fn default_last_year() -> u16 {
+    2026
+}
+
+fn wordlist_size(wordlist: &Path) -> Result<u64> {
+    let metadata = std::fs::metadata(wordlist)
+        .with_context(|| format!("Failed to read wordlist: {}", wordlist.display()))?;
+    Ok(metadata.len())
+}
+
+fn profile_hash(profile: &Path) -> Result<String> {
+    let content = std::fs::read(profile)
+        .with_context(|| format!("Failed to read profile: {}", profile.display()))?;
+    Ok(hex::encode(sha2::Sha256::digest(&content)))
+}
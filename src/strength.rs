@@ -0,0 +1,302 @@
+//! Guessability scores in the manner of zxcvbn: a password is split into the
+//! cheapest run of patterns (dictionary words, keyboard walks, repeats,
+//! sequences, years, brute force between them), and the guesses that run
+//! takes are bucketed into a score from 0 (guessed at once) to 4 (out of
+//! reach of an online attack and most offline ones).
+
+use std::collections::HashMap;
+
+use crate::common;
+use crate::date;
+use crate::keyboard::Layout;
+
+/// Dictionaries searched for words, besides the common passwords.
+const LANGUAGES: [&str; 5] = ["en", "es", "id", "de", "fr"];
+
+/// Guesses per character for text no pattern explains.
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+
+/// Guesses an attacker spends before trying runs of one more pattern.
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10_000.0;
+
+/// Fewest guesses a pattern inside a longer password is credited with.
+const MIN_SUBMATCH_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_MULTI_CHAR: f64 = 50.0;
+
+/// Shortest dictionary word, walk, repeat or sequence matched.
+const MIN_MATCH: usize = 3;
+
+/// Fewest years a year pattern is credited with.
+const MIN_YEAR_SPACE: f64 = 20.0;
+
+/// Typed substitutions, undone before dictionary lookups.
+const LEET: [(char, char); 8] = [
+    ('4', 'a'),
+    ('@', 'a'),
+    ('3', 'e'),
+    ('1', 'i'),
+    ('!', 'i'),
+    ('0', 'o'),
+    ('$', 's'),
+    ('7', 't'),
+];
+
+/// Scores passwords against the embedded dictionaries. Building one loads
+/// the dictionaries, so build it once and score many candidates with it.
+pub struct Estimator {
+    /// Each dictionary word's rank, lowest across the dictionaries
+    ranks: HashMap<String, usize>,
+    year: i64,
+}
+
+impl Default for Estimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Estimator {
+    pub fn new() -> Self {
+        let mut ranks = HashMap::new();
+        let dictionaries = std::iter::once(common::common_passwords())
+            .chain(LANGUAGES.iter().map(|l| common::language_words(l)));
+        for words in dictionaries {
+            for (i, word) in words.into_iter().enumerate() {
+                let rank = ranks.entry(word.to_lowercase()).or_insert(i + 1);
+                *rank = (*rank).min(i + 1);
+            }
+        }
+        Self {
+            ranks,
+            year: date::current_year(),
+        }
+    }
+
+    /// Estimated guesses to find `password`, as a power of ten.
+    pub fn guesses_log10(&self, password: &str) -> f64 {
+        let chars: Vec<char> = password.chars().collect();
+        let n = chars.len();
+        if n == 0 {
+            return 0.0;
+        }
+
+        // best[j][l]: fewest guesses (log10 of their product) to cover the
+        // first j characters with l patterns
+        let mut best = vec![vec![f64::INFINITY; n + 1]; n + 1];
+        best[0][0] = 0.0;
+        for j in 1..=n {
+            for i in 0..j {
+                let span = &chars[i..j];
+                let mut guesses = self.pattern_guesses(span).unwrap_or(f64::INFINITY);
+                guesses = guesses.min(BRUTEFORCE_CARDINALITY.powi(span.len() as i32));
+                if span.len() < n {
+                    let floor = match span.len() {
+                        1 => MIN_SUBMATCH_GUESSES_SINGLE_CHAR,
+                        _ => MIN_SUBMATCH_GUESSES_MULTI_CHAR,
+                    };
+                    guesses = guesses.max(floor);
+                }
+                let cost = guesses.log10();
+                for l in 1..=j {
+                    let total = best[i][l - 1] + cost;
+                    if total < best[j][l] {
+                        best[j][l] = total;
+                    }
+                }
+            }
+        }
+
+        // A run of l patterns: l! orderings of them, after everything
+        // shorter has been tried
+        (1..=n)
+            .filter(|&l| best[n][l].is_finite())
+            .map(|l| {
+                let orderings: f64 = (1..=l).map(|k| (k as f64).log10()).sum();
+                let run = best[n][l] + orderings;
+                let shorter = MIN_GUESSES_BEFORE_GROWING_SEQUENCE.log10() * (l - 1) as f64;
+                log10_sum(run, shorter)
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Score from 0 (too guessable) to 4 (very unguessable), on zxcvbn's
+    /// thresholds of 10^3, 10^6, 10^8 and 10^10 guesses.
+    pub fn score(&self, password: &str) -> u8 {
+        match self.guesses_log10(password) {
+            g if g < 3.0 => 0,
+            g if g < 6.0 => 1,
+            g if g < 8.0 => 2,
+            g if g < 10.0 => 3,
+            _ => 4,
+        }
+    }
+
+    /// Guesses for `span` as the cheapest single pattern it matches.
+    fn pattern_guesses(&self, span: &[char]) -> Option<f64> {
+        [
+            self.dictionary(span),
+            repeat(span),
+            sequence(span),
+            walk(span),
+            self.recent_year(span),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(f64::min)
+    }
+
+    /// A dictionary word, possibly capitalized, reversed or in leet.
+    fn dictionary(&self, span: &[char]) -> Option<f64> {
+        if span.len() < MIN_MATCH {
+            return None;
+        }
+        let lower: String = span.iter().flat_map(|c| c.to_lowercase()).collect();
+        let unleeted: String = lower.chars().map(unleet).collect();
+        let reversed: String = lower.chars().rev().collect();
+        let case = case_variations(span);
+        let leet = 2f64.powi(lower.chars().filter(|&c| unleet(c) != c).count() as i32);
+        [
+            (self.ranks.get(&lower), 1.0),
+            (self.ranks.get(&reversed), 2.0),
+            (
+                self.ranks.get(&unleeted).filter(|_| unleeted != lower),
+                leet,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(rank, factor)| rank.map(|&r| r as f64 * case * factor))
+        .reduce(f64::min)
+    }
+
+    /// A plausible year, credited with its distance from now.
+    fn recent_year(&self, span: &[char]) -> Option<f64> {
+        if span.len() != 4 || !span.iter().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let year: i64 = span.iter().collect::<String>().parse().ok()?;
+        (1900..=2099)
+            .contains(&year)
+            .then(|| ((year - self.year).abs() as f64).max(MIN_YEAR_SPACE))
+    }
+}
+
+/// One character over and over (`aaaa`, `1111`).
+fn repeat(span: &[char]) -> Option<f64> {
+    if span.len() < MIN_MATCH || span.iter().any(|&c| c != span[0]) {
+        return None;
+    }
+    Some(cardinality(span[0]) * span.len() as f64)
+}
+
+/// Characters one code point apart, up or down (`abcd`, `9876`).
+fn sequence(span: &[char]) -> Option<f64> {
+    if span.len() < MIN_MATCH {
+        return None;
+    }
+    let step = span[1] as i64 - span[0] as i64;
+    let steady = span.windows(2).all(|w| w[1] as i64 - w[0] as i64 == step);
+    if !steady || step.abs() != 1 {
+        return None;
+    }
+    // Sequences from an obvious start are tried first
+    let start = match span[0] {
+        'a' | 'z' | 'A' | 'Z' | '0' | '1' | '9' => 4.0,
+        c => cardinality(c),
+    };
+    let direction = if step < 0 { 2.0 } else { 1.0 };
+    Some(start * span.len() as f64 * direction)
+}
+
+/// Neighbouring keys along a QWERTY row (`asdf`, `poiuy`).
+fn walk(span: &[char]) -> Option<f64> {
+    if span.len() < MIN_MATCH {
+        return None;
+    }
+    let lower: Vec<char> = span.iter().flat_map(|c| c.to_lowercase()).collect();
+    let adjacent = lower
+        .windows(2)
+        .all(|w| Layout::Qwerty.neighbors(w[0]).contains(&w[1]));
+    // A starting key, then left or right at each step
+    adjacent.then(|| 40.0 * 2f64.powi(span.len() as i32 - 1))
+}
+
+/// Guesses per character of the class `c` is in.
+fn cardinality(c: char) -> f64 {
+    if c.is_ascii_digit() {
+        10.0
+    } else if c.is_alphabetic() {
+        26.0
+    } else {
+        33.0
+    }
+}
+
+/// The letter a leet character stands for, or `c` itself.
+fn unleet(c: char) -> char {
+    LEET.iter()
+        .find(|(l, _)| *l == c)
+        .map_or(c, |&(_, plain)| plain)
+}
+
+/// Ways of capitalizing a word an attacker would try before this one:
+/// 1 for lowercase, 2 for a capital first or last letter or all capitals,
+/// and otherwise every way to place that many capitals.
+fn case_variations(span: &[char]) -> f64 {
+    let upper = span.iter().filter(|c| c.is_uppercase()).count();
+    let lower = span.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        return 1.0;
+    }
+    let first = span[0].is_uppercase() && upper == 1;
+    let last = span[span.len() - 1].is_uppercase() && upper == 1;
+    if first || last || lower == 0 {
+        return 2.0;
+    }
+    (1..=upper.min(lower))
+        .map(|k| binomial(upper + lower, k))
+        .sum()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (1..=k).fold(1.0, |acc, i| acc * (n + 1 - i) as f64 / i as f64)
+}
+
+/// log10(10^a + 10^b).
+fn log10_sum(a: f64, b: f64) -> f64 {
+    let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+    hi + (1.0 + 10f64.powf(lo - hi)).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores() {
+        let estimator = Estimator::new();
+        for weak in ["password", "123456", "qwerty", "aaaaaa", "abcdef"] {
+            assert_eq!(estimator.score(weak), 0, "{}", weak);
+        }
+        assert!(estimator.score("Password1") <= 1);
+        assert!(estimator.score("p@ssw0rd") <= 1);
+        assert!(estimator.score("drowssap") <= 1);
+        assert_eq!(estimator.score("xK#9vQ!2mZ$7wL"), 4);
+
+        // Each pattern is cheaper than typing the same length at random
+        let random = estimator.guesses_log10("qzxjvkwp");
+        for patterned in ["asdfghjk", "abcdefgh", "zzzzzzzz", "monkey12"] {
+            assert!(estimator.guesses_log10(patterned) < random, "{}", patterned);
+        }
+    }
+
+    #[test]
+    fn test_years_and_case() {
+        let estimator = Estimator::new();
+        let recent = estimator.guesses_log10("monkey2020");
+        let distant = estimator.guesses_log10("monkey1903");
+        assert!(recent < distant);
+        assert!(estimator.guesses_log10("Monkey") < estimator.guesses_log10("mOnKeY"));
+        assert_eq!(case_variations(&['B', 'U', 'D']), 2.0);
+        assert_eq!(binomial(5, 2), 10.0);
+    }
+}
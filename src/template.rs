@@ -0,0 +1,373 @@
+//! Target-type templates: candidates for the kind of account under attack
+//! that the profile tiers don't produce. Corporate accounts follow helpdesk
+//! conventions (the company name and the year, `Welcome1`, `Summer2024!`),
+//! routers their vendors' defaults and SSID; a personal account gets the
+//! profile tiers alone.
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::profile::{Field, Profile};
+
+/// The kind of account the target password protects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Template {
+    /// A person's own account: the profile tiers alone
+    #[default]
+    Personal,
+    /// A company account: company name and year, onboarding defaults
+    Corporate,
+    /// A router's admin or WiFi password: vendor defaults, SSID forms
+    Router,
+}
+
+/// Years before the last of the year range that corporate patterns use;
+/// a password set five years ago has usually been rotated since.
+const RECENT_YEARS: u16 = 4;
+
+/// Seasons as helpdesks name quarterly passwords, most common first.
+const SEASONS: [&str; 5] = ["Summer", "Spring", "Winter", "Fall", "Autumn"];
+
+/// Words helpdesks hand out as first passwords.
+const ONBOARDING_WORDS: [&str; 4] = ["Welcome", "Password", "Changeme", "Letmein"];
+
+/// Endings that get a word through a complexity rule, most common first.
+const ENDINGS: [&str; 6] = ["1", "1!", "123", "123!", "!", "@123"];
+
+/// Admin and WiFi passwords routers commonly ship with.
+const ROUTER_DEFAULTS: [&str; 16] = [
+    "admin",
+    "password",
+    "1234",
+    "12345",
+    "12345678",
+    "admin123",
+    "admin1234",
+    "administrator",
+    "root",
+    "default",
+    "guest",
+    "user",
+    "0000",
+    "00000000",
+    "changeme",
+    "password1",
+];
+
+impl Template {
+    /// The template's tiers in order: each one's name, the profile fields
+    /// it draws on and its candidates, most likely first. None for
+    /// [`Template::Personal`].
+    pub fn tiers(
+        self,
+        profile: &Profile,
+        years: RangeInclusive<u16>,
+    ) -> Vec<(&'static str, &'static [&'static str], Vec<String>)> {
+        match self {
+            Template::Personal => Vec::new(),
+            Template::Corporate => {
+                let companies = values(&[&profile.work.company]);
+                vec![
+                    (
+                        "Corporate patterns",
+                        &["work.company"],
+                        corporate(&companies, years.clone()),
+                    ),
+                    (
+                        "Seasonal passwords",
+                        &["work.company"],
+                        seasonal(&companies, years),
+                    ),
+                ]
+            }
+            Template::Router => {
+                let n = &profile.network;
+                let brands = values(&[&n.router_brand, &n.isp]);
+                let ssids = values(&[&n.ssid]);
+                vec![(
+                    "Router defaults",
+                    &["network.router_brand", "network.isp", "network.ssid"],
+                    router(&brands, &ssids),
+                )]
+            }
+        }
+    }
+}
+
+/// Helpdesk-style passwords: each company name with a recent year or a
+/// complexity ending (`Acme2024!`, `acme@2024`, `Acme1!`), then the usual
+/// onboarding words (`Welcome1`, `Password2024!`).
+pub fn corporate(companies: &[String], years: RangeInclusive<u16>) -> Vec<String> {
+    let recent = recent(years);
+    let mut out = Vec::new();
+    for company in companies {
+        for name in case_forms(company) {
+            for year in &recent {
+                out.push(format!("{}{}!", name, year));
+                out.push(format!("{}{}", name, year));
+                out.push(format!("{}@{}", name, year));
+                out.push(format!("{}#{}", name, year));
+            }
+            for ending in ENDINGS {
+                out.push(format!("{}{}", name, ending));
+            }
+        }
+    }
+    for word in ONBOARDING_WORDS {
+        for ending in ENDINGS {
+            out.push(format!("{}{}", word, ending));
+        }
+        for year in &recent {
+            out.push(format!("{}{}!", word, year));
+            out.push(format!("{}{}", word, year));
+            out.push(format!("{}@{}", word, year));
+        }
+        out.push(format!("{}1", word.to_lowercase()));
+        out.push(format!("{}123", word.to_lowercase()));
+    }
+    out.extend(
+        [
+            "P@ssw0rd",
+            "P@ssw0rd1",
+            "P@ssword1",
+            "Passw0rd!",
+            "Welcome@1",
+        ]
+        .map(String::from),
+    );
+    unique(out)
+}
+
+/// Quarterly rotation passwords: each season with a recent year, newest
+/// first (`Summer2024!`, `Spring2024`, `summer24`), then each company name
+/// with a season and year (`AcmeSummer2024!`, `AcmeWinter24`).
+pub fn seasonal(companies: &[String], years: RangeInclusive<u16>) -> Vec<String> {
+    let recent = recent(years);
+    let mut out = Vec::new();
+    for year in &recent {
+        let short = &year[year.len().saturating_sub(2)..];
+        for season in SEASONS {
+            let lower = season.to_lowercase();
+            out.push(format!("{}{}!", season, year));
+            out.push(format!("{}{}", season, year));
+            out.push(format!("{}{}", lower, year));
+            out.push(format!("{}@{}", season, year));
+            out.push(format!("{}{}!", season.to_uppercase(), year));
+            out.push(format!("{}{}!", season, short));
+            out.push(format!("{}{}", season, short));
+            out.push(format!("{}{}", lower, short));
+        }
+    }
+    for company in companies {
+        // Capitalized forms only: "acmesummer2024" is rarely typed
+        let names: Vec<String> = case_forms(company)
+            .into_iter()
+            .filter(|n| {
+                n.starts_with(char::is_uppercase) && n.chars().skip(1).any(char::is_lowercase)
+            })
+            .collect();
+        for year in &recent {
+            let short = &year[year.len().saturating_sub(2)..];
+            for name in &names {
+                for season in SEASONS {
+                    out.push(format!("{}{}{}!", name, season, year));
+                    out.push(format!("{}{}{}", name, season, short));
+                }
+            }
+        }
+    }
+    unique(out)
+}
+
+/// The last few years of `years`, newest first.
+pub fn recent(years: RangeInclusive<u16>) -> Vec<String> {
+    let last = *years.end();
+    let first = last.saturating_sub(RECENT_YEARS).max(*years.start());
+    (first..=last).rev().map(|y| y.to_string()).collect()
+}
+
+/// Router passwords: common vendor defaults, then the brand or ISP name
+/// with default-style endings (`netgear123`, `adminnetgear`), then forms
+/// of the SSID, including the suffix vendors append to it
+/// (`Vodafone-A1B2C3` to `A1B2C3`).
+pub fn router(brands: &[String], ssids: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = ROUTER_DEFAULTS.map(String::from).to_vec();
+    for brand in brands {
+        let lower = brand.to_lowercase();
+        let mut names = vec![compact(&lower)];
+        // "tp-link" is typed "tplink"
+        names.push(lower.replace(['-', '_', '.'], ""));
+        for name in unique(names) {
+            out.push(name.clone());
+            for ending in ["123", "1234", "admin", "wifi"] {
+                out.push(format!("{}{}", name, ending));
+            }
+            out.push(format!("admin{}", name));
+        }
+    }
+    for ssid in ssids {
+        let ssid = ssid.trim();
+        let joined: String = ssid.chars().filter(|c| c.is_alphanumeric()).collect();
+        out.push(ssid.to_string());
+        out.push(ssid.to_lowercase());
+        out.push(joined.clone());
+        out.push(joined.to_lowercase());
+        if let Some((_, suffix)) = ssid.rsplit_once(['-', '_', ' ']) {
+            if suffix.len() >= 4 {
+                out.push(suffix.to_string());
+                out.push(suffix.to_lowercase());
+            }
+        }
+        for ending in ["123", "1234"] {
+            out.push(format!("{}{}", joined.to_lowercase(), ending));
+        }
+    }
+    unique(out)
+}
+
+/// The values of the set fields, in order.
+fn values(fields: &[&Option<Field>]) -> Vec<String> {
+    fields
+        .iter()
+        .filter_map(|f| f.as_ref())
+        .map(|f| f.value().trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// `text` without whitespace.
+fn compact(text: &str) -> String {
+    text.split_whitespace().collect()
+}
+
+/// A company name as typed in a password: as given, capitalized,
+/// lowercase and uppercase, spaces dropped, plus its first word alone when
+/// it has several ("Acme Corp" to "Acme").
+fn case_forms(name: &str) -> Vec<String> {
+    let mut bases = vec![compact(name)];
+    let mut words = name.split_whitespace();
+    if let (Some(first), Some(_)) = (words.next(), words.next()) {
+        bases.push(first.to_string());
+    }
+    let mut forms = Vec::new();
+    for base in bases {
+        let lower = base.to_lowercase();
+        let mut chars = lower.chars();
+        let capitalized: String = match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => continue,
+        };
+        forms.extend([base.clone(), capitalized, lower, base.to_uppercase()]);
+    }
+    unique(forms)
+}
+
+/// `items` without repeats, in first-seen order.
+fn unique(items: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen.insert(item.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corporate() {
+        let candidates = corporate(&["Acme Corp".to_string()], 1990..=2025);
+        assert_eq!(candidates[0], "AcmeCorp2025!");
+        for expected in [
+            "Acme2024!",
+            "acme@2021",
+            "ACMECORP#2023",
+            "Acmecorp1!",
+            "Welcome1",
+            "Password2025!",
+            "welcome123",
+            "P@ssw0rd",
+        ] {
+            assert!(candidates.contains(&expected.to_string()), "{}", expected);
+        }
+        // Only recent years
+        assert!(!candidates.contains(&"Acme2020!".to_string()));
+        let unique: std::collections::HashSet<&String> = candidates.iter().collect();
+        assert_eq!(unique.len(), candidates.len());
+
+        // Onboarding words even without a company, within the year range
+        let generic = corporate(&[], 2023..=2024);
+        assert!(generic.contains(&"Welcome2023!".to_string()));
+        assert!(!generic.contains(&"Welcome2022!".to_string()));
+    }
+
+    #[test]
+    fn test_seasonal() {
+        let candidates = seasonal(&["Acme Corp".to_string()], 2019..=2025);
+        assert_eq!(candidates[..2], ["Summer2025!", "Summer2025"]);
+        for expected in [
+            "Spring2025",
+            "winter2024",
+            "Fall@2023",
+            "AUTUMN2022!",
+            "Summer21!",
+            "AcmeCorpSummer2025!",
+            "AcmeWinter24",
+        ] {
+            assert!(candidates.contains(&expected.to_string()), "{}", expected);
+        }
+        // Newer years first, only recent ones
+        let at = |c: &str| candidates.iter().position(|x| x == c).unwrap();
+        assert!(at("Fall2025") < at("Summer2024"));
+        assert!(!candidates.contains(&"Summer2020!".to_string()));
+        assert!(!candidates.contains(&"ACMECORPSummer2025!".to_string()));
+    }
+
+    #[test]
+    fn test_router() {
+        let candidates = router(&["TP-Link".to_string()], &["Vodafone-A1B2C3".to_string()]);
+        assert_eq!(candidates[0], "admin");
+        for expected in [
+            "tp-link123",
+            "tplink",
+            "admintplink",
+            "Vodafone-A1B2C3",
+            "vodafonea1b2c3",
+            "A1B2C3",
+            "a1b2c3",
+        ] {
+            assert!(candidates.contains(&expected.to_string()), "{}", expected);
+        }
+    }
+
+    #[test]
+    fn test_tier() {
+        let profile: Profile = toml::from_str(
+            r#"
+            [work]
+            company = "Acme"
+            [network]
+            router_brand = "Netgear"
+            "#,
+        )
+        .unwrap();
+        assert!(Template::Personal.tiers(&profile, 2020..=2024).is_empty());
+        let tiers = Template::Corporate.tiers(&profile, 2020..=2024);
+        let (name, fields, candidates) = &tiers[0];
+        assert_eq!(
+            (*name, *fields),
+            ("Corporate patterns", &["work.company"][..])
+        );
+        assert!(candidates.contains(&"Acme2024!".to_string()));
+        let (name, _, candidates) = &tiers[1];
+        assert_eq!(*name, "Seasonal passwords");
+        assert!(candidates.contains(&"AcmeSpring2024!".to_string()));
+        let tiers = Template::Router.tiers(&profile, 2020..=2024);
+        let (name, _, candidates) = &tiers[0];
+        assert_eq!(*name, "Router defaults");
+        assert!(candidates.contains(&"netgear1234".to_string()));
+    }
+}
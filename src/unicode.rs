@@ -0,0 +1,154 @@
+//! Unicode normalization and ASCII transliteration for accented seeds.
+//!
+//! The same name can arrive precomposed (`é`, NFC) or as a letter plus a
+//! combining mark (`e` + U+0301, NFD), and people type it either way or
+//! drop the accent altogether. The tables cover the accented Latin letters
+//! of the European languages, which is where seeds come from in practice.
+
+/// Precomposed letters by combining mark: the n-th letter of the second
+/// string is the n-th letter of the third plus the mark.
+const COMPOSED: [(char, &str, &str); 13] = [
+    ('\u{300}', "àèìòùÀÈÌÒÙ", "aeiouAEIOU"),
+    (
+        '\u{301}',
+        "áéíóúýćĺńŕśźÁÉÍÓÚÝĆĹŃŔŚŹ",
+        "aeiouyclnrszAEIOUYCLNRSZ",
+    ),
+    (
+        '\u{302}',
+        "âêîôûĉĝĥĵŝŵŷÂÊÎÔÛĈĜĤĴŜŴŶ",
+        "aeioucghjswyAEIOUCGHJSWY",
+    ),
+    ('\u{303}', "ãñõĩũÃÑÕĨŨ", "anoiuANOIU"),
+    ('\u{304}', "āēīōūĀĒĪŌŪ", "aeiouAEIOU"),
+    ('\u{306}', "ăĕğĭŏŭĂĔĞĬŎŬ", "aegiouAEGIOU"),
+    ('\u{307}', "ċėġżĊĖĠŻİ", "cegzCEGZI"),
+    ('\u{308}', "äëïöüÿÄËÏÖÜŸ", "aeiouyAEIOUY"),
+    ('\u{30A}', "åůÅŮ", "auAU"),
+    ('\u{30B}', "őűŐŰ", "ouOU"),
+    ('\u{30C}', "čďěňřšťžČĎĚŇŘŠŤŽ", "cdenrstzCDENRSTZ"),
+    ('\u{327}', "çşţģķļņÇŞŢĢĶĻŅ", "cstgklnCSTGKLN"),
+    ('\u{328}', "ąęįųĄĘĮŲ", "aeiuAEIU"),
+];
+
+/// Letters with no decomposition, as they are usually written in ASCII.
+const TRANSLITERATIONS: [(char, &str); 16] = [
+    ('ß', "ss"),
+    ('æ', "ae"),
+    ('Æ', "AE"),
+    ('œ', "oe"),
+    ('Œ', "OE"),
+    ('ø', "o"),
+    ('Ø', "O"),
+    ('ł', "l"),
+    ('Ł', "L"),
+    ('đ', "d"),
+    ('Đ', "D"),
+    ('ð', "d"),
+    ('Ð', "D"),
+    ('þ', "th"),
+    ('Þ', "TH"),
+    ('ı', "i"),
+];
+
+/// `s` with every accented letter as a base letter plus a combining mark.
+pub fn nfd(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match decompose(c) {
+            Some((base, mark)) => {
+                out.push(base);
+                out.push(mark);
+            }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+/// `s` with every base letter plus combining mark as one precomposed letter.
+pub fn nfc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match out.chars().last().and_then(|base| compose(base, c)) {
+            Some(composed) => {
+                out.pop();
+                out.push(composed);
+            }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+/// `s` spelled without accents (`josé` → `jose`, `straße` → `strasse`).
+/// Letters from other scripts are kept as they are.
+pub fn to_ascii(s: &str) -> String {
+    nfd(s)
+        .chars()
+        .filter(|c| !is_combining_mark(*c))
+        .fold(String::new(), |mut out, c| {
+            match TRANSLITERATIONS.iter().find(|&&(from, _)| from == c) {
+                Some((_, to)) => out.push_str(to),
+                None => out.push(c),
+            }
+            out
+        })
+}
+
+fn decompose(c: char) -> Option<(char, char)> {
+    COMPOSED.iter().find_map(|&(mark, composed, bases)| {
+        let i = composed.chars().position(|x| x == c)?;
+        Some((bases.chars().nth(i)?, mark))
+    })
+}
+
+fn compose(base: char, mark: char) -> Option<char> {
+    let &(_, composed, bases) = COMPOSED.iter().find(|&&(m, _, _)| m == mark)?;
+    let i = bases.chars().position(|x| x == base)?;
+    composed.chars().nth(i)
+}
+
+fn is_combining_mark(c: char) -> bool {
+    ('\u{300}'..='\u{36F}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_line_up() {
+        for (mark, composed, bases) in COMPOSED {
+            assert_eq!(
+                composed.chars().count(),
+                bases.chars().count(),
+                "{:?}",
+                mark
+            );
+            for (c, base) in composed.chars().zip(bases.chars()) {
+                assert_eq!(decompose(c), Some((base, mark)));
+                assert_eq!(compose(base, mark), Some(c));
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalization() {
+        let decomposed = "Jose\u{301} Mun\u{303}oz";
+        assert_eq!(nfc(decomposed), "José Muñoz");
+        assert_eq!(nfd("José Muñoz"), decomposed);
+        assert_eq!(nfc("john"), "john");
+        assert_eq!(nfd("john"), "john");
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        assert_eq!(to_ascii("josé"), "jose");
+        assert_eq!(to_ascii("Jose\u{301}"), "Jose");
+        assert_eq!(to_ascii("straße"), "strasse");
+        assert_eq!(to_ascii("Łódź"), "Lodz");
+        assert_eq!(to_ascii("björn"), "bjorn");
+        assert_eq!(to_ascii("сергей"), "сергей");
+    }
+}
@@ -1,7 +1,19 @@
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
 use anyhow::{Context, Result};
+use colored::Colorize;
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+/// Bytes of a mapped wordlist counted per rayon task.
+const COUNT_SEGMENT: usize = 16 << 20;
+
+/// Bytes of a mapped wordlist decoded into candidates at a time.
+const DECODE_BATCH: usize = 4 << 20;
+
+/// Bytes of a decode batch per rayon task.
+const DECODE_SEGMENT: usize = 256 << 10;
 
 /// Write a list of candidates to a file, one per line.
 pub fn write_wordlist(path: &Path, candidates: &[String]) -> Result<()> {
@@ -18,6 +30,67 @@ pub fn write_wordlist(path: &Path, candidates: &[String]) -> Result<()> {
     Ok(())
 }
 
+/// Stream candidates to a file, or to stdout when `path` is `-`, one per
+/// line. Returns the number written. A reader that goes away early (e.g.
+/// `| head`) just ends the stream.
+pub fn write_candidates(path: &Path, candidates: impl Iterator<Item = String>) -> Result<usize> {
+    let out: Box<dyn Write> = if path == Path::new("-") {
+        Box::new(std::io::stdout().lock())
+    } else {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create wordlist: {}", path.display()))?;
+        Box::new(file)
+    };
+    write_lines(out, candidates)
+}
+
+/// Add candidates to the end of the wordlist at `path`, one per line,
+/// creating it if it doesn't exist. Returns the number written.
+pub fn append_candidates(path: &Path, candidates: impl Iterator<Item = String>) -> Result<usize> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .read(true)
+        .open(path)
+        .with_context(|| format!("Failed to open wordlist: {}", path.display()))?;
+    // A last line without its newline would run into the first new one
+    if ends_mid_line(&mut file)? {
+        writeln!(file).with_context(|| "Failed to write to wordlist")?;
+    }
+    write_lines(Box::new(file), candidates)
+}
+
+/// Whether `file` is non-empty and doesn't end with a newline.
+fn ends_mid_line(file: &mut std::fs::File) -> Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(false);
+    }
+    let mut last = [0];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+fn write_lines(out: Box<dyn Write>, candidates: impl Iterator<Item = String>) -> Result<usize> {
+    let mut writer = BufWriter::new(out);
+    let mut count = 0;
+
+    for candidate in candidates {
+        match writeln!(writer, "{}", candidate) {
+            Ok(()) => count += 1,
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(count),
+            Err(e) => return Err(e).with_context(|| "Failed to write to wordlist"),
+        }
+    }
+
+    match writer.flush() {
+        Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+            Err(e).with_context(|| "Failed to flush wordlist")
+        }
+        _ => Ok(count),
+    }
+}
+
 /// Read a wordlist from a file, one entry per line.
 pub fn read_wordlist(path: &Path) -> Result<Vec<String>> {
     let file = std::fs::File::open(path)
@@ -35,3 +108,395 @@ pub fn read_wordlist(path: &Path) -> Result<Vec<String>> {
 
     Ok(words)
 }
+
+/// Lazily read candidates from a wordlist file, or from stdin when `path`
+/// is `-`, so huge lists and pipes never sit in memory.
+///
+/// Only line endings are stripped: leading and trailing spaces can be part
+/// of a password. Empty lines and lines that aren't valid UTF-8 are skipped.
+pub fn stream_wordlist(path: &Path) -> Result<WordlistStream> {
+    let reader: Box<dyn BufRead + Send> = if path == Path::new("-") {
+        Box::new(BufReader::new(std::io::stdin()))
+    } else {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open wordlist: {}", path.display()))?;
+        Box::new(BufReader::new(file))
+    };
+    Ok(WordlistStream {
+        reader,
+        line: Vec::new(),
+        skipped: 0,
+        quiet: false,
+    })
+}
+
+/// Candidates from a wordlist file, like [`stream_wordlist`], but read
+/// through a memory map where the platform allows it: multi-GB lists are
+/// never loaded or copied through a read buffer, lines are decoded a batch
+/// at a time across rayon tasks, and skipping ahead (to resume a session)
+/// doesn't allocate. Falls back to streaming for stdin (`-`) and anything
+/// that can't be mapped, such as a FIFO.
+///
+/// # Safety
+///
+/// The file must not be truncated or written to until the iterator is
+/// dropped: reading a page the file no longer has kills the process with
+/// SIGBUS, and other writes change candidates under the reader.
+pub unsafe fn map_wordlist(path: &Path) -> Result<Box<dyn Iterator<Item = String> + Send>> {
+    if path != Path::new("-") {
+        // SAFETY: passed on to the caller
+        if let Some(map) = unsafe { open_map(path)? } {
+            return Ok(Box::new(MappedLines {
+                map,
+                pos: 0,
+                skipped: 0,
+                decoded: Vec::new().into_iter(),
+            }));
+        }
+    }
+    Ok(Box::new(stream_wordlist(path)?))
+}
+
+/// Number of candidates [`stream_wordlist`] would yield for a file, found
+/// with one quick pass and no allocation per line; mapped files are counted
+/// in parallel.
+///
+/// # Safety
+///
+/// As for [`map_wordlist`], the file must not shrink or change while it is
+/// counted.
+pub unsafe fn count_wordlist(path: &Path) -> Result<u64> {
+    // SAFETY: passed on to the caller
+    if let Some(map) = unsafe { open_map(path)? } {
+        return Ok(par_count(&map, COUNT_SEGMENT));
+    }
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open wordlist: {}", path.display()))?;
+    let mut reader = BufReader::with_capacity(1 << 16, file);
+    let mut line = Vec::new();
+    let mut count = 0;
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .with_context(|| format!("Failed to read wordlist: {}", path.display()))?;
+        if read == 0 {
+            return Ok(count);
+        }
+        trim_line_end(&mut line);
+        if !line.is_empty() && std::str::from_utf8(&line).is_ok() {
+            count += 1;
+        }
+    }
+}
+
+/// Strip `\n`, `\r\n` (and stray `\r`s) from the end of a raw line.
+fn trim_line_end(line: &mut Vec<u8>) {
+    let len = trimmed(line).len();
+    line.truncate(len);
+}
+
+fn trimmed(mut line: &[u8]) -> &[u8] {
+    while let [rest @ .., b'\n' | b'\r'] = line {
+        line = rest;
+    }
+    line
+}
+
+/// `bytes` cut into pieces of `segment` bytes, each rounded up to the end
+/// of a line, so no line is split between two rayon tasks.
+fn line_segments(bytes: &[u8], segment: usize) -> Vec<&[u8]> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        let end = (start + segment).min(bytes.len());
+        let end = match bytes[end..].iter().position(|&b| b == b'\n') {
+            Some(i) => end + i + 1,
+            None => bytes.len(),
+        };
+        segments.push(&bytes[start..end]);
+        start = end;
+    }
+    segments
+}
+
+/// Candidate lines in `bytes`, counted `segment` bytes (rounded up to a
+/// line end) per task.
+fn par_count(bytes: &[u8], segment: usize) -> u64 {
+    line_segments(bytes, segment)
+        .par_iter()
+        .map(|segment| {
+            segment
+                .split(|&b| b == b'\n')
+                .map(trimmed)
+                .filter(|line| !line.is_empty() && std::str::from_utf8(line).is_ok())
+                .count() as u64
+        })
+        .sum()
+}
+
+/// The candidates in `segment` and the number of lines skipped as invalid
+/// UTF-8.
+fn decode_segment(segment: &[u8]) -> (Vec<String>, u64) {
+    let mut lines = Vec::new();
+    let mut skipped = 0;
+    for line in segment.split(|&b| b == b'\n').map(trimmed) {
+        if line.is_empty() {
+            continue;
+        }
+        match std::str::from_utf8(line) {
+            Ok(line) => lines.push(line.to_string()),
+            Err(_) => skipped += 1,
+        }
+    }
+    (lines, skipped)
+}
+
+fn warn_skipped(skipped: u64) {
+    eprintln!(
+        "{} Skipped {} wordlist line(s) that are not valid UTF-8",
+        "WARNING".yellow().bold(),
+        skipped
+    );
+}
+
+/// Map `path` read-only, or `None` if it is empty or can't be mapped.
+///
+/// # Safety
+///
+/// See [`map_wordlist`].
+unsafe fn open_map(path: &Path) -> Result<Option<Mmap>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open wordlist: {}", path.display()))?;
+    let meta = file
+        .metadata()
+        .with_context(|| format!("Failed to read wordlist: {}", path.display()))?;
+    if !meta.is_file() || meta.len() == 0 {
+        return Ok(None);
+    }
+    // SAFETY: the caller keeps the file from changing while it is mapped
+    let Ok(map) = (unsafe { Mmap::map(&file) }) else {
+        return Ok(None);
+    };
+    // Only advice; failing to take it is harmless
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+    Ok(Some(map))
+}
+
+/// Iterator returned by [`map_wordlist`] for mapped files.
+struct MappedLines {
+    map: Mmap,
+    /// Start of the first byte not yet decoded
+    pos: usize,
+    skipped: u64,
+    /// Candidates of the last batch, not yet handed out
+    decoded: std::vec::IntoIter<String>,
+}
+
+impl MappedLines {
+    /// Decode the next [`DECODE_BATCH`] bytes, [`DECODE_SEGMENT`] per
+    /// rayon task; false at the end of the file.
+    fn decode_batch(&mut self) -> bool {
+        let Some(&batch) = line_segments(&self.map[self.pos..], DECODE_BATCH).first() else {
+            self.finish();
+            return false;
+        };
+        self.pos += batch.len();
+        let segments: Vec<(Vec<String>, u64)> = line_segments(batch, DECODE_SEGMENT)
+            .par_iter()
+            .map(|segment| decode_segment(segment))
+            .collect();
+        let mut lines = Vec::new();
+        for (segment, skipped) in segments {
+            lines.extend(segment);
+            self.skipped += skipped;
+        }
+        self.decoded = lines.into_iter();
+        true
+    }
+
+    /// Byte range of the next candidate past the decoded ones, stepping
+    /// over empty and invalid lines.
+    fn next_range(&mut self) -> Option<(usize, usize)> {
+        let bytes = &self.map[..];
+        while self.pos < bytes.len() {
+            let start = self.pos;
+            let rest = &bytes[start..];
+            self.pos += rest
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(rest.len(), |i| i + 1);
+            let line = trimmed(&bytes[start..self.pos]);
+            if line.is_empty() {
+                continue;
+            }
+            if std::str::from_utf8(line).is_ok() {
+                return Some((start, start + line.len()));
+            }
+            self.skipped += 1;
+        }
+        self.finish();
+        None
+    }
+
+    fn finish(&mut self) {
+        if self.skipped > 0 {
+            warn_skipped(self.skipped);
+            self.skipped = 0;
+        }
+    }
+}
+
+impl Iterator for MappedLines {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if let Some(line) = self.decoded.next() {
+                return Some(line);
+            }
+            if !self.decode_batch() {
+                return None;
+            }
+        }
+    }
+
+    fn nth(&mut self, mut n: usize) -> Option<String> {
+        // Hand out what is decoded, then step over the rest without
+        // allocating it
+        while n > 0 && self.decoded.next().is_some() {
+            n -= 1;
+        }
+        for _ in 0..n {
+            self.next_range()?;
+        }
+        self.next()
+    }
+}
+
+/// Iterator returned by [`stream_wordlist`].
+pub struct WordlistStream {
+    reader: Box<dyn BufRead + Send>,
+    line: Vec<u8>,
+    skipped: u64,
+    quiet: bool,
+}
+
+impl WordlistStream {
+    /// Don't warn about skipped lines, e.g. on a counting pass before the
+    /// real one.
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+}
+
+impl Iterator for WordlistStream {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!(
+                        "{} Stopped reading wordlist: {}",
+                        "WARNING".yellow().bold(),
+                        e
+                    );
+                    break;
+                }
+            }
+            trim_line_end(&mut self.line);
+            if self.line.is_empty() {
+                continue;
+            }
+            match String::from_utf8(std::mem::take(&mut self.line)) {
+                Ok(candidate) => return Some(candidate),
+                Err(_) => self.skipped += 1,
+            }
+        }
+
+        if self.skipped > 0 && !self.quiet {
+            warn_skipped(self.skipped);
+            self.skipped = 0;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_candidates() {
+        let path = std::env::temp_dir().join(format!("pg-append-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let words = |list: &[&str]| -> Vec<String> { list.iter().map(|w| w.to_string()).collect() };
+        let appended = append_candidates(&path, words(&["alpha"]).into_iter()).unwrap();
+        assert_eq!(appended, 1);
+        std::fs::write(&path, "alpha\nbeta").unwrap();
+        append_candidates(&path, words(&["gamma", "delta"]).into_iter()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "alpha\nbeta\ngamma\ndelta\n"
+        );
+        append_candidates(&path, words(&["epsilon"]).into_iter()).unwrap();
+        assert_eq!(read_wordlist(&path).unwrap().len(), 5);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_mapped_lines_match_stream() {
+        let path = std::env::temp_dir().join(format!("pg-mapped-{}", std::process::id()));
+        std::fs::write(&path, b"alpha\r\n\n\xff\xfe\n beta \ngamma").unwrap();
+
+        let streamed: Vec<String> = stream_wordlist(&path).unwrap().quiet().collect();
+        assert_eq!(streamed, ["alpha", " beta ", "gamma"]);
+        // SAFETY: nothing else writes the file during the test
+        let mapped: Vec<String> = unsafe { map_wordlist(&path) }.unwrap().collect();
+        assert_eq!(mapped, streamed);
+        assert_eq!(unsafe { count_wordlist(&path) }.unwrap(), 3);
+        // Resuming skips candidates, not raw lines
+        let mut resumed = unsafe { map_wordlist(&path) }.unwrap().skip(2);
+        assert_eq!(resumed.next().as_deref(), Some("gamma"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_mapped_batches() {
+        let path = std::env::temp_dir().join(format!("pg-batches-{}", std::process::id()));
+        let words: Vec<String> = (0..DECODE_BATCH / 4).map(|i| format!("w{:06}", i)).collect();
+        std::fs::write(&path, words.join("\n")).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > DECODE_BATCH as u64);
+
+        // SAFETY: nothing else writes the file during the test
+        let mapped: Vec<String> = unsafe { map_wordlist(&path) }.unwrap().collect();
+        assert_eq!(mapped, words);
+        let mut lines = unsafe { map_wordlist(&path) }.unwrap();
+        assert_eq!(lines.next().as_deref(), Some("w000000"));
+        // Skips the rest of the decoded batch and into the next
+        let n = words.len() - 3;
+        assert_eq!(lines.nth(n - 1), Some(words[n].clone()));
+        assert_eq!(lines.count(), 2);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_par_count_segments() {
+        let bytes = b"one\ntwo\r\n\nthree\nfour";
+        for segment in [1, 3, 5, 64] {
+            assert_eq!(par_count(bytes, segment), 4, "segment {}", segment);
+            let segments = line_segments(bytes, segment);
+            assert_eq!(segments.concat(), bytes);
+            assert!(segments[..segments.len() - 1].iter().all(|s| s.ends_with(b"\n")));
+            let lines: Vec<String> = segments.iter().flat_map(|s| decode_segment(s).0).collect();
+            assert_eq!(lines, ["one", "two", "three", "four"]);
+        }
+        assert_eq!(par_count(b"", 8), 0);
+    }
+}
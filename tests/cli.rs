@@ -0,0 +1,1453 @@
+//! End-to-end tests: run the built binary against the fixtures in
+//! `tests/fixtures/` and check what a user would see.
+//!
+//! Fixtures (all derived from `profile.toml`):
+//! - `hashes_md5.txt`: md5("john1990") plus one hash no wordlist contains
+//! - `hash_sha256.txt`: sha256("buddy42")
+//! - `handshake.hccapx`: WPA2 handshake for "SmithFamily", passphrase "JohnJohn"
+//! - `empty.pcap`: a capture header with no packets (detection only)
+//! - `archive.zip`: an archive, which `crack` recognizes but cannot attack
+//! - `engagement.toml`: a complete engagement, so `--authorized` skips the prompt
+//!
+//! Slow-ish (the WiFi test runs real PBKDF2), so the suite only builds with
+//! `cargo test --features integration`.
+
+#![cfg(feature = "integration")]
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+
+const MD5_JOHN1990: &str = "212abccf8e5b7c1210a4f4077493dcb0";
+const MD5_ZZ9: &str = "e0df37ad690a0effed4b5accbe85f047";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// A fresh scratch directory per test, so parallel tests never share files.
+fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pg-it-{}-{}", std::process::id(), test));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn command() -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_password-guesser"));
+    // Plain, stable output regardless of the caller's environment
+    cmd.env("NO_COLOR", "1")
+        .env_remove("RUST_BACKTRACE")
+        .env_remove("RUST_LIB_BACKTRACE")
+        .stdin(Stdio::null());
+    cmd
+}
+
+fn run(args: &[&str]) -> Output {
+    command().args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Run and require success, returning stdout.
+fn ok(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "{:?} failed\nstdout:\n{}\nstderr:\n{}",
+        args,
+        stdout(&output),
+        stderr(&output)
+    );
+    stdout(&output)
+}
+
+fn lines(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+fn path(p: &Path) -> &str {
+    p.to_str().unwrap()
+}
+
+#[test]
+fn generate_from_profile() {
+    let dir = scratch("generate");
+    let out = dir.join("words.txt");
+    let profile = fixture("profile.toml");
+    let text = ok(&["generate", "-p", path(&profile), "-o", path(&out)]);
+
+    assert!(text.contains("Seed words: john"));
+    assert!(text.contains("Kept original casing"));
+    let words = lines(&out);
+    assert!(text.contains(&format!("Wrote {} candidates", words.len())));
+    for expected in ["john1990", "buddy1990", "Buddy1990", "SmithFamily"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+    assert!(words.iter().all(|w| (6..=32).contains(&w.len())));
+}
+
+#[test]
+fn generate_to_stdout() {
+    let dir = scratch("stdout");
+    let out = dir.join("words.txt");
+    let profile = fixture("profile.toml");
+    ok(&["generate", "-p", path(&profile), "-o", path(&out)]);
+
+    let output = run(&["generate", "-p", path(&profile), "-o", "-"]);
+    assert!(output.status.success());
+    let piped: Vec<String> = stdout(&output).lines().map(String::from).collect();
+    assert_eq!(piped, lines(&out));
+    assert!(stderr(&output).contains("Wrote"));
+}
+
+#[test]
+fn generate_likely_first() {
+    let dir = scratch("likely");
+    let profile = fixture("profile.toml");
+    let (plain, likely) = (dir.join("plain.txt"), dir.join("likely.txt"));
+    ok(&["generate", "-p", path(&profile), "-o", path(&plain)]);
+    let text = ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "-o",
+        path(&likely),
+        "--order",
+        "likely-first",
+    ]);
+    assert!(text.contains("Sorting candidates by estimated likelihood"));
+
+    let words = lines(&likely);
+    let mut sorted = words.clone();
+    sorted.sort();
+    let mut expected = lines(&plain);
+    expected.sort();
+    assert_eq!(sorted, expected);
+    let position = |word: &str| words.iter().position(|w| w == word).unwrap();
+    assert!(position("buddy1") < position("buddy2007"));
+}
+
+#[test]
+fn generate_sorted() {
+    let dir = scratch("sorted");
+    let profile = fixture("profile.toml");
+    let (plain, sorted) = (dir.join("plain.txt"), dir.join("sorted.txt"));
+    ok(&["generate", "-p", path(&profile), "-o", path(&plain)]);
+    let args = ["generate", "-p", path(&profile), "-o", path(&sorted)];
+    let text = ok(&[&args[..], &["--sort"]].concat());
+    assert!(text.contains("Sorting candidates"));
+    let words = lines(&sorted);
+    let mut expected = lines(&plain);
+    expected.sort();
+    assert_eq!(words, expected);
+
+    ok(&[&args[..], &["--stable", "--max-candidates", "100"]].concat());
+    let words = lines(&sorted);
+    assert_eq!(words.len(), 100);
+    assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let output = run(&[&args[..], &["--sort", "--order", "likely-first"]].concat());
+    assert!(!output.status.success());
+}
+
+#[test]
+fn generate_append() {
+    let dir = scratch("append");
+    let profile = fixture("profile.toml");
+    let (full, grown) = (dir.join("full.txt"), dir.join("grown.txt"));
+    ok(&["generate", "-p", path(&profile), "-o", path(&full)]);
+    let args = ["generate", "-p", path(&profile), "-o", path(&grown)];
+    ok(&[&args[..], &["-d", "1"]].concat());
+    let shallow = lines(&grown).len();
+
+    let text = ok(&[&args[..], &["--append"]].concat());
+    let words = lines(&grown);
+    let new = words.len() - shallow;
+    let expected = format!(
+        "Appended {} new candidates to {} ({} already there)",
+        new,
+        path(&grown),
+        shallow
+    );
+    assert!(text.contains(&expected), "{}", text);
+    let mut sorted = words.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted.len(), words.len());
+    let mut expected = lines(&full);
+    expected.sort();
+    assert_eq!(sorted, expected);
+
+    let text = ok(&[&args[..], &["--append"]].concat());
+    assert!(text.contains("Appended 0 new candidates"), "{}", text);
+    assert_eq!(lines(&grown), words);
+
+    let output = run(&["generate", "-p", path(&profile), "-o", "-", "--append"]);
+    assert!(stderr(&output).contains("--append adds to a file"));
+}
+
+#[test]
+fn generate_bloom_dedup() {
+    let dir = scratch("bloom");
+    let profile = fixture("profile.toml");
+    let (plain, bloom) = (dir.join("plain.txt"), dir.join("bloom.txt"));
+    ok(&["generate", "-p", path(&profile), "-o", path(&plain)]);
+    let args = ["generate", "-p", path(&profile), "-o", path(&bloom)];
+    ok(&[&args[..], &["--bloom"]].concat());
+    assert_eq!(lines(&bloom), lines(&plain));
+    ok(&[&args[..], &["--bloom", "0.01"]].concat());
+    assert_eq!(lines(&bloom).len(), lines(&plain).len());
+
+    let output = run(&[&args[..], &["--bloom", "2"]].concat());
+    assert!(stderr(&output).contains("false-positive rate between 0 and 1"));
+}
+
+#[test]
+fn generate_max_candidates() {
+    let dir = scratch("budget");
+    let profile = fixture("profile.toml");
+    let out = dir.join("budget.txt");
+    let text = ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "-o",
+        path(&out),
+        "--max-candidates",
+        "1500",
+    ]);
+    assert!(text.contains("Depth: 3"), "{}", text);
+    assert!(text.contains("Budget of 1500: pruned the"), "{}", text);
+    let words = lines(&out);
+    assert_eq!(words.len(), 1500);
+    // The profile's own combinations survive the pruning
+    for expected in ["john1990", "Buddy1990", "buddy42"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+}
+
+#[test]
+fn generate_word_separators() {
+    let dir = scratch("separators");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "-o",
+        path(&out),
+        "--word-separator",
+        "-",
+        "--word-separator",
+        "@",
+    ]);
+    let words = lines(&out);
+    for expected in ["john-smith", "John@Smith", "smithjohn"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+    assert!(!words.iter().any(|w| w == "john_smith"));
+}
+
+#[test]
+fn generate_year_range() {
+    let dir = scratch("years");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "-o",
+        path(&out),
+        "--years",
+        "2016-2018",
+    ]);
+    let words = lines(&out);
+    assert!(words.iter().any(|w| w == "John2017"));
+    assert!(!words.iter().any(|w| w == "John2019" || w == "John1991"));
+
+    let output = run(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "-o",
+        "-",
+        "--years",
+        "85-27",
+    ]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("four-digit years"));
+}
+
+#[test]
+fn generate_date_formats() {
+    let dir = scratch("dates");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&args);
+    assert!(!lines(&out).iter().any(|w| w == "john15.05.1990"));
+
+    ok(&[&args[..], &["--date-format", "de"]].concat());
+    let words = lines(&out);
+    for expected in ["john15.05.1990", "John15mai1990", "john15.5.90"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+
+    let output = run(&[&args[..], &["--date-format", "xx"]].concat());
+    assert!(stderr(&output).contains("no date formats for locale 'xx'"));
+}
+
+#[test]
+fn generate_keyboard_layout() {
+    let dir = scratch("keyboard");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&args);
+    let words = lines(&out);
+    assert!(words.iter().any(|w| w == "1qaz2wsx3edc"));
+    assert!(!words.iter().any(|w| w == "azerty"));
+
+    ok(&[&args[..], &["--keyboard", "azerty"]].concat());
+    let words = lines(&out);
+    for expected in ["azerty", "qsdfgh", "1aqw2zsx", "123456"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+    assert!(!words.iter().any(|w| w == "1qaz2wsx3edc"));
+}
+
+#[test]
+fn generate_policy() {
+    let dir = scratch("policy");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&args);
+    let all = lines(&out);
+
+    let rules = ["--policy", "min-upper=1,min-digit=1,min-symbol=1"];
+    ok(&[&args[..], &rules].concat());
+    let words = lines(&out);
+    assert!(!words.is_empty() && words.len() < all.len() / 2);
+    let meets = |w: &String| {
+        w.chars().any(|c| c.is_ascii_uppercase())
+            && w.chars().any(|c| c.is_ascii_digit())
+            && w.chars().any(|c| !c.is_alphanumeric())
+    };
+    assert!(words.iter().all(meets));
+    assert!(words.iter().all(|w| all.contains(w)));
+
+    ok(&[&args[..], &["--policy", "windows-complexity"]].concat());
+    let words = lines(&out);
+    assert!(words.iter().any(|w| w == "Buddy1990"));
+    assert!(!words.iter().any(|w| w == "buddy1990"));
+
+    let output = run(&[&args[..], &["--policy", "max-upper=1"]].concat());
+    assert!(stderr(&output).contains("Unknown policy rule 'max-upper'"));
+
+    // A preset, tightened with a rule of its own
+    let preset = ["--policy-preset", "pci", "--policy", "min-upper=1"];
+    ok(&[&args[..], &preset, &["--max-length", "16"]].concat());
+    let words = lines(&out);
+    assert!(!words.is_empty());
+    assert!(words.iter().all(|w| w.len() >= 12
+        && w.chars().any(|c| c.is_ascii_uppercase())
+        && w.chars().any(|c| c.is_ascii_digit())));
+
+    let output = run(&[&args[..], &["--policy-preset", "hipaa"]].concat());
+    assert!(stderr(&output).contains("Unknown policy preset 'hipaa'"));
+}
+
+#[test]
+fn generate_charset() {
+    let dir = scratch("charset");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+
+    ok(&[&args[..], &["--charset", "alnum"]].concat());
+    let words = lines(&out);
+    assert!(words.iter().any(|w| w == "Buddy1990"));
+    let alnum = |w: &String| w.chars().all(|c| c.is_ascii_alphanumeric());
+    assert!(words.iter().all(alnum));
+
+    ok(&[&args[..], &["--exclude-chars", "!@"]].concat());
+    let words = lines(&out);
+    assert!(words.iter().any(|w| w == "buddy_buddy"));
+    assert!(!words.iter().any(|w| w.contains(['!', '@'])));
+
+    let output = run(&[&args[..], &["--charset", "utf8"]].concat());
+    assert!(stderr(&output).contains("Unknown charset 'utf8'"));
+}
+
+#[test]
+fn generate_estimate() {
+    let dir = scratch("estimate");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let text = ok(&["generate", "-p", path(&profile), "--estimate"]);
+    assert!(text.contains("Keyboard patterns"), "{}", text);
+    assert!(text.contains("Depth 1: 803 candidates"), "{}", text);
+
+    ok(&["generate", "-p", path(&profile), "-o", path(&out)]);
+    let depth2 = format!("Depth 2: {} candidates", lines(&out).len());
+    assert!(text.contains(&depth2), "{}", text);
+
+    let output = run(&["generate", "-p", path(&profile)]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn generate_tier_stats() {
+    let dir = scratch("stats");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let text = ok(&["generate", "-p", path(&profile), "-o", path(&out)]);
+    assert!(text.contains("Duplicates"), "{}", text);
+    let total = text.lines().find(|l| l.trim_start().starts_with("Total"));
+    let written = total.and_then(|l| l.split_whitespace().nth(1));
+    assert_eq!(written, Some(lines(&out).len().to_string().as_str()));
+}
+
+#[test]
+fn generate_base_wordlist() {
+    let dir = scratch("base");
+    let profile = fixture("profile.toml");
+    let base = dir.join("base.txt");
+    std::fs::write(&base, "marmalade\nmonkey\n").unwrap();
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&[&args[..], &["--base-wordlist", path(&base)]].concat());
+    let words = lines(&out);
+    for expected in ["Marmalade", "marmalade1990", "monkey42", "monkey1!"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+
+    let missing = dir.join("missing.txt");
+    let output = run(&[&args[..], &["--base-wordlist", path(&missing)]].concat());
+    assert!(stderr(&output).contains("can't read"));
+}
+
+#[test]
+fn generate_language_words() {
+    let dir = scratch("language");
+    let profile = dir.join("profile.toml");
+    let out = dir.join("words.txt");
+    let personal = "[personal]\nfirst_name = \"Budi\"\nbirthdate = \"1992-03-04\"\n";
+    std::fs::write(&profile, format!("{}language = \"id\"\n", personal)).unwrap();
+    ok(&["generate", "-p", path(&profile), "-o", path(&out)]);
+    let words = lines(&out);
+    for expected in ["sayang", "Sayang1992", "cintaku123"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+
+    std::fs::write(&profile, format!("{}language = \"xx\"\n", personal)).unwrap();
+    let output = run(&["generate", "-p", path(&profile), "-o", path(&out)]);
+    assert!(stderr(&output).contains("No dictionary for language 'xx'"));
+}
+
+#[test]
+fn generate_topic_words() {
+    let dir = scratch("topics");
+    let profile = dir.join("profile.toml");
+    let out = dir.join("words.txt");
+    let interests = "[personal]\nfirst_name = \"John\"\n\
+                     [interests]\nfavorite_team = \"Arsenal\"\nhobbies = [\"Minecraft\"]\n";
+    std::fs::write(&profile, interests).unwrap();
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&args);
+    assert!(!lines(&out).iter().any(|w| w == "gunners"));
+
+    ok(&[&args[..], &["--topics"]].concat());
+    let words = lines(&out);
+    for expected in ["gunners", "Highbury", "creeper123", "champions"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+    assert!(!words.iter().any(|w| w == "hallelujah"));
+
+    ok(&[&args[..], &["--topics", "religious,music"]].concat());
+    assert!(lines(&out).iter().any(|w| w == "hallelujah"));
+    let output = run(&[&args[..], &["--topics", "cooking"]].concat());
+    assert!(stderr(&output).contains("no dictionary for topic 'cooking'"));
+}
+
+#[test]
+fn generate_seed_weights() {
+    let dir = scratch("weights");
+    let profile = dir.join("profile.toml");
+    let out = dir.join("words.txt");
+    let seeds = "[personal]\nfirst_name = \"John\"\npet_name = \"Buddy\"\n\
+                 [network]\nisp = \"Comcast\"\n";
+    let weighed = |weights: &str| format!("{}[weights]\n{}\n", seeds, weights);
+    std::fs::write(&profile, weighed("isp = 0")).unwrap();
+    ok(&["generate", "-p", path(&profile), "-o", path(&out)]);
+    let words = lines(&out);
+    assert!(words.iter().any(|w| w == "johnbuddy"));
+    assert!(!words.iter().any(|w| w == "johncomcast"));
+
+    std::fs::write(&profile, weighed("dog = 3")).unwrap();
+    let output = run(&["generate", "-p", path(&profile), "-o", path(&out)]);
+    assert!(stderr(&output).contains("Unknown field 'dog' in [weights]"));
+    std::fs::write(&profile, weighed("isp = 11")).unwrap();
+    let output = run(&["generate", "-p", path(&profile), "-o", path(&out)]);
+    assert!(stderr(&output).contains("out of range (0 to 10)"));
+}
+
+#[test]
+fn explain_coverage() {
+    let dir = scratch("explain");
+    let passwords = dir.join("cracked.txt");
+    std::fs::write(&passwords, "Buddy1990\npassword\nxk29!!qz\n").unwrap();
+    let text = ok(&[
+        "explain",
+        path(&passwords),
+        "-p",
+        path(&fixture("profile.toml")),
+    ]);
+    assert!(text.contains("Applying affixes"), "{}", text);
+    assert!(text.contains("Common passwords"), "{}", text);
+    assert!(text.contains("not generated by any tier"), "{}", text);
+    assert!(text.contains("Covered 2 of 3 passwords"), "{}", text);
+}
+
+#[test]
+fn generate_user_rules() {
+    let dir = scratch("rules");
+    let profile = fixture("profile.toml");
+    let rules = dir.join("target.rules");
+    let house_style = "# house style\ntoggle 0 | append 2024\nsubstitute o 0\n";
+    std::fs::write(&rules, house_style).unwrap();
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&[&args[..], &["--rules", path(&rules)]].concat());
+    let words = lines(&out);
+    for expected in ["Buddy2024", "Smith2024", "j0hnny"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+
+    std::fs::write(&rules, "append 1\nreverse\n").unwrap();
+    let output = run(&[&args[..], &["--rules", path(&rules)]].concat());
+    assert!(stderr(&output).contains("line 2: Unknown operation 'reverse'"));
+}
+
+#[test]
+fn generate_hashcat_rules() {
+    let dir = scratch("hashcat-rules");
+    let profile = fixture("profile.toml");
+    let rules = dir.join("best.rule");
+    let best = "## community set\nc $2 $0 $2 $4\nso0 $!\nr\n<5 $~\n";
+    std::fs::write(&rules, best).unwrap();
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&[&args[..], &["--hashcat-rules", path(&rules)]].concat());
+    let words = lines(&out);
+    for expected in ["Buddy2024", "j0hnny!", "ynnhoj", "buddy~", "smith~"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+    assert!(!words.iter().any(|w| w == "johnny~"));
+
+    std::fs::write(&rules, "c\n$\n").unwrap();
+    let output = run(&[&args[..], &["--hashcat-rules", path(&rules)]].concat());
+    assert!(stderr(&output).contains("line 2: '$' needs a character"));
+}
+
+#[test]
+fn generate_case_toggles() {
+    let dir = scratch("case-toggles");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&args);
+    assert!(!lines(&out).iter().any(|w| w == "jOhNny"));
+
+    ok(&[&args[..], &["--case-toggles", "2"]].concat());
+    let words = lines(&out);
+    for expected in ["jOhNny", "JohnnY"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+}
+
+#[test]
+fn generate_rotations() {
+    let dir = scratch("rotations");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&args);
+    assert!(!lines(&out).iter().any(|w| w == "ohnnyj"));
+
+    ok(&[&args[..], &["--rotations"]].concat());
+    let words = lines(&out);
+    for expected in ["ohnnyj", "Yjohnn"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+}
+
+#[test]
+fn generate_insertions() {
+    let dir = scratch("insertions");
+    let profile = fixture("profile.toml");
+    let out = dir.join("words.txt");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&[&args[..], &["--insertions", "2"]].concat());
+    let words = lines(&out);
+    for expected in ["bud1dy", "Bu.ddy", "b!ud!dy"] {
+        assert!(words.iter().any(|w| w == expected), "missing {}", expected);
+    }
+
+    ok(&[&args[..], &["--insertions", "0"]].concat());
+    assert!(!lines(&out).iter().any(|w| w == "bud1dy"));
+}
+
+#[test]
+fn generate_emit_rules() {
+    let dir = scratch("emit-rules");
+    let profile = fixture("profile.toml");
+    let (rules, base) = (dir.join("profile.rule"), dir.join("base.txt"));
+    let args = ["generate", "-p", path(&profile), "--emit-rules"];
+    let args = [&args[..], &[path(&rules)]].concat();
+    ok(&[&args[..], &["--emit-base", path(&base)]].concat());
+    let words = lines(&base);
+    assert!(words.iter().any(|w| w == "buddy"));
+    let rules = lines(&rules);
+    let leet = "l sa@ se3 si1 sl1 so0 ss$ st7";
+    for expected in ["c", leet, "c $1 $9 $9 $0", "l ^y ^m"] {
+        assert!(rules.iter().any(|r| r == expected), "missing {}", expected);
+    }
+
+    let output = run(&args);
+    assert!(stderr(&output).contains("--emit-base"));
+}
+
+#[test]
+fn generate_mask_combinator_prince() {
+    let dir = scratch("attacks");
+    let profile = fixture("profile.toml");
+
+    let out = dir.join("mask.txt");
+    ok(&["generate", "--mask", "?u?d", "-o", path(&out)]);
+    let words = lines(&out);
+    assert_eq!(words.len(), 260);
+    assert_eq!(words[0], "A0");
+
+    let right = dir.join("right.txt");
+    std::fs::write(&right, "2024\n!\n").unwrap();
+    let out = dir.join("combo.txt");
+    ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "--right",
+        path(&right),
+        "--min-length",
+        "1",
+        "-o",
+        path(&out),
+    ]);
+    let words = lines(&out);
+    assert!(words.iter().any(|w| w == "buddy2024"));
+    assert!(words.iter().any(|w| w == "john!"));
+
+    let pool = dir.join("pool.txt");
+    std::fs::write(&pool, "red\nhorse\n").unwrap();
+    let out = dir.join("prince.txt");
+    ok(&[
+        "generate",
+        "--prince",
+        "--prince-wordlist",
+        path(&pool),
+        "--prince-max-elements",
+        "2",
+        "--min-length",
+        "1",
+        "-o",
+        path(&out),
+    ]);
+    assert_eq!(lines(&out)[..2], ["redred", "redhorse"]);
+}
+
+#[test]
+fn train_and_generate_pcfg() {
+    let dir = scratch("pcfg");
+    let corpus = dir.join("leak.txt");
+    std::fs::write(&corpus, "password1\npassword1\nmonkey12\ndragon12!\nqwerty\n").unwrap();
+    let model = dir.join("model.toml");
+    let text = ok(&["train", path(&corpus), "-o", path(&model)]);
+    assert!(text.contains("4 structures"), "{}", text);
+
+    let out = dir.join("pcfg.txt");
+    ok(&[
+        "generate",
+        "--pcfg",
+        path(&model),
+        "--pcfg-limit",
+        "5",
+        "-o",
+        path(&out),
+    ]);
+    let words = lines(&out);
+    assert_eq!(words.len(), 5);
+    assert_eq!(words[0], "password1");
+    // Seen parts in combinations the corpus never had
+    let all = dir.join("all.txt");
+    ok(&["generate", "--pcfg", path(&model), "-o", path(&all)]);
+    assert!(lines(&all).iter().any(|w| w == "qwerty12!"));
+
+    std::fs::write(&corpus, "").unwrap();
+    let output = run(&["train", path(&corpus), "-o", path(&model)]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("No passwords"));
+}
+
+#[test]
+fn train_and_generate_markov() {
+    let dir = scratch("markov");
+    let corpus = dir.join("leak.txt");
+    std::fs::write(&corpus, "password1\npassword1\npassword2\nmonkey12\n").unwrap();
+    let model = dir.join("model.toml");
+    let text = ok(&["train-markov", path(&corpus), "-o", path(&model)]);
+    assert!(text.contains("from 4 passwords"), "{}", text);
+
+    let out = dir.join("markov.txt");
+    ok(&[
+        "generate",
+        "--markov-model",
+        path(&model),
+        "--markov-limit",
+        "2",
+        "--min-length",
+        "8",
+        "--max-length",
+        "9",
+        "-o",
+        path(&out),
+    ]);
+    let words = lines(&out);
+    assert_eq!(words.len(), 2);
+    assert_eq!(words[0], "password1");
+    assert!(words.iter().all(|w| (8..=9).contains(&w.len())));
+}
+
+#[test]
+fn generate_template() {
+    let dir = scratch("template");
+    let profile = dir.join("employee.toml");
+    std::fs::write(
+        &profile,
+        "[personal]\nfirst_name = \"Jane\"\n[work]\ncompany = \"Acme Corp\"\n",
+    )
+    .unwrap();
+    let out = dir.join("corp.txt");
+    ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "--template",
+        "corporate",
+        "--years",
+        "2020-2024",
+        "-o",
+        path(&out),
+    ]);
+    let words = lines(&out);
+    assert_eq!(words[0], "AcmeCorp2024!");
+    for expected in [
+        "Acme2024!",
+        "Welcome1",
+        "P@ssw0rd",
+        "Summer2024!",
+        "AcmeSpring23",
+    ] {
+        assert!(words.iter().any(|w| w == expected), "{}", expected);
+    }
+
+    let out = dir.join("router.txt");
+    ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "--template",
+        "router",
+        "--min-length",
+        "4",
+        "-o",
+        path(&out),
+    ]);
+    assert_eq!(lines(&out)[0], "admin");
+}
+
+#[test]
+fn generate_sample() {
+    let dir = scratch("sample");
+    let out = dir.join("sample.txt");
+    let profile = fixture("profile.toml");
+    let args = ["generate", "-p", path(&profile), "-o", path(&out)];
+    ok(&args);
+    let all = lines(&out);
+
+    let sample = ["--sample", "20", "--seed", "7"];
+    let text = ok(&[&args[..], &sample].concat());
+    let expected = format!("Sampled 20 of {} candidates", all.len());
+    assert!(text.contains(&expected), "{}", text);
+    let words = lines(&out);
+    assert_eq!(words.len(), 20);
+    // In generated order, and the same again with the same seed
+    let positions: Vec<usize> = words
+        .iter()
+        .map(|w| all.iter().position(|a| a == w).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|p| p[0] < p[1]));
+    ok(&[&args[..], &sample].concat());
+    assert_eq!(lines(&out), words);
+
+    // A mask far too big to expand
+    let mask = ["generate", "--mask", "?a?a?a?a?a?a?a?a?a?a", "--sample", "5"];
+    ok(&[&mask[..], &["-o", path(&out)]].concat());
+    let words = lines(&out);
+    assert_eq!(words.len(), 5);
+    assert!(words.iter().all(|w| w.chars().count() == 10));
+}
+
+#[test]
+fn generate_annotate() {
+    let dir = scratch("annotate");
+    let out = dir.join("annotated.tsv");
+    let profile = fixture("profile.toml");
+    ok(&[
+        "generate",
+        "-p",
+        path(&profile),
+        "--annotate",
+        "-o",
+        path(&out),
+    ]);
+    let rows = lines(&out);
+    assert!(rows.len() > 100);
+    let fields: Vec<Vec<&str>> = rows.iter().map(|r| r.split('\t').collect()).collect();
+    assert!(fields
+        .iter()
+        .all(|f| f.len() == 3 && matches!(f[1], "0" | "1" | "2" | "3" | "4")));
+    assert_eq!(fields[0][2], "Common passwords");
+    assert!(fields.contains(&vec!["password", "0", "Common passwords"]));
+    assert!(fields
+        .iter()
+        .any(|f| f[0] == "buddy1990" && f[2] == "Applying affixes"));
+}
+
+#[test]
+fn estimate_per_tier_and_depth() {
+    let text = ok(&[
+        "estimate",
+        "-p",
+        path(&fixture("profile.toml")),
+        "-a",
+        "sha256",
+        "--calibration",
+        "0.2",
+    ]);
+    assert!(text.contains("Calibrating SHA256"), "{}", text);
+    assert!(text.contains("Common passwords"));
+    for depth in 1..=3 {
+        assert!(text.contains(&format!("Depth {}: ", depth)), "{}", text);
+    }
+}
+
+#[test]
+fn crack_hash_file_with_session_and_resume() {
+    let dir = scratch("session");
+    let session = dir.join("run.session");
+    let profile = fixture("profile.toml");
+    let hashes = fixture("hashes_md5.txt");
+
+    let text = ok(&[
+        "crack-hash",
+        "--hash-file",
+        path(&hashes),
+        "-a",
+        "md5",
+        "-p",
+        path(&profile),
+        "--session",
+        path(&session),
+    ]);
+    assert!(text.contains("Pre-flight: "), "{}", text);
+    assert!(text.contains("Cracked 1/2 hash(es)"), "{}", text);
+    assert!(text.contains(&format!("{} -> john1990", MD5_JOHN1990)));
+    assert!(session.exists());
+
+    // The finished session still knows what it cracked
+    let text = ok(&["crack-hash", "--resume", path(&session)]);
+    assert!(text.contains("Resuming session"), "{}", text);
+    assert!(text.contains("john1990"), "{}", text);
+}
+
+#[test]
+fn crack_hash_brute_force() {
+    let text = ok(&[
+        "--threads",
+        "2",
+        "--nice",
+        "crack-hash",
+        "--hash",
+        MD5_ZZ9,
+        "-a",
+        "md5",
+        "--brute",
+        "--charset",
+        "lower+digits",
+        "--length",
+        "1-3",
+    ]);
+    assert!(text.contains("Cracked 1/1 hash(es)"), "{}", text);
+    assert!(text.contains("-> zz9"));
+}
+
+#[test]
+fn crack_hash_time_budget() {
+    let dir = scratch("budget");
+    let session = dir.join("run.session");
+    // Far more candidates than fit in the budget
+    let args = [
+        "crack-hash",
+        "--hash",
+        MD5_ZZ9,
+        "-a",
+        "md5",
+        "--brute",
+        "--charset",
+        "all",
+        "--length",
+        "8",
+        "--session",
+        path(&session),
+        "--max-duration",
+        "0.02",
+        "--status-interval",
+        "0.005",
+    ];
+    let text = ok(&args);
+    assert!(text.contains("Time budget reached after"), "{}", text);
+    assert!(text.contains(" tested ("), "{}", text);
+    assert!(text.contains(&format!("--resume {}", path(&session))));
+
+    let saved = std::fs::read_to_string(&session).unwrap();
+    assert!(!saved.contains("position = 0\n"), "{}", saved);
+    let text = ok(&[
+        "crack-hash",
+        "--resume",
+        path(&session),
+        "--max-duration",
+        "0.01",
+    ]);
+    assert!(text.contains("Resuming session"), "{}", text);
+    assert!(text.contains("Time budget reached after"), "{}", text);
+
+    let output = run(&[&args[..10], &["--max-duration", "0"]].concat());
+    assert!(!output.status.success());
+}
+
+#[test]
+fn crack_hash_wordlist_file() {
+    let dir = scratch("wordlist");
+    let words = dir.join("words.txt");
+    let session = dir.join("run.session");
+    std::fs::write(&words, "alpha\r\n\nbeta\nzz9\ngamma\n").unwrap();
+
+    let text = ok(&[
+        "crack-hash",
+        "--hash",
+        MD5_ZZ9,
+        "-a",
+        "md5",
+        "--wordlist",
+        path(&words),
+        "--session",
+        path(&session),
+    ]);
+    assert!(text.contains("Keyspace: 4"), "{}", text);
+    assert!(text.contains("-> zz9"), "{}", text);
+    let text = ok(&["crack-hash", "--resume", path(&session)]);
+    assert!(text.contains("-> zz9"), "{}", text);
+
+    // A session is only valid for the list it was started on
+    std::fs::write(&words, "delta\n").unwrap();
+    let output = run(&["crack-hash", "--resume", path(&session)]);
+    assert!(stderr(&output).contains("changed since the session was created"));
+}
+
+#[test]
+fn crack_hash_json() {
+    let text = ok(&[
+        "crack-hash",
+        "--hash-file",
+        path(&fixture("hashes_md5.txt")),
+        "-a",
+        "md5",
+        "-p",
+        path(&fixture("profile.toml")),
+        "--format",
+        "json",
+    ]);
+    // stdout holds the document and nothing else
+    assert!(
+        text.starts_with('{') && text.trim_end().ends_with('}'),
+        "{}",
+        text
+    );
+    assert!(text.contains(r#""plaintext": "john1990""#), "{}", text);
+    assert!(text.contains(r#""algorithm": "md5""#), "{}", text);
+    assert!(text.contains(r#""tier": ""#), "{}", text);
+    // Cracking schedules the profile's own numbers ahead of generic affixes
+    assert!(text.contains(r#""tier": "Seed numbers""#), "{}", text);
+    assert!(text.contains(r#""uncracked": [""#), "{}", text);
+}
+
+#[test]
+fn crack_hash_outfile() {
+    let dir = scratch("outfile");
+    let hashes = dir.join("users.txt");
+    let outfile = dir.join("cracked.txt");
+    std::fs::write(
+        &hashes,
+        format!("john:{}\njane:{}\n", MD5_JOHN1990, MD5_JOHN1990),
+    )
+    .unwrap();
+
+    ok(&[
+        "crack-hash",
+        "--hash-file",
+        path(&hashes),
+        "--username",
+        "-a",
+        "md5",
+        "-p",
+        path(&fixture("profile.toml")),
+        "--outfile",
+        path(&outfile),
+        "--outfile-format",
+        "user:plain",
+    ]);
+    let written = std::fs::read_to_string(&outfile).unwrap();
+    assert_eq!(written, "john:john1990\njane:john1990\n");
+}
+
+#[test]
+fn crack_hash_auto_escalate() {
+    // md5("05151990Buddy"), which the fixture profile first yields at depth 2
+    let text = ok(&[
+        "crack-hash",
+        "--hash",
+        "cd67b8c260bce47762c11cccef7b0b19",
+        "-a",
+        "md5",
+        "-p",
+        path(&fixture("profile.toml")),
+        "-d",
+        "1",
+        "--auto-escalate",
+    ]);
+    assert!(text.contains("Escalating to depth 2"), "{}", text);
+    assert!(!text.contains("Escalating to depth 3"), "{}", text);
+    assert!(text.contains("-> 05151990Buddy"), "{}", text);
+}
+
+#[test]
+fn crack_hash_bcrypt_plan() {
+    let hash = bcrypt::hash("johnjohn", 4).unwrap();
+    let profile = fixture("profile.toml");
+    let args = [
+        "crack-hash",
+        "--hash",
+        &hash,
+        "-a",
+        "bcrypt",
+        "-p",
+        path(&profile),
+        "-d",
+        "1",
+    ];
+
+    // Nobody at stdin to confirm, so nothing is cracked
+    let text = ok(&args);
+    assert!(
+        text.contains("bcrypt attack plan: 1 hash(es), cost 4"),
+        "{}",
+        text
+    );
+    assert!(text.contains("Depth 3:"), "{}", text);
+    assert!(text.contains("Aborted before cracking"), "{}", text);
+
+    let text = ok(&[&args[..], &["--yes"]].concat());
+    assert!(text.contains("-> johnjohn"), "{}", text);
+}
+
+#[test]
+fn crack_batch_manifest() {
+    let dir = scratch("batch");
+    let manifest = dir.join("jobs.json");
+    std::fs::write(
+        &manifest,
+        format!(
+            r#"{{"jobs": [
+                {{"name": "md5", "profile": "{profile}", "hash_file": "{md5}", "algo": "md5"}},
+                {{"name": "sha", "profile": "{profile}", "hash_file": "{sha}", "algo": "sha256",
+                  "depth": 2}},
+                {{"name": "gone", "profile": "{profile}", "hash_file": "missing.txt", "algo": "md5"}}
+            ]}}"#,
+            profile = path(&fixture("profile.toml")),
+            md5 = path(&fixture("hashes_md5.txt")),
+            sha = path(&fixture("hash_sha256.txt")),
+        ),
+    )
+    .unwrap();
+
+    for parallel in ["1", "3"] {
+        let text = ok(&["crack-batch", "-m", path(&manifest), "--parallel", parallel]);
+        assert!(text.contains("Batch summary"), "{}", text);
+        assert!(
+            text.contains(&format!("md5: {} -> john1990", MD5_JOHN1990)),
+            "{}",
+            text
+        );
+        assert!(text.contains("-> buddy42"), "{}", text);
+        assert!(
+            text.contains("Cracked 2/3 hash(es) across 3 job(s), 1 failed"),
+            "{}",
+            text
+        );
+    }
+
+    let text = ok(&["crack-batch", "-m", path(&manifest), "--format", "json"]);
+    assert!(text.starts_with('{'), "{}", text);
+    assert!(text.contains(r#""name": "sha","#), "{}", text);
+    assert!(text.contains(r#""plaintext": "buddy42""#), "{}", text);
+    assert!(
+        text.contains(r#""error": "Failed to open wordlist"#),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn verify_results() {
+    let text = ok(&["verify", "--hash", MD5_JOHN1990, "--plaintext", "john1990"]);
+    assert!(text.contains("guessed from the hash: MD5"), "{}", text);
+    assert!(text.contains("VERIFIED"), "{}", text);
+
+    // md5("password" . "salt"), as hashcat's hash:salt
+    let text = ok(&[
+        "verify",
+        "--hash",
+        "b305cadbb3bce54f3aa59c64fec00dea:salt",
+        "-a",
+        "md5",
+        "--plaintext",
+        "password",
+    ]);
+    assert!(
+        text.contains(r#"VERIFIED MD5("password" . "salt")"#),
+        "{}",
+        text
+    );
+
+    let output = run(&["verify", "--hash", MD5_JOHN1990, "--plaintext", "john1991"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("MISMATCH"), "{}", stderr(&output));
+}
+
+#[test]
+fn crack_hash_digest_cache() {
+    let dir = scratch("cache");
+    let cache = dir.join("digests");
+    let hashes = fixture("hashes_md5.txt");
+    let profile = fixture("profile.toml");
+    let args = [
+        "crack-hash",
+        "--hash-file",
+        path(&hashes),
+        "-a",
+        "md5",
+        "-p",
+        path(&profile),
+        "--cache",
+        path(&cache),
+    ];
+
+    let text = ok(&args);
+    assert!(text.contains("Built digest cache of"), "{}", text);
+    assert!(text.contains("-> john1990"), "{}", text);
+
+    // The second run only looks the hashes up
+    let text = ok(&args);
+    assert!(text.contains("Digest cache: "), "{}", text);
+    assert!(!text.contains("Tested"), "{}", text);
+    assert!(text.contains("-> john1990"), "{}", text);
+    assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 1);
+}
+
+#[test]
+fn crack_hash_profile_then_wordlist() {
+    let dir = scratch("hybrid");
+    let words = dir.join("words.txt");
+    // "john1990" is generated from the profile too, so only two lines are new
+    std::fs::write(&words, "john1990\nzz9\nfoo\n").unwrap();
+    let hashes = dir.join("hashes.txt");
+    std::fs::write(&hashes, format!("{}\n{}\n", MD5_JOHN1990, MD5_ZZ9)).unwrap();
+
+    let text = ok(&[
+        "crack-hash",
+        "--hash-file",
+        path(&hashes),
+        "-a",
+        "md5",
+        "-p",
+        path(&fixture("profile.toml")),
+        "--wordlist",
+        path(&words),
+    ]);
+    assert!(text.contains("Base wordlist: "), "{}", text);
+    assert!(text.contains("| 2 new after the "), "{}", text);
+    assert!(text.contains("Cracked 2/2 hash(es)"), "{}", text);
+}
+
+#[test]
+fn crack_hash_from_stdin() {
+    let mut child = command()
+        .args([
+            "crack-hash",
+            "--hash",
+            MD5_ZZ9,
+            "-a",
+            "md5",
+            "--wordlist",
+            "-",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"foo\r\nbar\nzz9\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.contains("Wordlist: stdin"), "{}", text);
+    assert!(text.contains("-> zz9"), "{}", text);
+}
+
+#[test]
+fn crack_hash_loopback() {
+    let dir = scratch("loopback");
+    let words = dir.join("words.txt");
+    std::fs::write(&words, "Acme2023!\n").unwrap();
+    // md5("Acme2023!") and md5("acme2024"), a colleague's variation
+    let hashes = dir.join("hashes.txt");
+    std::fs::write(
+        &hashes,
+        "c2726b63313a44d31a20f6637034d30e\n029631dd2500a1d9ee3aae6ffe10db7e\n",
+    )
+    .unwrap();
+    let args = [
+        "crack-hash",
+        "--hash-file",
+        path(&hashes),
+        "-a",
+        "md5",
+        "--wordlist",
+        path(&words),
+    ];
+
+    let text = ok(&args);
+    assert!(text.contains("Cracked 1/2 hash(es)"), "{}", text);
+
+    let text = ok(&[&args[..], &["--loopback"]].concat());
+    assert!(text.contains("Loopback round 1: 1 new seed(s)"), "{}", text);
+    assert!(text.contains("Cracked 2/2 hash(es)"), "{}", text);
+    assert!(text.contains("-> acme2024"));
+}
+
+#[test]
+fn crack_detects_targets() {
+    let profile = fixture("profile.toml");
+
+    let hash = std::fs::read_to_string(fixture("hash_sha256.txt")).unwrap();
+    let text = ok(&["crack", hash.trim(), "-p", path(&profile)]);
+    assert!(text.contains("Detected 1 SHA256 hash(es)"), "{}", text);
+    assert!(text.contains("-> buddy42"));
+
+    let text = ok(&[
+        "crack",
+        path(&fixture("hashes_md5.txt")),
+        "-p",
+        path(&profile),
+    ]);
+    assert!(text.contains("Detected 2 MD5 hash(es)"), "{}", text);
+
+    // Recognized but unsupported: a clean error, not a crash or a bogus crack
+    let output = run(&["crack", path(&fixture("archive.zip")), "-p", path(&profile)]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("zip archive, which is not supported yet"));
+
+    // Captures go to the handshake path; whether aircrack-ng is installed
+    // decides the rest, so only the dispatch is checked
+    let output = run(&[
+        "crack",
+        path(&fixture("empty.pcap")),
+        "-p",
+        path(&profile),
+        "-d",
+        "1",
+    ]);
+    assert!(stdout(&output).contains("Detected WiFi handshake capture"));
+}
+
+#[test]
+fn crack_wifi_native() {
+    let text = ok(&[
+        "crack-wifi",
+        "--handshake",
+        path(&fixture("handshake.hccapx")),
+        "-p",
+        path(&fixture("profile.toml")),
+        "--native",
+        "-d",
+        "1",
+    ]);
+    assert!(text.contains("WiFi key cracked: JohnJohn"), "{}", text);
+}
+
+#[test]
+fn serve_and_worker() {
+    // Reserve a free port, then hand it to the server
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+    let profile = fixture("profile.toml");
+
+    let server = command()
+        .args([
+            "serve",
+            "--bind",
+            &addr,
+            "--hash",
+            MD5_JOHN1990,
+            "-a",
+            "md5",
+            "-p",
+            path(&profile),
+            "--chunk-size",
+            "500",
+            "--token",
+            "lab-secret",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // The server needs a moment to bind; a wrong token is turned away
+    let mut denied = false;
+    for _ in 0..50 {
+        let output = run(&["worker", "--server", &addr, "--token", "guess"]);
+        if stderr(&output).contains("refused the token") {
+            denied = true;
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(denied, "worker with the wrong token was not refused");
+    let mut worker = None;
+    for _ in 0..50 {
+        let output = run(&[
+            "worker",
+            "--server",
+            &addr,
+            "--name",
+            "ci",
+            "--token",
+            "lab-secret",
+        ]);
+        if output.status.success() {
+            worker = Some(stdout(&output));
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let worker = worker.expect("worker never connected");
+    assert!(worker.contains("Worker finished"), "{}", worker);
+
+    let output = server.wait_with_output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.contains("Cracked 1/1 hash(es)"), "{}", text);
+    assert!(text.contains("john1990"));
+}
+
+#[test]
+fn engagement_gate_logs_authorized_runs() {
+    let dir = scratch("engagement");
+    let engagement = dir.join("engagement.toml");
+    std::fs::copy(fixture("engagement.toml"), &engagement).unwrap();
+
+    let text = ok(&[
+        "--engagement",
+        path(&engagement),
+        "--authorized",
+        "crack-hash",
+        "--hash",
+        MD5_JOHN1990,
+        "-a",
+        "md5",
+        "-p",
+        path(&fixture("profile.toml")),
+    ]);
+    assert!(
+        text.contains("Engagement: Integration test fixtures"),
+        "{}",
+        text
+    );
+
+    let log = std::fs::read_to_string(engagement.with_extension("log")).unwrap();
+    assert!(log.contains("authorization=CI-0000"), "{}", log);
+    assert!(log.contains(MD5_JOHN1990));
+}
+
+#[test]
+fn bad_input_fails_cleanly() {
+    let output = run(&["crack", "not-a-hash-or-file"]);
+    assert!(!output.status.success());
+
+    let output = run(&["--threads", "0", "generate", "--mask", "?d", "-o", "x"]);
+    assert!(!output.status.success());
+
+    let output = run(&["generate", "--mask", "?u", "--right", "x.txt", "-o", "x"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("cannot be used with"));
+
+    let output = run(&["generate", "-p", path(&fixture("missing.toml")), "-o", "x"]);
+    assert!(!output.status.success());
+}